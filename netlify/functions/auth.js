@@ -57,10 +57,12 @@ exports.handler = async function (event, context) {
 
             // Redirect to custom URL scheme that the Electron app handles
             // This works in sandboxed environments (Mac App Store, Windows Store)
+            // The app checks `state` to make sure the tokens belong to the sign-in it started.
             const params = new URLSearchParams({
                 access_token: tokenData.access_token,
                 refresh_token: tokenData.refresh_token,
-                expires_in: tokenData.expires_in
+                expires_in: tokenData.expires_in,
+                state
             });
 
             const localhostPort = getLocalCallbackPort(state);
@@ -94,7 +96,8 @@ exports.handler = async function (event, context) {
             // Redirect to app with error so user gets feedback
             const errorParams = new URLSearchParams({
                 error: 'auth_failed',
-                error_description: error.message
+                error_description: error.message,
+                state
             });
 
             const localhostPort = getLocalCallbackPort(state);
@@ -161,12 +164,15 @@ function exchangeToken(payload) {
     });
 }
 
+// `state` is `localhost:<port>:<nonce>` when the app listens on a localhost
+// bridge (just `localhost:<port>` from older versions), otherwise the nonce.
 function getLocalCallbackPort(state) {
     if (!state || !state.startsWith(LOCAL_CALLBACK_STATE_PREFIX)) {
         return null;
     }
 
-    const port = Number.parseInt(state.slice(LOCAL_CALLBACK_STATE_PREFIX.length), 10);
+    const [portText] = state.slice(LOCAL_CALLBACK_STATE_PREFIX.length).split(':');
+    const port = Number.parseInt(portText, 10);
     if (!Number.isInteger(port) || port < 1 || port > 65535) {
         return null;
     }
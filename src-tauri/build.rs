@@ -1,3 +1,13 @@
 fn main() {
+    // The command line finds the app's data directory without Tauri, by the
+    // bundle identifier; take it from the config so the two can't drift.
+    let config = std::fs::read_to_string("tauri.conf.json").expect("read tauri.conf.json");
+    let config: serde_json::Value = serde_json::from_str(&config).expect("parse tauri.conf.json");
+    let identifier = config["identifier"]
+        .as_str()
+        .expect("tauri.conf.json has an identifier");
+    println!("cargo:rustc-env=APP_IDENTIFIER={identifier}");
+    println!("cargo:rerun-if-changed=tauri.conf.json");
+
     tauri_build::build()
 }
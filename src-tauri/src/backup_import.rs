@@ -0,0 +1,661 @@
+//! Validation, dry-run diffing and merging of backup files before import.
+//!
+//! A backup file is the JSON the Export button writes: the task snapshot plus
+//! optional `planData` and `uiPrefs`. It is checked field by field so
+//! problems can be reported with their JSON path instead of a bare parse error.
+
+use crate::store::{self, AppData, Tab, Task};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupFile {
+    #[serde(flatten)]
+    pub data: AppData,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub plan_data: Option<Map<String, Value>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ui_prefs: Option<Value>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportMode {
+    /// Overwrite all current data with the backup (the Import button's behaviour).
+    #[default]
+    Replace,
+    /// Add the backup's lists and tasks next to the existing ones.
+    Merge,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ValidationIssue {
+    pub path: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ItemRef {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TabDiff {
+    pub id: String,
+    pub name: String,
+    pub renamed_from: Option<String>,
+    pub tasks_added: Vec<ItemRef>,
+    pub tasks_removed: Vec<ItemRef>,
+    pub tasks_changed: Vec<ItemRef>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupDiff {
+    pub tabs_added: Vec<ItemRef>,
+    pub tabs_removed: Vec<ItemRef>,
+    pub tabs_changed: Vec<TabDiff>,
+    /// Totals across all tabs, including tasks in added/removed tabs.
+    pub tasks_added: usize,
+    pub tasks_removed: usize,
+    pub tasks_changed: usize,
+}
+
+struct Checker {
+    issues: Vec<ValidationIssue>,
+}
+
+impl Checker {
+    fn push(&mut self, path: &str, message: impl Into<String>) {
+        self.issues.push(ValidationIssue {
+            path: path.to_string(),
+            message: message.into(),
+        });
+    }
+
+    fn expect(&mut self, obj: &Map<String, Value>, path: &str, key: &str, kind: Kind) {
+        match obj.get(key) {
+            None if kind.required => self.push(&format!("{path}.{key}"), "is required"),
+            None => {}
+            Some(value) if !kind.matches(value) => self.push(
+                &format!("{path}.{key}"),
+                format!("expected {}, found {}", kind.name, type_name(value)),
+            ),
+            Some(_) => {}
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Kind {
+    name: &'static str,
+    required: bool,
+    check: fn(&Value) -> bool,
+}
+
+impl Kind {
+    fn matches(&self, value: &Value) -> bool {
+        (self.check)(value)
+    }
+
+    const fn required(self) -> Self {
+        Kind {
+            required: true,
+            ..self
+        }
+    }
+}
+
+const STRING: Kind = Kind {
+    name: "a string",
+    required: false,
+    check: Value::is_string,
+};
+const BOOL: Kind = Kind {
+    name: "a boolean",
+    required: false,
+    check: Value::is_boolean,
+};
+const OBJECT: Kind = Kind {
+    name: "an object",
+    required: false,
+    check: Value::is_object,
+};
+const ARRAY: Kind = Kind {
+    name: "an array",
+    required: false,
+    check: Value::is_array,
+};
+const STRING_OR_NULL: Kind = Kind {
+    name: "a string or null",
+    required: false,
+    check: |v| v.is_string() || v.is_null(),
+};
+const NUMBER_OR_NULL: Kind = Kind {
+    name: "a number or null",
+    required: false,
+    check: |v| v.is_number() || v.is_null(),
+};
+const COUNTER: Kind = Kind {
+    name: "a non-negative integer",
+    required: false,
+    check: |v| v.is_u64(),
+};
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+/// Check a backup for schema errors. An empty result means it
+/// will deserialize into a `BackupFile`.
+pub fn validate(root: &Value) -> Vec<ValidationIssue> {
+    let mut checker = Checker { issues: Vec::new() };
+    let Some(root) = root.as_object() else {
+        checker.push(
+            "$",
+            format!("expected an object, found {}", type_name(root)),
+        );
+        return checker.issues;
+    };
+
+    checker.expect(root, "$", "tabs", OBJECT.required());
+    checker.expect(root, "$", "groups", OBJECT);
+    checker.expect(root, "$", "currentTabId", STRING_OR_NULL);
+    checker.expect(root, "$", "currentGroupId", STRING_OR_NULL);
+    checker.expect(root, "$", "taskCounter", COUNTER);
+    checker.expect(root, "$", "planData", OBJECT);
+    checker.expect(root, "$", "uiPrefs", OBJECT);
+
+    let mut seen_task_ids: HashSet<&str> = HashSet::new();
+    for (key, tab) in root
+        .get("tabs")
+        .and_then(Value::as_object)
+        .into_iter()
+        .flatten()
+    {
+        let path = format!("$.tabs.{key}");
+        let Some(tab) = tab.as_object() else {
+            checker.push(
+                &path,
+                format!("expected an object, found {}", type_name(tab)),
+            );
+            continue;
+        };
+        checker.expect(tab, &path, "id", STRING.required());
+        if let Some(id) = tab.get("id").and_then(Value::as_str) {
+            if id != key {
+                checker.push(
+                    &format!("{path}.id"),
+                    format!("\"{id}\" does not match its key"),
+                );
+            }
+        }
+        checker.expect(tab, &path, "name", STRING);
+        checker.expect(tab, &path, "groupId", STRING_OR_NULL);
+        checker.expect(tab, &path, "tasks", ARRAY);
+
+        let tasks = tab.get("tasks").and_then(Value::as_array);
+        for (index, task) in tasks.into_iter().flatten().enumerate() {
+            let path = format!("{path}.tasks[{index}]");
+            let Some(task) = task.as_object() else {
+                checker.push(
+                    &path,
+                    format!("expected an object, found {}", type_name(task)),
+                );
+                continue;
+            };
+            checker.expect(task, &path, "id", STRING.required());
+            checker.expect(task, &path, "text", STRING.required());
+            checker.expect(task, &path, "completed", BOOL);
+            checker.expect(task, &path, "isFavourite", BOOL);
+            checker.expect(task, &path, "createdAt", STRING_OR_NULL);
+            checker.expect(task, &path, "completedAt", STRING_OR_NULL);
+            checker.expect(task, &path, "expectedDuration", NUMBER_OR_NULL);
+            checker.expect(task, &path, "actualDuration", NUMBER_OR_NULL);
+            checker.expect(task, &path, "timeSpent", NUMBER_OR_NULL);
+            checker.expect(task, &path, "notes", STRING_OR_NULL);
+            if let Some(id) = task.get("id").and_then(Value::as_str) {
+                if !seen_task_ids.insert(id) {
+                    checker.push(&format!("{path}.id"), format!("duplicate task id \"{id}\""));
+                }
+            }
+        }
+    }
+
+    for (key, group) in root
+        .get("groups")
+        .and_then(Value::as_object)
+        .into_iter()
+        .flatten()
+    {
+        let path = format!("$.groups.{key}");
+        let Some(group) = group.as_object() else {
+            checker.push(
+                &path,
+                format!("expected an object, found {}", type_name(group)),
+            );
+            continue;
+        };
+        checker.expect(group, &path, "id", STRING.required());
+        checker.expect(group, &path, "name", STRING);
+        checker.expect(group, &path, "order", NUMBER_OR_NULL);
+    }
+
+    checker.issues
+}
+
+/// Validate and parse backup file contents.
+pub fn parse(contents: &str) -> Result<BackupFile, Vec<ValidationIssue>> {
+    let root: Value = serde_json::from_str(contents).map_err(|e| {
+        vec![ValidationIssue {
+            path: "$".to_string(),
+            message: format!("not valid JSON: {e}"),
+        }]
+    })?;
+    let issues = validate(&root);
+    if !issues.is_empty() {
+        return Err(issues);
+    }
+    serde_json::from_value(root).map_err(|e| {
+        vec![ValidationIssue {
+            path: "$".to_string(),
+            message: e.to_string(),
+        }]
+    })
+}
+
+fn task_ref(task: &Task) -> ItemRef {
+    ItemRef {
+        id: task.id.clone(),
+        name: task.text.clone(),
+    }
+}
+
+fn tab_ref(tab: &Tab) -> ItemRef {
+    ItemRef {
+        id: tab.id.clone(),
+        name: tab.name.clone(),
+    }
+}
+
+/// Describe what replacing `current` with `next` would change.
+pub fn diff(current: &AppData, next: &AppData) -> BackupDiff {
+    let mut result = BackupDiff::default();
+
+    for (id, tab) in &next.tabs {
+        let Some(before) = current.tabs.get(id) else {
+            result.tasks_added += tab.tasks.len();
+            result.tabs_added.push(tab_ref(tab));
+            continue;
+        };
+        if before == tab {
+            continue;
+        }
+
+        let mut tab_diff = TabDiff {
+            id: id.clone(),
+            name: tab.name.clone(),
+            renamed_from: (before.name != tab.name).then(|| before.name.clone()),
+            ..TabDiff::default()
+        };
+        for task in &tab.tasks {
+            match before.tasks.iter().find(|t| t.id == task.id) {
+                None => tab_diff.tasks_added.push(task_ref(task)),
+                Some(old) if old != task => tab_diff.tasks_changed.push(task_ref(task)),
+                Some(_) => {}
+            }
+        }
+        for task in &before.tasks {
+            if !tab.tasks.iter().any(|t| t.id == task.id) {
+                tab_diff.tasks_removed.push(task_ref(task));
+            }
+        }
+        result.tasks_added += tab_diff.tasks_added.len();
+        result.tasks_removed += tab_diff.tasks_removed.len();
+        result.tasks_changed += tab_diff.tasks_changed.len();
+        result.tabs_changed.push(tab_diff);
+    }
+
+    for (id, tab) in &current.tabs {
+        if !next.tabs.contains_key(id) {
+            result.tasks_removed += tab.tasks.len();
+            result.tabs_removed.push(tab_ref(tab));
+        }
+    }
+
+    result
+}
+
+/// Whether `existing` is `incoming` as imported earlier: the same tasks
+/// (their ids may have been reassigned) under the same name, or the name
+/// given to an imported copy.
+fn already_imported(existing: &Tab, incoming: &Tab) -> bool {
+    let same_name =
+        existing.name == incoming.name || existing.name == format!("{} (imported)", incoming.name);
+    same_name
+        && existing.tasks.len() == incoming.tasks.len()
+        && existing.tasks.iter().zip(&incoming.tasks).all(|(a, b)| {
+            Task {
+                id: b.id.clone(),
+                ..a.clone()
+            } == *b
+        })
+}
+
+/// Import `incoming` next to `current` without touching existing lists.
+/// Tabs already present (see [`already_imported`]) are skipped, so importing
+/// the same backup again changes nothing; a tab whose id is taken by a
+/// different list is added under a new id. Task ids that would collide are
+/// reassigned.
+pub fn merge(current: &AppData, incoming: &AppData) -> AppData {
+    let mut merged = current.clone();
+    merged.task_counter = merged.task_counter.max(incoming.task_counter);
+
+    for (id, group) in &incoming.groups {
+        if !merged.groups.contains_key(id) {
+            merged.groups.insert(id.clone(), group.clone());
+        }
+    }
+
+    for (id, tab) in &incoming.tabs {
+        if merged
+            .tabs
+            .values()
+            .any(|existing| already_imported(existing, tab))
+        {
+            continue;
+        }
+        let mut tab = tab.clone();
+        if merged.tabs.contains_key(id) {
+            tab.id = store::unique_collection_id("tab", &merged.tabs);
+            tab.name = format!("{} (imported)", tab.name);
+        }
+        if tab
+            .group_id
+            .as_ref()
+            .is_some_and(|group| !merged.groups.contains_key(group))
+        {
+            tab.group_id = merged.current_group_id.clone();
+        }
+
+        let mut taken: HashSet<String> = HashSet::new();
+        for task in &mut tab.tasks {
+            if merged.find_task(&task.id).is_some() || !taken.insert(task.id.clone()) {
+                task.id = merged.next_task_id();
+                taken.insert(task.id.clone());
+            }
+        }
+        merged.tabs.insert(tab.id.clone(), tab);
+    }
+
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn current() -> AppData {
+        serde_json::from_value(json!({
+            "tabs": {
+                "tab_1": {
+                    "id": "tab_1",
+                    "name": "Work",
+                    "groupId": "group_1",
+                    "tasks": [
+                        { "id": "task_1", "text": "Write email", "expectedDuration": 15 },
+                        { "id": "task_2", "text": "Review PR" }
+                    ]
+                }
+            },
+            "groups": { "group_1": { "id": "group_1", "name": "General", "order": 0 } },
+            "currentGroupId": "group_1",
+            "taskCounter": 2
+        }))
+        .unwrap()
+    }
+
+    fn paths(issues: &[ValidationIssue]) -> Vec<&str> {
+        issues.iter().map(|issue| issue.path.as_str()).collect()
+    }
+
+    #[test]
+    fn rejects_invalid_json() {
+        let issues = parse("{ \"tabs\": ").unwrap_err();
+        assert_eq!(paths(&issues), ["$"]);
+        assert!(issues[0].message.starts_with("not valid JSON"));
+    }
+
+    #[test]
+    fn rejects_non_object_root_and_missing_tabs() {
+        assert_eq!(paths(&parse("[]").unwrap_err()), ["$"]);
+        assert_eq!(paths(&parse("{}").unwrap_err()), ["$.tabs"]);
+    }
+
+    #[test]
+    fn reports_every_bad_field_with_its_path() {
+        let contents = json!({
+            "tabs": {
+                "tab_1": {
+                    "id": "tab_2",
+                    "name": 7,
+                    "tasks": [
+                        { "id": "task_1", "text": "ok", "completed": "yes" },
+                        { "text": "no id", "expectedDuration": [] },
+                        "not a task"
+                    ]
+                }
+            },
+            "groups": { "group_1": { "name": "General" } },
+            "taskCounter": -1
+        })
+        .to_string();
+
+        let issues = parse(&contents).unwrap_err();
+        assert_eq!(
+            paths(&issues),
+            [
+                "$.taskCounter",
+                "$.tabs.tab_1.id",
+                "$.tabs.tab_1.name",
+                "$.tabs.tab_1.tasks[0].completed",
+                "$.tabs.tab_1.tasks[1].id",
+                "$.tabs.tab_1.tasks[1].expectedDuration",
+                "$.tabs.tab_1.tasks[2]",
+                "$.groups.group_1.id",
+            ]
+        );
+        assert_eq!(issues[2].message, "expected a string, found a number");
+    }
+
+    #[test]
+    fn reports_duplicate_task_ids_across_tabs() {
+        let contents = json!({
+            "tabs": {
+                "a": { "id": "a", "tasks": [{ "id": "task_1", "text": "x" }] },
+                "b": { "id": "b", "tasks": [{ "id": "task_1", "text": "y" }] }
+            }
+        })
+        .to_string();
+        let issues = parse(&contents).unwrap_err();
+        assert_eq!(paths(&issues), ["$.tabs.b.tasks[0].id"]);
+    }
+
+    const EXPORT: &str = include_str!("../tests/fixtures/backup/export.json");
+
+    #[test]
+    fn accepts_an_export_from_the_app() {
+        let backup = parse(EXPORT).unwrap();
+        assert_eq!(backup.data.tabs.len(), 2);
+        assert_eq!(backup.data.task_counter, 3);
+
+        let (tab, task) = backup.data.find_task("task_2").unwrap();
+        assert_eq!(tab.id, "tab_1");
+        assert!(task.completed);
+        assert_eq!(task.actual_duration, Some(1_500_000.0));
+        assert_eq!(
+            task.extra["statusChangedAt"],
+            json!("2025-03-03T10:02:00.000Z")
+        );
+        let (tab, task) = backup.data.find_task("task_3").unwrap();
+        assert_eq!(tab.extra["basecampListId"], json!(6617002));
+        assert_eq!(task.extra["basecampId"], json!(7311042));
+
+        assert_eq!(
+            backup.plan_data.unwrap()["calendar-view-mode"],
+            json!("week")
+        );
+        assert_eq!(backup.ui_prefs.unwrap()["theme"], json!("system"));
+        assert_eq!(backup.data.extra["favouritesOrder"], json!(["task_1"]));
+    }
+
+    #[test]
+    fn rejects_shapes_the_app_never_exported() {
+        let contents = json!({
+            "tabs": {
+                "tab_1": {
+                    "name": "No id",
+                    "tasks": [{ "id": 3, "text": "Numeric id", "expectedDuration": "25" }]
+                }
+            }
+        })
+        .to_string();
+        assert_eq!(
+            paths(&parse(&contents).unwrap_err()),
+            [
+                "$.tabs.tab_1.id",
+                "$.tabs.tab_1.tasks[0].id",
+                "$.tabs.tab_1.tasks[0].expectedDuration",
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_plan_data_and_ui_prefs_out_of_task_data() {
+        let contents = json!({
+            "tabs": {},
+            "planData": { "groups": [] },
+            "uiPrefs": { "theme": "dark" },
+            "enablePlan": true
+        })
+        .to_string();
+        let backup = parse(&contents).unwrap();
+        assert_eq!(backup.plan_data.unwrap()["groups"], json!([]));
+        assert_eq!(backup.ui_prefs.unwrap()["theme"], json!("dark"));
+        assert!(!backup.data.extra.contains_key("planData"));
+        assert_eq!(backup.data.extra["enablePlan"], json!(true));
+    }
+
+    #[test]
+    fn diff_reports_added_removed_and_changed() {
+        let before = current();
+        let mut after = before.clone();
+        let tab = after.tabs.get_mut("tab_1").unwrap();
+        tab.name = "Work stuff".to_string();
+        tab.tasks[0].completed = true;
+        tab.tasks.remove(1);
+        tab.tasks.push(Task {
+            id: "task_3".to_string(),
+            text: "New".to_string(),
+            ..Task::default()
+        });
+        after.tabs.insert(
+            "tab_2".to_string(),
+            Tab {
+                id: "tab_2".to_string(),
+                name: "Home".to_string(),
+                ..Tab::default()
+            },
+        );
+
+        let result = diff(&before, &after);
+        assert_eq!(
+            result.tabs_added,
+            [ItemRef {
+                id: "tab_2".into(),
+                name: "Home".into()
+            }]
+        );
+        assert!(result.tabs_removed.is_empty());
+        assert_eq!(result.tabs_changed.len(), 1);
+        let changed = &result.tabs_changed[0];
+        assert_eq!(changed.renamed_from.as_deref(), Some("Work"));
+        assert_eq!(changed.tasks_added[0].id, "task_3");
+        assert_eq!(changed.tasks_removed[0].id, "task_2");
+        assert_eq!(changed.tasks_changed[0].id, "task_1");
+        assert_eq!(
+            (
+                result.tasks_added,
+                result.tasks_removed,
+                result.tasks_changed
+            ),
+            (1, 1, 1)
+        );
+
+        let reverse = diff(&after, &before);
+        assert_eq!(reverse.tabs_removed[0].id, "tab_2");
+    }
+
+    #[test]
+    fn merge_keeps_existing_lists_and_reassigns_colliding_ids() {
+        let base = current();
+        let incoming: AppData = serde_json::from_value(json!({
+            "tabs": {
+                "tab_1": {
+                    "id": "tab_1",
+                    "name": "Work",
+                    "groupId": "group_9",
+                    "tasks": [{ "id": "task_1", "text": "Other email" }]
+                },
+                "tab_5": {
+                    "id": "tab_5",
+                    "name": "Side project",
+                    "tasks": [{ "id": "task_7", "text": "Ship it" }]
+                }
+            },
+            "taskCounter": 7
+        }))
+        .unwrap();
+
+        let merged = merge(&base, &incoming);
+        assert_eq!(merged.tabs["tab_1"], base.tabs["tab_1"]);
+        assert_eq!(merged.tabs.len(), 3);
+
+        let imported = merged.tabs.values().nth(1).unwrap();
+        assert_eq!(imported.name, "Work (imported)");
+        assert_eq!(imported.group_id.as_deref(), Some("group_1"));
+        assert_eq!(imported.tasks[0].id, "task_8");
+        assert_eq!(merged.tabs["tab_5"].tasks[0].id, "task_7");
+        assert_eq!(merged.task_counter, 8);
+
+        let result = diff(&base, &merged);
+        assert!(result.tabs_removed.is_empty() && result.tabs_changed.is_empty());
+        assert_eq!(result.tasks_added, 2);
+
+        assert_eq!(merge(&merged, &incoming), merged);
+        assert_eq!(merge(&base, &base), base);
+    }
+
+    #[test]
+    fn importing_an_export_twice_changes_nothing() {
+        let base = current();
+        let export = parse(EXPORT).unwrap().data;
+
+        let once = merge(&base, &export);
+        assert_eq!(once.tabs.len(), 3);
+        assert_eq!(once.tabs.values().nth(1).unwrap().name, "Work (imported)");
+        assert_eq!(merge(&once, &export), once);
+    }
+}
@@ -0,0 +1,441 @@
+//! Automatic, rotating snapshots of the task store and plan data.
+//!
+//! Snapshots are written to `app_data_dir()/backups/` in the same JSON shape
+//! the Export button produces (task data plus `planData`), so any snapshot can
+//! also be imported by hand. Retention keeps the newest snapshot per hour,
+//! day and ISO week, up to the configured number of each. The backup settings
+//! live next to the other settings files in `app_data_dir()`.
+
+use crate::store;
+use chrono::{DateTime, NaiveDateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const BACKUP_DIR_NAME: &str = "backups";
+pub const SETTINGS_FILE_NAME: &str = "backups.json";
+/// Where the settings were kept before they moved out of `backups/`.
+const LEGACY_SETTINGS_FILE_NAME: &str = "settings.json";
+const SNAPSHOT_PREFIX: &str = "redd-todo-backup-";
+const SNAPSHOT_SUFFIX: &str = ".json";
+const SNAPSHOT_TIME_FORMAT: &str = "%Y%m%dT%H%M%S%.3fZ";
+/// Snapshots taken before ids carried milliseconds.
+const LEGACY_SNAPSHOT_TIME_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetentionPolicy {
+    pub hourly: usize,
+    pub daily: usize,
+    pub weekly: usize,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            hourly: 24,
+            daily: 7,
+            weekly: 8,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BackupSettings {
+    pub enabled: bool,
+    pub interval_minutes: u64,
+    pub retention: RetentionPolicy,
+}
+
+impl Default for BackupSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_minutes: 60,
+            retention: RetentionPolicy::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupInfo {
+    /// File name inside `backups/`; pass back to `restore_backup`.
+    pub id: String,
+    pub created_at: String,
+    pub size_bytes: u64,
+    pub tab_count: usize,
+    pub task_count: usize,
+}
+
+pub fn backup_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join(BACKUP_DIR_NAME)
+}
+
+pub fn load_settings(data_dir: &Path) -> BackupSettings {
+    if data_dir.join(SETTINGS_FILE_NAME).exists() {
+        store::load_json_settings(data_dir, SETTINGS_FILE_NAME)
+    } else {
+        store::load_json_settings(&backup_dir(data_dir), LEGACY_SETTINGS_FILE_NAME)
+    }
+}
+
+pub fn save_settings(data_dir: &Path, settings: &BackupSettings) -> Result<(), String> {
+    store::save_json_settings(data_dir, SETTINGS_FILE_NAME, settings)
+}
+
+/// Build the export-shaped payload from what is currently on disk.
+/// Returns `None` if no task data has been persisted yet.
+pub fn current_payload(data_dir: &Path) -> Result<Option<Value>, String> {
+    let Some(data) = store::load(data_dir)? else {
+        return Ok(None);
+    };
+    let mut payload = serde_json::to_value(&data).map_err(|e| e.to_string())?;
+    if let Some(obj) = payload.as_object_mut() {
+        obj.insert(
+            "planData".to_string(),
+            Value::Object(store::load_plan(data_dir)?),
+        );
+    }
+    Ok(Some(payload))
+}
+
+fn snapshot_time(id: &str) -> Option<DateTime<Utc>> {
+    let stamp = id
+        .strip_prefix(SNAPSHOT_PREFIX)?
+        .strip_suffix(SNAPSHOT_SUFFIX)?;
+    NaiveDateTime::parse_from_str(stamp, SNAPSHOT_TIME_FORMAT)
+        .or_else(|_| NaiveDateTime::parse_from_str(stamp, LEGACY_SNAPSHOT_TIME_FORMAT))
+        .ok()
+        .map(|naive| naive.and_utc())
+}
+
+fn snapshot_id(time: DateTime<Utc>) -> String {
+    format!(
+        "{SNAPSHOT_PREFIX}{}{SNAPSHOT_SUFFIX}",
+        time.format(SNAPSHOT_TIME_FORMAT)
+    )
+}
+
+/// Snapshot file names with their timestamps, newest first.
+fn snapshot_ids(data_dir: &Path) -> Vec<(String, DateTime<Utc>)> {
+    let Ok(entries) = fs::read_dir(backup_dir(data_dir)) else {
+        return Vec::new();
+    };
+    let mut ids: Vec<(String, DateTime<Utc>)> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let id = entry.file_name().to_string_lossy().to_string();
+            snapshot_time(&id).map(|time| (id, time))
+        })
+        .collect();
+    ids.sort_by_key(|(_, time)| std::cmp::Reverse(*time));
+    ids
+}
+
+pub fn list(data_dir: &Path) -> Vec<BackupInfo> {
+    let dir = backup_dir(data_dir);
+    snapshot_ids(data_dir)
+        .into_iter()
+        .map(|(id, time)| {
+            let path = dir.join(&id);
+            let size_bytes = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+            let (tab_count, task_count) = fs::read_to_string(&path)
+                .ok()
+                .and_then(|raw| serde_json::from_str::<store::AppData>(&raw).ok())
+                .map(|data| {
+                    let tasks = data.tabs.values().map(|tab| tab.tasks.len()).sum();
+                    (data.tabs.len(), tasks)
+                })
+                .unwrap_or((0, 0));
+            BackupInfo {
+                id,
+                created_at: time.to_rfc3339(),
+                size_bytes,
+                tab_count,
+                task_count,
+            }
+        })
+        .collect()
+}
+
+/// Write a snapshot of the current data. Unless `force` is set, nothing is
+/// written when the data is identical to the newest existing snapshot.
+/// Returns the new snapshot's id, if one was written.
+pub fn create_snapshot(data_dir: &Path, force: bool) -> Result<Option<String>, String> {
+    let Some(payload) = current_payload(data_dir)? else {
+        return Ok(None);
+    };
+    let json = serde_json::to_vec_pretty(&payload).map_err(|e| e.to_string())?;
+
+    let dir = backup_dir(data_dir);
+    if !force {
+        if let Some((latest, _)) = snapshot_ids(data_dir).first() {
+            if fs::read(dir.join(latest)).is_ok_and(|existing| existing == json) {
+                return Ok(None);
+            }
+        }
+    }
+
+    // Two snapshots within the same millisecond (e.g. "Back up now" followed
+    // by a restore) each get their own file.
+    let mut time = Utc::now();
+    let mut id = snapshot_id(time);
+    while dir.join(&id).exists() {
+        time += TimeDelta::milliseconds(1);
+        id = snapshot_id(time);
+    }
+    let path = dir.join(&id);
+    store::write_atomic(&path, &json).map_err(|e| format!("Failed to write {path:?}: {e}"))?;
+    Ok(Some(id))
+}
+
+/// Pick the snapshots `policy` no longer retains. `snapshots` must be sorted
+/// newest first; the newest snapshot is always kept.
+pub fn expired_snapshots<'a>(
+    snapshots: &'a [(String, DateTime<Utc>)],
+    policy: &RetentionPolicy,
+) -> Vec<&'a str> {
+    let mut keep: HashSet<&str> = HashSet::new();
+    if let Some((newest, _)) = snapshots.first() {
+        keep.insert(newest);
+    }
+    for (limit, bucket_format) in [
+        (policy.hourly, "%Y%m%d%H"),
+        (policy.daily, "%Y%m%d"),
+        (policy.weekly, "%G-W%V"),
+    ] {
+        let mut buckets: HashSet<String> = HashSet::new();
+        for (id, time) in snapshots {
+            if buckets.len() >= limit {
+                break;
+            }
+            if buckets.insert(time.format(bucket_format).to_string()) {
+                keep.insert(id);
+            }
+        }
+    }
+    snapshots
+        .iter()
+        .map(|(id, _)| id.as_str())
+        .filter(|id| !keep.contains(id))
+        .collect()
+}
+
+/// Delete snapshots outside the retention window. Returns how many were removed.
+pub fn prune(data_dir: &Path, policy: &RetentionPolicy) -> usize {
+    let snapshots = snapshot_ids(data_dir);
+    let dir = backup_dir(data_dir);
+    let mut removed = 0;
+    for id in expired_snapshots(&snapshots, policy) {
+        match fs::remove_file(dir.join(id)) {
+            Ok(()) => removed += 1,
+            Err(e) => log::warn!("[Backups] Failed to remove {id}: {e}"),
+        }
+    }
+    removed
+}
+
+/// Read a snapshot by id. Rejects anything that isn't a snapshot file name so
+/// callers can't reach outside `backups/`.
+pub fn read_snapshot(data_dir: &Path, id: &str) -> Result<Value, String> {
+    if id.contains(['/', '\\']) || snapshot_time(id).is_none() {
+        return Err(format!("Invalid backup id: {id}"));
+    }
+    let path = backup_dir(data_dir).join(id);
+    let raw = fs::read_to_string(&path).map_err(|e| format!("Failed to read {path:?}: {e}"))?;
+    serde_json::from_str(&raw).map_err(|e| format!("Failed to parse {path:?}: {e}"))
+}
+
+/// Replace the task store and plan data with the contents of snapshot `id`.
+/// The current data is snapshotted first so the restore itself can be undone.
+/// Returns the restored payload for the frontend to apply.
+pub fn restore(data_dir: &Path, id: &str) -> Result<Value, String> {
+    let mut payload = read_snapshot(data_dir, id)?;
+    create_snapshot(data_dir, true)?;
+
+    let plan = match payload
+        .as_object_mut()
+        .and_then(|obj| obj.remove("planData"))
+    {
+        Some(Value::Object(plan)) => plan,
+        _ => serde_json::Map::new(),
+    };
+    let data: store::AppData = serde_json::from_value(payload.clone())
+        .map_err(|e| format!("Backup {id} is not valid task data: {e}"))?;
+    store::save(data_dir, &data)?;
+    store::save_plan(data_dir, &plan)?;
+
+    if let Some(obj) = payload.as_object_mut() {
+        obj.insert("planData".to_string(), Value::Object(plan));
+    }
+    Ok(payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Snapshot ids for `times` ("YYYY-MM-DD HH:MM"), sorted newest first.
+    fn snapshots(times: &[&str]) -> Vec<(String, DateTime<Utc>)> {
+        let mut snapshots: Vec<(String, DateTime<Utc>)> = times
+            .iter()
+            .map(|time| {
+                let time = NaiveDateTime::parse_from_str(time, "%Y-%m-%d %H:%M")
+                    .unwrap()
+                    .and_utc();
+                (snapshot_id(time), time)
+            })
+            .collect();
+        snapshots.sort_by_key(|(_, time)| std::cmp::Reverse(*time));
+        snapshots
+    }
+
+    fn policy(hourly: usize, daily: usize, weekly: usize) -> RetentionPolicy {
+        RetentionPolicy {
+            hourly,
+            daily,
+            weekly,
+        }
+    }
+
+    fn expired(snapshots: &[(String, DateTime<Utc>)], policy: RetentionPolicy) -> Vec<String> {
+        let mut expired: Vec<String> = expired_snapshots(snapshots, &policy)
+            .into_iter()
+            .map(|id| {
+                let time = snapshot_time(id).unwrap();
+                time.format("%Y-%m-%d %H:%M").to_string()
+            })
+            .collect();
+        expired.sort();
+        expired
+    }
+
+    #[test]
+    fn keeps_the_newest_snapshot_of_the_last_n_hours() {
+        let all = snapshots(&[
+            "2026-03-02 08:00",
+            "2026-03-02 09:00",
+            "2026-03-02 10:00",
+            "2026-03-02 11:00",
+            "2026-03-02 12:00",
+        ]);
+        assert_eq!(
+            expired(&all, policy(3, 0, 0)),
+            ["2026-03-02 08:00", "2026-03-02 09:00"]
+        );
+        // The daily bucket keeps the newest of the day, which is already kept.
+        assert_eq!(
+            expired(&all, policy(3, 1, 1)),
+            expired(&all, policy(3, 0, 0))
+        );
+        assert!(expired(&all, policy(5, 0, 0)).is_empty());
+    }
+
+    #[test]
+    fn keeps_the_newest_snapshot_per_day_and_week() {
+        let all = snapshots(&[
+            // Monday to Wednesday of ISO week 10, then Sunday of week 9.
+            "2026-03-01 18:00",
+            "2026-03-02 08:00",
+            "2026-03-02 20:00",
+            "2026-03-03 08:00",
+            "2026-03-04 08:00",
+        ]);
+        assert_eq!(
+            expired(&all, policy(1, 2, 2)),
+            ["2026-03-02 08:00", "2026-03-02 20:00"]
+        );
+        assert_eq!(
+            expired(&all, policy(1, 0, 2)),
+            ["2026-03-02 08:00", "2026-03-02 20:00", "2026-03-03 08:00"]
+        );
+    }
+
+    #[test]
+    fn treats_manual_snapshots_like_automatic_ones() {
+        // Hourly automatic snapshots plus manual ones taken with "Back up
+        // now" or before a restore; only the newest per hour is kept.
+        let all = snapshots(&[
+            "2026-03-02 09:00",
+            "2026-03-02 09:41",
+            "2026-03-02 10:00",
+            "2026-03-02 10:05",
+            "2026-03-02 10:30",
+        ]);
+        assert_eq!(
+            expired(&all, policy(2, 0, 0)),
+            ["2026-03-02 09:00", "2026-03-02 10:00", "2026-03-02 10:05"]
+        );
+    }
+
+    #[test]
+    fn always_keeps_the_newest_snapshot() {
+        let all = snapshots(&["2026-03-02 09:00", "2026-03-02 10:00"]);
+        assert_eq!(expired(&all, policy(0, 0, 0)), ["2026-03-02 09:00"]);
+    }
+
+    #[test]
+    fn handles_an_empty_backup_dir() {
+        assert!(expired_snapshots(&[], &RetentionPolicy::default()).is_empty());
+
+        let dir = tempfile::tempdir().unwrap();
+        assert!(list(dir.path()).is_empty());
+        assert_eq!(prune(dir.path(), &policy(0, 0, 0)), 0);
+
+        // Other files in the directory are not snapshots.
+        fs::create_dir_all(backup_dir(dir.path())).unwrap();
+        fs::write(backup_dir(dir.path()).join("notes.json"), "{}").unwrap();
+        assert!(list(dir.path()).is_empty());
+        assert_eq!(prune(dir.path(), &policy(0, 0, 0)), 0);
+    }
+
+    #[test]
+    fn forced_snapshots_in_the_same_second_do_not_collide() {
+        let dir = tempfile::tempdir().unwrap();
+        store::save(dir.path(), &store::AppData::default()).unwrap();
+
+        let first = create_snapshot(dir.path(), true).unwrap().unwrap();
+        let second = create_snapshot(dir.path(), true).unwrap().unwrap();
+        assert_ne!(first, second);
+        let ids: Vec<String> = list(dir.path()).into_iter().map(|info| info.id).collect();
+        assert_eq!(ids, [second, first]);
+    }
+
+    #[test]
+    fn reads_snapshot_ids_without_milliseconds() {
+        let time = snapshot_time("redd-todo-backup-20260302T101500Z.json").unwrap();
+        assert_eq!(
+            time.format("%Y-%m-%d %H:%M:%S").to_string(),
+            "2026-03-02 10:15:00"
+        );
+    }
+
+    #[test]
+    fn keeps_settings_outside_the_backup_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let settings = BackupSettings {
+            interval_minutes: 30,
+            ..BackupSettings::default()
+        };
+        save_settings(dir.path(), &settings).unwrap();
+        assert!(dir.path().join(SETTINGS_FILE_NAME).is_file());
+        assert!(!backup_dir(dir.path()).exists());
+        assert_eq!(load_settings(dir.path()), settings);
+    }
+
+    #[test]
+    fn reads_settings_from_their_old_place_in_the_backup_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(backup_dir(dir.path())).unwrap();
+        fs::write(
+            backup_dir(dir.path()).join(LEGACY_SETTINGS_FILE_NAME),
+            r#"{"enabled":false}"#,
+        )
+        .unwrap();
+        assert!(!load_settings(dir.path()).enabled);
+    }
+}
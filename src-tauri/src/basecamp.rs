@@ -0,0 +1,1110 @@
+//! Basecamp 3 API client.
+//!
+//! Typed access to projects, to-do sets, to-do lists, to-dos, completion and
+//! comments. List endpoints are paginated with `Link: <…>; rel="next"`
+//! headers, which [`Client`] follows. Rate-limited (429) and briefly
+//! unavailable (502–504) responses are retried after their `Retry-After`
+//! delay, and a rejected access token is refreshed once before giving up.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use reqwest::{header, Method, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fmt;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+pub const API_URL: &str = "https://3.basecampapi.com";
+pub const AUTHORIZATION_URL: &str = "https://launchpad.37signals.com/authorization.json";
+/// Netlify function that refreshes tokens, so the client secret stays
+/// server-side.
+pub const REFRESH_URL: &str = "https://redd-todo.netlify.app/.netlify/functions/auth";
+
+/// Basecamp asks API clients to name themselves and give a contact.
+const USER_AGENT: &str = "ReDD To-Do (team@reddfocus.org)";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+/// Tries per request while rate limited or the service is unavailable.
+const MAX_ATTEMPTS: u32 = 4;
+/// Longest `Retry-After` worth waiting for; past it the call fails.
+const MAX_RETRY_WAIT: Duration = Duration::from_secs(30);
+/// How much of an error response's body to keep in the message.
+const MAX_ERROR_BODY: usize = 300;
+/// How long before expiry to refresh an access token.
+const REFRESH_AHEAD: chrono::Duration = chrono::Duration::days(1);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BasecampError {
+    /// The access token was rejected and couldn't be refreshed.
+    Unauthorized,
+    NotFound,
+    /// Still rate limited after retrying, or asked to wait too long.
+    RateLimited {
+        retry_after: Duration,
+    },
+    Status {
+        status: u16,
+        message: String,
+    },
+    Network(String),
+    Decode(String),
+}
+
+impl fmt::Display for BasecampError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unauthorized => write!(f, "Basecamp authorization expired; please reconnect"),
+            Self::NotFound => write!(f, "Not found in Basecamp"),
+            Self::RateLimited { retry_after } => write!(
+                f,
+                "Basecamp rate limit reached; try again in {} s",
+                retry_after.as_secs().max(1)
+            ),
+            Self::Status { status, message } if message.is_empty() => {
+                write!(f, "Basecamp request failed ({status})")
+            }
+            Self::Status { status, message } => {
+                write!(f, "Basecamp request failed ({status}): {message}")
+            }
+            Self::Network(e) => write!(f, "Could not reach Basecamp: {e}"),
+            Self::Decode(e) => write!(f, "Unexpected response from Basecamp: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for BasecampError {}
+
+pub type Result<T> = std::result::Result<T, BasecampError>;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Tokens {
+    pub access_token: String,
+    #[serde(default)]
+    pub refresh_token: Option<String>,
+    /// Seconds the access token is valid for, counted from when it was issued.
+    #[serde(default)]
+    pub expires_in: Option<u64>,
+}
+
+/// The signed-in Basecamp connection the app keeps, encrypted, in its data
+/// directory; see [`crate::secrets`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Credentials {
+    pub account_id: Option<u64>,
+    pub email: Option<String>,
+    pub access_token: String,
+    pub refresh_token: Option<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+    /// When to refresh the access token, ahead of `expires_at`.
+    pub refresh_at: Option<DateTime<Utc>>,
+}
+
+impl Credentials {
+    pub fn new(tokens: Tokens, now: DateTime<Utc>) -> Self {
+        let mut credentials = Self {
+            account_id: None,
+            email: None,
+            access_token: String::new(),
+            refresh_token: None,
+            expires_at: None,
+            refresh_at: None,
+        };
+        credentials.update(tokens, now);
+        credentials
+    }
+
+    /// Take fresh tokens, keeping the refresh token if none came with them.
+    pub fn update(&mut self, tokens: Tokens, now: DateTime<Utc>) {
+        self.access_token = tokens.access_token;
+        self.refresh_token = tokens.refresh_token.or(self.refresh_token.take());
+        let lifetime = tokens
+            .expires_in
+            .and_then(|seconds| chrono::Duration::try_seconds(seconds as i64));
+        self.expires_at = lifetime.map(|lifetime| now + lifetime);
+        // A day ahead, or halfway through for short-lived tokens.
+        self.refresh_at = lifetime.map(|lifetime| now + lifetime - REFRESH_AHEAD.min(lifetime / 2));
+    }
+
+    pub fn tokens(&self) -> Tokens {
+        Tokens {
+            access_token: self.access_token.clone(),
+            refresh_token: self.refresh_token.clone(),
+            expires_in: None,
+        }
+    }
+
+    pub fn needs_refresh(&self, now: DateTime<Utc>) -> bool {
+        self.refresh_token.is_some() && self.refresh_at.is_some_and(|at| at <= now)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Authorization {
+    pub identity: Identity,
+    #[serde(default)]
+    pub accounts: Vec<Account>,
+}
+
+impl Authorization {
+    /// The account to sync with: the first Basecamp 3 or 4 account, since
+    /// launchpad also lists other 37signals products.
+    pub fn basecamp_account(&self) -> Option<&Account> {
+        self.accounts
+            .iter()
+            .find(|account| account.product.as_deref() == Some("bc3"))
+            .or_else(|| self.accounts.first())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Identity {
+    pub id: u64,
+    #[serde(default)]
+    pub first_name: Option<String>,
+    #[serde(default)]
+    pub last_name: Option<String>,
+    pub email_address: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Account {
+    pub id: u64,
+    pub name: String,
+    #[serde(default)]
+    pub product: Option<String>,
+    #[serde(default)]
+    pub href: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Project {
+    pub id: u64,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// The tools in the project, including its to-do set.
+    #[serde(default)]
+    pub dock: Vec<DockItem>,
+}
+
+impl Project {
+    pub fn todoset_id(&self) -> Option<u64> {
+        self.dock
+            .iter()
+            .find(|item| item.name == "todoset")
+            .map(|item| item.id)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DockItem {
+    pub id: u64,
+    pub name: String,
+    #[serde(default)]
+    pub title: Option<String>,
+    pub url: String,
+    #[serde(default)]
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Todoset {
+    pub id: u64,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub todolists_count: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Todolist {
+    pub id: u64,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub completed: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Todo {
+    pub id: u64,
+    pub content: String,
+    /// Rich-text notes; empty when there are none.
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub completed: bool,
+    #[serde(default)]
+    pub completion: Option<Completion>,
+    #[serde(default)]
+    pub due_on: Option<NaiveDate>,
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Completion {
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub creator: Option<Person>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Comment {
+    pub id: u64,
+    pub content: String,
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub creator: Option<Person>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Person {
+    pub id: u64,
+    pub name: String,
+    #[serde(default)]
+    pub email_address: Option<String>,
+}
+
+/// Fields of a to-do to create or update. Basecamp needs `content` on
+/// updates too.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TodoParams {
+    pub content: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+fn http_client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .user_agent(USER_AGENT)
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .unwrap_or_default()
+}
+
+/// The URL of the `rel="next"` page in a `Link` header.
+pub fn next_link(header: &str) -> Option<String> {
+    header.split(',').find_map(|link| {
+        let (target, params) = link.trim().split_once(';')?;
+        let is_next = params.split(';').any(|param| {
+            param.trim().strip_prefix("rel=").is_some_and(|rel| {
+                rel.trim_matches('"')
+                    .split_whitespace()
+                    .any(|r| r == "next")
+            })
+        });
+        let url = target.trim().strip_prefix('<')?.strip_suffix('>')?;
+        is_next.then(|| url.to_string())
+    })
+}
+
+/// A `Retry-After` value, given either in seconds or as an HTTP date.
+pub fn parse_retry_after(value: &str, now: DateTime<Utc>) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let at = DateTime::parse_from_rfc2822(value).ok()?;
+    Some(
+        (at.with_timezone(&Utc) - now)
+            .to_std()
+            .unwrap_or(Duration::ZERO),
+    )
+}
+
+fn retry_after(response: &reqwest::Response) -> Option<Duration> {
+    let value = response.headers().get(header::RETRY_AFTER)?.to_str().ok()?;
+    parse_retry_after(value, Utc::now())
+}
+
+/// Rate limits are always retried. Gateway errors are retried only for
+/// idempotent methods: a POST that timed out may still have created the
+/// record, and sending it again would make a duplicate.
+fn is_retryable(method: &Method, status: StatusCode) -> bool {
+    match status {
+        StatusCode::TOO_MANY_REQUESTS => true,
+        StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE | StatusCode::GATEWAY_TIMEOUT => {
+            *method != Method::POST
+        }
+        _ => false,
+    }
+}
+
+async fn error_for(response: reqwest::Response) -> BasecampError {
+    let status = response.status();
+    match status {
+        StatusCode::UNAUTHORIZED => BasecampError::Unauthorized,
+        StatusCode::NOT_FOUND => BasecampError::NotFound,
+        StatusCode::TOO_MANY_REQUESTS => BasecampError::RateLimited {
+            retry_after: retry_after(&response).unwrap_or(MAX_RETRY_WAIT),
+        },
+        _ => {
+            let body = response.text().await.unwrap_or_default();
+            BasecampError::Status {
+                status: status.as_u16(),
+                message: body.trim().chars().take(MAX_ERROR_BODY).collect(),
+            }
+        }
+    }
+}
+
+async fn decode<T: DeserializeOwned>(response: reqwest::Response) -> Result<T> {
+    response
+        .json()
+        .await
+        .map_err(|e| BasecampError::Decode(e.to_string()))
+}
+
+fn network(e: reqwest::Error) -> BasecampError {
+    BasecampError::Network(e.to_string())
+}
+
+/// The signed-in user and the Basecamp accounts they can use.
+pub async fn authorization(url: &str, access_token: &str) -> Result<Authorization> {
+    let response = http_client()
+        .get(url)
+        .bearer_auth(access_token)
+        .send()
+        .await
+        .map_err(network)?;
+    if !response.status().is_success() {
+        return Err(error_for(response).await);
+    }
+    decode(response).await
+}
+
+/// Exchange a refresh token for a new access token.
+pub async fn refresh_tokens(url: &str, refresh_token: &str) -> Result<Tokens> {
+    let response = http_client()
+        .post(url)
+        .json(&json!({ "refresh_token": refresh_token }))
+        .send()
+        .await
+        .map_err(network)?;
+    let status = response.status();
+    if status.is_client_error() {
+        // The refresh token itself was rejected.
+        return Err(BasecampError::Unauthorized);
+    }
+    if !status.is_success() {
+        return Err(error_for(response).await);
+    }
+    decode(response).await
+}
+
+/// Refreshes the access token for every client of a sign-in. Refreshes run
+/// one at a time, and a caller whose token was replaced while it waited gets
+/// the replacement instead of spending the refresh token again.
+pub struct Refresher {
+    url: String,
+    /// The access token last replaced, and what replaced it.
+    last: tokio::sync::Mutex<Option<(String, Tokens)>>,
+}
+
+impl Refresher {
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            last: tokio::sync::Mutex::new(None),
+        }
+    }
+
+    /// Fresh tokens to replace `current`, keeping its refresh token if none
+    /// came with them.
+    pub async fn refresh(&self, current: &Tokens) -> Result<Tokens> {
+        let mut last = self.last.lock().await;
+        if let Some((replaced, fresh)) = last.as_ref() {
+            if *replaced == current.access_token {
+                return Ok(fresh.clone());
+            }
+        }
+        let Some(refresh_token) = current.refresh_token.clone() else {
+            return Err(BasecampError::Unauthorized);
+        };
+        let mut fresh = refresh_tokens(&self.url, &refresh_token).await?;
+        fresh.refresh_token = fresh.refresh_token.or(Some(refresh_token));
+        *last = Some((current.access_token.clone(), fresh.clone()));
+        Ok(fresh)
+    }
+}
+
+/// A client for one Basecamp account.
+pub struct Client {
+    http: reqwest::Client,
+    /// `https://3.basecampapi.com/<account id>`
+    account_url: String,
+    refresher: Arc<Refresher>,
+    tokens: Mutex<Tokens>,
+    refreshed: Mutex<bool>,
+}
+
+impl Client {
+    pub fn new(account_id: u64, tokens: Tokens, refresher: Arc<Refresher>) -> Self {
+        Self::with_urls(account_id, tokens, API_URL, refresher)
+    }
+
+    /// A client against another API endpoint, such as a mock server.
+    pub fn with_urls(
+        account_id: u64,
+        tokens: Tokens,
+        api_url: &str,
+        refresher: Arc<Refresher>,
+    ) -> Self {
+        Self {
+            http: http_client(),
+            account_url: format!("{}/{account_id}", api_url.trim_end_matches('/')),
+            refresher,
+            tokens: Mutex::new(tokens),
+            refreshed: Mutex::new(false),
+        }
+    }
+
+    /// The current tokens if they were refreshed during a call.
+    pub fn refreshed_tokens(&self) -> Option<Tokens> {
+        let refreshed = *self
+            .refreshed
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        refreshed.then(|| self.tokens().clone())
+    }
+
+    fn tokens(&self) -> std::sync::MutexGuard<'_, Tokens> {
+        self.tokens.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{path}", self.account_url)
+    }
+
+    async fn refresh(&self) -> Result<()> {
+        let current = self.tokens().clone();
+        let fresh = self.refresher.refresh(&current).await?;
+        *self.tokens() = fresh;
+        *self
+            .refreshed
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = true;
+        Ok(())
+    }
+
+    /// Send a request, refreshing the token on a 401 and waiting out rate
+    /// limits. Only successful responses are returned.
+    async fn send(
+        &self,
+        method: Method,
+        url: &str,
+        body: Option<&Value>,
+    ) -> Result<reqwest::Response> {
+        let mut refreshed = false;
+        let mut attempt = 1;
+        loop {
+            let access_token = self.tokens().access_token.clone();
+            let mut request = self
+                .http
+                .request(method.clone(), url)
+                .bearer_auth(access_token);
+            if let Some(body) = body {
+                request = request.json(body);
+            }
+            let response = request.send().await.map_err(network)?;
+            let status = response.status();
+            if status.is_success() {
+                return Ok(response);
+            }
+            if status == StatusCode::UNAUTHORIZED && !refreshed {
+                self.refresh().await?;
+                refreshed = true;
+                continue;
+            }
+            if is_retryable(&method, status) && attempt < MAX_ATTEMPTS {
+                let wait =
+                    retry_after(&response).unwrap_or(Duration::from_secs(1 << (attempt - 1)));
+                if wait <= MAX_RETRY_WAIT {
+                    log::warn!("[Basecamp] {status} from {url}; retrying in {wait:?}");
+                    tokio::time::sleep(wait).await;
+                    attempt += 1;
+                    continue;
+                }
+            }
+            return Err(error_for(response).await);
+        }
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
+        decode(self.send(Method::GET, &self.url(path), None).await?).await
+    }
+
+    /// Every page of a list endpoint. Only links within the account are
+    /// followed, since they get the access token.
+    async fn get_all<T: DeserializeOwned>(&self, path: &str) -> Result<Vec<T>> {
+        let mut url = self.url(path);
+        let mut items = Vec::new();
+        loop {
+            let response = self.send(Method::GET, &url, None).await?;
+            let next = response
+                .headers()
+                .get(header::LINK)
+                .and_then(|link| link.to_str().ok())
+                .and_then(next_link)
+                .filter(|next| next.starts_with(&format!("{}/", self.account_url)) && *next != url);
+            items.extend(decode::<Vec<T>>(response).await?);
+            match next {
+                Some(next) => url = next,
+                None => return Ok(items),
+            }
+        }
+    }
+
+    async fn send_json<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        body: &Value,
+    ) -> Result<T> {
+        decode(self.send(method, &self.url(path), Some(body)).await?).await
+    }
+
+    async fn send_empty(&self, method: Method, path: &str, body: Option<&Value>) -> Result<()> {
+        self.send(method, &self.url(path), body).await.map(|_| ())
+    }
+
+    pub async fn projects(&self) -> Result<Vec<Project>> {
+        self.get_all("/projects.json").await
+    }
+
+    pub async fn project(&self, project_id: u64) -> Result<Project> {
+        self.get(&format!("/projects/{project_id}.json")).await
+    }
+
+    pub async fn project_people(&self, project_id: u64) -> Result<Vec<Person>> {
+        self.get_all(&format!("/projects/{project_id}/people.json"))
+            .await
+    }
+
+    pub async fn todoset(&self, project_id: u64, todoset_id: u64) -> Result<Todoset> {
+        self.get(&format!("/buckets/{project_id}/todosets/{todoset_id}.json"))
+            .await
+    }
+
+    pub async fn todolists(&self, project_id: u64, todoset_id: u64) -> Result<Vec<Todolist>> {
+        self.get_all(&format!(
+            "/buckets/{project_id}/todosets/{todoset_id}/todolists.json"
+        ))
+        .await
+    }
+
+    /// The to-do lists of a project's to-do set; none if the project has no
+    /// to-dos tool.
+    pub async fn project_todolists(&self, project_id: u64) -> Result<Vec<Todolist>> {
+        match self.project(project_id).await?.todoset_id() {
+            Some(todoset_id) => self.todolists(project_id, todoset_id).await,
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Open to-dos, or completed ones with `completed`.
+    pub async fn todos(
+        &self,
+        project_id: u64,
+        todolist_id: u64,
+        completed: bool,
+    ) -> Result<Vec<Todo>> {
+        let query = if completed { "?completed=true" } else { "" };
+        self.get_all(&format!(
+            "/buckets/{project_id}/todolists/{todolist_id}/todos.json{query}"
+        ))
+        .await
+    }
+
+    pub async fn todo(&self, project_id: u64, todo_id: u64) -> Result<Todo> {
+        self.get(&format!("/buckets/{project_id}/todos/{todo_id}.json"))
+            .await
+    }
+
+    pub async fn create_todo(
+        &self,
+        project_id: u64,
+        todolist_id: u64,
+        params: &TodoParams,
+    ) -> Result<Todo> {
+        self.send_json(
+            Method::POST,
+            &format!("/buckets/{project_id}/todolists/{todolist_id}/todos.json"),
+            &json!(params),
+        )
+        .await
+    }
+
+    pub async fn update_todo(
+        &self,
+        project_id: u64,
+        todo_id: u64,
+        params: &TodoParams,
+    ) -> Result<Todo> {
+        self.send_json(
+            Method::PUT,
+            &format!("/buckets/{project_id}/todos/{todo_id}.json"),
+            &json!(params),
+        )
+        .await
+    }
+
+    pub async fn set_completed(
+        &self,
+        project_id: u64,
+        todo_id: u64,
+        completed: bool,
+    ) -> Result<()> {
+        let method = if completed {
+            Method::POST
+        } else {
+            Method::DELETE
+        };
+        self.send_empty(
+            method,
+            &format!("/buckets/{project_id}/todos/{todo_id}/completion.json"),
+            None,
+        )
+        .await
+    }
+
+    /// Move a to-do, comment or other recording to the trash.
+    pub async fn trash(&self, project_id: u64, recording_id: u64) -> Result<()> {
+        self.send_empty(
+            Method::PUT,
+            &format!("/buckets/{project_id}/recordings/{recording_id}/status/trashed.json"),
+            None,
+        )
+        .await
+    }
+
+    /// Move a to-do to another list, possibly in another project. Returns
+    /// the to-do's id afterwards: when Basecamp won't re-parent it, it is
+    /// copied to the target list and the original trashed.
+    pub async fn move_todo(
+        &self,
+        project_id: u64,
+        todo_id: u64,
+        target_project_id: u64,
+        target_todolist_id: u64,
+    ) -> Result<u64> {
+        let mut parent = json!({ "id": target_todolist_id, "type": "Todolist" });
+        if target_project_id != project_id {
+            parent["bucket_id"] = json!(target_project_id);
+        }
+        let moved = self
+            .send_empty(
+                Method::PUT,
+                &format!("/buckets/{project_id}/recordings/{todo_id}/parent.json"),
+                Some(&json!({ "parent": parent })),
+            )
+            .await;
+        match moved {
+            Ok(()) => return Ok(todo_id),
+            Err(e @ (BasecampError::Unauthorized | BasecampError::Network(_))) => return Err(e),
+            Err(e) => log::warn!("[Basecamp] Move failed ({e}); copying the to-do instead"),
+        }
+        let todo = self.todo(project_id, todo_id).await?;
+        let params = TodoParams {
+            content: todo.content,
+            description: Some(todo.description).filter(|d| !d.is_empty()),
+        };
+        let copy = self
+            .create_todo(target_project_id, target_todolist_id, &params)
+            .await?;
+        if todo.completed {
+            self.set_completed(target_project_id, copy.id, true).await?;
+        }
+        if let Err(e) = self.trash(project_id, todo_id).await {
+            log::warn!("[Basecamp] Copied to-do {todo_id} but could not trash it: {e}");
+        }
+        Ok(copy.id)
+    }
+
+    pub async fn comments(&self, project_id: u64, recording_id: u64) -> Result<Vec<Comment>> {
+        self.get_all(&format!(
+            "/buckets/{project_id}/recordings/{recording_id}/comments.json"
+        ))
+        .await
+    }
+
+    pub async fn create_comment(
+        &self,
+        project_id: u64,
+        recording_id: u64,
+        content: &str,
+    ) -> Result<Comment> {
+        self.send_json(
+            Method::POST,
+            &format!("/buckets/{project_id}/recordings/{recording_id}/comments.json"),
+            &json!({ "content": content }),
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::thread;
+
+    /// A request the mock server received.
+    #[derive(Debug)]
+    struct Seen {
+        method: String,
+        url: String,
+        authorization: Option<String>,
+        body: String,
+    }
+
+    /// A canned response: status, extra headers and body.
+    type Reply = (u16, Vec<(&'static str, String)>, String);
+
+    fn mock(replies: Vec<Reply>) -> (String, mpsc::Receiver<Seen>) {
+        mock_with(|_| replies)
+    }
+
+    /// Serve the replies, built from the server's URL, in order on a local
+    /// port; the receiver gets each request as it comes in.
+    fn mock_with(replies: impl FnOnce(&str) -> Vec<Reply>) -> (String, mpsc::Receiver<Seen>) {
+        let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", server.server_addr().to_ip().unwrap());
+        let replies = replies(&url);
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            for (status, headers, body) in replies {
+                let Ok(mut request) = server.recv() else {
+                    return;
+                };
+                let mut seen_body = String::new();
+                let _ = request.as_reader().read_to_string(&mut seen_body);
+                let authorization = request
+                    .headers()
+                    .iter()
+                    .find(|h| h.field.equiv("Authorization"))
+                    .map(|h| h.value.to_string());
+                let _ = sender.send(Seen {
+                    method: request.method().to_string(),
+                    url: request.url().to_string(),
+                    authorization,
+                    body: seen_body,
+                });
+                let mut response = tiny_http::Response::from_string(body).with_status_code(status);
+                for (name, value) in headers {
+                    response.add_header(tiny_http::Header::from_bytes(name, value).unwrap());
+                }
+                let _ = request.respond(response);
+            }
+        });
+        (url, receiver)
+    }
+
+    fn ok(body: Value) -> Reply {
+        (200, vec![], body.to_string())
+    }
+
+    fn tokens(refresh_token: Option<&str>) -> Tokens {
+        Tokens {
+            access_token: "old".to_string(),
+            refresh_token: refresh_token.map(str::to_string),
+            expires_in: None,
+        }
+    }
+
+    fn no_refresh() -> Arc<Refresher> {
+        Arc::new(Refresher::new(""))
+    }
+
+    fn run<T>(future: impl std::future::Future<Output = T>) -> T {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    fn todo(id: u64, content: &str) -> Value {
+        json!({ "id": id, "content": content, "completed": false, "description": "" })
+    }
+
+    #[test]
+    fn schedules_refresh_ahead_of_expiry() {
+        let now = DateTime::parse_from_rfc3339("2026-03-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let hours = |h: i64| chrono::Duration::hours(h);
+        let mut credentials = Credentials::new(
+            Tokens {
+                access_token: "a".to_string(),
+                refresh_token: Some("r".to_string()),
+                expires_in: Some(14 * 24 * 3600),
+            },
+            now,
+        );
+        assert_eq!(credentials.expires_at, Some(now + hours(14 * 24)));
+        assert_eq!(credentials.refresh_at, Some(now + hours(13 * 24)));
+        assert!(!credentials.needs_refresh(now + hours(13 * 24 - 1)));
+        assert!(credentials.needs_refresh(now + hours(13 * 24)));
+
+        credentials.update(
+            Tokens {
+                access_token: "b".to_string(),
+                refresh_token: None,
+                expires_in: Some(2 * 3600),
+            },
+            now,
+        );
+        assert_eq!(credentials.refresh_token.as_deref(), Some("r"));
+        assert_eq!(credentials.refresh_at, Some(now + hours(1)));
+
+        credentials.update(tokens(None), now);
+        assert_eq!(credentials.refresh_at, None);
+        assert!(!credentials.needs_refresh(now + hours(1000)));
+    }
+
+    #[test]
+    fn parses_link_and_retry_after_headers() {
+        assert_eq!(
+            next_link(r#"<https://3.basecampapi.com/1/projects.json?page=2>; rel="next""#),
+            Some("https://3.basecampapi.com/1/projects.json?page=2".to_string())
+        );
+        assert_eq!(
+            next_link(r#"<https://x/a?page=1>; rel="prev", <https://x/a?page=3>; rel="next""#),
+            Some("https://x/a?page=3".to_string())
+        );
+        assert_eq!(next_link(r#"<https://x/a?page=1>; rel="prev""#), None);
+
+        let now = DateTime::parse_from_rfc3339("2015-10-21T07:28:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(parse_retry_after("12", now), Some(Duration::from_secs(12)));
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:30 GMT", now),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:27:00 GMT", now),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(parse_retry_after("soon", now), None);
+    }
+
+    #[test]
+    fn follows_pagination_within_the_account() {
+        // The second page links outside the account, which is not followed.
+        let (url, seen) = mock_with(|url| {
+            let next = format!(r#"<{url}/99/buckets/5/todolists/7/todos.json?page=2>; rel="next""#);
+            let outside = r#"<https://example.com/steal>; rel="next""#.to_string();
+            vec![
+                (
+                    200,
+                    vec![("Link", next)],
+                    json!([todo(1, "One")]).to_string(),
+                ),
+                (
+                    200,
+                    vec![("Link", outside)],
+                    json!([todo(2, "Two")]).to_string(),
+                ),
+            ]
+        });
+        let client = Client::with_urls(99, tokens(None), &url, no_refresh());
+        let todos = run(client.todos(5, 7, true)).unwrap();
+        assert_eq!(
+            todos.iter().map(|t| t.content.as_str()).collect::<Vec<_>>(),
+            ["One", "Two"]
+        );
+        let first = seen.recv().unwrap();
+        assert_eq!(
+            first.url,
+            "/99/buckets/5/todolists/7/todos.json?completed=true"
+        );
+        assert_eq!(first.authorization.as_deref(), Some("Bearer old"));
+        assert_eq!(
+            seen.recv().unwrap().url,
+            "/99/buckets/5/todolists/7/todos.json?page=2"
+        );
+        assert!(seen.try_recv().is_err());
+    }
+
+    #[test]
+    fn waits_out_rate_limits() {
+        let (url, seen) = mock(vec![
+            (429, vec![("Retry-After", "0".to_string())], String::new()),
+            (503, vec![("Retry-After", "0".to_string())], String::new()),
+            ok(json!([{ "id": 1, "name": "Launch", "dock": [] }])),
+        ]);
+        let client = Client::with_urls(99, tokens(None), &url, no_refresh());
+        let projects = run(client.projects()).unwrap();
+        assert_eq!(projects[0].name, "Launch");
+        assert_eq!(seen.try_iter().count(), 3);
+
+        let (url, _seen) = mock(vec![(
+            429,
+            vec![("Retry-After", "3600".to_string())],
+            String::new(),
+        )]);
+        let client = Client::with_urls(99, tokens(None), &url, no_refresh());
+        assert_eq!(
+            run(client.projects()),
+            Err(BasecampError::RateLimited {
+                retry_after: Duration::from_secs(3600)
+            })
+        );
+    }
+
+    #[test]
+    fn does_not_resend_a_post_after_a_gateway_error() {
+        let (url, seen) = mock(vec![
+            (504, vec![("Retry-After", "0".to_string())], String::new()),
+            ok(todo(3, "Ship it")),
+        ]);
+        let client = Client::with_urls(99, tokens(None), &url, no_refresh());
+        let params = TodoParams {
+            content: "Ship it".to_string(),
+            description: None,
+        };
+        assert!(run(client.create_todo(5, 7, &params)).is_err());
+        assert_eq!(seen.recv().unwrap().method, "POST");
+        assert!(seen.try_recv().is_err());
+
+        let (url, seen) = mock(vec![
+            (429, vec![("Retry-After", "0".to_string())], String::new()),
+            (201, vec![], todo(3, "Ship it").to_string()),
+        ]);
+        let client = Client::with_urls(99, tokens(None), &url, no_refresh());
+        assert_eq!(run(client.create_todo(5, 7, &params)).unwrap().id, 3);
+        assert_eq!(seen.try_iter().count(), 2);
+    }
+
+    #[test]
+    fn refreshes_a_rejected_token_once() {
+        let (refresh_url, refresh_seen) = mock(vec![ok(
+            json!({ "access_token": "new", "expires_in": 1209600 }),
+        )]);
+        let (url, seen) = mock(vec![(401, vec![], String::new()), ok(todo(3, "Ship it"))]);
+        let client = Client::with_urls(
+            99,
+            tokens(Some("refresh")),
+            &url,
+            Arc::new(Refresher::new(&refresh_url)),
+        );
+        assert_eq!(client.refreshed_tokens(), None);
+        let created = run(client.create_todo(
+            5,
+            7,
+            &TodoParams {
+                content: "Ship it".to_string(),
+                description: None,
+            },
+        ))
+        .unwrap();
+        assert_eq!(created.id, 3);
+        assert_eq!(
+            client.refreshed_tokens(),
+            Some(Tokens {
+                access_token: "new".to_string(),
+                refresh_token: Some("refresh".to_string()),
+                expires_in: Some(1209600),
+            })
+        );
+        let refresh = refresh_seen.recv().unwrap();
+        assert_eq!(refresh.method, "POST");
+        assert!(refresh.body.contains("\"refresh_token\":\"refresh\""));
+        let requests: Vec<Seen> = seen.try_iter().collect();
+        assert_eq!(requests[1].authorization.as_deref(), Some("Bearer new"));
+        assert_eq!(requests[1].body, r#"{"content":"Ship it"}"#);
+
+        let (url, _seen) = mock(vec![(401, vec![], String::new())]);
+        let client = Client::with_urls(99, tokens(None), &url, no_refresh());
+        assert_eq!(run(client.projects()), Err(BasecampError::Unauthorized));
+    }
+
+    #[test]
+    fn concurrent_rejections_refresh_once() {
+        let (refresh_url, refresh_seen) = mock(vec![
+            ok(json!({ "access_token": "new", "expires_in": 1209600 })),
+            ok(json!({ "access_token": "newer", "expires_in": 1209600 })),
+        ]);
+        // Rejects the old token however the two clients' requests interleave.
+        let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", server.server_addr().to_ip().unwrap());
+        thread::spawn(move || {
+            for request in server.incoming_requests() {
+                let fresh = request
+                    .headers()
+                    .iter()
+                    .any(|h| h.field.equiv("Authorization") && h.value == "Bearer new");
+                let status = if fresh { 200 } else { 401 };
+                let _ = request
+                    .respond(tiny_http::Response::from_string("[]").with_status_code(status));
+            }
+        });
+
+        let refresher = Arc::new(Refresher::new(&refresh_url));
+        let client = || Client::with_urls(99, tokens(Some("refresh")), &url, refresher.clone());
+        let (first, second) = (client(), client());
+        thread::scope(|scope| {
+            let a = scope.spawn(|| run(first.projects()));
+            let b = scope.spawn(|| run(second.projects()));
+            assert_eq!(a.join().unwrap(), Ok(Vec::new()));
+            assert_eq!(b.join().unwrap(), Ok(Vec::new()));
+        });
+
+        assert_eq!(refresh_seen.recv().unwrap().method, "POST");
+        assert!(refresh_seen.try_recv().is_err());
+        let refreshed = Some(Tokens {
+            access_token: "new".to_string(),
+            refresh_token: Some("refresh".to_string()),
+            expires_in: Some(1209600),
+        });
+        assert_eq!(first.refreshed_tokens(), refreshed);
+        assert_eq!(second.refreshed_tokens(), refreshed);
+    }
+
+    #[test]
+    fn reports_errors_and_copies_when_a_move_is_refused() {
+        let (url, _seen) = mock(vec![(422, vec![], "Content can't be blank".to_string())]);
+        let client = Client::with_urls(99, tokens(None), &url, no_refresh());
+        let error = run(client.create_comment(5, 3, "")).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Basecamp request failed (422): Content can't be blank"
+        );
+
+        let (url, seen) = mock(vec![
+            (403, vec![], String::new()),
+            ok(
+                json!({ "id": 3, "content": "Ship it", "completed": true, "description": "<div>Notes</div>" }),
+            ),
+            (201, vec![], todo(4, "Ship it").to_string()),
+            (204, vec![], String::new()),
+            (204, vec![], String::new()),
+        ]);
+        let client = Client::with_urls(99, tokens(None), &url, no_refresh());
+        assert_eq!(run(client.move_todo(5, 3, 6, 8)), Ok(4));
+        let requests: Vec<(String, String)> = seen
+            .try_iter()
+            .map(|seen| (seen.method, seen.url))
+            .collect();
+        let expected = [
+            ("PUT", "/99/buckets/5/recordings/3/parent.json"),
+            ("GET", "/99/buckets/5/todos/3.json"),
+            ("POST", "/99/buckets/6/todolists/8/todos.json"),
+            ("POST", "/99/buckets/6/todos/4/completion.json"),
+            ("PUT", "/99/buckets/5/recordings/3/status/trashed.json"),
+        ];
+        assert_eq!(
+            requests,
+            expected.map(|(method, url)| (method.to_string(), url.to_string()))
+        );
+    }
+}
@@ -0,0 +1,181 @@
+//! Quick capture: turning one line of text into a task.
+//!
+//! A trailing number is the task's expected duration in minutes, as in
+//! "Write email 15". `m`/`min` and `h` suffixes are accepted too
+//! ("Review PR 45m", "Deep work 2h"). A line that is only a number stays the
+//! task's text.
+
+use crate::store::{self, AppData, Task};
+
+/// Longest expected duration accepted from the trailing number, in minutes.
+pub const MAX_DURATION_MINUTES: u32 = 24 * 60;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CapturedTask {
+    pub text: String,
+    /// Minutes, if the line ended in a duration.
+    pub duration: Option<u32>,
+}
+
+fn parse_duration(token: &str) -> Option<u32> {
+    let lower = token.to_ascii_lowercase();
+    let (digits, scale) = if let Some(hours) = lower.strip_suffix('h') {
+        (hours, 60)
+    } else if let Some(minutes) = lower
+        .strip_suffix("min")
+        .or_else(|| lower.strip_suffix('m'))
+    {
+        (minutes, 1)
+    } else {
+        (lower.as_str(), 1)
+    };
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let minutes = digits.parse::<u32>().ok()?.checked_mul(scale)?;
+    (1..=MAX_DURATION_MINUTES)
+        .contains(&minutes)
+        .then_some(minutes)
+}
+
+/// Split `input` into task text and an optional trailing duration.
+pub fn parse_capture(input: &str) -> CapturedTask {
+    let input = input.trim();
+    if let Some((text, last)) = input.rsplit_once(char::is_whitespace) {
+        if let Some(duration) = parse_duration(last) {
+            let text = text.trim_end();
+            if !text.is_empty() {
+                return CapturedTask {
+                    text: text.to_string(),
+                    duration: Some(duration),
+                };
+            }
+        }
+    }
+    CapturedTask {
+        text: input.to_string(),
+        duration: None,
+    }
+}
+
+/// Add the task parsed from `input` to `tab_id`, falling back to the current
+/// tab and then the first one. Returns the tab used and the new task.
+pub fn add_captured_task(
+    data: &mut AppData,
+    input: &str,
+    tab_id: Option<&str>,
+) -> Result<(String, Task), String> {
+    let captured = parse_capture(input);
+    add_task(data, &captured.text, captured.duration, tab_id)
+}
+
+/// Add a task with `text` taken as is, like [`add_captured_task`] but with
+/// the duration given separately.
+pub fn add_task(
+    data: &mut AppData,
+    text: &str,
+    duration: Option<u32>,
+    tab_id: Option<&str>,
+) -> Result<(String, Task), String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err("Task text is empty".to_string());
+    }
+    let tab_id = tab_id
+        .filter(|id| data.tabs.contains_key(*id))
+        .map(str::to_string)
+        .or_else(|| {
+            data.current_tab_id
+                .clone()
+                .filter(|id| data.tabs.contains_key(id))
+        })
+        .or_else(|| data.tabs.keys().next().cloned())
+        .unwrap_or_else(|| data.add_tab("Tasks", None));
+
+    let task = Task {
+        id: data.next_task_id(),
+        text: text.to_string(),
+        created_at: Some(store::now_iso()),
+        expected_duration: duration.map(f64::from),
+        ..Task::default()
+    };
+    let tab = data
+        .tabs
+        .get_mut(&tab_id)
+        .ok_or_else(|| format!("Tab not found: {tab_id}"))?;
+    tab.tasks.push(task.clone());
+    Ok((tab_id, task))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn captured(text: &str, duration: Option<u32>) -> CapturedTask {
+        CapturedTask {
+            text: text.to_string(),
+            duration,
+        }
+    }
+
+    #[test]
+    fn trailing_number_is_the_duration() {
+        assert_eq!(
+            parse_capture("Write email 15"),
+            captured("Write email", Some(15))
+        );
+        assert_eq!(
+            parse_capture("  Review PR   45m "),
+            captured("Review PR", Some(45))
+        );
+        assert_eq!(
+            parse_capture("Deep work 2h"),
+            captured("Deep work", Some(120))
+        );
+        assert_eq!(parse_capture("Call 10min"), captured("Call", Some(10)));
+    }
+
+    #[test]
+    fn other_numbers_stay_in_the_text() {
+        assert_eq!(parse_capture("15"), captured("15", None));
+        assert_eq!(
+            parse_capture("Buy 3 apples"),
+            captured("Buy 3 apples", None)
+        );
+        assert_eq!(
+            parse_capture("Fix issue #42"),
+            captured("Fix issue #42", None)
+        );
+        assert_eq!(parse_capture("Sleep 0"), captured("Sleep 0", None));
+        assert_eq!(
+            parse_capture("Marathon 100h"),
+            captured("Marathon 100h", None)
+        );
+    }
+
+    #[test]
+    fn adds_to_the_requested_tab_or_the_current_one() {
+        let mut data = AppData::default();
+        let inbox = data.add_tab("Inbox", None);
+        let work = data.add_tab("Work", None);
+        data.current_tab_id = Some(work.clone());
+
+        let (tab, task) = add_captured_task(&mut data, "Write email 15", Some(&inbox)).unwrap();
+        assert_eq!(tab, inbox);
+        assert_eq!(task.text, "Write email");
+        assert_eq!(task.expected_duration, Some(15.0));
+        assert_eq!(data.tabs[&inbox].tasks, vec![task]);
+
+        let (tab, _) = add_captured_task(&mut data, "Plan week", Some("tab_gone")).unwrap();
+        assert_eq!(tab, work);
+        assert!(add_captured_task(&mut data, "   ", None).is_err());
+    }
+
+    #[test]
+    fn creates_a_tab_when_there_is_none() {
+        let mut data = AppData::default();
+        let (tab, task) = add_captured_task(&mut data, "First task", None).unwrap();
+        assert_eq!(data.tabs[&tab].name, "Tasks");
+        assert_eq!(task.id, "task_1");
+    }
+}
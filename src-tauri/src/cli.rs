@@ -0,0 +1,840 @@
+//! Command-line interface on the app binary.
+//!
+//! `redd-todo add|list|done|focus|export …` works on the task store directly
+//! while the app isn't running. When it is, commands that change tasks are
+//! passed to it through the single-instance callback and carried out as deep
+//! links, so the open windows never work from a stale copy. `focus` always
+//! needs the app, and so do changes to Basecamp and Reminders lists, which
+//! the app keeps in sync.
+//!
+//! `redd-todo mcp` serves the same commands to AI assistants; see [`crate::mcp`].
+
+use crate::capture::{self, MAX_DURATION_MINUTES};
+use crate::deep_link::{self, DeepLinkAction};
+use crate::formats::markdown::{self, Scope};
+use crate::formats::{format_minutes, ical, todotxt};
+use crate::mcp;
+use crate::store::{self, AppData, Tab};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Bundle identifier from tauri.conf.json, which names the data directory;
+/// set by build.rs.
+const APP_IDENTIFIER: &str = env!("APP_IDENTIFIER");
+/// Holds the pid of the running app; see [`instance_running`].
+pub const INSTANCE_FILE_NAME: &str = "instance.pid";
+
+pub const USAGE: &str = "\
+Usage: redd-todo [COMMAND]
+
+Without a command, ReDD To-Do opens as usual.
+
+Commands:
+  add <TEXT> [--minutes N] [--tab TAB]    Add a task
+  list [--tab TAB] [--all]                 List open tasks, or all with --all
+  done <TASK>                              Complete a task
+  focus <TASK>                             Open ReDD To-Do focused on a task
+  export [--format md|txt|ics] [--tab TAB] [--output FILE]
+                                           Export tasks (Markdown by default)
+  mcp [--read-only]                        Serve tasks to AI assistants over
+                                           MCP on stdin/stdout
+  help                                     Show this message
+
+TAB and TASK are ids (as shown by `list`) or names.
+";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    Markdown,
+    TodoTxt,
+    Ics,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Add {
+        text: String,
+        minutes: Option<u32>,
+        tab: Option<String>,
+    },
+    List {
+        tab: Option<String>,
+        include_completed: bool,
+    },
+    Done {
+        task: String,
+    },
+    Focus {
+        task: String,
+    },
+    Export {
+        format: ExportFormat,
+        tab: Option<String>,
+        output: Option<PathBuf>,
+    },
+    Mcp {
+        read_only: bool,
+    },
+    Help,
+}
+
+impl Command {
+    /// The deep link a running app carries this command out through; `None`
+    /// for commands that only read tasks.
+    pub fn deep_link(&self) -> Option<DeepLinkAction> {
+        match self.clone() {
+            Command::Add { text, minutes, tab } => Some(DeepLinkAction::Add { text, tab, minutes }),
+            Command::Done { task } => Some(DeepLinkAction::Complete { task }),
+            Command::Focus { task } => Some(DeepLinkAction::Focus { task }),
+            Command::List { .. } | Command::Export { .. } | Command::Mcp { .. } | Command::Help => {
+                None
+            }
+        }
+    }
+
+    /// Arguments that make the binary run this command.
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        let option = |args: &mut Vec<String>, name: &str, value: Option<String>| {
+            if let Some(value) = value {
+                args.push(format!("--{name}={value}"));
+            }
+        };
+        match self.clone() {
+            Command::Add { text, minutes, tab } => {
+                args.push("add".to_string());
+                option(&mut args, "minutes", minutes.map(|m| m.to_string()));
+                option(&mut args, "tab", tab);
+                args.extend(["--".to_string(), text]);
+            }
+            Command::List {
+                tab,
+                include_completed,
+            } => {
+                args.push("list".to_string());
+                option(&mut args, "tab", tab);
+                if include_completed {
+                    args.push("--all".to_string());
+                }
+            }
+            Command::Done { task } => args.extend(["done".to_string(), "--".to_string(), task]),
+            Command::Focus { task } => args.extend(["focus".to_string(), "--".to_string(), task]),
+            Command::Export {
+                format,
+                tab,
+                output,
+            } => {
+                let format = match format {
+                    ExportFormat::Markdown => "md",
+                    ExportFormat::TodoTxt => "txt",
+                    ExportFormat::Ics => "ics",
+                };
+                args.extend(["export".to_string(), format!("--format={format}")]);
+                option(&mut args, "tab", tab);
+                option(&mut args, "output", output.map(|o| o.display().to_string()));
+            }
+            Command::Mcp { read_only } => {
+                args.push("mcp".to_string());
+                if read_only {
+                    args.push("--read-only".to_string());
+                }
+            }
+            Command::Help => args.push("help".to_string()),
+        }
+        args
+    }
+}
+
+/// Positional arguments and `--name value` options of one subcommand.
+#[derive(Default)]
+struct Arguments {
+    positional: Vec<String>,
+    values: Vec<(&'static str, String)>,
+    flags: Vec<&'static str>,
+}
+
+impl Arguments {
+    fn parse(
+        command: &str,
+        args: impl Iterator<Item = String>,
+        value_options: &[&'static str],
+        flag_options: &[&'static str],
+    ) -> Result<Self, String> {
+        let mut parsed = Arguments::default();
+        let mut args = args;
+        let mut options_ended = false;
+        while let Some(arg) = args.next() {
+            let Some(option) = arg.strip_prefix("--").filter(|_| !options_ended) else {
+                parsed.positional.push(arg);
+                continue;
+            };
+            if option.is_empty() {
+                options_ended = true;
+                continue;
+            }
+            let (name, inline) = match option.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_string())),
+                None => (option, None),
+            };
+            if let Some(name) = value_options.iter().find(|known| **known == name) {
+                let value = inline
+                    .or_else(|| args.next())
+                    .ok_or_else(|| format!("--{name} needs a value"))?;
+                parsed.values.push((name, value));
+            } else if let Some(name) = flag_options
+                .iter()
+                .find(|known| **known == name && inline.is_none())
+            {
+                parsed.flags.push(name);
+            } else {
+                return Err(format!("Unknown option for {command}: {arg}"));
+            }
+        }
+        Ok(parsed)
+    }
+
+    fn value(&self, name: &str) -> Option<String> {
+        self.values
+            .iter()
+            .rev()
+            .find(|(option, _)| *option == name)
+            .map(|(_, value)| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.flags.contains(&name)
+    }
+
+    /// The positional arguments as one string, so names needn't be quoted.
+    fn text(&self) -> Option<String> {
+        Some(self.positional.join(" ").trim().to_string()).filter(|text| !text.is_empty())
+    }
+
+    fn no_positional(&self, command: &str) -> Result<(), String> {
+        match self.positional.first() {
+            Some(arg) => Err(format!("Unexpected argument for {command}: {arg}")),
+            None => Ok(()),
+        }
+    }
+}
+
+fn parse_minutes(value: String) -> Result<u32, String> {
+    value
+        .parse::<u32>()
+        .ok()
+        .filter(|minutes| (1..=MAX_DURATION_MINUTES).contains(minutes))
+        .ok_or_else(|| {
+            format!(
+                "--minutes must be a whole number from 1 to {MAX_DURATION_MINUTES}, not {value:?}"
+            )
+        })
+}
+
+fn parse_format(value: &str) -> Result<ExportFormat, String> {
+    match value.to_ascii_lowercase().as_str() {
+        "md" | "markdown" => Ok(ExportFormat::Markdown),
+        "txt" | "todotxt" | "todo.txt" => Ok(ExportFormat::TodoTxt),
+        "ics" | "ical" => Ok(ExportFormat::Ics),
+        _ => Err(format!(
+            "Unknown export format: {value} (use md, txt or ics)"
+        )),
+    }
+}
+
+/// Parse the arguments after the program name. `Ok(None)` means there is no
+/// command and the app should start as usual; that includes launches with a
+/// deep link or with flags the OS adds.
+pub fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Option<Command>, String> {
+    let mut args = args.into_iter();
+    let Some(name) = args.next() else {
+        return Ok(None);
+    };
+    let command = match name.as_str() {
+        "add" => {
+            let parsed = Arguments::parse("add", args, &["minutes", "tab"], &[])?;
+            Command::Add {
+                text: parsed.text().ok_or("add needs the task text")?,
+                minutes: parsed.value("minutes").map(parse_minutes).transpose()?,
+                tab: parsed.value("tab"),
+            }
+        }
+        "list" => {
+            let parsed = Arguments::parse("list", args, &["tab"], &["all"])?;
+            parsed.no_positional("list")?;
+            Command::List {
+                tab: parsed.value("tab"),
+                include_completed: parsed.flag("all"),
+            }
+        }
+        "done" | "focus" => {
+            let parsed = Arguments::parse(&name, args, &[], &[])?;
+            let task = parsed
+                .text()
+                .ok_or_else(|| format!("{name} needs a task id or name"))?;
+            if name == "done" {
+                Command::Done { task }
+            } else {
+                Command::Focus { task }
+            }
+        }
+        "export" => {
+            let parsed = Arguments::parse("export", args, &["format", "tab", "output"], &[])?;
+            parsed.no_positional("export")?;
+            Command::Export {
+                format: parsed
+                    .value("format")
+                    .map_or(Ok(ExportFormat::Markdown), |format| parse_format(&format))?,
+                tab: parsed.value("tab"),
+                output: parsed.value("output").map(PathBuf::from),
+            }
+        }
+        "mcp" => {
+            let parsed = Arguments::parse("mcp", args, &[], &["read-only"])?;
+            parsed.no_positional("mcp")?;
+            Command::Mcp {
+                read_only: parsed.flag("read-only"),
+            }
+        }
+        "help" | "--help" | "-h" => Command::Help,
+        _ if name.starts_with('-') || name.contains(':') => return Ok(None),
+        _ => return Err(format!("Unknown command: {name}")),
+    };
+    Ok(Some(command))
+}
+
+/// What running a command against the task store came to.
+#[derive(Debug, PartialEq)]
+pub enum Outcome {
+    /// Print this; the store is unchanged.
+    Output(String),
+    /// Save the store, then print this.
+    Changed(String),
+    /// Only the app can do this, so start it (or pass the command on).
+    NeedsApp,
+}
+
+/// Basecamp and Reminders lists are synced by the app, so changes to them
+/// have to go through it.
+fn is_synced(tab: &Tab) -> bool {
+    ["basecampListId", "remindersListId"]
+        .iter()
+        .any(|key| tab.extra.get(*key).is_some_and(|id| !id.is_null()))
+}
+
+fn resolve_tab(data: &AppData, tab: Option<&str>) -> Result<Option<String>, String> {
+    tab.map(|tab| deep_link::resolve_tab(data, tab).map_err(|e| e.to_string()))
+        .transpose()
+}
+
+fn list(data: &AppData, tab: Option<&str>, include_completed: bool) -> Result<String, String> {
+    let listings =
+        deep_link::list_tasks(data, tab, include_completed).map_err(|e| e.to_string())?;
+    let blocks: Vec<String> = listings
+        .into_iter()
+        .filter_map(|listing| {
+            if listing.tasks.is_empty() && tab.is_none() {
+                return None;
+            }
+            let mut block = format!("{}\n", listing.name.trim());
+            for task in listing.tasks {
+                let mark = if task.completed { 'x' } else { ' ' };
+                block.push_str(&format!("  [{mark}] {}  {}", task.id, task.text.trim()));
+                if let Some(minutes) = task.expected_duration.filter(|m| *m > 0.0) {
+                    block.push_str(&format!(" ({})", format_minutes(minutes)));
+                }
+                block.push('\n');
+            }
+            Some(block)
+        })
+        .collect();
+    Ok(blocks.join("\n"))
+}
+
+/// Run `command` against `data`.
+pub fn execute(data: &mut AppData, command: &Command) -> Result<Outcome, String> {
+    match command {
+        Command::Add { text, minutes, tab } => {
+            let tab_id = resolve_tab(data, tab.as_deref())?;
+            let (tab_id, task) = capture::add_task(data, text, *minutes, tab_id.as_deref())?;
+            let tab = &data.tabs[&tab_id];
+            if is_synced(tab) {
+                return Ok(Outcome::NeedsApp);
+            }
+            Ok(Outcome::Changed(format!(
+                "Added {} to {}\n",
+                task.id,
+                tab.name.trim()
+            )))
+        }
+        Command::List {
+            tab,
+            include_completed,
+        } => list(data, tab.as_deref(), *include_completed).map(Outcome::Output),
+        Command::Done { task } => {
+            let task_id = deep_link::resolve_task(data, task).map_err(|e| e.to_string())?;
+            let Some((tab, task)) = data.find_task(&task_id) else {
+                return Err(format!("Task not found: {task_id}"));
+            };
+            if task.completed {
+                return Ok(Outcome::Output(format!(
+                    "{task_id} is already done: {}\n",
+                    task.text.trim()
+                )));
+            }
+            if is_synced(tab) {
+                return Ok(Outcome::NeedsApp);
+            }
+            let task = data
+                .find_task_mut(&task_id)
+                .ok_or_else(|| format!("Task not found: {task_id}"))?;
+            // Same fields as `toggleTask` in the frontend.
+            let now = store::now_iso();
+            task.completed = true;
+            task.completed_at = Some(now.clone());
+            task.extra.insert("statusChangedAt".to_string(), now.into());
+            Ok(Outcome::Changed(format!(
+                "Completed {task_id}: {}\n",
+                task.text.trim()
+            )))
+        }
+        Command::Focus { .. } => Ok(Outcome::NeedsApp),
+        Command::Export { format, tab, .. } => {
+            let tab_id = resolve_tab(data, tab.as_deref())?;
+            let tab_id = tab_id.as_deref();
+            match format {
+                ExportFormat::Markdown => {
+                    markdown::export(data, tab_id.map_or(Scope::All, Scope::Tab))
+                }
+                ExportFormat::TodoTxt => todotxt::export(data, tab_id),
+                ExportFormat::Ics => ical::export(data, tab_id, chrono::Utc::now()),
+            }
+            .map(Outcome::Output)
+        }
+        Command::Mcp { .. } => Err("mcp runs a server, not a task command".to_string()),
+        Command::Help => Ok(Outcome::Output(USAGE.to_string())),
+    }
+}
+
+/// The app's `app_data_dir()`, found without a running app the way Tauri
+/// finds it: the platform data directory joined with the bundle identifier.
+pub fn data_dir() -> Result<PathBuf, String> {
+    #[cfg(target_os = "windows")]
+    let base = std::env::var_os("APPDATA").map(PathBuf::from);
+    #[cfg(not(target_os = "windows"))]
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    #[cfg(target_os = "macos")]
+    let base = home.map(|home| home.join("Library/Application Support"));
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    let base = std::env::var_os("XDG_DATA_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| home.map(|home| home.join(".local/share")));
+    base.map(|base| base.join(APP_IDENTIFIER))
+        .ok_or_else(|| "Could not find the app data directory".to_string())
+}
+
+/// Where the running app's automation API listens, found the way
+/// `commands::rpc` finds it: the runtime directory where the platform has
+/// one, otherwise the data directory.
+pub fn socket_dir() -> Result<PathBuf, String> {
+    #[cfg(target_os = "linux")]
+    let runtime = std::env::var_os("XDG_RUNTIME_DIR").filter(|dir| !dir.is_empty());
+    #[cfg(not(target_os = "linux"))]
+    let runtime: Option<std::ffi::OsString> = None;
+    match runtime {
+        Some(runtime) => Ok(PathBuf::from(runtime).join(APP_IDENTIFIER)),
+        None => data_dir(),
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn process_alive(pid: u32) -> bool {
+    Path::new(&format!("/proc/{pid}")).exists()
+}
+
+#[cfg(target_os = "macos")]
+fn process_alive(pid: u32) -> bool {
+    std::process::Command::new("kill")
+        .args(["-0", &pid.to_string()])
+        .stderr(std::process::Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+#[cfg(target_os = "windows")]
+fn process_alive(pid: u32) -> bool {
+    std::process::Command::new("tasklist")
+        .args(["/FI", &format!("PID eq {pid}"), "/NH"])
+        .output()
+        .is_ok_and(|output| String::from_utf8_lossy(&output.stdout).contains(&pid.to_string()))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn process_alive(_pid: u32) -> bool {
+    true
+}
+
+/// Record that the app is running, for [`instance_running`].
+pub fn write_instance_marker(dir: &Path) -> Result<(), String> {
+    let path = dir.join(INSTANCE_FILE_NAME);
+    store::write_atomic(&path, std::process::id().to_string().as_bytes())
+        .map_err(|e| format!("Failed to write {path:?}: {e}"))
+}
+
+pub fn remove_instance_marker(dir: &Path) {
+    let _ = fs::remove_file(dir.join(INSTANCE_FILE_NAME));
+}
+
+/// Whether another process holds the marker and is still alive; a marker
+/// left behind by a crash points at a dead pid.
+pub fn instance_running(dir: &Path) -> bool {
+    fs::read_to_string(dir.join(INSTANCE_FILE_NAME))
+        .ok()
+        .and_then(|pid| pid.trim().parse::<u32>().ok())
+        .is_some_and(|pid| pid != std::process::id() && process_alive(pid))
+}
+
+/// Release builds on Windows have no console; write to the one the command
+/// was run from.
+#[cfg(target_os = "windows")]
+fn attach_console() {
+    use windows_sys::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+    // SAFETY: no preconditions; fails harmlessly without a parent console.
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn attach_console() {}
+
+/// Run `command` without the app if possible. `Ok(None)` means the app has
+/// to do it.
+fn run_headless(command: &Command) -> Result<Option<String>, String> {
+    if *command == Command::Help {
+        return Ok(Some(USAGE.to_string()));
+    }
+    let dir = data_dir()?;
+    let changes_tasks = command.deep_link().is_some();
+    if changes_tasks && instance_running(&dir) {
+        eprintln!("Passing this to the running ReDD To-Do.");
+        return Ok(None);
+    }
+    run_in_store(&dir, command)
+}
+
+/// Run `command` against the task store in `dir`, whether or not the app is
+/// running. `Ok(None)` means the app has to do it.
+pub fn run_in_store(dir: &Path, command: &Command) -> Result<Option<String>, String> {
+    let changes_tasks = command.deep_link().is_some();
+    // Other command-line or MCP processes may be changing the store too.
+    let _lock = if changes_tasks {
+        Some(store::StoreLock::acquire(dir)?)
+    } else {
+        None
+    };
+    let stored = store::load(dir)?;
+    if changes_tasks && stored.is_none() {
+        // Until the app has saved once, its tasks only exist in the webview.
+        return Ok(None);
+    }
+    let before = stored.unwrap_or_default();
+    let mut data = before.clone();
+    let output = match execute(&mut data, command)? {
+        Outcome::Output(output) => output,
+        Outcome::Changed(output) => {
+            let changes = store::ExternalChanges::between(&before, &data);
+            store::save_external(dir, &data, changes)?;
+            output
+        }
+        Outcome::NeedsApp => return Ok(None),
+    };
+    if let Command::Export {
+        output: Some(path), ..
+    } = command
+    {
+        fs::write(path, output).map_err(|e| format!("Failed to write {path:?}: {e}"))?;
+        return Ok(Some(format!("Exported to {}\n", path.display())));
+    }
+    Ok(Some(output))
+}
+
+/// Handle the command line before the app starts. Returns the exit code when
+/// it was handled here, or `None` to start the app, which passes the command
+/// on if another instance is running.
+pub fn run_from_args() -> Option<i32> {
+    let command = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(command)) => command,
+        Ok(None) => return None,
+        Err(e) => {
+            attach_console();
+            eprintln!("redd-todo: {e}\n\n{USAGE}");
+            return Some(2);
+        }
+    };
+    if let Command::Mcp { read_only } = command {
+        // stdin and stdout belong to the MCP client, so no console here.
+        return Some(
+            match data_dir().and_then(|dir| mcp::serve_stdio(&dir, read_only)) {
+                Ok(()) => 0,
+                Err(e) => {
+                    eprintln!("redd-todo: {e}");
+                    1
+                }
+            },
+        );
+    }
+    attach_console();
+    match run_headless(&command) {
+        Ok(Some(output)) => {
+            print!("{output}");
+            Some(0)
+        }
+        Ok(None) => None,
+        Err(e) => {
+            eprintln!("redd-todo: {e}");
+            Some(1)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::Task;
+
+    fn parse(args: &[&str]) -> Result<Option<Command>, String> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    fn sample() -> (AppData, String) {
+        let mut data = AppData::default();
+        let work = data.add_tab("Work", None);
+        data.add_tab("Empty", None);
+        data.tabs[&work].tasks = vec![
+            Task {
+                id: "task_1".to_string(),
+                text: "Write report".to_string(),
+                expected_duration: Some(90.0),
+                ..Task::default()
+            },
+            Task {
+                id: "task_2".to_string(),
+                text: "Plan week".to_string(),
+                completed: true,
+                ..Task::default()
+            },
+        ];
+        data.task_counter = 2;
+        (data, work)
+    }
+
+    #[test]
+    fn parses_commands() {
+        assert_eq!(parse(&[]), Ok(None));
+        assert_eq!(
+            parse(&["add", "Write", "email", "--minutes", "15", "--tab=Work"]),
+            Ok(Some(Command::Add {
+                text: "Write email".to_string(),
+                minutes: Some(15),
+                tab: Some("Work".to_string()),
+            }))
+        );
+        assert_eq!(
+            parse(&["add", "--", "--verbose flag"]),
+            Ok(Some(Command::Add {
+                text: "--verbose flag".to_string(),
+                minutes: None,
+                tab: None,
+            }))
+        );
+        assert_eq!(
+            parse(&["list", "--all"]),
+            Ok(Some(Command::List {
+                tab: None,
+                include_completed: true
+            }))
+        );
+        assert_eq!(
+            parse(&["done", "task_3"]),
+            Ok(Some(Command::Done {
+                task: "task_3".to_string()
+            }))
+        );
+        assert_eq!(
+            parse(&["export", "--format", "ics", "--output", "out.ics"]),
+            Ok(Some(Command::Export {
+                format: ExportFormat::Ics,
+                tab: None,
+                output: Some(PathBuf::from("out.ics")),
+            }))
+        );
+        assert_eq!(
+            parse(&["mcp", "--read-only"]),
+            Ok(Some(Command::Mcp { read_only: true }))
+        );
+        assert_eq!(parse(&["--help"]), Ok(Some(Command::Help)));
+        // Deep links and OS launch flags start the app.
+        assert_eq!(parse(&["redddo://add?text=x"]), Ok(None));
+        assert_eq!(parse(&["-psn_0_12345"]), Ok(None));
+    }
+
+    #[test]
+    fn commands_round_trip_through_args() {
+        let commands = [
+            Command::Add {
+                text: "--tab is part of the text".to_string(),
+                minutes: Some(25),
+                tab: Some("Work".to_string()),
+            },
+            Command::List {
+                tab: None,
+                include_completed: true,
+            },
+            Command::Done {
+                task: "Plan week".to_string(),
+            },
+            Command::Focus {
+                task: "task_1".to_string(),
+            },
+            Command::Export {
+                format: ExportFormat::TodoTxt,
+                tab: Some("Work".to_string()),
+                output: Some(PathBuf::from("todo.txt")),
+            },
+            Command::Mcp { read_only: false },
+            Command::Help,
+        ];
+        for command in commands {
+            assert_eq!(parse_args(command.to_args()), Ok(Some(command)));
+        }
+    }
+
+    #[test]
+    fn rejects_bad_arguments() {
+        assert!(parse(&["ad", "x"]).is_err());
+        assert!(parse(&["add"]).is_err());
+        assert!(parse(&["add", "x", "--minutes"]).is_err());
+        assert!(parse(&["add", "x", "--minutes", "0"]).is_err());
+        assert!(parse(&["add", "x", "--all"]).is_err());
+        assert!(parse(&["list", "Work"]).is_err());
+        assert!(parse(&["done"]).is_err());
+        assert!(parse(&["export", "--format", "pdf"]).is_err());
+    }
+
+    #[test]
+    fn lists_tasks() {
+        let (mut data, _) = sample();
+        let list = |data: &mut AppData, command| match execute(data, &command) {
+            Ok(Outcome::Output(output)) => output,
+            other => panic!("unexpected {other:?}"),
+        };
+        assert_eq!(
+            list(
+                &mut data,
+                Command::List {
+                    tab: None,
+                    include_completed: false
+                }
+            ),
+            "Work\n  [ ] task_1  Write report (1h30m)\n"
+        );
+        assert_eq!(
+            list(
+                &mut data,
+                Command::List {
+                    tab: Some("work".to_string()),
+                    include_completed: true
+                }
+            ),
+            "Work\n  [ ] task_1  Write report (1h30m)\n  [x] task_2  Plan week\n"
+        );
+        assert_eq!(
+            list(
+                &mut data,
+                Command::List {
+                    tab: Some("Empty".to_string()),
+                    include_completed: false
+                }
+            ),
+            "Empty\n"
+        );
+    }
+
+    #[test]
+    fn adds_and_completes_tasks() {
+        let (mut data, work) = sample();
+        let added = execute(
+            &mut data,
+            &Command::Add {
+                text: "Chapter 3".to_string(),
+                minutes: Some(30),
+                tab: Some("Work".to_string()),
+            },
+        );
+        assert_eq!(
+            added,
+            Ok(Outcome::Changed("Added task_3 to Work\n".to_string()))
+        );
+        assert_eq!(data.tabs[&work].tasks[2].text, "Chapter 3");
+        assert_eq!(data.tabs[&work].tasks[2].expected_duration, Some(30.0));
+
+        let done = Command::Done {
+            task: "write report".to_string(),
+        };
+        assert_eq!(
+            execute(&mut data, &done),
+            Ok(Outcome::Changed(
+                "Completed task_1: Write report\n".to_string()
+            ))
+        );
+        let task = &data.tabs[&work].tasks[0];
+        assert!(task.completed && task.completed_at.is_some());
+        assert!(task.extra.contains_key("statusChangedAt"));
+        assert!(matches!(execute(&mut data, &done), Ok(Outcome::Output(_))));
+        assert!(execute(
+            &mut data,
+            &Command::Done {
+                task: "task_9".to_string()
+            }
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn synced_lists_and_focus_need_the_app() {
+        let (mut data, work) = sample();
+        data.tabs[&work]
+            .extra
+            .insert("basecampListId".to_string(), 42.into());
+        let done = Command::Done {
+            task: "task_1".to_string(),
+        };
+        assert_eq!(execute(&mut data, &done), Ok(Outcome::NeedsApp));
+        assert!(!data.tabs[&work].tasks[0].completed);
+        let focus = Command::Focus {
+            task: "task_1".to_string(),
+        };
+        assert_eq!(execute(&mut data, &focus), Ok(Outcome::NeedsApp));
+        assert_eq!(
+            focus.deep_link(),
+            Some(DeepLinkAction::Focus {
+                task: "task_1".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn ignores_stale_instance_markers() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(!instance_running(dir.path()));
+        write_instance_marker(dir.path()).unwrap();
+        // Our own pid is never "another" instance.
+        assert!(!instance_running(dir.path()));
+        fs::write(dir.path().join(INSTANCE_FILE_NAME), "not a pid").unwrap();
+        assert!(!instance_running(dir.path()));
+        remove_instance_marker(dir.path());
+        assert!(!dir.path().join(INSTANCE_FILE_NAME).exists());
+    }
+}
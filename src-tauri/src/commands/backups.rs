@@ -0,0 +1,168 @@
+use super::store::{store_dir, store_lock};
+use crate::backup_import::{self, BackupDiff, BackupFile, ImportMode, ValidationIssue};
+use crate::backups::{self, BackupInfo, BackupSettings};
+use crate::store::{self, AppData};
+use serde::Serialize;
+use serde_json::Value;
+use std::time::Duration;
+use tauri::{command, AppHandle};
+
+/// How often the scheduler wakes up to check whether a snapshot is due.
+const SCHEDULER_TICK: Duration = Duration::from_secs(60);
+
+/// Take a snapshot (if the data changed) and prune old ones.
+fn run_backup_cycle(app: &AppHandle) -> Result<(), String> {
+    let dir = store_dir(app)?;
+    let settings = backups::load_settings(&dir);
+    let _guard = store_lock().lock().map_err(|e| e.to_string())?;
+    if let Some(id) = backups::create_snapshot(&dir, false)? {
+        log::info!("[Backups] Wrote snapshot {id}");
+    }
+    let removed = backups::prune(&dir, &settings.retention);
+    if removed > 0 {
+        log::info!("[Backups] Pruned {removed} old snapshot(s)");
+    }
+    Ok(())
+}
+
+/// Start the background thread that writes rotating snapshots. Runs one
+/// cycle immediately so every launch has a restore point.
+pub fn spawn_backup_scheduler(app: AppHandle) {
+    std::thread::spawn(move || {
+        let mut elapsed = Duration::MAX;
+        loop {
+            let settings = store_dir(&app)
+                .map(|dir| backups::load_settings(&dir))
+                .unwrap_or_default();
+            let interval = Duration::from_secs(settings.interval_minutes.max(1) * 60);
+            if settings.enabled && elapsed >= interval {
+                if let Err(e) = run_backup_cycle(&app) {
+                    log::warn!("[Backups] {e}");
+                }
+                elapsed = Duration::ZERO;
+            }
+            std::thread::sleep(SCHEDULER_TICK);
+            elapsed = elapsed.saturating_add(SCHEDULER_TICK);
+        }
+    });
+}
+
+/// List automatic snapshots, newest first.
+#[command]
+pub fn list_backups(app: AppHandle) -> Result<Vec<BackupInfo>, String> {
+    Ok(backups::list(&store_dir(&app)?))
+}
+
+/// Take a snapshot right away, even if nothing changed since the last one.
+#[command]
+pub fn create_backup(app: AppHandle) -> Result<Option<String>, String> {
+    let dir = store_dir(&app)?;
+    let _guard = store_lock().lock().map_err(|e| e.to_string())?;
+    backups::create_snapshot(&dir, true)
+}
+
+/// Restore a snapshot into the task store and return its payload so the
+/// frontend can apply it the same way as an imported backup file.
+#[command]
+pub fn restore_backup(app: AppHandle, backup_id: String) -> Result<Value, String> {
+    let dir = store_dir(&app)?;
+    let _guard = store_lock().lock().map_err(|e| e.to_string())?;
+    backups::restore(&dir, &backup_id)
+}
+
+#[command]
+pub fn get_backup_settings(app: AppHandle) -> Result<BackupSettings, String> {
+    Ok(backups::load_settings(&store_dir(&app)?))
+}
+
+#[command]
+pub fn set_backup_settings(app: AppHandle, settings: BackupSettings) -> Result<(), String> {
+    let dir = store_dir(&app)?;
+    backups::save_settings(&dir, &settings)?;
+    let _guard = store_lock().lock().map_err(|e| e.to_string())?;
+    backups::prune(&dir, &settings.retention);
+    Ok(())
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupReport {
+    pub valid: bool,
+    pub errors: Vec<ValidationIssue>,
+    /// What importing with the requested mode would change; `None` when invalid.
+    pub diff: Option<BackupDiff>,
+}
+
+fn imported_data(current: &AppData, backup: &BackupFile, mode: ImportMode) -> AppData {
+    match mode {
+        ImportMode::Replace => backup.data.clone(),
+        ImportMode::Merge => backup_import::merge(current, &backup.data),
+    }
+}
+
+/// Validate a backup file and dry-run the import against the current data.
+#[command]
+pub fn validate_backup(
+    app: AppHandle,
+    contents: String,
+    mode: Option<ImportMode>,
+) -> Result<BackupReport, String> {
+    let backup = match backup_import::parse(&contents) {
+        Ok(backup) => backup,
+        Err(errors) => {
+            return Ok(BackupReport {
+                valid: false,
+                errors,
+                diff: None,
+            })
+        }
+    };
+    let dir = store_dir(&app)?;
+    let current = store::load(&dir)?.unwrap_or_default();
+    let next = imported_data(&current, &backup, mode.unwrap_or_default());
+    Ok(BackupReport {
+        valid: true,
+        errors: Vec::new(),
+        diff: Some(backup_import::diff(&current, &next)),
+    })
+}
+
+/// Import a backup file into the task store. The current data is snapshotted
+/// first. Returns the export-shaped payload for the frontend to apply.
+#[command]
+pub fn import_backup(
+    app: AppHandle,
+    contents: String,
+    mode: Option<ImportMode>,
+) -> Result<Value, String> {
+    let mode = mode.unwrap_or_default();
+    let backup = backup_import::parse(&contents).map_err(|errors| {
+        errors
+            .iter()
+            .map(|issue| format!("{}: {}", issue.path, issue.message))
+            .collect::<Vec<_>>()
+            .join("\n")
+    })?;
+
+    let dir = store_dir(&app)?;
+    let _guard = store_lock().lock().map_err(|e| e.to_string())?;
+    backups::create_snapshot(&dir, true)?;
+
+    let current = store::load(&dir)?.unwrap_or_default();
+    let next = imported_data(&current, &backup, mode);
+    let plan = match mode {
+        ImportMode::Replace => backup.plan_data.clone().unwrap_or_default(),
+        ImportMode::Merge => store::load_plan(&dir)?,
+    };
+    store::save(&dir, &next)?;
+    store::save_plan(&dir, &plan)?;
+
+    let mut payload = serde_json::to_value(&next).map_err(|e| e.to_string())?;
+    if let Some(obj) = payload.as_object_mut() {
+        obj.insert("planData".to_string(), Value::Object(plan));
+        if let (ImportMode::Replace, Some(ui_prefs)) = (mode, backup.ui_prefs) {
+            obj.insert("uiPrefs".to_string(), ui_prefs);
+        }
+    }
+    Ok(payload)
+}
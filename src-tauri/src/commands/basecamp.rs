@@ -0,0 +1,430 @@
+use super::store::{store_dir, store_lock};
+use crate::basecamp::{
+    self, BasecampError, Client, Comment, Credentials, Person, Project, Refresher, Todo,
+    TodoParams, Todolist, Todoset, Tokens,
+};
+use crate::secrets::{KeyStore, Secrets};
+use crate::store::{self, SECRET_CONFIG_KEYS};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+use std::time::Duration;
+use tauri::{command, AppHandle, Emitter};
+
+/// Name of the encrypted file holding the Basecamp credentials.
+const SECRET_NAME: &str = "basecamp";
+/// Bounds on how long the refresh loop sleeps before checking again.
+const MIN_REFRESH_WAIT: Duration = Duration::from_secs(60);
+const MAX_REFRESH_WAIT: Duration = Duration::from_secs(60 * 60);
+
+/// The connection as the frontend sees it; the tokens never leave the backend.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BasecampStatus {
+    pub connected: bool,
+    pub account_id: Option<u64>,
+    pub email: Option<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Where the key to the credentials is kept: "keychain",
+    /// "secret-service" or "file".
+    pub key_storage: Option<&'static str>,
+}
+
+fn credentials() -> &'static Mutex<Option<Credentials>> {
+    static CREDENTIALS: OnceLock<Mutex<Option<Credentials>>> = OnceLock::new();
+    CREDENTIALS.get_or_init(|| Mutex::new(None))
+}
+
+/// Shared by the refresh loop and every client, so concurrent calls don't
+/// spend the refresh token twice.
+fn refresher() -> &'static Arc<Refresher> {
+    static REFRESHER: OnceLock<Arc<Refresher>> = OnceLock::new();
+    REFRESHER.get_or_init(|| Arc::new(Refresher::new(basecamp::REFRESH_URL)))
+}
+
+fn current() -> Option<Credentials> {
+    credentials()
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+}
+
+/// Where the credentials key is kept, worked out once per run.
+fn key_store(dir: &std::path::Path) -> KeyStore {
+    static KEY_STORE: OnceLock<KeyStore> = OnceLock::new();
+    *KEY_STORE.get_or_init(|| KeyStore::detect(dir))
+}
+
+fn secrets(app: &AppHandle) -> Result<Secrets, String> {
+    let dir = store_dir(app)?;
+    Ok(Secrets::new(&dir, key_store(&dir)))
+}
+
+/// Persist `new` (or forget the credentials) and make it current.
+fn store_credentials(app: &AppHandle, new: Option<Credentials>) -> Result<(), String> {
+    let secrets = secrets(app)?;
+    match &new {
+        Some(new) => secrets.save(SECRET_NAME, new)?,
+        None => secrets.delete(SECRET_NAME)?,
+    }
+    *credentials().lock().unwrap_or_else(PoisonError::into_inner) = new;
+    Ok(())
+}
+
+fn status(app: &AppHandle) -> BasecampStatus {
+    let current = current();
+    BasecampStatus {
+        connected: current.is_some(),
+        account_id: current.as_ref().and_then(|c| c.account_id),
+        email: current.as_ref().and_then(|c| c.email.clone()),
+        expires_at: current.as_ref().and_then(|c| c.expires_at),
+        key_storage: secrets(app).ok().map(|s| s.key_store().label()),
+    }
+}
+
+fn disconnect(app: &AppHandle) {
+    if let Err(e) = store_credentials(app, None) {
+        log::warn!("[Basecamp] Failed to remove credentials: {e}");
+    }
+    let _ = app.emit("basecamp-status-changed", status(app));
+}
+
+/// Refresh the access token if it is close to expiring. A failed refresh
+/// keeps the current token, unless Basecamp rejected the refresh token.
+async fn refresh_if_due(app: &AppHandle) -> Result<(), String> {
+    let Some(mut current) = current() else {
+        return Ok(());
+    };
+    if !current.needs_refresh(Utc::now()) {
+        return Ok(());
+    }
+    match refresher().refresh(&current.tokens()).await {
+        Ok(tokens) => {
+            log::info!("[Basecamp] Refreshed the access token");
+            current.update(tokens, Utc::now());
+            store_credentials(app, Some(current))
+        }
+        Err(BasecampError::Unauthorized) => {
+            disconnect(app);
+            Err("Basecamp sign-in expired; connect again".to_string())
+        }
+        Err(e) => {
+            log::warn!("[Basecamp] Token refresh failed: {e}");
+            Ok(())
+        }
+    }
+}
+
+async fn client(app: &AppHandle) -> Result<Client, String> {
+    refresh_if_due(app).await?;
+    let current = current().ok_or("Not connected to Basecamp")?;
+    let account_id = current
+        .account_id
+        .ok_or("No Basecamp account for this sign-in")?;
+    Ok(Client::new(
+        account_id,
+        current.tokens(),
+        refresher().clone(),
+    ))
+}
+
+/// Keep tokens the client refreshed during the call, and disconnect if
+/// Basecamp no longer accepts them.
+fn finish<T>(
+    app: &AppHandle,
+    client: &Client,
+    result: Result<T, BasecampError>,
+) -> Result<T, String> {
+    if let Some(tokens) = client.refreshed_tokens() {
+        if let Some(mut current) = current() {
+            current.update(tokens, Utc::now());
+            if let Err(e) = store_credentials(app, Some(current)) {
+                log::warn!("[Basecamp] Failed to save refreshed tokens: {e}");
+            }
+        }
+    }
+    if let Err(BasecampError::Unauthorized) = result {
+        disconnect(app);
+    }
+    result.map_err(|e| {
+        log::warn!("[Basecamp] {e}");
+        e.to_string()
+    })
+}
+
+/// Sign in with `tokens`, looking up the account and email if not given.
+pub async fn connect(
+    app: &AppHandle,
+    tokens: Tokens,
+    account_id: Option<u64>,
+    email: Option<String>,
+) -> Result<BasecampStatus, String> {
+    let now = Utc::now();
+    let mut new = Credentials::new(tokens, now);
+    if new.expires_at.is_none() {
+        // Tokens of unknown age: refresh right away to learn when they expire.
+        new.refresh_at = Some(now);
+    }
+    match account_id {
+        Some(account_id) => {
+            new.account_id = Some(account_id);
+            new.email = email;
+        }
+        None => {
+            let authorization =
+                basecamp::authorization(basecamp::AUTHORIZATION_URL, &new.access_token)
+                    .await
+                    .map_err(|e| e.to_string())?;
+            let account = authorization
+                .basecamp_account()
+                .ok_or("This sign-in has no Basecamp accounts")?;
+            new.account_id = Some(account.id);
+            new.email = Some(authorization.identity.email_address.clone());
+        }
+    }
+    store_credentials(app, Some(new))?;
+    let status = status(app);
+    let _ = app.emit("basecamp-status-changed", &status);
+    Ok(status)
+}
+
+/// Move tokens an older version kept in the task store into the encrypted
+/// credentials, then scrub them from the store.
+fn migrate_store_tokens(app: &AppHandle) -> Result<(), String> {
+    let dir = store_dir(app)?;
+    let _guard = store_lock().lock().map_err(|e| e.to_string())?;
+    let Some(data) = store::load(&dir)? else {
+        return Ok(());
+    };
+    let Some(config) = data.extra.get("basecampConfig").and_then(Value::as_object) else {
+        return Ok(());
+    };
+    if !SECRET_CONFIG_KEYS
+        .iter()
+        .any(|key| config.contains_key(*key))
+    {
+        return Ok(());
+    }
+    let text = |key: &str| {
+        config
+            .get(key)
+            .and_then(Value::as_str)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+    };
+    if let (None, Some(access_token)) = (current(), text("accessToken")) {
+        let now = Utc::now();
+        let mut migrated = Credentials::new(
+            Tokens {
+                access_token,
+                refresh_token: text("refreshToken"),
+                expires_in: None,
+            },
+            now,
+        );
+        migrated.refresh_at = Some(now);
+        migrated.account_id = config.get("accountId").and_then(|id| match id {
+            Value::String(id) => id.parse().ok(),
+            id => id.as_u64(),
+        });
+        migrated.email = text("email");
+        store_credentials(app, Some(migrated))?;
+        log::info!("[Basecamp] Moved tokens from the task store to encrypted storage");
+    }
+    store::save(&dir, &data)
+}
+
+/// Load the saved credentials and keep the access token fresh in the background.
+pub fn setup_basecamp(app: &AppHandle) {
+    match secrets(app).and_then(|secrets| secrets.load::<Credentials>(SECRET_NAME)) {
+        Ok(loaded) => *credentials().lock().unwrap_or_else(PoisonError::into_inner) = loaded,
+        Err(e) => log::warn!("[Basecamp] Failed to load credentials: {e}"),
+    }
+    if let Err(e) = migrate_store_tokens(app) {
+        log::warn!("[Basecamp] Failed to migrate stored tokens: {e}");
+    }
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let wait = current()
+                .and_then(|current| current.refresh_at)
+                .map(|at| (at - Utc::now()).to_std().unwrap_or(Duration::ZERO))
+                .unwrap_or(MAX_REFRESH_WAIT)
+                .clamp(MIN_REFRESH_WAIT, MAX_REFRESH_WAIT);
+            tokio::time::sleep(wait).await;
+            if let Err(e) = refresh_if_due(&app).await {
+                log::warn!("[Basecamp] {e}");
+            }
+        }
+    });
+}
+
+#[command]
+pub fn get_basecamp_status(app: AppHandle) -> BasecampStatus {
+    status(&app)
+}
+
+#[command]
+pub fn basecamp_disconnect(app: AppHandle) -> BasecampStatus {
+    disconnect(&app);
+    status(&app)
+}
+
+/// Connect with tokens obtained outside the OAuth flow: entered by hand, or
+/// left in the webview's storage by an older version.
+#[command]
+pub async fn basecamp_import_tokens(
+    app: AppHandle,
+    access_token: String,
+    refresh_token: Option<String>,
+    account_id: Option<u64>,
+    email: Option<String>,
+) -> Result<BasecampStatus, String> {
+    let tokens = Tokens {
+        access_token,
+        refresh_token: refresh_token.filter(|token| !token.is_empty()),
+        expires_in: None,
+    };
+    connect(&app, tokens, account_id, email).await
+}
+
+#[command]
+pub async fn basecamp_projects(app: AppHandle) -> Result<Vec<Project>, String> {
+    let client = client(&app).await?;
+    let result = client.projects().await;
+    finish(&app, &client, result)
+}
+
+#[command]
+pub async fn basecamp_project_people(
+    app: AppHandle,
+    project_id: u64,
+) -> Result<Vec<Person>, String> {
+    let client = client(&app).await?;
+    let result = client.project_people(project_id).await;
+    finish(&app, &client, result)
+}
+
+/// The project's to-do set; `None` if the project has no to-dos tool.
+#[command]
+pub async fn basecamp_todoset(app: AppHandle, project_id: u64) -> Result<Option<Todoset>, String> {
+    let client = client(&app).await?;
+    let result = match client.project(project_id).await {
+        Ok(project) => match project.todoset_id() {
+            Some(todoset_id) => client.todoset(project_id, todoset_id).await.map(Some),
+            None => Ok(None),
+        },
+        Err(e) => Err(e),
+    };
+    finish(&app, &client, result)
+}
+
+#[command]
+pub async fn basecamp_todolists(app: AppHandle, project_id: u64) -> Result<Vec<Todolist>, String> {
+    let client = client(&app).await?;
+    let result = client.project_todolists(project_id).await;
+    finish(&app, &client, result)
+}
+
+#[command]
+pub async fn basecamp_todos(
+    app: AppHandle,
+    project_id: u64,
+    todolist_id: u64,
+    completed: bool,
+) -> Result<Vec<Todo>, String> {
+    let client = client(&app).await?;
+    let result = client.todos(project_id, todolist_id, completed).await;
+    finish(&app, &client, result)
+}
+
+#[command]
+pub async fn basecamp_create_todo(
+    app: AppHandle,
+    project_id: u64,
+    todolist_id: u64,
+    todo: TodoParams,
+) -> Result<Todo, String> {
+    let client = client(&app).await?;
+    let result = client.create_todo(project_id, todolist_id, &todo).await;
+    finish(&app, &client, result)
+}
+
+#[command]
+pub async fn basecamp_update_todo(
+    app: AppHandle,
+    project_id: u64,
+    todo_id: u64,
+    todo: TodoParams,
+) -> Result<Todo, String> {
+    let client = client(&app).await?;
+    let result = client.update_todo(project_id, todo_id, &todo).await;
+    finish(&app, &client, result)
+}
+
+#[command]
+pub async fn basecamp_set_todo_completed(
+    app: AppHandle,
+    project_id: u64,
+    todo_id: u64,
+    completed: bool,
+) -> Result<(), String> {
+    let client = client(&app).await?;
+    let result = client.set_completed(project_id, todo_id, completed).await;
+    finish(&app, &client, result)
+}
+
+#[command]
+pub async fn basecamp_trash_todo(
+    app: AppHandle,
+    project_id: u64,
+    todo_id: u64,
+) -> Result<(), String> {
+    let client = client(&app).await?;
+    let result = client.trash(project_id, todo_id).await;
+    finish(&app, &client, result)
+}
+
+/// Returns the to-do's id in the target list, which is new if Basecamp had
+/// to copy it.
+#[command]
+pub async fn basecamp_move_todo(
+    app: AppHandle,
+    project_id: u64,
+    todo_id: u64,
+    target_project_id: u64,
+    target_todolist_id: u64,
+) -> Result<u64, String> {
+    let client = client(&app).await?;
+    let result = client
+        .move_todo(project_id, todo_id, target_project_id, target_todolist_id)
+        .await;
+    finish(&app, &client, result)
+}
+
+#[command]
+pub async fn basecamp_comments(
+    app: AppHandle,
+    project_id: u64,
+    recording_id: u64,
+) -> Result<Vec<Comment>, String> {
+    let client = client(&app).await?;
+    let result = client.comments(project_id, recording_id).await;
+    finish(&app, &client, result)
+}
+
+#[command]
+pub async fn basecamp_create_comment(
+    app: AppHandle,
+    project_id: u64,
+    recording_id: u64,
+    content: String,
+) -> Result<Comment, String> {
+    let client = client(&app).await?;
+    let result = client
+        .create_comment(project_id, recording_id, &content)
+        .await;
+    finish(&app, &client, result)
+}
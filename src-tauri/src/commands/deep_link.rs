@@ -0,0 +1,280 @@
+use super::focus_timer::timer_snapshots;
+use super::oauth::handle_oauth_callback;
+use super::store::{store_dir, store_lock, update_store};
+use super::tray::{emit_action, show_main_window};
+use crate::capture;
+use crate::cli;
+use crate::deep_link::{self, DeepLinkAction};
+use crate::store::{self, AppData, Task};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tauri::{command, AppHandle, Emitter};
+
+/// The same link delivered twice within this window is handled once (macOS
+/// can report the launch URL both at startup and as an open-URL event).
+const DUPLICATE_WINDOW: Duration = Duration::from_secs(2);
+
+/// Where a link came from. `focus` and `complete` links opened from outside
+/// the app, e.g. by any web page, wait for the user's go-ahead; commands the
+/// user ran (`redd-todo done …`) don't.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Source {
+    Link,
+    CommandLine,
+}
+
+#[derive(Default)]
+struct Router {
+    /// Set once the main window can act on links; until then they queue up.
+    ready: bool,
+    pending: Vec<(String, Source)>,
+    last: Option<(String, Instant)>,
+}
+
+fn router() -> &'static Mutex<Router> {
+    static ROUTER: OnceLock<Mutex<Router>> = OnceLock::new();
+    ROUTER.get_or_init(|| Mutex::new(Router::default()))
+}
+
+/// Entry point for every deep link the app receives.
+pub fn handle_deep_link(app: &AppHandle, url: &str) {
+    route(app, url, Source::Link);
+}
+
+fn route(app: &AppHandle, url: &str, source: Source) {
+    {
+        let Ok(mut router) = router().lock() else {
+            return;
+        };
+        let now = Instant::now();
+        let duplicate = router
+            .last
+            .as_ref()
+            .is_some_and(|(last, at)| last == url && now.duration_since(*at) < DUPLICATE_WINDOW);
+        if duplicate {
+            return;
+        }
+        router.last = Some((url.to_string(), now));
+        if !router.ready {
+            router.pending.push((url.to_string(), source));
+            return;
+        }
+    }
+    dispatch(app, url, source);
+}
+
+/// Carry out a command-line command (`redd-todo add …`) that needs the app,
+/// given to this instance or forwarded by another one. `argv` includes the
+/// program name.
+pub fn handle_command_line(app: &AppHandle, argv: &[String]) {
+    match cli::parse_args(argv.iter().skip(1).cloned()) {
+        Ok(Some(command)) => {
+            if let Some(action) = command.deep_link() {
+                route(app, &action.to_url(), Source::CommandLine);
+            }
+        }
+        Ok(None) => {}
+        Err(e) => log::warn!("[CLI] {e}"),
+    }
+}
+
+/// Handle links the app was launched with, and on macOS those opened while
+/// it runs (other platforms get them through the single-instance callback).
+pub fn setup_deep_links(app: &AppHandle) {
+    #[cfg(any(target_os = "macos", target_os = "linux"))]
+    {
+        use tauri_plugin_deep_link::DeepLinkExt;
+        if let Ok(Some(urls)) = app.deep_link().get_current() {
+            for url in urls {
+                log::info!(
+                    "[Deep Link] Startup URL: {}",
+                    deep_link::redact(url.as_str())
+                );
+                handle_deep_link(app, url.as_str());
+            }
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        use tauri_plugin_deep_link::DeepLinkExt;
+        let handle = app.clone();
+        app.deep_link().on_open_url(move |event| {
+            for url in event.urls() {
+                handle_deep_link(&handle, url.as_str());
+            }
+        });
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "linux")))]
+    let _ = app;
+}
+
+fn dispatch(app: &AppHandle, url: &str, source: Source) {
+    let redacted = deep_link::redact(url);
+    log::info!("[Deep Link] Handling {redacted}");
+    let result = deep_link::parse_deep_link(url)
+        .map_err(|e| e.to_string())
+        .and_then(|action| run_action(app, url, action, source));
+    if let Err(message) = result {
+        log::warn!("[Deep Link] {redacted}: {message}");
+        let _ = app.emit_to(
+            "main",
+            "deep-link-error",
+            serde_json::json!({ "url": redacted, "message": message }),
+        );
+    }
+}
+
+fn load_data(app: &AppHandle) -> Result<AppData, String> {
+    let dir = store_dir(app)?;
+    let _guard = store_lock().lock().map_err(|e| e.to_string())?;
+    Ok(store::load(&dir)?.unwrap_or_default())
+}
+
+fn resolve_tab(app: &AppHandle, tab: Option<String>) -> Result<Option<String>, String> {
+    tab.map(|tab| deep_link::resolve_tab(&load_data(app)?, &tab).map_err(|e| e.to_string()))
+        .transpose()
+}
+
+fn resolve_task(app: &AppHandle, task: &str) -> Result<String, String> {
+    deep_link::resolve_task(&load_data(app)?, task).map_err(|e| e.to_string())
+}
+
+/// Add a task to `tab` (an id or name) the way the capture window does.
+pub(crate) fn add_task(
+    app: &AppHandle,
+    text: &str,
+    tab: Option<String>,
+    minutes: Option<u32>,
+) -> Result<Task, String> {
+    let tab_id = resolve_tab(app, tab)?;
+    let (tab_id, task) = update_store(app, |data| {
+        capture::add_task(data, text, minutes, tab_id.as_deref())
+    })?;
+    // Same follow-up as a task from the capture window.
+    let _ = app.emit_to(
+        "main",
+        "task-captured",
+        serde_json::json!({ "taskId": task.id, "tabId": tab_id }),
+    );
+    Ok(task)
+}
+
+/// Start focus on `task` (an id or name). Returns the task's id.
+pub(crate) fn focus_task(app: &AppHandle, task: &str) -> Result<String, String> {
+    let task_id = resolve_task(app, task)?;
+    // In-window focus mode happens in the main window.
+    #[cfg(not(target_os = "macos"))]
+    show_main_window(app);
+    emit_action(app, "start", Some(&task_id), None);
+    Ok(task_id)
+}
+
+/// Complete `task` (an id or name). Returns the task's id.
+pub(crate) fn complete_task(app: &AppHandle, task: &str) -> Result<String, String> {
+    let task_id = resolve_task(app, task)?;
+    let in_focus = timer_snapshots()
+        .iter()
+        .any(|snapshot| snapshot.task_id == task_id);
+    if in_focus {
+        // Let the focus window finish its session.
+        emit_action(app, "complete", Some(&task_id), None);
+    } else {
+        let _ = app.emit_to(
+            "main",
+            "deep-link-action",
+            serde_json::json!({ "action": "complete", "taskId": task_id }),
+        );
+    }
+    Ok(task_id)
+}
+
+/// Ask in the main window before a link from outside starts focus on or
+/// completes a task. The window calls `confirm_deep_link_action` if the user
+/// agrees.
+fn ask_to_confirm(app: &AppHandle, action: &str, task: &str) -> Result<(), String> {
+    let data = load_data(app)?;
+    let task_id = deep_link::resolve_task(&data, task).map_err(|e| e.to_string())?;
+    let text = data
+        .find_task(&task_id)
+        .map(|(_, task)| task.text.clone())
+        .unwrap_or_default();
+    show_main_window(app);
+    let _ = app.emit_to(
+        "main",
+        "deep-link-confirm",
+        serde_json::json!({ "action": action, "taskId": task_id, "text": text }),
+    );
+    Ok(())
+}
+
+fn run_action(
+    app: &AppHandle,
+    url: &str,
+    action: DeepLinkAction,
+    source: Source,
+) -> Result<(), String> {
+    match action {
+        DeepLinkAction::Add { text, tab, minutes } => {
+            add_task(app, &text, tab, minutes)?;
+        }
+        DeepLinkAction::Focus { task } if source == Source::Link => {
+            ask_to_confirm(app, "focus", &task)?;
+        }
+        DeepLinkAction::Focus { task } => {
+            focus_task(app, &task)?;
+        }
+        DeepLinkAction::Complete { task } if source == Source::Link => {
+            ask_to_confirm(app, "complete", &task)?;
+        }
+        DeepLinkAction::Complete { task } => {
+            complete_task(app, &task)?;
+        }
+        DeepLinkAction::Open { tab } => {
+            let tab_id = resolve_tab(app, tab)?;
+            show_main_window(app);
+            let _ = app.emit_to(
+                "main",
+                "deep-link-action",
+                serde_json::json!({ "action": "open", "tabId": tab_id }),
+            );
+        }
+        DeepLinkAction::OAuthCallback => {
+            // Handled here so the tokens in the link never reach the webview.
+            let (app, url) = (app.clone(), url.to_string());
+            tauri::async_runtime::spawn(async move {
+                let _ = handle_oauth_callback(app, url).await;
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Called by the main window once it is set up; handles links that arrived
+/// before then.
+#[command]
+pub fn deep_link_ready(app: AppHandle) -> Result<(), String> {
+    let pending = {
+        let mut router = router().lock().map_err(|e| e.to_string())?;
+        router.ready = true;
+        std::mem::take(&mut router.pending)
+    };
+    for (url, source) in pending {
+        dispatch(&app, &url, source);
+    }
+    Ok(())
+}
+
+/// Carry out a `focus` or `complete` link the user agreed to.
+#[command]
+pub fn confirm_deep_link_action(
+    app: AppHandle,
+    action: String,
+    task_id: String,
+) -> Result<(), String> {
+    match action.as_str() {
+        "focus" => focus_task(&app, &task_id).map(drop),
+        "complete" => complete_task(&app, &task_id).map(drop),
+        _ => Err(format!("Unknown link action: {action}")),
+    }
+}
@@ -0,0 +1,167 @@
+use super::store::store_dir;
+use crate::focus_log::{self, FocusLogEvent, FocusSession, SessionOutcome, SessionQuery};
+use crate::store::now_iso;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tauri::{command, AppHandle};
+
+/// How often running sessions are marked as still running in the log.
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Running sessions, keyed by task id.
+fn open_sessions() -> &'static Mutex<HashMap<String, String>> {
+    static SESSIONS: OnceLock<Mutex<HashMap<String, String>>> = OnceLock::new();
+    SESSIONS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn append(app: &AppHandle, event: FocusLogEvent) {
+    let result = store_dir(app).and_then(|dir| focus_log::append(&dir, &event));
+    if let Err(e) = result {
+        log::warn!("[FocusLog] {e}");
+    }
+}
+
+fn session_for(task_id: &str) -> Option<String> {
+    open_sessions().lock().ok()?.get(task_id).cloned()
+}
+
+/// Task ids with a running session.
+pub(crate) fn focused_task_ids() -> Vec<String> {
+    open_sessions()
+        .lock()
+        .map(|sessions| sessions.keys().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Start a session for `task_id` unless one is already running (reopening
+/// the panel after a full-screen handoff continues the same session).
+pub(crate) fn log_focus_start(app: &AppHandle, task_id: &str, task_name: &str) {
+    let session_id = {
+        let Ok(mut sessions) = open_sessions().lock() else {
+            return;
+        };
+        if sessions.contains_key(task_id) {
+            return;
+        }
+        let session_id = focus_log::new_session_id(task_id);
+        sessions.insert(task_id.to_string(), session_id.clone());
+        session_id
+    };
+    append(
+        app,
+        FocusLogEvent::Start {
+            session_id,
+            task_id: task_id.to_string(),
+            task_name: task_name.to_string(),
+            at: now_iso(),
+        },
+    );
+}
+
+pub(crate) fn log_focus_handoff(app: &AppHandle, task_id: &str, fullscreen: bool) {
+    if let Some(session_id) = session_for(task_id) {
+        append(
+            app,
+            FocusLogEvent::Handoff {
+                session_id,
+                at: now_iso(),
+                fullscreen,
+            },
+        );
+    }
+}
+
+pub(crate) fn log_focus_pause(app: &AppHandle, task_id: &str) {
+    if let Some(session_id) = session_for(task_id) {
+        append(
+            app,
+            FocusLogEvent::Pause {
+                session_id,
+                at: now_iso(),
+            },
+        );
+    }
+}
+
+pub(crate) fn log_focus_resume(app: &AppHandle, task_id: &str) {
+    if let Some(session_id) = session_for(task_id) {
+        append(
+            app,
+            FocusLogEvent::Resume {
+                session_id,
+                at: now_iso(),
+            },
+        );
+    }
+}
+
+/// Log a heartbeat for the sessions of `task_ids` (the tasks whose timers
+/// are counting) at most once per [`HEARTBEAT_INTERVAL`]; called with each
+/// focus tick.
+pub(crate) fn log_focus_heartbeats(app: &AppHandle, task_ids: &[String]) {
+    static LAST: OnceLock<Mutex<Option<Instant>>> = OnceLock::new();
+    let Ok(mut last) = LAST.get_or_init(|| Mutex::new(None)).lock() else {
+        return;
+    };
+    let now = Instant::now();
+    if last.is_some_and(|last| now.duration_since(last) < HEARTBEAT_INTERVAL) {
+        return;
+    }
+    *last = Some(now);
+    for task_id in task_ids {
+        if let Some(session_id) = session_for(task_id) {
+            append(
+                app,
+                FocusLogEvent::Heartbeat {
+                    session_id,
+                    at: now_iso(),
+                },
+            );
+        }
+    }
+}
+
+/// End the running session for `task_id`. The caller says whether the task
+/// was completed, since the store on disk can lag behind the frontend.
+pub(crate) fn log_focus_end(
+    app: &AppHandle,
+    task_id: &str,
+    outcome: SessionOutcome,
+    elapsed_ms: Option<f64>,
+) {
+    let Some(session_id) = open_sessions()
+        .lock()
+        .ok()
+        .and_then(|mut sessions| sessions.remove(task_id))
+    else {
+        return;
+    };
+    append(
+        app,
+        FocusLogEvent::End {
+            session_id,
+            at: now_iso(),
+            outcome,
+            elapsed_ms,
+        },
+    );
+}
+
+/// Close sessions a previous run left open. Called once at startup.
+pub fn close_dangling_focus_sessions(app: &AppHandle) {
+    match store_dir(app).and_then(|dir| focus_log::close_dangling(&dir)) {
+        Ok(0) => {}
+        Ok(closed) => log::info!("[FocusLog] Closed {closed} interrupted session(s)"),
+        Err(e) => log::warn!("[FocusLog] {e}"),
+    }
+}
+
+/// Focus sessions from the log, oldest first.
+#[command]
+pub fn query_focus_sessions(
+    app: AppHandle,
+    query: Option<SessionQuery>,
+) -> Result<Vec<FocusSession>, String> {
+    focus_log::sessions(&store_dir(&app)?, &query.unwrap_or_default())
+}
@@ -0,0 +1,97 @@
+use super::store::store_dir;
+use super::window::focus_window_label;
+use crate::focus_pins::{self, PinSettings, PIN_GAP};
+use crate::window_geometry::Rect;
+use std::sync::{Mutex, OnceLock};
+use tauri::{command, AppHandle, Manager};
+
+/// Task ids with a focus window open, in the order they were pinned.
+fn pinned() -> &'static Mutex<Vec<String>> {
+    static PINNED: OnceLock<Mutex<Vec<String>>> = OnceLock::new();
+    PINNED.get_or_init(|| Mutex::new(Vec::new()))
+}
+
+pub(crate) fn multiple_pins_enabled(app: &AppHandle) -> bool {
+    store_dir(app)
+        .map(|dir| focus_pins::load_settings(&dir).multiple)
+        .unwrap_or(false)
+}
+
+pub(crate) fn pin_task(task_id: &str) {
+    if let Ok(mut pinned) = pinned().lock() {
+        if !pinned.iter().any(|id| id == task_id) {
+            pinned.push(task_id.to_string());
+        }
+    }
+}
+
+pub(crate) fn unpin_task(task_id: &str) {
+    if let Ok(mut pinned) = pinned().lock() {
+        pinned.retain(|id| id != task_id);
+    }
+}
+
+/// Drop every pin but `task_id`'s, for single-pin mode.
+pub(crate) fn unpin_other_tasks(task_id: &str) {
+    if let Ok(mut pinned) = pinned().lock() {
+        pinned.retain(|id| id == task_id);
+    }
+}
+
+pub(crate) fn pinned_task_ids() -> Vec<String> {
+    pinned()
+        .lock()
+        .map(|pinned| pinned.clone())
+        .unwrap_or_default()
+}
+
+/// Stack the visible pins under the first one, on its monitor.
+pub(crate) fn tile_focus_pins(app: &AppHandle) {
+    let windows: Vec<_> = pinned_task_ids()
+        .iter()
+        .filter_map(|task_id| app.get_webview_window(&focus_window_label(task_id)))
+        .filter(|window| window.is_visible().unwrap_or(false))
+        .collect();
+    let Some(first) = windows.first() else {
+        return;
+    };
+    let (Ok(origin), Ok(Some(monitor))) = (first.outer_position(), first.current_monitor()) else {
+        return;
+    };
+    let area = monitor.work_area();
+    let work_area = Rect {
+        x: area.position.x,
+        y: area.position.y,
+        width: area.size.width,
+        height: area.size.height,
+    };
+    let sizes: Vec<(u32, u32)> = windows
+        .iter()
+        .map(|window| {
+            window
+                .outer_size()
+                .map(|size| (size.width, size.height))
+                .unwrap_or((360, 56))
+        })
+        .collect();
+    let positions = focus_pins::stack_positions((origin.x, origin.y), &sizes, &work_area, PIN_GAP);
+    for (window, (x, y)) in windows.iter().zip(positions) {
+        let _ = window.set_position(tauri::PhysicalPosition::new(x, y));
+    }
+}
+
+/// Task ids that currently have a focus window, first pin first.
+#[command]
+pub fn get_focus_pins() -> Vec<String> {
+    pinned_task_ids()
+}
+
+#[command]
+pub fn get_focus_pin_settings(app: AppHandle) -> Result<PinSettings, String> {
+    Ok(focus_pins::load_settings(&store_dir(&app)?))
+}
+
+#[command]
+pub fn set_focus_pin_settings(app: AppHandle, settings: PinSettings) -> Result<(), String> {
+    focus_pins::save_settings(&store_dir(&app)?, &settings)
+}
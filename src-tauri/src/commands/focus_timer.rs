@@ -0,0 +1,253 @@
+use super::focus_log::{
+    log_focus_end, log_focus_heartbeats, log_focus_pause, log_focus_resume, log_focus_start,
+};
+use super::idle::hold_while_idle;
+use super::pomodoro::{
+    begin_pomodoro, emit_break_ended, emit_break_started, end_pomodoro, pomodoro_steps, skip_break,
+    PomodoroStep,
+};
+use crate::focus_log::SessionOutcome;
+use crate::focus_timer::{FocusTimer, TimerSnapshot, TimerState};
+use crate::pomodoro::PhaseInfo;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tauri::{command, AppHandle, Emitter};
+
+const TICK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Active timers, keyed by task id.
+fn timers() -> &'static Mutex<HashMap<String, FocusTimer>> {
+    static TIMERS: OnceLock<Mutex<HashMap<String, FocusTimer>>> = OnceLock::new();
+    TIMERS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn minutes(duration: Option<f64>) -> Option<Duration> {
+    duration
+        .filter(|m| m.is_finite() && *m > 0.0)
+        .map(|m| Duration::from_secs_f64(m * 60.0))
+}
+
+fn millis(ms: Option<f64>) -> Duration {
+    Duration::from_millis(ms.filter(|ms| ms.is_finite() && *ms > 0.0).unwrap_or(0.0) as u64)
+}
+
+/// Apply `f` to the task's timer and return its new reading.
+fn update_timer(
+    task_id: &str,
+    f: impl FnOnce(&mut FocusTimer, Instant) -> Result<(), String>,
+) -> Result<TimerSnapshot, String> {
+    let mut timers = timers().lock().map_err(|e| e.to_string())?;
+    let timer = timers
+        .get_mut(task_id)
+        .ok_or("No focus timer for this task")?;
+    let now = Instant::now();
+    f(timer, now)?;
+    timer
+        .snapshot(now)
+        .ok_or_else(|| "No focus timer for this task".to_string())
+}
+
+fn emit_tick(app: &AppHandle, snapshot: &TimerSnapshot) {
+    let _ = app.emit("focus-tick", snapshot);
+}
+
+/// Start timing `task_id` unless its timer is already active, in which case
+/// the running timer wins over `initial_elapsed_ms` (a window taking over the
+/// session continues from the authoritative time). Returns the reading and
+/// whether a new timer was started.
+pub(crate) fn ensure_focus_timer(
+    task_id: &str,
+    duration: Option<f64>,
+    initial_elapsed_ms: Option<f64>,
+) -> Result<(TimerSnapshot, bool), String> {
+    let mut timers = timers().lock().map_err(|e| e.to_string())?;
+    let now = Instant::now();
+    let timer = timers.entry(task_id.to_string()).or_default();
+    let started = timer.task_id().is_none();
+    if started {
+        timer.start(task_id, minutes(duration), millis(initial_elapsed_ms), now)?;
+    } else {
+        timer.set_duration(minutes(duration))?;
+    }
+    let snapshot = timer
+        .snapshot(now)
+        .ok_or_else(|| "Focus timer failed to start".to_string())?;
+    Ok((snapshot, started))
+}
+
+pub(crate) fn pause_task_timer(app: &AppHandle, task_id: &str) -> Result<TimerSnapshot, String> {
+    let snapshot = update_timer(task_id, FocusTimer::pause)?;
+    log_focus_pause(app, task_id);
+    emit_tick(app, &snapshot);
+    Ok(snapshot)
+}
+
+pub(crate) fn resume_task_timer(app: &AppHandle, task_id: &str) -> Result<TimerSnapshot, String> {
+    let snapshot = update_timer(task_id, FocusTimer::resume)?;
+    log_focus_resume(app, task_id);
+    emit_tick(app, &snapshot);
+    Ok(snapshot)
+}
+
+/// Task ids whose timers are counting (running or in overtime).
+pub(crate) fn running_task_ids() -> Vec<String> {
+    let Ok(timers) = timers().lock() else {
+        return Vec::new();
+    };
+    let now = Instant::now();
+    timers
+        .iter()
+        .filter(|(_, timer)| matches!(timer.state(now), TimerState::Running | TimerState::Overtime))
+        .map(|(task_id, _)| task_id.clone())
+        .collect()
+}
+
+pub(crate) fn set_task_timer_elapsed(
+    app: &AppHandle,
+    task_id: &str,
+    elapsed: Duration,
+) -> Result<TimerSnapshot, String> {
+    let snapshot = update_timer(task_id, |timer, now| timer.set_elapsed(elapsed, now))?;
+    emit_tick(app, &snapshot);
+    Ok(snapshot)
+}
+
+/// Readings of every active focus timer.
+pub(crate) fn timer_snapshots() -> Vec<TimerSnapshot> {
+    let Ok(timers) = timers().lock() else {
+        return Vec::new();
+    };
+    let now = Instant::now();
+    timers
+        .values()
+        .filter_map(|timer| timer.snapshot(now))
+        .collect()
+}
+
+/// Elapsed milliseconds of the task's active timer.
+pub(crate) fn focus_timer_elapsed_ms(task_id: &str) -> Option<f64> {
+    let timers = timers().lock().ok()?;
+    let timer = timers.get(task_id)?;
+    timer.task_id()?;
+    Some(timer.elapsed(Instant::now()).as_millis() as f64)
+}
+
+/// Stop and drop the task's timer, returning its final reading.
+pub(crate) fn stop_task_timer(app: &AppHandle, task_id: &str) -> Option<TimerSnapshot> {
+    let mut timer = timers().lock().ok()?.remove(task_id)?;
+    end_pomodoro(app, task_id);
+    let snapshot = timer.stop(Instant::now()).ok()?;
+    emit_tick(app, &snapshot);
+    Some(snapshot)
+}
+
+fn end_break(app: &AppHandle, task_id: &str, info: &PhaseInfo, reason: &str) {
+    // The timer is already running when the break ended by resuming it. A
+    // break that runs out while the user is away waits for their return.
+    if !hold_while_idle(task_id) {
+        let _ = resume_task_timer(app, task_id);
+    }
+    emit_break_ended(app, task_id, info, reason);
+}
+
+/// Apply the Pomodoro steps due at this tick to the task timers.
+fn drive_pomodoro(app: &AppHandle, snapshots: &[TimerSnapshot]) {
+    for (task_id, step) in pomodoro_steps(snapshots) {
+        match step {
+            PomodoroStep::StartBreak(info) => {
+                if let Err(e) = pause_task_timer(app, &task_id) {
+                    log::warn!("[Pomodoro] {e}");
+                }
+                emit_break_started(app, &task_id, &info);
+            }
+            PomodoroStep::EndBreak { info, reason } => end_break(app, &task_id, &info, reason),
+        }
+    }
+}
+
+/// Emit a `focus-tick` with every active timer's reading once a second.
+pub fn spawn_focus_ticker(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(TICK_INTERVAL);
+        let snapshots = timer_snapshots();
+        for snapshot in &snapshots {
+            emit_tick(&app, snapshot);
+        }
+        let running: Vec<String> = snapshots
+            .iter()
+            .filter(|snapshot| matches!(snapshot.state, TimerState::Running | TimerState::Overtime))
+            .map(|snapshot| snapshot.task_id.clone())
+            .collect();
+        log_focus_heartbeats(&app, &running);
+        drive_pomodoro(&app, &snapshots);
+    });
+}
+
+/// Start (or join) the focus timer for a task. `duration` is the expected
+/// duration in minutes; `initial_elapsed_ms` is time already spent on it.
+#[command]
+pub fn start_focus_timer(
+    app: AppHandle,
+    task_id: String,
+    task_name: Option<String>,
+    duration: Option<f64>,
+    initial_elapsed_ms: Option<f64>,
+) -> Result<TimerSnapshot, String> {
+    let (snapshot, started) = ensure_focus_timer(&task_id, duration, initial_elapsed_ms)?;
+    log_focus_start(&app, &task_id, task_name.as_deref().unwrap_or_default());
+    if started {
+        begin_pomodoro(&app, &task_id, snapshot.elapsed_ms);
+    }
+    emit_tick(&app, &snapshot);
+    Ok(snapshot)
+}
+
+#[command]
+pub fn pause_focus_timer(app: AppHandle, task_id: String) -> Result<TimerSnapshot, String> {
+    pause_task_timer(&app, &task_id)
+}
+
+#[command]
+pub fn resume_focus_timer(app: AppHandle, task_id: String) -> Result<TimerSnapshot, String> {
+    resume_task_timer(&app, &task_id)
+}
+
+/// Stop the task's timer and end its focus session. Returns the final
+/// reading, or `None` if no timer was running.
+#[command]
+pub fn stop_focus_timer(app: AppHandle, task_id: String) -> Option<TimerSnapshot> {
+    let snapshot = stop_task_timer(&app, &task_id)?;
+    log_focus_end(
+        &app,
+        &task_id,
+        SessionOutcome::Exited,
+        Some(snapshot.elapsed_ms as f64),
+    );
+    Some(snapshot)
+}
+
+/// Overwrite the elapsed time, e.g. for the focus panel's reset and undo.
+#[command]
+pub fn set_focus_timer_elapsed(
+    app: AppHandle,
+    task_id: String,
+    elapsed_ms: f64,
+) -> Result<TimerSnapshot, String> {
+    set_task_timer_elapsed(&app, &task_id, millis(Some(elapsed_ms)))
+}
+
+#[command]
+pub fn get_focus_timer(task_id: String) -> Option<TimerSnapshot> {
+    let timers = timers().lock().ok()?;
+    timers.get(&task_id)?.snapshot(Instant::now())
+}
+
+/// End the current Pomodoro break early and start the next work phase.
+#[command]
+pub fn skip_pomodoro_break(app: AppHandle, task_id: String) -> Result<(), String> {
+    let elapsed = focus_timer_elapsed_ms(&task_id).ok_or("No focus timer for this task")?;
+    let info = skip_break(&task_id, elapsed as u64)?;
+    end_break(&app, &task_id, &info, "skipped");
+    Ok(())
+}
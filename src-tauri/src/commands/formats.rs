@@ -0,0 +1,73 @@
+use super::store::{store_dir, store_lock, update_store};
+use crate::formats::markdown::{self, Scope};
+use crate::formats::{ical, todotxt, ImportSummary};
+use crate::store::{self, AppData};
+use tauri::{command, AppHandle};
+
+fn load_data(app: &AppHandle) -> Result<AppData, String> {
+    let dir = store_dir(app)?;
+    let _guard = store_lock().lock().map_err(|e| e.to_string())?;
+    Ok(store::load(&dir)?.unwrap_or_default())
+}
+
+/// Render one tab (or every tab) as todo.txt lines.
+#[command]
+pub fn export_todotxt(app: AppHandle, tab_id: Option<String>) -> Result<String, String> {
+    todotxt::export(&load_data(&app)?, tab_id.as_deref())
+}
+
+/// Add the tasks from a todo.txt file to the task store.
+#[command]
+pub fn import_todotxt(
+    app: AppHandle,
+    contents: String,
+    tab_id: Option<String>,
+) -> Result<ImportSummary, String> {
+    update_store(&app, |data| {
+        todotxt::import(data, &contents, tab_id.as_deref())
+    })
+}
+
+/// Render a tab, a group, or (with neither id) everything as a Markdown
+/// checklist.
+#[command]
+pub fn export_markdown(
+    app: AppHandle,
+    tab_id: Option<String>,
+    group_id: Option<String>,
+) -> Result<String, String> {
+    let scope = match (&tab_id, &group_id) {
+        (Some(tab_id), _) => Scope::Tab(tab_id),
+        (None, Some(group_id)) => Scope::Group(group_id),
+        (None, None) => Scope::All,
+    };
+    markdown::export(&load_data(&app)?, scope)
+}
+
+/// Add the checklists in a Markdown file to the task store as new tabs.
+#[command]
+pub fn import_markdown(
+    app: AppHandle,
+    contents: String,
+    name: Option<String>,
+) -> Result<ImportSummary, String> {
+    update_store(&app, |data| {
+        markdown::import(data, &contents, name.as_deref())
+    })
+}
+
+/// Render one tab (or every tab) as an iCalendar file of VTODOs.
+#[command]
+pub fn export_ics(app: AppHandle, tab_id: Option<String>) -> Result<String, String> {
+    ical::export(&load_data(&app)?, tab_id.as_deref(), chrono::Utc::now())
+}
+
+/// Import the VTODOs of an iCalendar file into a new tab.
+#[command]
+pub fn import_ics(
+    app: AppHandle,
+    contents: String,
+    name: Option<String>,
+) -> Result<ImportSummary, String> {
+    update_store(&app, |data| ical::import(data, &contents, name.as_deref()))
+}
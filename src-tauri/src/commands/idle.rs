@@ -0,0 +1,180 @@
+use super::focus_timer::{
+    focus_timer_elapsed_ms, pause_task_timer, resume_task_timer, running_task_ids,
+    set_task_timer_elapsed,
+};
+use super::store::store_dir;
+use crate::idle::{self, IdleEvent, IdleMonitor, IdleSettings};
+use serde::Serialize;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tauri::{command, AppHandle, Emitter};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Focus timers paused while the user was away, until they decide whether
+/// to keep the time.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IdlePause {
+    pub task_ids: Vec<String>,
+    /// Idle time the timers had already counted when they were paused.
+    pub counted_idle_ms: u64,
+    /// Total time away; `None` until the user is back.
+    pub total_idle_ms: Option<u64>,
+}
+
+fn idle_pause() -> &'static Mutex<Option<IdlePause>> {
+    static PAUSE: OnceLock<Mutex<Option<IdlePause>>> = OnceLock::new();
+    PAUSE.get_or_init(|| Mutex::new(None))
+}
+
+/// Focus timers whose Pomodoro break ran out while the user was away; `Some`
+/// while the user is idle. They stay paused until the user is back.
+fn held_while_idle() -> &'static Mutex<Option<Vec<String>>> {
+    static HELD: OnceLock<Mutex<Option<Vec<String>>>> = OnceLock::new();
+    HELD.get_or_init(|| Mutex::new(None))
+}
+
+/// Keep the task's timer paused until the user is back, if they're away.
+/// Returns whether it was held.
+pub(crate) fn hold_while_idle(task_id: &str) -> bool {
+    let Ok(mut held) = held_while_idle().lock() else {
+        return false;
+    };
+    let Some(task_ids) = held.as_mut() else {
+        return false;
+    };
+    task_ids.push(task_id.to_string());
+    true
+}
+
+fn went_idle(app: &AppHandle, idle_for: Duration) {
+    if let Ok(mut held) = held_while_idle().lock() {
+        *held = Some(Vec::new());
+    }
+    let task_ids: Vec<String> = running_task_ids()
+        .into_iter()
+        .filter(|task_id| pause_task_timer(app, task_id).is_ok())
+        .collect();
+    if task_ids.is_empty() {
+        return;
+    }
+    let pause = IdlePause {
+        task_ids,
+        counted_idle_ms: idle_for.as_millis() as u64,
+        total_idle_ms: None,
+    };
+    log::info!("[Idle] Paused {} focus timer(s)", pause.task_ids.len());
+    let _ = app.emit("focus-idle-paused", &pause);
+    if let Ok(mut pending) = idle_pause().lock() {
+        *pending = Some(pause);
+    }
+}
+
+/// Resume the paused timers straight away and ask about the time away;
+/// `resolve_idle_time` applies the answer.
+fn returned(app: &AppHandle, idle_for: Duration) {
+    // These counted none of the time away, so there is nothing to ask.
+    let held = held_while_idle()
+        .lock()
+        .ok()
+        .and_then(|mut held| held.take())
+        .unwrap_or_default();
+    for task_id in &held {
+        let _ = resume_task_timer(app, task_id);
+    }
+    let Some(pause) = idle_pause().lock().ok().and_then(|mut pending| {
+        let pause = pending.as_mut()?;
+        pause.total_idle_ms = Some(idle_for.as_millis() as u64);
+        Some(pause.clone())
+    }) else {
+        return;
+    };
+    for task_id in &pause.task_ids {
+        let _ = resume_task_timer(app, task_id);
+    }
+    let _ = app.emit("focus-idle-returned", &pause);
+}
+
+/// Poll the platform idle source in the background. Does nothing where
+/// there is no source.
+pub fn spawn_idle_monitor(app: AppHandle) {
+    std::thread::spawn(move || {
+        let source = match idle::system_idle_source() {
+            Ok(source) => source,
+            Err(e) => {
+                log::info!("[Idle] Idle detection disabled: {e}");
+                return;
+            }
+        };
+        let mut monitor = IdleMonitor::new(source, IdleSettings::default().threshold());
+        let mut last_error = None;
+        loop {
+            std::thread::sleep(POLL_INTERVAL);
+            let settings = store_dir(&app)
+                .map(|dir| idle::load_settings(&dir))
+                .unwrap_or_default();
+            // Once idle, keep polling so the return is still noticed.
+            if !settings.enabled && !monitor.is_idle() {
+                continue;
+            }
+            monitor.set_threshold(settings.threshold());
+            match monitor.poll(Instant::now()) {
+                Ok(Some(IdleEvent::WentIdle { idle_for })) => went_idle(&app, idle_for),
+                Ok(Some(IdleEvent::Returned { idle_for })) => returned(&app, idle_for),
+                Ok(None) => {}
+                Err(e) => {
+                    if last_error.as_ref() != Some(&e) {
+                        log::warn!("[Idle] {e}");
+                    }
+                    last_error = Some(e);
+                }
+            }
+        }
+    });
+}
+
+/// Keep or discard the time away from the last idle pause.
+#[command]
+pub fn resolve_idle_time(app: AppHandle, keep: bool) -> Result<(), String> {
+    let pause = idle_pause()
+        .lock()
+        .map_err(|e| e.to_string())?
+        .take()
+        .ok_or("No idle time to resolve")?;
+    let counted = Duration::from_millis(pause.counted_idle_ms);
+    let total = pause
+        .total_idle_ms
+        .map(Duration::from_millis)
+        .unwrap_or(counted);
+    for task_id in &pause.task_ids {
+        // The session may have ended in the meantime.
+        let Some(elapsed) = focus_timer_elapsed_ms(task_id) else {
+            continue;
+        };
+        let elapsed = Duration::from_millis(elapsed as u64);
+        set_task_timer_elapsed(
+            &app,
+            task_id,
+            idle::resolve_elapsed(elapsed, counted, total, keep),
+        )?;
+        if pause.total_idle_ms.is_none() {
+            let _ = resume_task_timer(&app, task_id);
+        }
+    }
+    let _ = app.emit(
+        "focus-idle-resolved",
+        serde_json::json!({ "taskIds": pause.task_ids, "keep": keep }),
+    );
+    Ok(())
+}
+
+#[command]
+pub fn get_idle_settings(app: AppHandle) -> Result<IdleSettings, String> {
+    Ok(idle::load_settings(&store_dir(&app)?))
+}
+
+#[command]
+pub fn set_idle_settings(app: AppHandle, settings: IdleSettings) -> Result<(), String> {
+    idle::save_settings(&store_dir(&app)?, &settings)
+}
@@ -1,4 +1,18 @@
 pub mod app;
+pub mod backups;
+pub mod basecamp;
+pub mod deep_link;
+pub mod focus_log;
+pub mod focus_pins;
+pub mod focus_timer;
+pub mod formats;
+pub mod idle;
 pub mod oauth;
+pub mod pomodoro;
 pub mod reminders;
+pub mod reports;
+pub mod rpc;
+pub mod shortcuts;
+pub mod store;
+pub mod tray;
 pub mod window;
@@ -1,11 +1,22 @@
+use super::basecamp::{connect, BasecampStatus};
+use crate::basecamp::Tokens;
+use crate::oauth::{self, AuthStates, Progress};
 use std::env;
 use std::net::TcpListener;
+use std::sync::{Mutex, MutexGuard, OnceLock, PoisonError};
 use std::thread;
+use std::time::{Duration, Instant};
 use tauri::{command, AppHandle, Emitter, Manager};
 
 const BC_REDIRECT_URI_DEV: &str = "http://localhost:3000/callback";
 const BC_REDIRECT_URI_PROD: &str = "https://redd-todo.netlify.app/.netlify/functions/auth";
-const LOCAL_CALLBACK_STATE_PREFIX: &str = "localhost:";
+const DEV_CALLBACK_ADDR: &str = "127.0.0.1:3000";
+
+/// How often a callback listener checks whether its sign-in is still on.
+const LISTENER_POLL: Duration = Duration::from_millis(500);
+/// How long to retry binding the dev port while the previous sign-in's
+/// listener notices it was replaced and lets go of it.
+const DEV_BIND_WAIT: Duration = Duration::from_secs(2);
 
 // Dev client ID uses localhost redirect (must match netlify/functions/exchange.js DEV_CLIENT_ID)
 const BC_CLIENT_ID_DEV: &str = "aed7f4889aa6bb83b74e8e494e70701d59d1c9c5";
@@ -25,17 +36,24 @@ fn get_client_id(is_dev: bool) -> String {
     }
 }
 
-/// Start Basecamp OAuth flow
+fn auth_states() -> MutexGuard<'static, AuthStates> {
+    static STATES: OnceLock<Mutex<AuthStates>> = OnceLock::new();
+    STATES
+        .get_or_init(|| Mutex::new(AuthStates::default()))
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Start Basecamp OAuth flow. The callback listener gives up after
+/// `timeout_secs` (five minutes by default) and reports `basecamp-auth-error`.
 #[command]
-pub async fn start_basecamp_auth(app: AppHandle) -> Result<(), String> {
+pub async fn start_basecamp_auth(app: AppHandle, timeout_secs: Option<u64>) -> Result<(), String> {
     let is_dev = cfg!(debug_assertions);
     let client_id = get_client_id(is_dev);
 
     if client_id.is_empty() {
         let error_msg = "BASECAMP_CLIENT_ID not configured";
-        if let Some(window) = app.get_webview_window("main") {
-            let _ = window.emit("basecamp-auth-error", error_msg);
-        }
+        emit_auth_error(&app, error_msg);
         return Err(error_msg.to_string());
     }
 
@@ -45,164 +63,94 @@ pub async fn start_basecamp_auth(app: AppHandle) -> Result<(), String> {
         BC_REDIRECT_URI_PROD
     };
 
-    let mut auth_url = format!(
-        "https://launchpad.37signals.com/authorization/new?type=web_server&client_id={}&redirect_uri={}",
-        client_id,
-        urlencoding::encode(redirect_uri)
-    );
-
-    log::info!("[Basecamp OAuth] Opening auth URL: {}", auth_url);
-    log::info!(
-        "[Basecamp OAuth] isDev: {}, redirect_uri: {}",
-        is_dev,
-        redirect_uri
-    );
-
-    if is_dev {
-        // Dev mode: start local HTTP server to receive callback
-        let app_handle = app.clone();
-        let client_id_clone = client_id.clone();
+    let timeout = oauth::callback_timeout(timeout_secs);
+    let nonce = auth_states()
+        .begin(Instant::now(), timeout)
+        .inspect_err(|e| emit_auth_error(&app, e))?;
 
+    let state = if is_dev {
+        // Dev mode: Basecamp redirects straight to a local callback server
+        let server = match bind_dev_server() {
+            Ok(server) => server,
+            Err(e) => {
+                auth_states().cancel(&nonce);
+                emit_auth_error(&app, &e);
+                return Err(e);
+            }
+        };
+        log::info!("[Basecamp OAuth] Local server listening on port 3000");
+        let (app, nonce, client_id) = (app.clone(), nonce.clone(), client_id.clone());
         thread::spawn(move || {
-            start_local_callback_server(app_handle, client_id_clone);
+            serve_callback(&app, &server, &nonce, |parsed| {
+                finish_dev_callback(&app, parsed, &client_id)
+            });
         });
-
-        // Small delay to ensure server is ready
-        std::thread::sleep(std::time::Duration::from_millis(100));
+        oauth::encode_state(&nonce, None)
     } else {
         // Prefer the localhost bridge; if it can't start (e.g. sandbox restrictions),
-        // omit the state param so the Netlify callback falls back to the deep link.
-        match start_local_token_bridge_server(app.clone()) {
-            Ok(bridge_port) => {
-                let state = format!("{LOCAL_CALLBACK_STATE_PREFIX}{bridge_port}");
-                auth_url.push_str("&state=");
-                auth_url.push_str(&urlencoding::encode(&state));
-
-                log::info!(
-                    "[Basecamp OAuth] Added localhost bridge on port {} with state {}",
-                    bridge_port,
-                    state
-                );
-
-                // Give the bridge thread a moment to start listening before the browser redirects back.
-                std::thread::sleep(std::time::Duration::from_millis(100));
+        // leave the port out of the state so the Netlify callback falls back to the deep link.
+        match bind_bridge_server() {
+            Ok((server, port)) => {
+                log::info!("[Basecamp OAuth] Localhost bridge listening on port {port}");
+                let (app, nonce) = (app.clone(), nonce.clone());
+                thread::spawn(move || {
+                    serve_callback(&app, &server, &nonce, |parsed| {
+                        finish_bridge_callback(&app, parsed)
+                    });
+                });
+                oauth::encode_state(&nonce, Some(port))
             }
             Err(e) => {
                 log::warn!(
                     "[Basecamp OAuth] Localhost bridge unavailable ({}), falling back to deep link callback",
                     e
                 );
+                expire_after(&app, &nonce, timeout);
+                oauth::encode_state(&nonce, None)
             }
         }
-    }
+    };
+
+    let auth_url = format!(
+        "https://launchpad.37signals.com/authorization/new?type=web_server&client_id={}&redirect_uri={}&state={}",
+        client_id,
+        urlencoding::encode(redirect_uri),
+        urlencoding::encode(&state)
+    );
+
+    log::info!(
+        "[Basecamp OAuth] isDev: {}, redirect_uri: {}, timeout: {}s",
+        is_dev,
+        redirect_uri,
+        timeout.as_secs()
+    );
 
     // Open browser for OAuth. Uses NSWorkspace on macOS: spawning /usr/bin/open
     // fails inside the App Sandbox (Mac App Store builds).
     if let Err(e) = crate::opener::open_external(&auth_url) {
+        auth_states().cancel(&nonce);
         let error_msg = format!("Failed to open browser: {}", e);
-        if let Some(window) = app.get_webview_window("main") {
-            let _ = window.emit("basecamp-auth-error", &error_msg);
-        }
+        emit_auth_error(&app, &error_msg);
         return Err(error_msg);
     }
 
     Ok(())
 }
 
-/// Start local HTTP server to receive OAuth callback (dev mode only)
-fn start_local_callback_server(app: AppHandle, client_id: String) {
-    log::info!("[Basecamp OAuth] Starting local callback server on port 3000");
-
-    let server = match tiny_http::Server::http("127.0.0.1:3000") {
-        Ok(s) => s,
-        Err(e) => {
-            log::error!("[Basecamp OAuth] Failed to start server: {}", e);
-            if let Some(window) = app.get_webview_window("main") {
-                let _ = window.emit(
-                    "basecamp-auth-error",
-                    format!("Failed to start callback server: {}", e),
-                );
+fn bind_dev_server() -> Result<tiny_http::Server, String> {
+    let deadline = Instant::now() + DEV_BIND_WAIT;
+    loop {
+        match tiny_http::Server::http(DEV_CALLBACK_ADDR) {
+            Ok(server) => return Ok(server),
+            Err(e) if Instant::now() >= deadline => {
+                return Err(format!("Failed to start callback server: {}", e));
             }
-            return;
-        }
-    };
-
-    log::info!("[Basecamp OAuth] Local server listening on port 3000");
-
-    // Wait for one request (the callback)
-    if let Some(request) = server.recv().ok() {
-        let url = request.url().to_string();
-        log::info!("[Basecamp OAuth] Received request: {}", url);
-
-        if url.starts_with("/callback") {
-            // Parse the URL to get the code
-            let full_url = format!("http://localhost:3000{}", url);
-            if let Ok(parsed) = url::Url::parse(&full_url) {
-                let code = parsed
-                    .query_pairs()
-                    .find(|(k, _)| k == "code")
-                    .map(|(_, v)| v.to_string());
-
-                if let Some(code) = code {
-                    log::info!("[Basecamp OAuth] Received code, exchanging for token...");
-
-                    // Exchange code for token via Netlify function
-                    let rt = tokio::runtime::Runtime::new().unwrap();
-                    match rt.block_on(exchange_code_for_token(&code, &client_id)) {
-                        Ok(token_data) => {
-                            log::info!("[Basecamp OAuth] Token exchange successful");
-
-                            // Send success response to browser
-                            let response = tiny_http::Response::from_string(
-                                "<html><body style=\"font-family: system-ui; text-align: center; padding-top: 50px;\">
-                                <h1>Authentication successful!</h1>
-                                <p>You can close this window and return to ReDD To-Do.</p>
-                                <script>window.close()</script>
-                                </body></html>"
-                            ).with_header(
-                                tiny_http::Header::from_bytes(&b"Content-Type"[..], &b"text/html"[..]).unwrap()
-                            );
-                            let _ = request.respond(response);
-
-                            // Emit success to frontend
-                            if let Some(window) = app.get_webview_window("main") {
-                                let _ = window.emit("basecamp-auth-success", token_data);
-                                let _ = window.set_focus();
-                            }
-                        }
-                        Err(e) => {
-                            log::error!("[Basecamp OAuth] Token exchange failed: {}", e);
-
-                            let response = tiny_http::Response::from_string(format!(
-                                "Authentication failed: {}",
-                                e
-                            ))
-                            .with_status_code(500);
-                            let _ = request.respond(response);
-
-                            if let Some(window) = app.get_webview_window("main") {
-                                let _ = window.emit("basecamp-auth-error", e);
-                            }
-                        }
-                    }
-                } else {
-                    log::error!("[Basecamp OAuth] No code in callback URL");
-                    let response =
-                        tiny_http::Response::from_string("No authorization code received")
-                            .with_status_code(400);
-                    let _ = request.respond(response);
-                }
-            }
-        } else {
-            let response = tiny_http::Response::from_string("Not found").with_status_code(404);
-            let _ = request.respond(response);
+            Err(_) => thread::sleep(LISTENER_POLL),
         }
     }
-
-    log::info!("[Basecamp OAuth] Local server shutting down");
 }
 
-fn start_local_token_bridge_server(app: AppHandle) -> Result<u16, String> {
+fn bind_bridge_server() -> Result<(tiny_http::Server, u16), String> {
     let listener = TcpListener::bind("127.0.0.1:0")
         .map_err(|e| format!("Failed to bind localhost callback server: {}", e))?;
 
@@ -213,130 +161,135 @@ fn start_local_token_bridge_server(app: AppHandle) -> Result<u16, String> {
 
     let server = tiny_http::Server::from_listener(listener, None)
         .map_err(|e| format!("Failed to start localhost callback server: {}", e))?;
+    Ok((server, port))
+}
 
-    log::info!(
-        "[Basecamp OAuth] Localhost bridge listening on {}",
-        server
-            .server_addr()
-            .to_ip()
-            .map(|addr| addr.to_string())
-            .unwrap_or_else(|| format!("127.0.0.1:{port}"))
-    );
-
+/// Report the sign-in as timed out if nothing finished it in time; for the
+/// deep link path, which has no listener to do so.
+fn expire_after(app: &AppHandle, nonce: &str, timeout: Duration) {
+    let (app, nonce) = (app.clone(), nonce.to_string());
     thread::spawn(move || {
-        if let Some(request) = server.recv().ok() {
-            let url = request.url().to_string();
-            log::info!(
-                "[Basecamp OAuth] Localhost bridge received request: {}",
-                url
-            );
-
-            if !url.starts_with("/callback") {
-                let response = tiny_http::Response::from_string("Not found").with_status_code(404);
-                let _ = request.respond(response);
-                return;
+        thread::sleep(timeout);
+        if auth_states().progress(&nonce, Instant::now()) == Progress::TimedOut {
+            emit_auth_error(&app, oauth::TIMED_OUT);
+        }
+    });
+}
+
+/// Answer requests on `server` until the sign-in with `nonce` gets its
+/// callback, finishes another way, is replaced or times out. Stray requests
+/// (favicon fetches and the like) and callbacks with the wrong state are
+/// turned away without ending it.
+fn serve_callback(
+    app: &AppHandle,
+    server: &tiny_http::Server,
+    nonce: &str,
+    finish: impl FnOnce(&url::Url) -> Result<BasecampStatus, String>,
+) {
+    loop {
+        match auth_states().progress(nonce, Instant::now()) {
+            Progress::Waiting => {}
+            Progress::Over => break,
+            Progress::TimedOut => {
+                log::warn!("[Basecamp OAuth] Timed out waiting for the callback");
+                emit_auth_error(app, oauth::TIMED_OUT);
+                break;
             }
+        }
 
-            let full_url = format!("http://127.0.0.1:{}{}", port, url);
-            let parsed = match url::Url::parse(&full_url) {
-                Ok(parsed) => parsed,
-                Err(e) => {
-                    let error_msg = format!("Failed to parse localhost callback URL: {}", e);
-                    log::error!("[Basecamp OAuth] {}", error_msg);
-                    let response =
-                        tiny_http::Response::from_string(error_msg.clone()).with_status_code(400);
-                    let _ = request.respond(response);
-                    emit_auth_error(&app, &error_msg);
-                    return;
-                }
-            };
-
-            if let Some(error) = parsed
-                .query_pairs()
-                .find(|(k, _)| k == "error")
-                .map(|(_, v)| v.to_string())
-            {
-                let error_desc = parsed
-                    .query_pairs()
-                    .find(|(k, _)| k == "error_description")
-                    .map(|(_, v)| v.to_string())
-                    .unwrap_or(error);
-
-                let response =
-                    tiny_http::Response::from_string(build_callback_html(false, &error_desc))
-                        .with_status_code(400)
-                        .with_header(
-                            tiny_http::Header::from_bytes(
-                                &b"Content-Type"[..],
-                                &b"text/html; charset=utf-8"[..],
-                            )
-                            .unwrap(),
-                        );
-                let _ = request.respond(response);
-                emit_auth_error(&app, &error_desc);
-                return;
+        let request = match server.recv_timeout(LISTENER_POLL) {
+            Ok(Some(request)) => request,
+            Ok(None) => continue,
+            Err(e) => {
+                log::error!("[Basecamp OAuth] Callback server failed: {}", e);
+                auth_states().cancel(nonce);
+                emit_auth_error(app, &format!("Callback server failed: {}", e));
+                break;
             }
+        };
 
-            let access_token = parsed
-                .query_pairs()
-                .find(|(k, _)| k == "access_token")
-                .map(|(_, v)| v.to_string());
-            let refresh_token = parsed
-                .query_pairs()
-                .find(|(k, _)| k == "refresh_token")
-                .map(|(_, v)| v.to_string());
-            let expires_in = parsed
-                .query_pairs()
-                .find(|(k, _)| k == "expires_in")
-                .map(|(_, v)| v.to_string());
-
-            match access_token {
-                Some(token) => {
-                    let payload = serde_json::json!({
-                        "access_token": token,
-                        "refresh_token": refresh_token,
-                        "expires_in": expires_in,
-                        "client_id": get_client_id(false)
-                    });
+        // Only the path: the query carries the code or tokens.
+        let url = request.url().to_string();
+        let path = url.split('?').next().unwrap_or_default();
+        log::info!(
+            "[Basecamp OAuth] Callback server received request: {}",
+            path
+        );
+        if !oauth::is_callback_path(&url) {
+            let _ = request
+                .respond(tiny_http::Response::from_string("Not found").with_status_code(404));
+            continue;
+        }
 
-                    let response = tiny_http::Response::from_string(build_callback_html(
-                        true,
-                        "Authentication successful. You can return to ReDD To-Do.",
-                    ))
-                    .with_header(
-                        tiny_http::Header::from_bytes(
-                            &b"Content-Type"[..],
-                            &b"text/html; charset=utf-8"[..],
-                        )
-                        .unwrap(),
-                    );
-                    let _ = request.respond(response);
-
-                    if let Some(window) = app.get_webview_window("main") {
-                        let _ = window.emit("basecamp-auth-success", payload);
-                        let _ = window.set_focus();
-                    }
-                }
-                None => {
-                    let error_msg = "No access token received from localhost callback";
-                    let response =
-                        tiny_http::Response::from_string(build_callback_html(false, error_msg))
-                            .with_status_code(400)
-                            .with_header(
-                                tiny_http::Header::from_bytes(
-                                    &b"Content-Type"[..],
-                                    &b"text/html; charset=utf-8"[..],
-                                )
-                                .unwrap(),
-                            );
-                    let _ = request.respond(response);
-                    emit_auth_error(&app, error_msg);
-                }
+        let verified = url::Url::parse(&format!("http://127.0.0.1{}", url))
+            .map_err(|e| format!("Failed to parse callback URL: {}", e))
+            .and_then(|parsed| {
+                auth_states().verify(query_param(&parsed, "state").as_deref(), Instant::now())?;
+                Ok(parsed)
+            });
+        let parsed = match verified {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                log::warn!("[Basecamp OAuth] Rejected callback: {}", e);
+                let _ = request.respond(callback_response(false, &e));
+                continue;
+            }
+        };
+
+        let connected = finish(&parsed);
+        let _ = match &connected {
+            Ok(_) => request.respond(callback_response(
+                true,
+                "Authentication successful. You can return to ReDD To-Do.",
+            )),
+            Err(e) => request.respond(callback_response(false, e)),
+        };
+        match connected {
+            Ok(status) => emit_auth_success(app, &status),
+            Err(e) => {
+                log::error!("[Basecamp OAuth] Sign-in failed: {}", e);
+                emit_auth_error(app, &e);
             }
         }
-    });
+        break;
+    }
 
-    Ok(port)
+    log::info!("[Basecamp OAuth] Callback server shutting down");
+}
+
+/// Dev mode: exchange the code Basecamp sent for tokens.
+fn finish_dev_callback(
+    app: &AppHandle,
+    parsed: &url::Url,
+    client_id: &str,
+) -> Result<BasecampStatus, String> {
+    if let Some(error) = callback_error(parsed) {
+        return Err(error);
+    }
+    let code = query_param(parsed, "code").ok_or("No authorization code received")?;
+    log::info!("[Basecamp OAuth] Received code, exchanging for token...");
+    tauri::async_runtime::block_on(async {
+        let tokens = exchange_code_for_token(&code, client_id).await?;
+        connect(app, tokens, None, None).await
+    })
+}
+
+/// Release: the Netlify function already exchanged the code and passes the
+/// tokens on.
+fn finish_bridge_callback(app: &AppHandle, parsed: &url::Url) -> Result<BasecampStatus, String> {
+    if let Some(error) = callback_error(parsed) {
+        return Err(error);
+    }
+    let tokens = query_tokens(parsed).ok_or("No access token received from localhost callback")?;
+    tauri::async_runtime::block_on(connect(app, tokens, None, None))
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
 }
 
 fn build_callback_html(success: bool, message: &str) -> String {
@@ -348,10 +301,54 @@ fn build_callback_html(success: bool, message: &str) -> String {
 
     format!(
         "<html><body style=\"font-family: system-ui; text-align: center; padding: 48px 24px;\"><h1>{}</h1><p>{}</p><script>setTimeout(() => window.close(), 600);</script></body></html>",
-        title, message
+        title,
+        escape_html(message)
     )
 }
 
+fn callback_response(
+    success: bool,
+    message: &str,
+) -> tiny_http::Response<std::io::Cursor<Vec<u8>>> {
+    tiny_http::Response::from_string(build_callback_html(success, message))
+        .with_status_code(if success { 200 } else { 400 })
+        .with_header(
+            tiny_http::Header::from_bytes(&b"Content-Type"[..], &b"text/html; charset=utf-8"[..])
+                .unwrap(),
+        )
+}
+
+fn query_param(parsed: &url::Url, name: &str) -> Option<String> {
+    parsed
+        .query_pairs()
+        .find(|(k, _)| k == name)
+        .map(|(_, v)| v.to_string())
+        .filter(|v| !v.is_empty())
+}
+
+/// The error Basecamp (or the Netlify function) reported instead of tokens.
+fn callback_error(parsed: &url::Url) -> Option<String> {
+    let error = query_param(parsed, "error")?;
+    Some(query_param(parsed, "error_description").unwrap_or(error))
+}
+
+/// Tokens from a callback's query string, as the Netlify function passes them on.
+fn query_tokens(parsed: &url::Url) -> Option<Tokens> {
+    Some(Tokens {
+        access_token: query_param(parsed, "access_token")?,
+        refresh_token: query_param(parsed, "refresh_token"),
+        expires_in: query_param(parsed, "expires_in").and_then(|v| v.parse().ok()),
+    })
+}
+
+/// Tell the frontend it is connected; the tokens themselves stay in the backend.
+fn emit_auth_success(app: &AppHandle, status: &BasecampStatus) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.emit("basecamp-auth-success", status);
+        let _ = window.set_focus();
+    }
+}
+
 fn emit_auth_error(app: &AppHandle, error_msg: &str) {
     if let Some(window) = app.get_webview_window("main") {
         let _ = window.emit("basecamp-auth-error", error_msg);
@@ -359,7 +356,7 @@ fn emit_auth_error(app: &AppHandle, error_msg: &str) {
 }
 
 /// Exchange authorization code for tokens via Netlify function
-async fn exchange_code_for_token(code: &str, client_id: &str) -> Result<serde_json::Value, String> {
+async fn exchange_code_for_token(code: &str, client_id: &str) -> Result<Tokens, String> {
     let client = reqwest::Client::new();
 
     let response = client
@@ -378,87 +375,55 @@ async fn exchange_code_for_token(code: &str, client_id: &str) -> Result<serde_js
         return Err(format!("Token exchange failed: {}", error_text));
     }
 
-    let mut token_data: serde_json::Value = response
+    response
         .json()
         .await
-        .map_err(|e| format!("Failed to parse response: {}", e))?;
-
-    // Add client_id to response
-    if let Some(obj) = token_data.as_object_mut() {
-        obj.insert("client_id".to_string(), serde_json::json!(client_id));
-    }
-
-    Ok(token_data)
+        .map_err(|e| format!("Failed to parse response: {}", e))
 }
 
 /// Handle OAuth callback (called from deep link handler in production)
 #[command]
 pub async fn handle_oauth_callback(app: AppHandle, url: String) -> Result<(), String> {
-    log::info!("[Basecamp OAuth] Received callback URL: {}", url);
-
+    // Only a callback for the sign-in in progress may end it, so anything
+    // else is logged and dropped without telling the frontend. Timeouts are
+    // reported by the sign-in's own watcher.
     let parsed = match url::Url::parse(&url) {
         Ok(u) => u,
         Err(e) => {
             let error_msg = format!("Failed to parse OAuth URL: {}", e);
-            if let Some(window) = app.get_webview_window("main") {
-                let _ = window.emit("basecamp-auth-error", &error_msg);
-            }
+            log::warn!("[Basecamp OAuth] Rejected callback: {}", error_msg);
             return Err(error_msg);
         }
     };
+    log::info!(
+        "[Basecamp OAuth] Received callback deep link: {}",
+        parsed.host_str().unwrap_or_default()
+    );
 
-    // Check for error in callback
-    if let Some(error) = parsed.query_pairs().find(|(k, _)| k == "error") {
-        let error_desc = parsed
-            .query_pairs()
-            .find(|(k, _)| k == "error_description")
-            .map(|(_, v)| v.to_string())
-            .unwrap_or_else(|| error.1.to_string());
-
-        if let Some(window) = app.get_webview_window("main") {
-            let _ = window.emit("basecamp-auth-error", &error_desc);
-        }
-        return Err(error_desc);
+    // The state must match, errors included, so a link from elsewhere can
+    // neither connect nor cancel the sign-in.
+    let state = query_param(&parsed, "state");
+    let verified = auth_states().verify(state.as_deref(), Instant::now());
+    if let Err(e) = verified {
+        log::warn!("[Basecamp OAuth] Rejected callback: {}", e);
+        return Err(e);
     }
 
-    // Extract tokens
-    let access_token = parsed
-        .query_pairs()
-        .find(|(k, _)| k == "access_token")
-        .map(|(_, v)| v.to_string());
-    let refresh_token = parsed
-        .query_pairs()
-        .find(|(k, _)| k == "refresh_token")
-        .map(|(_, v)| v.to_string());
-    let expires_in = parsed
-        .query_pairs()
-        .find(|(k, _)| k == "expires_in")
-        .map(|(_, v)| v.to_string());
-
-    let is_dev = cfg!(debug_assertions);
-
-    match access_token {
-        Some(token) => {
-            let payload = serde_json::json!({
-                "access_token": token,
-                "refresh_token": refresh_token,
-                "expires_in": expires_in,
-                "client_id": get_client_id(is_dev)
-            });
-
-            if let Some(window) = app.get_webview_window("main") {
-                let _ = window.emit("basecamp-auth-success", payload);
-                let _ = window.set_focus();
-            }
-
+    let connected = match callback_error(&parsed) {
+        Some(error) => Err(error),
+        None => match query_tokens(&parsed) {
+            Some(tokens) => connect(&app, tokens, None, None).await,
+            None => Err("No access token received".to_string()),
+        },
+    };
+    match connected {
+        Ok(status) => {
+            emit_auth_success(&app, &status);
             Ok(())
         }
-        None => {
-            let error_msg = "No access token received";
-            if let Some(window) = app.get_webview_window("main") {
-                let _ = window.emit("basecamp-auth-error", error_msg);
-            }
-            Err(error_msg.to_string())
+        Err(e) => {
+            emit_auth_error(&app, &e);
+            Err(e)
         }
     }
 }
@@ -0,0 +1,142 @@
+use super::store::store_dir;
+use crate::focus_timer::{TimerSnapshot, TimerState};
+use crate::pomodoro::{self, PhaseInfo, PomodoroCycle, PomodoroSettings};
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tauri::{command, AppHandle, Emitter};
+
+/// Pomodoro cycles of running focus sessions, keyed by task id.
+fn pomodoro_cycles() -> &'static Mutex<HashMap<String, PomodoroCycle>> {
+    static CYCLES: OnceLock<Mutex<HashMap<String, PomodoroCycle>>> = OnceLock::new();
+    CYCLES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// What a task's focus timer has to do for its cycle; applied by
+/// `commands::focus_timer`, which owns the timers. The cycle keeps its own
+/// phase countdown, so the timer's expected duration is left alone.
+pub(crate) enum PomodoroStep {
+    /// Pause the timer for a break.
+    StartBreak(PhaseInfo),
+    /// Resume the timer for the next work phase.
+    EndBreak {
+        info: PhaseInfo,
+        reason: &'static str,
+    },
+}
+
+fn epoch_ms_after(length_ms: u64) -> i64 {
+    chrono::Utc::now().timestamp_millis() + length_ms as i64
+}
+
+pub(crate) fn emit_break_started(app: &AppHandle, task_id: &str, info: &PhaseInfo) {
+    let _ = app.emit(
+        "focus-break-started",
+        serde_json::json!({
+            "taskId": task_id,
+            "phase": info.phase,
+            "lengthMs": info.length_ms,
+            "endsAt": epoch_ms_after(info.length_ms),
+            "completedWorkPhases": info.completed_work_phases
+        }),
+    );
+}
+
+pub(crate) fn emit_break_ended(app: &AppHandle, task_id: &str, info: &PhaseInfo, reason: &str) {
+    let _ = app.emit(
+        "focus-break-ended",
+        serde_json::json!({
+            "taskId": task_id,
+            "reason": reason,
+            "workLengthMs": info.length_ms,
+            "completedWorkPhases": info.completed_work_phases
+        }),
+    );
+}
+
+/// Start a Pomodoro cycle for a newly started focus timer if Pomodoro is
+/// enabled.
+pub(crate) fn begin_pomodoro(app: &AppHandle, task_id: &str, focus_elapsed_ms: u64) {
+    let Ok(dir) = store_dir(app) else {
+        return;
+    };
+    let settings = pomodoro::load_settings(&dir);
+    if !settings.enabled {
+        return;
+    }
+    let cycle = PomodoroCycle::new(settings, Duration::from_millis(focus_elapsed_ms));
+    if let Ok(mut cycles) = pomodoro_cycles().lock() {
+        cycles.insert(task_id.to_string(), cycle);
+    }
+}
+
+/// Drop the task's cycle when its focus timer stops, closing any break view.
+pub(crate) fn end_pomodoro(app: &AppHandle, task_id: &str) {
+    let Some(cycle) = pomodoro_cycles()
+        .lock()
+        .ok()
+        .and_then(|mut cycles| cycles.remove(task_id))
+    else {
+        return;
+    };
+    if cycle.phase().is_break() {
+        let _ = app.emit(
+            "focus-break-ended",
+            serde_json::json!({ "taskId": task_id, "reason": "stopped" }),
+        );
+    }
+}
+
+/// Advance every running cycle; called with each focus tick. A finished work
+/// phase asks for a break, and a finished break (or resuming the timer
+/// during one) starts the next work phase.
+pub(crate) fn pomodoro_steps(snapshots: &[TimerSnapshot]) -> Vec<(String, PomodoroStep)> {
+    let now = Instant::now();
+    let Ok(mut cycles) = pomodoro_cycles().lock() else {
+        return Vec::new();
+    };
+    snapshots
+        .iter()
+        .filter_map(|snapshot| {
+            let cycle = cycles.get_mut(&snapshot.task_id)?;
+            let elapsed = Duration::from_millis(snapshot.elapsed_ms);
+            let resumed = cycle.phase().is_break() && snapshot.state != TimerState::Paused;
+            let change = if resumed {
+                Some(cycle.skip(elapsed, now))
+            } else {
+                cycle.advance(elapsed, now)
+            };
+            let info = change?;
+            let step = if info.phase.is_break() {
+                PomodoroStep::StartBreak(info)
+            } else {
+                PomodoroStep::EndBreak {
+                    info,
+                    reason: if resumed { "resumed" } else { "finished" },
+                }
+            };
+            Some((snapshot.task_id.clone(), step))
+        })
+        .collect()
+}
+
+/// End the task's break early. Returns the new work phase.
+pub(crate) fn skip_break(task_id: &str, focus_elapsed_ms: u64) -> Result<PhaseInfo, String> {
+    let mut cycles = pomodoro_cycles().lock().map_err(|e| e.to_string())?;
+    let cycle = cycles
+        .get_mut(task_id)
+        .filter(|cycle| cycle.phase().is_break())
+        .ok_or("This focus session is not on a break")?;
+    Ok(cycle.skip(Duration::from_millis(focus_elapsed_ms), Instant::now()))
+}
+
+#[command]
+pub fn get_pomodoro_settings(app: AppHandle) -> Result<PomodoroSettings, String> {
+    Ok(pomodoro::load_settings(&store_dir(&app)?))
+}
+
+/// Save Pomodoro settings. They apply to focus sessions started afterwards.
+#[command]
+pub fn set_pomodoro_settings(app: AppHandle, settings: PomodoroSettings) -> Result<(), String> {
+    pomodoro::save_settings(&store_dir(&app)?, &settings)
+}
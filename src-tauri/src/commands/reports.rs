@@ -0,0 +1,36 @@
+use super::store::{store_dir, store_lock};
+use crate::reports::{self, ReportFormat, ReportGrouping};
+use crate::store;
+use tauri::{command, AppHandle};
+
+/// Build a time-tracking report for `from..=to` (inclusive `YYYY-MM-DD`
+/// dates, either may be omitted) from the persisted task data. The frontend
+/// saves it through the dialog and fs plugins, so their scope applies.
+#[command]
+pub fn export_time_report(
+    app: AppHandle,
+    from: Option<String>,
+    to: Option<String>,
+    format: Option<ReportFormat>,
+    grouping: Option<ReportGrouping>,
+) -> Result<String, String> {
+    let from = reports::parse_date(from.as_deref())?;
+    let to = reports::parse_date(to.as_deref())?;
+    if let (Some(from), Some(to)) = (from, to) {
+        if from > to {
+            return Err(format!("Report range starts after it ends: {from} > {to}"));
+        }
+    }
+
+    let dir = store_dir(&app)?;
+    let data = {
+        let _guard = store_lock().lock().map_err(|e| e.to_string())?;
+        store::load(&dir)?.unwrap_or_default()
+    };
+    let report = reports::build(&data, from, to);
+    reports::render(
+        &report,
+        format.unwrap_or_default(),
+        grouping.unwrap_or_default(),
+    )
+}
@@ -0,0 +1,204 @@
+use super::deep_link::{add_task, complete_task, focus_task};
+use super::focus_timer::timer_snapshots;
+use super::store::{store_dir, store_lock};
+use super::tray::active_focus_task_id;
+use crate::capture::MAX_DURATION_MINUTES;
+use crate::deep_link;
+use crate::rpc::server::RpcServer;
+use crate::rpc::{self, RpcError, RpcSettings};
+use crate::store::{self, AppData};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use tauri::{command, AppHandle, Listener, Manager};
+
+#[derive(Default)]
+struct RpcState {
+    server: Option<RpcServer>,
+    /// Why the server isn't running although it is enabled.
+    error: Option<String>,
+}
+
+fn rpc_state() -> &'static Mutex<RpcState> {
+    static STATE: OnceLock<Mutex<RpcState>> = OnceLock::new();
+    STATE.get_or_init(|| Mutex::new(RpcState::default()))
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcStatus {
+    pub running: bool,
+    pub socket_path: Option<String>,
+    pub token_path: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Where the socket and token file live: the user's runtime directory if
+/// the platform has one, otherwise the app's local data directory. Keep in
+/// step with `cli::socket_dir`, which finds it without a running app.
+fn socket_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let path = app.path();
+    path.runtime_dir()
+        .map(|dir| dir.join(&app.config().identifier))
+        .or_else(|_| path.app_local_data_dir())
+        .map_err(|e| e.to_string())
+}
+
+fn load_data(app: &AppHandle) -> Result<AppData, String> {
+    let dir = store_dir(app)?;
+    let _guard = store_lock().lock().map_err(|e| e.to_string())?;
+    Ok(store::load(&dir)?.unwrap_or_default())
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct ListParams {
+    /// Tab id or name; every tab if missing.
+    tab: Option<String>,
+    include_completed: bool,
+}
+
+#[derive(Deserialize)]
+struct AddParams {
+    text: String,
+    tab: Option<String>,
+    minutes: Option<u32>,
+}
+
+#[derive(Deserialize)]
+struct TaskParams {
+    /// Task id or name.
+    task: String,
+}
+
+fn list(app: &AppHandle, params: ListParams) -> Result<Value, RpcError> {
+    let data = load_data(app)?;
+    let tabs = deep_link::list_tasks(&data, params.tab.as_deref(), params.include_completed)
+        .map_err(|e| RpcError::failed(e.to_string()))?;
+    Ok(json!(tabs))
+}
+
+/// The timer of the session the tray shows, with the task's text.
+fn current_timer(app: &AppHandle) -> Result<Value, RpcError> {
+    let snapshots = timer_snapshots();
+    let active = active_focus_task_id();
+    let Some(snapshot) = snapshots
+        .iter()
+        .find(|snapshot| Some(&snapshot.task_id) == active.as_ref())
+        .or_else(|| snapshots.first())
+    else {
+        return Ok(Value::Null);
+    };
+    let mut timer = json!(snapshot);
+    let text = load_data(app)?
+        .find_task(&snapshot.task_id)
+        .map(|(_, task)| task.text.clone());
+    timer["text"] = json!(text);
+    Ok(timer)
+}
+
+struct AppMethods(AppHandle);
+
+impl rpc::Handler for AppMethods {
+    fn call(&self, method: &str, params: Value) -> Result<Value, RpcError> {
+        let app = &self.0;
+        match method {
+            "list" => list(app, rpc::params(params)?),
+            "add" => {
+                let AddParams { text, tab, minutes } = rpc::params(params)?;
+                if minutes.is_some_and(|m| !(1..=MAX_DURATION_MINUTES).contains(&m)) {
+                    return Err(RpcError::invalid_params(format!(
+                        "minutes must be from 1 to {MAX_DURATION_MINUTES}"
+                    )));
+                }
+                Ok(json!(add_task(app, &text, tab, minutes)?))
+            }
+            "complete" => {
+                let TaskParams { task } = rpc::params(params)?;
+                Ok(json!({ "taskId": complete_task(app, &task)? }))
+            }
+            "focus" => {
+                let TaskParams { task } = rpc::params(params)?;
+                Ok(json!({ "taskId": focus_task(app, &task)? }))
+            }
+            "current-timer" => current_timer(app),
+            _ => Err(RpcError::method_not_found(method)),
+        }
+    }
+}
+
+fn status(app: &AppHandle, state: &RpcState) -> RpcStatus {
+    let running = state.server.is_some();
+    let dir = socket_dir(app).ok().filter(|_| running);
+    let path = |name: &str| dir.as_ref().map(|dir| dir.join(name).display().to_string());
+    RpcStatus {
+        running,
+        socket_path: path(rpc::SOCKET_FILE_NAME),
+        token_path: path(rpc::TOKEN_FILE_NAME),
+        error: state.error.clone(),
+    }
+}
+
+/// Start or stop the server to match `settings`.
+fn apply_settings(app: &AppHandle, settings: &RpcSettings) -> Result<RpcStatus, String> {
+    let mut state = rpc_state().lock().map_err(|e| e.to_string())?;
+    // Dropping the old server removes its socket and token file.
+    state.server = None;
+    state.error = None;
+    if settings.enabled {
+        match socket_dir(app).and_then(|dir| RpcServer::start(&dir, AppMethods(app.clone()))) {
+            Ok(server) => state.server = Some(server),
+            Err(e) => {
+                log::warn!("[RPC] {e}");
+                state.error = Some(e);
+            }
+        }
+    }
+    Ok(status(app, &state))
+}
+
+/// Forward app events to subscribed clients, and start the server if the
+/// user turned it on.
+pub fn setup_rpc_server(app: &AppHandle) {
+    for event in rpc::EVENTS {
+        app.listen_any(event, move |message| {
+            if let Ok(state) = rpc_state().lock() {
+                if let Some(server) = state.server.as_ref() {
+                    let payload = serde_json::from_str(message.payload()).unwrap_or(Value::Null);
+                    server.notify(event, &payload);
+                }
+            }
+        });
+    }
+    let settings = store_dir(app)
+        .map(|dir| rpc::load_settings(&dir))
+        .unwrap_or_default();
+    if settings.enabled {
+        let _ = apply_settings(app, &settings);
+    }
+}
+
+/// Remove the socket and token file on exit.
+pub fn stop_rpc_server() {
+    if let Ok(mut state) = rpc_state().lock() {
+        state.server = None;
+    }
+}
+
+#[command]
+pub fn get_rpc_settings(app: AppHandle) -> Result<RpcSettings, String> {
+    Ok(rpc::load_settings(&store_dir(&app)?))
+}
+
+#[command]
+pub fn set_rpc_settings(app: AppHandle, settings: RpcSettings) -> Result<RpcStatus, String> {
+    rpc::save_settings(&store_dir(&app)?, &settings)?;
+    apply_settings(&app, &settings)
+}
+
+#[command]
+pub fn get_rpc_status(app: AppHandle) -> Result<RpcStatus, String> {
+    let state = rpc_state().lock().map_err(|e| e.to_string())?;
+    Ok(status(&app, &state))
+}
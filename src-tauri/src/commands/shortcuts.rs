@@ -0,0 +1,151 @@
+use super::focus_timer::{pause_task_timer, resume_task_timer, timer_snapshots};
+use super::store::store_dir;
+use super::tray::{active_focus_task_id, complete_focused_task, toggle_focus};
+use super::window::open_capture_window;
+use crate::focus_timer::TimerState;
+use crate::shortcuts::{self, ShortcutAction, ShortcutSettings};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use tauri::{command, AppHandle};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutEvent, ShortcutState};
+
+/// Outcome of registering one action's shortcut, shown next to it in
+/// settings.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShortcutStatus {
+    pub action: ShortcutAction,
+    pub accelerator: String,
+    pub registered: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Default)]
+struct Registered {
+    /// Shortcut id to action, for the plugin handler.
+    actions: HashMap<u32, ShortcutAction>,
+    statuses: Vec<ShortcutStatus>,
+}
+
+fn registered() -> &'static Mutex<Registered> {
+    static REGISTERED: OnceLock<Mutex<Registered>> = OnceLock::new();
+    REGISTERED.get_or_init(|| Mutex::new(Registered::default()))
+}
+
+/// Handler passed to the global-shortcut plugin.
+pub fn handle_global_shortcut(app: &AppHandle, shortcut: &Shortcut, event: ShortcutEvent) {
+    if event.state() != ShortcutState::Pressed {
+        return;
+    }
+    let action = registered()
+        .lock()
+        .ok()
+        .and_then(|registered| registered.actions.get(&shortcut.id()).copied());
+    match action {
+        Some(ShortcutAction::QuickAdd) => {
+            if let Err(e) = open_capture_window(app) {
+                log::warn!("[Shortcuts] Failed to open capture window: {e}");
+            }
+        }
+        Some(ShortcutAction::ToggleFocus) => toggle_focus(app),
+        Some(ShortcutAction::CompleteTask) => complete_focused_task(app),
+        Some(ShortcutAction::ToggleTimer) => toggle_focus_timer(app),
+        None => {}
+    }
+}
+
+/// Pause the focused task's timer, or resume it if it is paused.
+fn toggle_focus_timer(app: &AppHandle) {
+    let Some(task_id) = active_focus_task_id() else {
+        return;
+    };
+    let paused = timer_snapshots()
+        .iter()
+        .any(|snapshot| snapshot.task_id == task_id && snapshot.state == TimerState::Paused);
+    let result = if paused {
+        resume_task_timer(app, &task_id)
+    } else {
+        pause_task_timer(app, &task_id)
+    };
+    if let Err(e) = result {
+        log::warn!("[Shortcuts] Failed to toggle focus timer: {e}");
+    }
+}
+
+/// Replace the registered shortcuts with `settings`. Shortcuts that clash
+/// with each other or that the OS refuses (usually because another app holds
+/// them) are left out and reported.
+fn apply_shortcuts(app: &AppHandle, settings: &ShortcutSettings) -> Vec<ShortcutStatus> {
+    let global_shortcut = app.global_shortcut();
+    if let Err(e) = global_shortcut.unregister_all() {
+        log::warn!("[Shortcuts] Failed to unregister shortcuts: {e}");
+    }
+
+    let mut actions = HashMap::new();
+    let statuses: Vec<ShortcutStatus> = shortcuts::resolve_bindings(settings)
+        .into_iter()
+        .map(|(action, accelerator, resolved)| {
+            let result = resolved.and_then(|normalized| {
+                let shortcut: Shortcut = normalized.parse().map_err(|e| format!("{e}"))?;
+                global_shortcut
+                    .register(shortcut)
+                    .map_err(|e| format!("Unavailable: {e}"))?;
+                actions.insert(shortcut.id(), action);
+                Ok(())
+            });
+            ShortcutStatus {
+                action,
+                accelerator,
+                registered: result.is_ok(),
+                error: result.err(),
+            }
+        })
+        .collect();
+
+    for status in statuses.iter().filter(|status| !status.registered) {
+        log::warn!(
+            "[Shortcuts] {} ({}) not registered: {}",
+            status.action.label(),
+            status.accelerator,
+            status.error.as_deref().unwrap_or_default()
+        );
+    }
+    if let Ok(mut registered) = registered().lock() {
+        registered.actions = actions;
+        registered.statuses = statuses.clone();
+    }
+    statuses
+}
+
+/// Register the saved shortcuts at startup.
+pub fn register_global_shortcuts(app: &AppHandle) {
+    let settings = store_dir(app)
+        .map(|dir| shortcuts::load_settings(&dir))
+        .unwrap_or_default();
+    apply_shortcuts(app, &settings);
+}
+
+#[command]
+pub fn get_shortcut_settings(app: AppHandle) -> Result<ShortcutSettings, String> {
+    Ok(shortcuts::load_settings(&store_dir(&app)?))
+}
+
+/// Save and re-register the shortcuts, returning how each registration went.
+#[command]
+pub fn set_shortcut_settings(
+    app: AppHandle,
+    settings: ShortcutSettings,
+) -> Result<Vec<ShortcutStatus>, String> {
+    shortcuts::save_settings(&store_dir(&app)?, &settings)?;
+    Ok(apply_shortcuts(&app, &settings))
+}
+
+/// How the current shortcuts were registered.
+#[command]
+pub fn get_shortcut_status() -> Vec<ShortcutStatus> {
+    registered()
+        .lock()
+        .map(|registered| registered.statuses.clone())
+        .unwrap_or_default()
+}
@@ -0,0 +1,129 @@
+use crate::store::{self, AppData, ExternalChanges, Task};
+use serde_json::{Map, Value};
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use tauri::{command, AppHandle, Emitter, Manager};
+
+/// Serializes read-modify-write cycles on the task store across windows.
+pub(crate) fn store_lock() -> &'static Mutex<()> {
+    static LOCK: OnceLock<Mutex<()>> = OnceLock::new();
+    LOCK.get_or_init(|| Mutex::new(()))
+}
+
+pub(crate) fn store_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path().app_data_dir().map_err(|e| e.to_string())
+}
+
+/// Apply a backend-side change to the task store and send the changed tasks
+/// to the windows as `store-updated`. The store can lag the frontend while
+/// its saves are queued, so the main window merges in just those tasks (see
+/// `merge_store_changes`) rather than adopting the whole store.
+pub(crate) fn update_store<T>(
+    app: &AppHandle,
+    f: impl FnOnce(&mut AppData) -> Result<T, String>,
+) -> Result<T, String> {
+    let dir = store_dir(app)?;
+    let (result, data, changes) = {
+        let _guard = store_lock().lock().map_err(|e| e.to_string())?;
+        let _lock = store::StoreLock::acquire(&dir)?;
+        let before = store::load(&dir)?.unwrap_or_default();
+        let mut data = before.clone();
+        let result = f(&mut data)?;
+        store::save(&dir, &data)?;
+        let changes = ExternalChanges::between(&before, &data);
+        (result, data, changes)
+    };
+    let _ = app.emit(
+        "store-updated",
+        serde_json::json!({ "data": data, "changes": changes }),
+    );
+    Ok(result)
+}
+
+/// Load the persisted task store, or `None` if nothing has been saved yet.
+#[command]
+pub fn load_state(app: AppHandle) -> Result<Option<AppData>, String> {
+    let dir = store_dir(&app)?;
+    let _guard = store_lock().lock().map_err(|e| e.to_string())?;
+    store::load(&dir)
+}
+
+/// If tasks were edited while the app wasn't running (see
+/// `store::save_external`), merge them into the main window's `local` copy
+/// and return the result for it to adopt at startup. Without a local copy
+/// the store is returned as is.
+#[command]
+pub fn take_external_store_changes(
+    app: AppHandle,
+    local: Option<AppData>,
+) -> Result<Option<AppData>, String> {
+    let dir = store_dir(&app)?;
+    let _guard = store_lock().lock().map_err(|e| e.to_string())?;
+    let _lock = store::StoreLock::acquire(&dir)?;
+    let Some(changes) = store::take_external_changes(&dir) else {
+        return Ok(None);
+    };
+    let external = store::load(&dir)?;
+    let Some(mut local) = local else {
+        return Ok(external);
+    };
+    store::merge_external(&mut local, &external.unwrap_or_default(), &changes);
+    store::save(&dir, &local)?;
+    Ok(Some(local))
+}
+
+/// Bring the tasks a `store-updated` event reports as changed into the
+/// window's `local` copy and return the result for it to adopt.
+#[command]
+pub fn merge_store_changes(mut local: AppData, data: AppData, changes: ExternalChanges) -> AppData {
+    store::merge_external(&mut local, &data, &changes);
+    local
+}
+
+/// Replace the persisted task store with the frontend's current snapshot.
+#[command]
+pub fn save_state(app: AppHandle, state: AppData) -> Result<(), String> {
+    let dir = store_dir(&app)?;
+    {
+        let _guard = store_lock().lock().map_err(|e| e.to_string())?;
+        let _lock = store::StoreLock::acquire(&dir)?;
+        store::save(&dir, &state)?;
+    }
+    // Not `store-updated`: windows would re-adopt their own snapshot. The tray
+    // listens for this to pick up renamed tasks and favourites.
+    let _ = app.emit("store-saved", ());
+    Ok(())
+}
+
+/// Keep the backend's copy of plan-mode data in sync so backups include it.
+#[command]
+pub fn save_plan_data(app: AppHandle, plan: Map<String, Value>) -> Result<(), String> {
+    let dir = store_dir(&app)?;
+    let _guard = store_lock().lock().map_err(|e| e.to_string())?;
+    store::save_plan(&dir, &plan)
+}
+
+/// Patch a single task in place without rewriting the caller's full snapshot.
+/// Used by focus windows so periodic saves can't clobber main-window edits.
+#[command]
+pub fn patch_task(
+    app: AppHandle,
+    task_id: String,
+    patch: Map<String, Value>,
+) -> Result<Task, String> {
+    let dir = store_dir(&app)?;
+    let task = {
+        let _guard = store_lock().lock().map_err(|e| e.to_string())?;
+        let _lock = store::StoreLock::acquire(&dir)?;
+        let mut data = store::load(&dir)?.ok_or("No saved data to patch")?;
+        let task = data.patch_task(&task_id, &patch)?;
+        store::save(&dir, &data)?;
+        task
+    };
+
+    let _ = app.emit(
+        "task-updated",
+        serde_json::json!({ "taskId": task.id, "text": task.text }),
+    );
+    Ok(task)
+}
@@ -0,0 +1,429 @@
+use super::focus_timer::timer_snapshots;
+use super::store::store_dir;
+use super::window::{focus_window_label, fullscreen_focus_window_label};
+use crate::focus_timer::{TimerSnapshot, TimerState};
+use crate::store;
+use serde::Deserialize;
+use serde_json::Value;
+use std::sync::{Mutex, OnceLock};
+use tauri::menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem, Submenu};
+use tauri::tray::{TrayIcon, TrayIconBuilder};
+use tauri::{command, AppHandle, Emitter, Listener, Manager, Wry};
+
+/// Favourites listed under "Switch to".
+const MAX_FAVOURITES: usize = 10;
+const SWITCH_ID_PREFIX: &str = "tray-switch:";
+
+struct TrayHandles {
+    icon: TrayIcon<Wry>,
+    status: MenuItem<Wry>,
+    toggle: MenuItem<Wry>,
+    complete: MenuItem<Wry>,
+    switch: Submenu<Wry>,
+    open: MenuItem<Wry>,
+}
+
+/// Menu texts in the app's language, sent by the main window. `start_focus_on`
+/// contains `{task}` where the task name goes.
+#[derive(Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TrayLabels {
+    no_focus_session: String,
+    start_focus: String,
+    start_focus_on: String,
+    stop_focus: String,
+    complete_task: String,
+    switch_to_favourite: String,
+    open_app: String,
+    untitled_task: String,
+    paused: String,
+}
+
+impl Default for TrayLabels {
+    fn default() -> Self {
+        Self {
+            no_focus_session: "No focus session".to_string(),
+            start_focus: "Start focus".to_string(),
+            start_focus_on: "Start focus: {task}".to_string(),
+            stop_focus: "Stop focus".to_string(),
+            complete_task: "Complete task".to_string(),
+            switch_to_favourite: "Switch to favourite".to_string(),
+            open_app: "Open ReDD To-Do".to_string(),
+            untitled_task: "Untitled task".to_string(),
+            paused: "paused".to_string(),
+        }
+    }
+}
+
+#[derive(Default)]
+struct TrayState {
+    /// Task most recently opened in focus mode; wins when several timers run.
+    preferred: Option<String>,
+    /// Last task that had a focus session, offered by "Start focus".
+    last: Option<(String, String)>,
+    active: Option<(String, String)>,
+    favourites: Vec<(String, String)>,
+}
+
+/// Set once at startup. Menu calls block on the main thread, so they are made
+/// without holding the state lock.
+fn handles() -> &'static OnceLock<TrayHandles> {
+    static HANDLES: OnceLock<TrayHandles> = OnceLock::new();
+    &HANDLES
+}
+
+fn tray_state() -> &'static Mutex<TrayState> {
+    static STATE: OnceLock<Mutex<TrayState>> = OnceLock::new();
+    STATE.get_or_init(|| Mutex::new(TrayState::default()))
+}
+
+fn tray_labels() -> &'static Mutex<TrayLabels> {
+    static LABELS: OnceLock<Mutex<TrayLabels>> = OnceLock::new();
+    LABELS.get_or_init(|| Mutex::new(TrayLabels::default()))
+}
+
+fn current_labels() -> TrayLabels {
+    tray_labels()
+        .lock()
+        .map(|labels| labels.clone())
+        .unwrap_or_default()
+}
+
+fn format_elapsed(ms: u64) -> String {
+    let secs = ms / 1000;
+    let (hours, minutes, seconds) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if hours > 0 {
+        format!("{hours}:{minutes:02}:{seconds:02}")
+    } else {
+        format!("{minutes:02}:{seconds:02}")
+    }
+}
+
+/// Task names and incomplete favourites from the task store.
+fn load_tasks(app: &AppHandle) -> (Vec<(String, String)>, Vec<(String, String)>) {
+    let data = store_dir(app)
+        .ok()
+        .and_then(|dir| store::load(&dir).ok().flatten())
+        .unwrap_or_default();
+    let tasks: Vec<_> = data
+        .tabs
+        .values()
+        .flat_map(|tab| tab.tasks.iter())
+        .collect();
+    let names = tasks
+        .iter()
+        .map(|task| (task.id.clone(), task.text.clone()))
+        .collect();
+    let favourites = tasks
+        .iter()
+        .filter(|task| task.is_favourite && !task.completed)
+        .map(|task| (task.id.clone(), task.text.clone()))
+        .collect();
+    (names, favourites)
+}
+
+/// Bring the tray in line with the focus timers. `reload_tasks` re-reads task
+/// names and favourites from disk; otherwise only the elapsed time changes.
+/// The focus state is tracked even without a tray icon, since global
+/// shortcuts act on it too.
+pub(crate) fn refresh_tray(app: &AppHandle, reload_tasks: bool) {
+    let snapshots = timer_snapshots();
+    let labels = current_labels();
+
+    let (status, title, toggle, has_active, favourites) = {
+        let Ok(mut state) = tray_state().lock() else {
+            return;
+        };
+        let snapshot: Option<&TimerSnapshot> = state
+            .preferred
+            .as_ref()
+            .and_then(|id| snapshots.iter().find(|s| &s.task_id == id))
+            .or_else(|| snapshots.iter().find(|s| s.state != TimerState::Paused))
+            .or_else(|| snapshots.first());
+
+        let active_changed = snapshot.map(|s| &s.task_id) != state.active.as_ref().map(|a| &a.0);
+        let mut favourites = None;
+        if reload_tasks || active_changed {
+            let (names, loaded) = load_tasks(app);
+            let name_of = |id: &str| {
+                names
+                    .iter()
+                    .find(|(task_id, _)| task_id == id)
+                    .map(|(_, name)| name.clone())
+            };
+            state.active = snapshot.map(|s| {
+                let name = name_of(&s.task_id).unwrap_or_else(|| labels.untitled_task.clone());
+                (s.task_id.clone(), name)
+            });
+            if let Some((id, _)) = &state.last {
+                state.last = name_of(id).map(|name| (id.clone(), name));
+            }
+            if state.favourites != loaded {
+                state.favourites = loaded;
+                favourites = Some(state.favourites.clone());
+            }
+        }
+        if let Some(active) = &state.active {
+            state.last = Some(active.clone());
+        }
+
+        // Elapsed time on its own, for the menu bar / panel label.
+        let title = snapshot.filter(|_| state.active.is_some()).map(|s| {
+            let paused = if s.state == TimerState::Paused {
+                format!(" ({})", labels.paused)
+            } else {
+                String::new()
+            };
+            format!("{}{paused}", format_elapsed(s.elapsed_ms))
+        });
+        let status = state
+            .active
+            .as_ref()
+            .zip(title.as_ref())
+            .map(|((_, name), title)| format!("{name} — {title}"));
+        let toggle = match (&state.active, &state.last) {
+            (Some(_), _) => Some(labels.stop_focus.clone()),
+            (None, Some((_, name))) => Some(labels.start_focus_on.replace("{task}", name)),
+            (None, None) => None,
+        };
+        (status, title, toggle, state.active.is_some(), favourites)
+    };
+
+    let Some(handles) = handles().get() else {
+        return;
+    };
+    let _ = handles
+        .status
+        .set_text(status.as_deref().unwrap_or(&labels.no_focus_session));
+    let _ = handles
+        .toggle
+        .set_text(toggle.as_deref().unwrap_or(&labels.start_focus));
+    let _ = handles.toggle.set_enabled(toggle.is_some());
+    let _ = handles.complete.set_enabled(has_active);
+    let _ = handles
+        .icon
+        .set_tooltip(Some(status.as_deref().unwrap_or("ReDD To-Do")));
+    // Only macOS and Linux show a title next to the icon.
+    #[cfg(any(target_os = "macos", target_os = "linux"))]
+    let _ = handles.icon.set_title(title.as_deref());
+    #[cfg(not(any(target_os = "macos", target_os = "linux")))]
+    let _ = title;
+
+    if let Some(favourites) = favourites {
+        rebuild_favourites(app, &handles.switch, &favourites);
+    }
+}
+
+fn rebuild_favourites(app: &AppHandle, switch: &Submenu<Wry>, favourites: &[(String, String)]) {
+    if let Ok(items) = switch.items() {
+        for item in items {
+            let _ = switch.remove(&item);
+        }
+    }
+    for (id, name) in favourites.iter().take(MAX_FAVOURITES) {
+        if let Ok(item) = MenuItem::with_id(
+            app,
+            format!("{SWITCH_ID_PREFIX}{id}"),
+            name,
+            true,
+            None::<&str>,
+        ) {
+            let _ = switch.append(&item);
+        }
+    }
+    let _ = switch.set_enabled(!favourites.is_empty());
+}
+
+pub(crate) fn show_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+/// The window showing `task_id`'s focus session: its fullscreen window, its
+/// focus panel, or the main window (in-window focus mode, or no session).
+fn host_window_label(app: &AppHandle, task_id: Option<&str>) -> String {
+    let visible = |label: &str| {
+        app.get_webview_window(label)
+            .is_some_and(|window| window.is_visible().unwrap_or(false))
+    };
+    task_id
+        .into_iter()
+        .flat_map(|id| [fullscreen_focus_window_label(id), focus_window_label(id)])
+        .find(|label| visible(label))
+        .unwrap_or_else(|| "main".to_string())
+}
+
+/// Tray actions are carried out by the window hosting the session, through
+/// the same code paths as its buttons.
+pub(crate) fn emit_action(
+    app: &AppHandle,
+    action: &str,
+    task_id: Option<&str>,
+    next_task_id: Option<&str>,
+) {
+    let target = if action == "start" {
+        "main".to_string()
+    } else {
+        host_window_label(app, task_id)
+    };
+    let _ = app.emit_to(
+        target.as_str(),
+        "tray-action",
+        serde_json::json!({
+            "action": action,
+            "taskId": task_id,
+            "nextTaskId": next_task_id
+        }),
+    );
+}
+
+/// The task shown as focused, and the last task that had a focus session.
+fn session_task_ids() -> (Option<String>, Option<String>) {
+    tray_state()
+        .lock()
+        .map(|state| {
+            (
+                state.active.as_ref().map(|(id, _)| id.clone()),
+                state.last.as_ref().map(|(id, _)| id.clone()),
+            )
+        })
+        .unwrap_or_default()
+}
+
+pub(crate) fn active_focus_task_id() -> Option<String> {
+    session_task_ids().0
+}
+
+/// Stop the focus session, or start one on the last focused task.
+pub(crate) fn toggle_focus(app: &AppHandle) {
+    match session_task_ids() {
+        (Some(task_id), _) => emit_action(app, "stop", Some(&task_id), None),
+        (None, Some(task_id)) => {
+            // In-window focus mode happens in the main window.
+            #[cfg(not(target_os = "macos"))]
+            show_main_window(app);
+            emit_action(app, "start", Some(&task_id), None);
+        }
+        (None, None) => {}
+    }
+}
+
+pub(crate) fn complete_focused_task(app: &AppHandle) {
+    if let Some(task_id) = active_focus_task_id() {
+        emit_action(app, "complete", Some(&task_id), None);
+    }
+}
+
+fn on_menu_event(app: &AppHandle, event: MenuEvent) {
+    match event.id().as_ref() {
+        "tray-toggle" => toggle_focus(app),
+        "tray-complete" => complete_focused_task(app),
+        "tray-open" => show_main_window(app),
+        id => {
+            if let Some(next) = id.strip_prefix(SWITCH_ID_PREFIX) {
+                let active = active_focus_task_id();
+                #[cfg(not(target_os = "macos"))]
+                if active.is_none() {
+                    show_main_window(app);
+                }
+                emit_action(app, "switch", active.as_deref(), Some(next));
+            }
+        }
+    }
+}
+
+/// Create the tray icon and keep it in sync with focus events.
+pub fn setup_tray(app: &AppHandle) -> tauri::Result<()> {
+    // Listen first, so the focus state is tracked even if the icon can't be
+    // created.
+    let handle = app.clone();
+    app.listen_any("focus-tick", move |_| refresh_tray(&handle, false));
+    let handle = app.clone();
+    app.listen_any("focus-status-changed", move |event| {
+        if let Ok(payload) = serde_json::from_str::<Value>(event.payload()) {
+            let id = |key: &str| payload.get(key).and_then(Value::as_str).map(str::to_string);
+            if let Ok(mut state) = tray_state().lock() {
+                if let Some(opened) = id("openedTaskId").or_else(|| id("activeTaskId")) {
+                    state.preferred = Some(opened);
+                } else if id("closedTaskId") == state.preferred {
+                    state.preferred = None;
+                }
+            }
+        }
+        refresh_tray(&handle, true);
+    });
+    for event in ["task-updated", "store-updated", "store-saved"] {
+        let handle = app.clone();
+        app.listen_any(event, move |_| refresh_tray(&handle, true));
+    }
+
+    let labels = current_labels();
+    let status = MenuItem::with_id(
+        app,
+        "tray-status",
+        &labels.no_focus_session,
+        false,
+        None::<&str>,
+    )?;
+    let toggle = MenuItem::with_id(app, "tray-toggle", &labels.start_focus, false, None::<&str>)?;
+    let complete = MenuItem::with_id(
+        app,
+        "tray-complete",
+        &labels.complete_task,
+        false,
+        None::<&str>,
+    )?;
+    let switch = Submenu::with_id(app, "tray-switch", &labels.switch_to_favourite, false)?;
+    let open = MenuItem::with_id(app, "tray-open", &labels.open_app, true, None::<&str>)?;
+    let menu = Menu::with_items(
+        app,
+        &[
+            &status,
+            &PredefinedMenuItem::separator(app)?,
+            &toggle,
+            &complete,
+            &switch,
+            &PredefinedMenuItem::separator(app)?,
+            &open,
+            &PredefinedMenuItem::quit(app, None)?,
+        ],
+    )?;
+
+    let mut builder = TrayIconBuilder::with_id("main")
+        .menu(&menu)
+        .tooltip("ReDD To-Do")
+        .on_menu_event(on_menu_event);
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    let icon = builder.build(app)?;
+
+    let _ = handles().set(TrayHandles {
+        icon,
+        status,
+        toggle,
+        complete,
+        switch,
+        open,
+    });
+
+    refresh_tray(app, true);
+    Ok(())
+}
+
+/// Translate the tray menu; called by the main window whenever its language
+/// is set.
+#[command]
+pub fn set_tray_labels(app: AppHandle, labels: TrayLabels) -> Result<(), String> {
+    *tray_labels().lock().map_err(|e| e.to_string())? = labels.clone();
+    if let Some(handles) = handles().get() {
+        let _ = handles.complete.set_text(&labels.complete_task);
+        let _ = handles.switch.set_text(&labels.switch_to_favourite);
+        let _ = handles.open.set_text(&labels.open_app);
+    }
+    refresh_tray(&app, false);
+    Ok(())
+}
@@ -1,5 +1,13 @@
-use std::collections::HashMap;
-use std::sync::{Mutex, OnceLock};
+use super::focus_log::{focused_task_ids, log_focus_end, log_focus_handoff, log_focus_start};
+use super::focus_pins::{
+    multiple_pins_enabled, pin_task, tile_focus_pins, unpin_other_tasks, unpin_task,
+};
+use super::focus_timer::{focus_timer_elapsed_ms, stop_task_timer};
+use super::store::{store_dir, update_store};
+use crate::capture;
+use crate::focus_log::SessionOutcome;
+use crate::store::Task;
+use crate::window_geometry::{self, monitor_key, MonitorArea, Rect, SavedGeometry};
 use tauri::WebviewWindowBuilder;
 use tauri::{command, AppHandle, Emitter, LogicalPosition, Manager, WebviewUrl, WebviewWindow};
 
@@ -20,6 +28,15 @@ pub fn flush_all_webview_persisted_state(app: &AppHandle) {
     }
 }
 
+/// Save the geometry of any visible focus window before process exit.
+pub fn save_open_focus_window_geometry(app: &AppHandle) {
+    for (label, window) in app.webview_windows() {
+        if label.starts_with("focus-") && window.is_visible().unwrap_or(false) {
+            save_focus_window_geometry(&window);
+        }
+    }
+}
+
 pub fn flush_webview_persisted_state(window: &WebviewWindow) {
     let _ = window.eval(FLUSH_PERSISTED_STATE_JS);
 }
@@ -55,13 +72,13 @@ tauri_nspanel::tauri_panel! {
     panel_event!(FocusModePanelEventHandler {})
 }
 
-fn focus_window_label(task_id: &str) -> String {
+pub(crate) fn focus_window_label(task_id: &str) -> String {
     // Tauri labels should avoid special characters.
     let safe = urlencoding::encode(task_id).replace('%', "_");
     format!("focus-{safe}")
 }
 
-fn fullscreen_focus_window_label(task_id: &str) -> String {
+pub(crate) fn fullscreen_focus_window_label(task_id: &str) -> String {
     let safe = urlencoding::encode(task_id).replace('%', "_");
     format!("focusfs-{safe}")
 }
@@ -72,54 +89,94 @@ const FOCUS_WINDOW_MIN_HEIGHT: f64 = 48.0;
 /// Same on macOS and Windows; see `set_focus_window_size` and `set_focus_mode_window_state`.
 const FOCUS_BAR_HEIGHT: f64 = 48.0;
 
-#[derive(Clone, Copy)]
-struct FocusWindowGeometry {
-    x: f64,
-    y: f64,
-    width: f64,
-    height: f64,
+/// Change the saved window geometry on disk (see `window_geometry`).
+fn update_saved_geometry(app: &AppHandle, f: impl FnOnce(&mut SavedGeometry)) {
+    let Ok(dir) = store_dir(app) else {
+        return;
+    };
+    let mut saved = window_geometry::load(&dir);
+    f(&mut saved);
+    if let Err(e) = window_geometry::save(&dir, &saved) {
+        log::warn!("[Focus] Failed to save focus window geometry: {e}");
+    }
 }
 
-fn fullscreen_handoff_geometry() -> &'static Mutex<HashMap<String, FocusWindowGeometry>> {
-    static STORE: OnceLock<Mutex<HashMap<String, FocusWindowGeometry>>> = OnceLock::new();
-    STORE.get_or_init(|| Mutex::new(HashMap::new()))
+/// The window's position and inner size, in physical pixels.
+fn window_rect(window: &WebviewWindow) -> Option<Rect> {
+    let (pos, size) = (window.outer_position().ok()?, window.inner_size().ok()?);
+    Some(Rect {
+        x: pos.x,
+        y: pos.y,
+        width: size.width,
+        height: size.height,
+    })
 }
 
-fn last_focus_panel_geometry() -> &'static Mutex<Option<FocusWindowGeometry>> {
-    static STORE: OnceLock<Mutex<Option<FocusWindowGeometry>>> = OnceLock::new();
-    STORE.get_or_init(|| Mutex::new(None))
+/// Put the window back at a saved rect, pulled into a visible work area.
+fn restore_window_rect(window: &WebviewWindow, rect: &Rect) {
+    let Some(rect) = window_geometry::fit(rect, &monitor_areas(window)) else {
+        return;
+    };
+    let _ = window.set_size(tauri::PhysicalSize::new(rect.width, rect.height));
+    let _ = window.set_position(tauri::PhysicalPosition::new(rect.x, rect.y));
 }
 
-/// Stores the window geometry before entering fullscreen on non-macOS.
-fn pre_fullscreen_geometry() -> &'static Mutex<Option<FocusWindowGeometry>> {
-    static STORE: OnceLock<Mutex<Option<FocusWindowGeometry>>> = OnceLock::new();
-    STORE.get_or_init(|| Mutex::new(None))
+/// Connected monitors, primary first.
+fn monitor_areas(window: &WebviewWindow) -> Vec<MonitorArea> {
+    let primary = window.primary_monitor().ok().flatten();
+    let mut monitors = window.available_monitors().unwrap_or_default();
+    if let Some(primary) = &primary {
+        if let Some(index) = monitors.iter().position(|m| m.name() == primary.name()) {
+            let primary = monitors.remove(index);
+            monitors.insert(0, primary);
+        }
+    }
+    monitors
+        .iter()
+        .map(|monitor| {
+            let area = monitor.work_area();
+            MonitorArea {
+                key: monitor_key(
+                    monitor.name().map(String::as_str),
+                    monitor.size().width,
+                    monitor.size().height,
+                ),
+                work_area: Rect {
+                    x: area.position.x,
+                    y: area.position.y,
+                    width: area.size.width,
+                    height: area.size.height,
+                },
+            }
+        })
+        .collect()
 }
 
+/// Remember the focus window's geometry for the monitor it is on, on disk.
 fn save_focus_window_geometry(window: &tauri::WebviewWindow) {
-    let scale = window.scale_factor().unwrap_or(1.0);
-    if let (Ok(pos), Ok(size)) = (window.outer_position(), window.outer_size()) {
-        let geometry = FocusWindowGeometry {
-            x: (pos.x as f64) / scale,
-            y: (pos.y as f64) / scale,
-            width: (size.width as f64) / scale,
-            height: (size.height as f64) / scale,
-        };
-        if let Ok(mut store) = last_focus_panel_geometry().lock() {
-            *store = Some(geometry);
-        }
-    }
+    let (Some(rect), Ok(Some(monitor))) = (window_rect(window), window.current_monitor()) else {
+        return;
+    };
+    let key = monitor_key(
+        monitor.name().map(String::as_str),
+        monitor.size().width,
+        monitor.size().height,
+    );
+    update_saved_geometry(window.app_handle(), |saved| saved.record(&key, rect));
 }
 
+/// Restore the focus window's saved geometry for the connected monitors,
+/// clamped to a visible work area. Returns `false` if nothing was saved yet.
 fn apply_last_focus_window_geometry(window: &tauri::WebviewWindow) -> bool {
-    if let Ok(store) = last_focus_panel_geometry().lock() {
-        if let Some(geometry) = *store {
-            let _ = window.set_size(tauri::LogicalSize::new(geometry.width, geometry.height));
-            let _ = window.set_position(LogicalPosition::new(geometry.x, geometry.y));
-            return true;
-        }
-    }
-    false
+    let saved = store_dir(window.app_handle())
+        .ok()
+        .and_then(|dir| window_geometry::load(&dir).resolve(&monitor_areas(window)));
+    let Some(rect) = saved else {
+        return false;
+    };
+    let _ = window.set_size(tauri::PhysicalSize::new(rect.width, rect.height));
+    let _ = window.set_position(tauri::PhysicalPosition::new(rect.x, rect.y));
+    true
 }
 
 #[cfg(target_os = "macos")]
@@ -138,6 +195,46 @@ fn configure_focus_panel_hover_activation(app: &tauri::AppHandle, label: &str) {
     }
 }
 
+/// Pin a Linux focus window the way the macOS panel is pinned: on every
+/// workspace and out of the taskbar and pager. Keep-above itself comes from
+/// `always_on_top`. Wayland compositors decide most of this themselves; see
+/// docs/linux-focus-window.md.
+#[cfg(target_os = "linux")]
+fn configure_linux_focus_window(window: &WebviewWindow, enabled: bool) {
+    let _ = window.set_visible_on_all_workspaces(enabled);
+    let _ = window.set_skip_taskbar(enabled);
+    let handle = window.clone();
+    let _ = window.run_on_main_thread(move || {
+        use gtk::prelude::*;
+        if let Ok(gtk_window) = handle.gtk_window() {
+            gtk_window.set_skip_pager_hint(enabled);
+        }
+    });
+}
+
+/// Type a new focus window as an X11 utility window so window managers keep
+/// it above normal windows. Window managers read the type only when the
+/// window is first mapped, so this is for windows that are still hidden.
+#[cfg(target_os = "linux")]
+fn mark_linux_utility_window(window: &WebviewWindow) {
+    let handle = window.clone();
+    let _ = window.run_on_main_thread(move || {
+        use gtk::prelude::*;
+        let Ok(gtk_window) = handle.gtk_window() else {
+            return;
+        };
+        if gtk_window.display().type_().name() != "GdkX11Display" {
+            log::info!("[Focus] Not on X11; the compositor decides focus window placement");
+            return;
+        }
+        // Utility rather than dock: docks can't be dragged or focused under
+        // most window managers, and the focus bar needs both.
+        gtk_window.set_type_hint(gtk::gdk::WindowTypeHint::Utility);
+    });
+}
+
+/// Place a focus window with no saved geometry next to the task it was
+/// opened from, cascading each further focus window.
 fn position_focus_window(
     app: &tauri::AppHandle,
     window: &tauri::WebviewWindow,
@@ -145,6 +242,18 @@ fn position_focus_window(
     anchor_right: Option<f64>,
     anchor_top: Option<f64>,
 ) {
+    let position =
+        anchored_focus_window_position(app, window, anchor_left, anchor_right, anchor_top);
+    let _ = window.set_position(position);
+}
+
+fn anchored_focus_window_position(
+    app: &tauri::AppHandle,
+    window: &tauri::WebviewWindow,
+    anchor_left: Option<f64>,
+    anchor_right: Option<f64>,
+    anchor_top: Option<f64>,
+) -> LogicalPosition<f64> {
     let offset_step = 28.0;
     let focus_count = app
         .webview_windows()
@@ -189,18 +298,16 @@ fn position_focus_window(
                 }
 
                 let y = anchor_top.map(|top| main_y + top).unwrap_or(main_y + 32.0) + cascade;
-                let _ = window.set_position(LogicalPosition::new(x, y));
-                return;
+                return LogicalPosition::new(x, y);
             }
 
             let x = main_x + 32.0 + cascade;
             let y = main_y + 32.0 + cascade;
-            let _ = window.set_position(LogicalPosition::new(x, y));
-            return;
+            return LogicalPosition::new(x, y);
         }
     }
 
-    let _ = window.set_position(LogicalPosition::new(120.0 + cascade, 120.0 + cascade));
+    LogicalPosition::new(120.0 + cascade, 120.0 + cascade)
 }
 
 /// Minimize the main window
@@ -241,22 +348,33 @@ pub fn open_focus_window(
 ) -> Result<(), String> {
     let label = focus_window_label(&task_id);
     let preserve_window_geometry = preserve_window_geometry.unwrap_or(false);
+    // A running timer is authoritative over the caller's last known time.
+    let time_spent = focus_timer_elapsed_ms(&task_id).or(time_spent);
+    log_focus_start(&app, &task_id, &task_name);
+    let multiple_pins = multiple_pins_enabled(&app);
+    if !multiple_pins {
+        unpin_other_tasks(&task_id);
+    }
+    pin_task(&task_id);
 
     #[cfg(target_os = "macos")]
     {
-        // Keep a single visible focus panel at a time.
-        let stale_labels: Vec<String> = app
-            .webview_windows()
-            .keys()
-            .filter(|existing| existing.starts_with("focus-") && *existing != &label)
-            .cloned()
-            .collect();
-        for stale in stale_labels {
-            if let Ok(panel) = app.get_webview_panel(&stale) {
-                panel.hide();
-            }
-            if let Some(win) = app.get_webview_window(&stale) {
-                let _ = win.hide();
+        // Keep a single visible focus panel at a time, unless the user
+        // opted into multiple pins.
+        if !multiple_pins {
+            let stale_labels: Vec<String> = app
+                .webview_windows()
+                .keys()
+                .filter(|existing| existing.starts_with("focus-") && *existing != &label)
+                .cloned()
+                .collect();
+            for stale in stale_labels {
+                if let Ok(panel) = app.get_webview_panel(&stale) {
+                    panel.hide();
+                }
+                if let Some(win) = app.get_webview_window(&stale) {
+                    let _ = win.hide();
+                }
             }
         }
 
@@ -286,6 +404,9 @@ pub fn open_focus_window(
             if !apply_last_focus_window_geometry(&window) {
                 position_focus_window(&app, &window, anchor_left, anchor_right, anchor_top);
             }
+            if multiple_pins {
+                tile_focus_pins(&app);
+            }
 
             // Emit event to the window with task data
             let _ = window.emit(
@@ -308,7 +429,10 @@ pub fn open_focus_window(
                         "openedTaskId": task_id
                     }),
                 );
-                let _ = main_window.hide();
+                // Stay reachable for pinning more tasks.
+                if !multiple_pins {
+                    let _ = main_window.hide();
+                }
             }
 
             return Ok(());
@@ -353,6 +477,9 @@ pub fn open_focus_window(
             if !apply_last_focus_window_geometry(&window) {
                 position_focus_window(&app, &window, anchor_left, anchor_right, anchor_top);
             }
+            if multiple_pins {
+                tile_focus_pins(&app);
+            }
             if preserve_window_geometry {
                 let _ = window.emit(
                     "enter-focus-mode",
@@ -376,7 +503,9 @@ pub fn open_focus_window(
                     "openedTaskId": task_id
                 }),
             );
-            let _ = main_window.hide();
+            if !multiple_pins {
+                let _ = main_window.hide();
+            }
         }
 
         Ok(())
@@ -402,12 +531,25 @@ pub fn open_focus_window(
             .focused(true)
             .inner_size(360.0, 56.0)
             .min_inner_size(FOCUS_WINDOW_MIN_WIDTH, FOCUS_WINDOW_MIN_HEIGHT)
+            // X11 window managers read the type hint when the window is
+            // mapped, so it has to be set before the first show.
+            .visible(!cfg!(target_os = "linux"))
             .build()
             .map_err(|e| e.to_string())?;
 
-        position_focus_window(&app, &window, anchor_left, anchor_right, anchor_top);
+        #[cfg(target_os = "linux")]
+        {
+            mark_linux_utility_window(&window);
+            configure_linux_focus_window(&window, true);
+        }
+        if !apply_last_focus_window_geometry(&window) {
+            position_focus_window(&app, &window, anchor_left, anchor_right, anchor_top);
+        }
         let _ = window.show();
         let _ = window.set_focus();
+        if multiple_pins {
+            tile_focus_pins(&app);
+        }
         let payload = serde_json::json!({
             "taskId": task_id,
             "taskName": task_name,
@@ -444,19 +586,35 @@ pub fn open_focus_window(
     }
 }
 
-/// Exit focus mode and hide/close the dedicated focus window.
+/// Exit focus mode and hide/close the dedicated focus window. `completed`
+/// says whether the task was completed from focus mode.
 #[command]
 pub fn exit_focus_mode(
     app: tauri::AppHandle,
     window: tauri::WebviewWindow,
     task_id: Option<String>,
+    completed: Option<bool>,
     _width: Option<f64>,
     _height: Option<f64>,
 ) -> Result<(), String> {
-    if let Some(id) = &task_id {
-        if let Ok(mut store) = fullscreen_handoff_geometry().lock() {
-            store.remove(id);
-        }
+    let ended_task_id = task_id.clone().or_else(|| {
+        focused_task_ids().into_iter().find(|id| {
+            focus_window_label(id) == window.label()
+                || fullscreen_focus_window_label(id) == window.label()
+        })
+    });
+    if let Some(id) = &ended_task_id {
+        unpin_task(id);
+        update_saved_geometry(&app, |saved| {
+            saved.handoff.shift_remove(id);
+        });
+        let elapsed_ms = stop_task_timer(&app, id).map(|timer| timer.elapsed_ms as f64);
+        let outcome = if completed.unwrap_or(false) {
+            SessionOutcome::Completed
+        } else {
+            SessionOutcome::Exited
+        };
+        log_focus_end(&app, id, outcome, elapsed_ms);
     }
 
     let target_label = if let Some(id) = &task_id {
@@ -482,6 +640,8 @@ pub fn exit_focus_mode(
             save_focus_window_geometry(&window);
             let _ = window.hide();
         }
+        // Close the gap left in the stack.
+        tile_focus_pins(&app);
 
         // Notify main window that focus mode ended and bring it to front
         if let Some(main_window) = app.get_webview_window("main") {
@@ -504,6 +664,7 @@ pub fn exit_focus_mode(
     {
         let mut closed_any = false;
         if let Some(window) = app.get_webview_window(&target_label) {
+            save_focus_window_geometry(&window);
             let _ = window.close();
             closed_any = true;
         }
@@ -524,6 +685,7 @@ pub fn exit_focus_mode(
                 }
             }
         }
+        tile_focus_pins(&app);
 
         if let Some(main_window) = app.get_webview_window("main") {
             let _ = main_window.emit(
@@ -612,16 +774,10 @@ pub fn enter_fullscreen_focus(window: tauri::WebviewWindow) -> Result<(), String
     {
         // Save the current geometry so we can restore it when exiting fullscreen.
         let scale = window.scale_factor().unwrap_or(1.0);
-        if let (Ok(pos), Ok(size)) = (window.outer_position(), window.inner_size()) {
-            let geometry = FocusWindowGeometry {
-                x: (pos.x as f64) / scale,
-                y: (pos.y as f64) / scale,
-                width: (size.width as f64) / scale,
-                height: (size.height as f64) / scale,
-            };
-            if let Ok(mut store) = pre_fullscreen_geometry().lock() {
-                *store = Some(geometry);
-            }
+        if let Some(rect) = window_rect(&window) {
+            update_saved_geometry(window.app_handle(), |saved| {
+                saved.pre_fullscreen = Some(rect)
+            });
         }
 
         // Manually size the window to fill the monitor instead of using
@@ -651,11 +807,12 @@ pub fn exit_fullscreen_focus(window: tauri::WebviewWindow) -> Result<(), String>
 
     #[cfg(not(target_os = "macos"))]
     {
-        if let Ok(mut store) = pre_fullscreen_geometry().lock() {
-            if let Some(geometry) = store.take() {
-                let _ = window.set_size(tauri::LogicalSize::new(geometry.width, geometry.height));
-                let _ = window.set_position(LogicalPosition::new(geometry.x, geometry.y));
-            }
+        let mut geometry = None;
+        update_saved_geometry(window.app_handle(), |saved| {
+            geometry = saved.pre_fullscreen.take()
+        });
+        if let Some(rect) = geometry {
+            restore_window_rect(&window, &rect);
         }
     }
 
@@ -671,21 +828,18 @@ pub fn enter_fullscreen_focus_handoff(
     duration: Option<f64>,
     time_spent: Option<f64>,
 ) -> Result<(), String> {
+    log_focus_handoff(&app, &task_id, true);
+    let time_spent = focus_timer_elapsed_ms(&task_id).or(time_spent);
+
     #[cfg(target_os = "macos")]
     {
         let fullscreen_label = fullscreen_focus_window_label(&task_id);
         let panel_label = focus_window_label(&task_id);
-        let scale = window.scale_factor().unwrap_or(1.0);
-        if let (Ok(pos), Ok(size)) = (window.outer_position(), window.outer_size()) {
-            let geometry = FocusWindowGeometry {
-                x: (pos.x as f64) / scale,
-                y: (pos.y as f64) / scale,
-                width: (size.width as f64) / scale,
-                height: (size.height as f64) / scale,
-            };
-            if let Ok(mut store) = fullscreen_handoff_geometry().lock() {
-                store.insert(task_id.clone(), geometry);
-            }
+        save_focus_window_geometry(&window);
+        if let Some(rect) = window_rect(&window) {
+            update_saved_geometry(&app, |saved| {
+                saved.handoff.insert(task_id.clone(), rect);
+            });
         }
 
         if app.get_webview_window(&fullscreen_label).is_none() {
@@ -760,6 +914,9 @@ pub fn exit_fullscreen_focus_handoff(
     duration: Option<f64>,
     time_spent: Option<f64>,
 ) -> Result<(), String> {
+    log_focus_handoff(&app, &task_id, false);
+    let time_spent = focus_timer_elapsed_ms(&task_id).or(time_spent);
+
     #[cfg(target_os = "macos")]
     {
         open_focus_window(
@@ -774,16 +931,15 @@ pub fn exit_fullscreen_focus_handoff(
             Some(true),
         )?;
 
-        if let Ok(mut store) = fullscreen_handoff_geometry().lock() {
-            if let Some(geometry) = store.remove(&task_id) {
-                if let Some(restored_window) = app.get_webview_window(&focus_window_label(&task_id))
-                {
-                    let _ = restored_window
-                        .set_size(tauri::LogicalSize::new(geometry.width, geometry.height));
-                    let _ =
-                        restored_window.set_position(LogicalPosition::new(geometry.x, geometry.y));
-                }
-            }
+        let mut geometry = None;
+        update_saved_geometry(&app, |saved| {
+            geometry = saved.handoff.shift_remove(&task_id)
+        });
+        if let (Some(rect), Some(restored_window)) = (
+            geometry,
+            app.get_webview_window(&focus_window_label(&task_id)),
+        ) {
+            restore_window_rect(&restored_window, &rect);
         }
 
         if window.label().starts_with("focusfs-") {
@@ -816,12 +972,21 @@ pub fn exit_fullscreen_focus_to_home(
     complete_on_home: Option<bool>,
     elapsed_ms: Option<f64>,
 ) -> Result<(), String> {
+    let outcome = if complete_on_home.unwrap_or(false) {
+        SessionOutcome::Completed
+    } else {
+        SessionOutcome::Exited
+    };
+    unpin_task(&task_id);
+    update_saved_geometry(&app, |saved| {
+        saved.handoff.shift_remove(&task_id);
+    });
+    let stopped = stop_task_timer(&app, &task_id);
+    let elapsed_ms = stopped.map(|timer| timer.elapsed_ms as f64).or(elapsed_ms);
+    log_focus_end(&app, &task_id, outcome, elapsed_ms);
+
     #[cfg(target_os = "macos")]
     {
-        if let Ok(mut store) = fullscreen_handoff_geometry().lock() {
-            store.remove(&task_id);
-        }
-
         if window.label().starts_with("focusfs-") {
             let _ = window.close();
         } else if let Some(fullscreen_window) =
@@ -865,6 +1030,58 @@ pub fn exit_fullscreen_focus_to_home(
     }
 }
 
+const CAPTURE_WINDOW_LABEL: &str = "capture";
+
+/// Open (or bring back) the small quick-add window, centered on screen.
+pub(crate) fn open_capture_window(app: &AppHandle) -> Result<(), String> {
+    if let Some(window) = app.get_webview_window(CAPTURE_WINDOW_LABEL) {
+        let _ = window.center();
+        let _ = window.show();
+        return window.set_focus().map_err(|e| e.to_string());
+    }
+    WebviewWindowBuilder::new(
+        app,
+        CAPTURE_WINDOW_LABEL,
+        WebviewUrl::App("index.html?capture=1".into()),
+    )
+    .title("Quick add")
+    .zoom_hotkeys_enabled(false)
+    .always_on_top(true)
+    .decorations(false)
+    .resizable(false)
+    .skip_taskbar(true)
+    .inner_size(480.0, 56.0)
+    .center()
+    .focused(true)
+    .build()
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Add a task typed into the capture window ("Write email 15") to `tab_id`,
+/// or the current tab, then close the capture window. The store change is
+/// broadcast as `store-updated`; the main window also gets `task-captured`.
+#[command]
+pub fn capture_task(
+    app: tauri::AppHandle,
+    text: String,
+    tab_id: Option<String>,
+) -> Result<Task, String> {
+    let (tab_id, task) = update_store(&app, |data| {
+        capture::add_captured_task(data, &text, tab_id.as_deref())
+    })?;
+    if let Some(main) = app.get_webview_window("main") {
+        let _ = main.emit(
+            "task-captured",
+            serde_json::json!({ "taskId": task.id, "tabId": tab_id }),
+        );
+    }
+    if let Some(window) = app.get_webview_window(CAPTURE_WINDOW_LABEL) {
+        let _ = window.close();
+    }
+    Ok(task)
+}
+
 /// Emit refresh event to main window (for panel window to trigger main refresh)
 #[command]
 pub fn refresh_main_window(app: tauri::AppHandle) -> Result<(), String> {
@@ -916,6 +1133,11 @@ pub fn set_focus_mode_window_state(
         let _ = window.set_visible_on_all_workspaces(enabled);
     }
 
+    // Linux focus mode keeps using the main window, so pin it in place
+    // while focusing and restore it afterwards.
+    #[cfg(target_os = "linux")]
+    configure_linux_focus_window(&window, enabled);
+
     // On Windows, remove the min size constraint so the focus bar can be narrow,
     // and restore it when exiting focus mode.
     #[cfg(target_os = "windows")]
@@ -0,0 +1,428 @@
+//! `redddo://` deep links.
+//!
+//! Scripts, bookmarklets and other apps drive ReDD To-Do through URLs such as
+//! `redddo://add?text=Write%20email&tab=Work&minutes=15`. `reddtodo://` is
+//! accepted as an alias. Parameters this module doesn't know are ignored, so
+//! tracking parameters added by browsers don't break a link.
+
+use crate::store::{AppData, Task};
+use serde::Serialize;
+use std::fmt;
+use url::Url;
+
+pub const SCHEMES: [&str; 2] = ["redddo", "reddtodo"];
+
+/// Longest `minutes` accepted by `add`, matching the capture syntax.
+const MAX_MINUTES: u32 = 24 * 60;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeepLinkAction {
+    /// `add?text=…&tab=…&minutes=…`. `tab` is a tab id or name.
+    Add {
+        text: String,
+        tab: Option<String>,
+        minutes: Option<u32>,
+    },
+    /// `focus?task=…`. `task` is a task id or name, as for `complete`.
+    Focus {
+        task: String,
+    },
+    Complete {
+        task: String,
+    },
+    /// `open?tab=…`; without a tab it only brings the main window forward.
+    Open {
+        tab: Option<String>,
+    },
+    /// `oauth-callback?…`, which completes the Basecamp sign-in.
+    OAuthCallback,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeepLinkError {
+    InvalidUrl(String),
+    UnsupportedScheme(String),
+    UnknownAction(String),
+    MissingParameter {
+        action: &'static str,
+        name: &'static str,
+    },
+    InvalidMinutes(String),
+    TabNotFound(String),
+    TaskNotFound(String),
+}
+
+impl fmt::Display for DeepLinkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeepLinkError::InvalidUrl(e) => write!(f, "Invalid link: {e}"),
+            DeepLinkError::UnsupportedScheme(scheme) => {
+                write!(f, "Unsupported link scheme: {scheme}")
+            }
+            DeepLinkError::UnknownAction(action) => write!(f, "Unknown link action: {action}"),
+            DeepLinkError::MissingParameter { action, name } => {
+                write!(f, "The {action} link needs a {name} parameter")
+            }
+            DeepLinkError::InvalidMinutes(value) => write!(
+                f,
+                "minutes must be a whole number from 1 to {MAX_MINUTES}, not {value:?}"
+            ),
+            DeepLinkError::TabNotFound(tab) => write!(f, "No list called {tab:?}"),
+            DeepLinkError::TaskNotFound(task) => write!(f, "No task called {task:?}"),
+        }
+    }
+}
+
+impl std::error::Error for DeepLinkError {}
+
+/// The link's scheme and action, for logging. The query and fragment are
+/// dropped, since they can carry OAuth tokens and task text.
+pub fn redact(link: &str) -> String {
+    match Url::parse(link.trim()) {
+        Ok(url) => format!(
+            "{}://{}",
+            url.scheme(),
+            format!("{}{}", url.host_str().unwrap_or_default(), url.path()).trim_matches('/')
+        ),
+        Err(_) => "(invalid link)".to_string(),
+    }
+}
+
+/// Parse and validate a deep link. Tabs and tasks are only looked up when
+/// the action is carried out; see [`resolve_tab`] and [`resolve_task`].
+pub fn parse_deep_link(link: &str) -> Result<DeepLinkAction, DeepLinkError> {
+    let url = Url::parse(link.trim()).map_err(|e| DeepLinkError::InvalidUrl(e.to_string()))?;
+    if !SCHEMES.contains(&url.scheme()) {
+        return Err(DeepLinkError::UnsupportedScheme(url.scheme().to_string()));
+    }
+    // `redddo://add?…` carries the action as the host, `redddo:add?…` as
+    // the path.
+    let action = format!("{}{}", url.host_str().unwrap_or_default(), url.path())
+        .trim_matches('/')
+        .to_ascii_lowercase();
+    let param = |name: &str| {
+        url.query_pairs()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+    let required = |action: &'static str, name: &'static str| {
+        param(name).ok_or(DeepLinkError::MissingParameter { action, name })
+    };
+
+    match action.as_str() {
+        "add" => {
+            let minutes = param("minutes")
+                .map(|value| {
+                    value
+                        .parse::<u32>()
+                        .ok()
+                        .filter(|minutes| (1..=MAX_MINUTES).contains(minutes))
+                        .ok_or(DeepLinkError::InvalidMinutes(value))
+                })
+                .transpose()?;
+            Ok(DeepLinkAction::Add {
+                text: required("add", "text")?,
+                tab: param("tab"),
+                minutes,
+            })
+        }
+        "focus" => Ok(DeepLinkAction::Focus {
+            task: required("focus", "task")?,
+        }),
+        "complete" => Ok(DeepLinkAction::Complete {
+            task: required("complete", "task")?,
+        }),
+        "open" => Ok(DeepLinkAction::Open { tab: param("tab") }),
+        "oauth-callback" => Ok(DeepLinkAction::OAuthCallback),
+        _ => Err(DeepLinkError::UnknownAction(action)),
+    }
+}
+
+impl DeepLinkAction {
+    /// The `redddo://` link that [`parse_deep_link`] turns back into this
+    /// action.
+    pub fn to_url(&self) -> String {
+        let (action, params): (&str, Vec<(&str, String)>) = match self {
+            DeepLinkAction::Add { text, tab, minutes } => (
+                "add",
+                [
+                    Some(("text", text.clone())),
+                    tab.clone().map(|tab| ("tab", tab)),
+                    minutes.map(|minutes| ("minutes", minutes.to_string())),
+                ]
+                .into_iter()
+                .flatten()
+                .collect(),
+            ),
+            DeepLinkAction::Focus { task } => ("focus", vec![("task", task.clone())]),
+            DeepLinkAction::Complete { task } => ("complete", vec![("task", task.clone())]),
+            DeepLinkAction::Open { tab } => (
+                "open",
+                tab.clone().map(|tab| ("tab", tab)).into_iter().collect(),
+            ),
+            DeepLinkAction::OAuthCallback => ("oauth-callback", Vec::new()),
+        };
+        let mut url = format!("{}://{action}", SCHEMES[0]);
+        if !params.is_empty() {
+            url.push('?');
+            url.push_str(
+                &url::form_urlencoded::Serializer::new(String::new())
+                    .extend_pairs(params)
+                    .finish(),
+            );
+        }
+        url
+    }
+}
+
+/// Id of the tab with id `tab`, or else the first one named `tab`.
+pub fn resolve_tab(data: &AppData, tab: &str) -> Result<String, DeepLinkError> {
+    if data.tabs.contains_key(tab) {
+        return Ok(tab.to_string());
+    }
+    data.tab_id_by_name(tab)
+        .ok_or_else(|| DeepLinkError::TabNotFound(tab.to_string()))
+}
+
+/// Id of the task with id `task`, or else of a task with that text,
+/// preferring one that isn't completed.
+pub fn resolve_task(data: &AppData, task: &str) -> Result<String, DeepLinkError> {
+    if data.find_task(task).is_some() {
+        return Ok(task.to_string());
+    }
+    let named: Vec<_> = data
+        .tabs
+        .values()
+        .flat_map(|tab| tab.tasks.iter())
+        .filter(|candidate| candidate.text.trim().eq_ignore_ascii_case(task))
+        .collect();
+    named
+        .iter()
+        .find(|candidate| !candidate.completed)
+        .or_else(|| named.first())
+        .map(|candidate| candidate.id.clone())
+        .ok_or_else(|| DeepLinkError::TaskNotFound(task.to_string()))
+}
+
+/// A tab and the tasks listed for it, as the CLI, RPC and MCP lists show
+/// them.
+#[derive(Debug, Serialize)]
+pub struct TabListing<'a> {
+    pub id: &'a str,
+    pub name: &'a str,
+    pub tasks: Vec<&'a Task>,
+}
+
+/// The tasks of tab `tab` (id or name), or of every tab if `None`. Completed
+/// tasks are left out unless `include_completed` is set.
+pub fn list_tasks<'a>(
+    data: &'a AppData,
+    tab: Option<&str>,
+    include_completed: bool,
+) -> Result<Vec<TabListing<'a>>, DeepLinkError> {
+    let tab_id = tab.map(|tab| resolve_tab(data, tab)).transpose()?;
+    Ok(data
+        .tabs
+        .values()
+        .filter(|tab| tab_id.as_ref().map_or(true, |id| *id == tab.id))
+        .map(|tab| TabListing {
+            id: &tab.id,
+            name: &tab.name,
+            tasks: tab
+                .tasks
+                .iter()
+                .filter(|task| include_completed || !task.completed)
+                .collect(),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_actions() {
+        assert_eq!(
+            parse_deep_link("redddo://add?text=Write%20email&tab=Work&minutes=15"),
+            Ok(DeepLinkAction::Add {
+                text: "Write email".to_string(),
+                tab: Some("Work".to_string()),
+                minutes: Some(15),
+            })
+        );
+        assert_eq!(
+            parse_deep_link("reddtodo:add?text=Call+mum&utm_source=x"),
+            Ok(DeepLinkAction::Add {
+                text: "Call mum".to_string(),
+                tab: None,
+                minutes: None,
+            })
+        );
+        assert_eq!(
+            parse_deep_link("redddo://focus/?task=task_3"),
+            Ok(DeepLinkAction::Focus {
+                task: "task_3".to_string()
+            })
+        );
+        assert_eq!(
+            parse_deep_link("redddo://Complete?task=task_3"),
+            Ok(DeepLinkAction::Complete {
+                task: "task_3".to_string()
+            })
+        );
+        assert_eq!(
+            parse_deep_link("redddo://open?tab="),
+            Ok(DeepLinkAction::Open { tab: None })
+        );
+        assert_eq!(
+            parse_deep_link("redddo://oauth-callback?access_token=abc"),
+            Ok(DeepLinkAction::OAuthCallback)
+        );
+    }
+
+    #[test]
+    fn redacts_query_and_fragment() {
+        assert_eq!(
+            redact("redddo://oauth-callback?access_token=abc&state=xyz#token=abc"),
+            "redddo://oauth-callback"
+        );
+        assert_eq!(redact("reddtodo:add?text=Secret"), "reddtodo://add");
+        assert_eq!(redact("not a link ?token=abc"), "(invalid link)");
+    }
+
+    #[test]
+    fn actions_round_trip_through_urls() {
+        let actions = [
+            DeepLinkAction::Add {
+                text: "Pay rent & bills 100%".to_string(),
+                tab: Some("Home admin".to_string()),
+                minutes: Some(20),
+            },
+            DeepLinkAction::Add {
+                text: "Chapter 3".to_string(),
+                tab: None,
+                minutes: None,
+            },
+            DeepLinkAction::Focus {
+                task: "task_3".to_string(),
+            },
+            DeepLinkAction::Complete {
+                task: "Write report?".to_string(),
+            },
+            DeepLinkAction::Open { tab: None },
+        ];
+        for action in actions {
+            assert_eq!(parse_deep_link(&action.to_url()), Ok(action));
+        }
+    }
+
+    #[test]
+    fn reports_typed_errors() {
+        assert!(matches!(
+            parse_deep_link("not a url"),
+            Err(DeepLinkError::InvalidUrl(_))
+        ));
+        assert_eq!(
+            parse_deep_link("https://example.com/add?text=x"),
+            Err(DeepLinkError::UnsupportedScheme("https".to_string()))
+        );
+        assert_eq!(
+            parse_deep_link("redddo://delete?task=task_1"),
+            Err(DeepLinkError::UnknownAction("delete".to_string()))
+        );
+        assert_eq!(
+            parse_deep_link("redddo://add?text=%20&minutes=5"),
+            Err(DeepLinkError::MissingParameter {
+                action: "add",
+                name: "text"
+            })
+        );
+        assert_eq!(
+            parse_deep_link("redddo://focus"),
+            Err(DeepLinkError::MissingParameter {
+                action: "focus",
+                name: "task"
+            })
+        );
+        for minutes in ["0", "-5", "1.5", "ten", "1441"] {
+            assert_eq!(
+                parse_deep_link(&format!("redddo://add?text=x&minutes={minutes}")),
+                Err(DeepLinkError::InvalidMinutes(minutes.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn resolves_tabs_and_tasks_by_id_or_name() {
+        let mut data = AppData::default();
+        let work = data.add_tab("Work", None);
+        let task = |id: &str, text: &str, completed: bool| Task {
+            id: id.to_string(),
+            text: text.to_string(),
+            completed,
+            ..Task::default()
+        };
+        data.tabs[&work].tasks = vec![
+            task("task_1", "Write report", true),
+            task("task_2", "Write report", false),
+        ];
+
+        assert_eq!(resolve_tab(&data, &work), Ok(work.clone()));
+        assert_eq!(resolve_tab(&data, "work"), Ok(work.clone()));
+        assert_eq!(
+            resolve_tab(&data, "Home"),
+            Err(DeepLinkError::TabNotFound("Home".to_string()))
+        );
+        assert_eq!(resolve_task(&data, "task_1"), Ok("task_1".to_string()));
+        assert_eq!(
+            resolve_task(&data, "write report"),
+            Ok("task_2".to_string())
+        );
+        assert!(resolve_task(&data, "task_9").is_err());
+    }
+
+    #[test]
+    fn lists_open_tasks_unless_asked_for_completed_ones() {
+        let mut data = AppData::default();
+        let work = data.add_tab("Work", None);
+        data.add_tab("Home", None);
+        data.tabs[&work].tasks = vec![
+            Task {
+                id: "task_1".to_string(),
+                completed: true,
+                ..Task::default()
+            },
+            Task {
+                id: "task_2".to_string(),
+                ..Task::default()
+            },
+        ];
+        let ids = |listings: Vec<TabListing>| -> Vec<(String, Vec<String>)> {
+            listings
+                .into_iter()
+                .map(|listing| {
+                    let tasks = listing.tasks.iter().map(|task| task.id.clone()).collect();
+                    (listing.name.to_string(), tasks)
+                })
+                .collect()
+        };
+
+        assert_eq!(
+            ids(list_tasks(&data, None, false).unwrap()),
+            [
+                ("Work".to_string(), vec!["task_2".to_string()]),
+                ("Home".to_string(), vec![])
+            ]
+        );
+        assert_eq!(
+            ids(list_tasks(&data, Some("work"), true).unwrap()),
+            [(
+                "Work".to_string(),
+                vec!["task_1".to_string(), "task_2".to_string()]
+            )]
+        );
+        assert!(list_tasks(&data, Some("Errands"), false).is_err());
+    }
+}
@@ -0,0 +1,461 @@
+//! Append-only log of focus sessions.
+//!
+//! Every start, pause, resume, window handoff and end of a focus session is
+//! appended as one JSON line to `app_data_dir()/focus-sessions.jsonl`. Lines
+//! are never rewritten; [`sessions`] replays them into one record per
+//! session. Running sessions also log a heartbeat every minute, so sessions
+//! still open when the app starts (after a crash or force quit) are closed
+//! as `interrupted` by [`close_dangling`] at most a minute short.
+
+use crate::store::now_iso;
+use chrono::{DateTime, NaiveDate, Utc};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+pub const LOG_FILE_NAME: &str = "focus-sessions.jsonl";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionOutcome {
+    /// The task was completed from focus mode.
+    Completed,
+    /// Focus mode was left without completing the task.
+    Exited,
+    /// The app stopped before the session was ended.
+    Interrupted,
+}
+
+/// One line of the log.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "camelCase")]
+pub enum FocusLogEvent {
+    #[serde(rename_all = "camelCase")]
+    Start {
+        session_id: String,
+        task_id: String,
+        task_name: String,
+        at: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    Pause { session_id: String, at: String },
+    #[serde(rename_all = "camelCase")]
+    Resume { session_id: String, at: String },
+    /// The session was still running; only marks the last time it was seen.
+    #[serde(rename_all = "camelCase")]
+    Heartbeat { session_id: String, at: String },
+    /// The session moved between the focus panel and full-screen focus.
+    #[serde(rename_all = "camelCase")]
+    Handoff {
+        session_id: String,
+        at: String,
+        fullscreen: bool,
+    },
+    #[serde(rename_all = "camelCase")]
+    End {
+        session_id: String,
+        at: String,
+        outcome: SessionOutcome,
+        /// Elapsed time as measured by the focus window, when it reported one.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        elapsed_ms: Option<f64>,
+    },
+}
+
+impl FocusLogEvent {
+    fn session_id(&self) -> &str {
+        match self {
+            Self::Start { session_id, .. }
+            | Self::Pause { session_id, .. }
+            | Self::Resume { session_id, .. }
+            | Self::Heartbeat { session_id, .. }
+            | Self::Handoff { session_id, .. }
+            | Self::End { session_id, .. } => session_id,
+        }
+    }
+
+    fn at(&self) -> &str {
+        match self {
+            Self::Start { at, .. }
+            | Self::Pause { at, .. }
+            | Self::Resume { at, .. }
+            | Self::Heartbeat { at, .. }
+            | Self::Handoff { at, .. }
+            | Self::End { at, .. } => at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PauseInterval {
+    pub start: String,
+    /// `None` while the session is still paused.
+    pub end: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FocusSession {
+    pub id: String,
+    pub task_id: String,
+    pub task_name: String,
+    pub started_at: String,
+    pub ended_at: Option<String>,
+    pub pauses: Vec<PauseInterval>,
+    pub handoffs: usize,
+    /// `None` while the session is still running.
+    pub outcome: Option<SessionOutcome>,
+    /// Wall-clock time between start and end (or now), minus pauses.
+    pub active_ms: f64,
+    pub reported_elapsed_ms: Option<f64>,
+}
+
+pub fn log_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(LOG_FILE_NAME)
+}
+
+/// Append one event to the log.
+pub fn append(data_dir: &Path, event: &FocusLogEvent) -> Result<(), String> {
+    let path = log_path(data_dir);
+    let mut line = serde_json::to_string(event).map_err(|e| e.to_string())?;
+    line.push('\n');
+    fs::create_dir_all(data_dir).map_err(|e| e.to_string())?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("Failed to open {path:?}: {e}"))?;
+    file.write_all(line.as_bytes())
+        .and_then(|()| file.sync_data())
+        .map_err(|e| format!("Failed to append to {path:?}: {e}"))
+}
+
+/// Read every event in the log, skipping lines that don't parse.
+pub fn read_events(data_dir: &Path) -> Result<Vec<FocusLogEvent>, String> {
+    let path = log_path(data_dir);
+    let raw = match fs::read_to_string(&path) {
+        Ok(raw) => raw,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to read {path:?}: {e}")),
+    };
+    Ok(raw
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .filter_map(|(index, line)| match serde_json::from_str(line) {
+            Ok(event) => Some(event),
+            Err(e) => {
+                log::warn!("[FocusLog] Skipping line {}: {e}", index + 1);
+                None
+            }
+        })
+        .collect())
+}
+
+fn parse_time(iso: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(iso)
+        .ok()
+        .map(|time| time.with_timezone(&Utc))
+}
+
+fn millis_between(start: &str, end: Option<&str>, now: DateTime<Utc>) -> f64 {
+    let (Some(start), end) = (parse_time(start), end.and_then(parse_time)) else {
+        return 0.0;
+    };
+    (end.unwrap_or(now) - start).num_milliseconds().max(0) as f64
+}
+
+/// Replay `events` into sessions, in start order. `now` closes the running
+/// time of sessions and pauses that haven't ended.
+pub fn replay(events: &[FocusLogEvent], now: DateTime<Utc>) -> Vec<FocusSession> {
+    let mut sessions: IndexMap<String, FocusSession> = IndexMap::new();
+    for event in events {
+        if let FocusLogEvent::Start {
+            session_id,
+            task_id,
+            task_name,
+            at,
+        } = event
+        {
+            sessions.insert(
+                session_id.clone(),
+                FocusSession {
+                    id: session_id.clone(),
+                    task_id: task_id.clone(),
+                    task_name: task_name.clone(),
+                    started_at: at.clone(),
+                    ended_at: None,
+                    pauses: Vec::new(),
+                    handoffs: 0,
+                    outcome: None,
+                    active_ms: 0.0,
+                    reported_elapsed_ms: None,
+                },
+            );
+            continue;
+        }
+        let Some(session) = sessions.get_mut(event.session_id()) else {
+            continue;
+        };
+        if session.outcome.is_some() {
+            continue;
+        }
+        let open_pause = session
+            .pauses
+            .last_mut()
+            .filter(|pause| pause.end.is_none());
+        match event {
+            FocusLogEvent::Pause { at, .. } if open_pause.is_none() => {
+                session.pauses.push(PauseInterval {
+                    start: at.clone(),
+                    end: None,
+                });
+            }
+            FocusLogEvent::Resume { at, .. } => {
+                if let Some(pause) = open_pause {
+                    pause.end = Some(at.clone());
+                }
+            }
+            FocusLogEvent::Handoff { .. } => session.handoffs += 1,
+            FocusLogEvent::End {
+                at,
+                outcome,
+                elapsed_ms,
+                ..
+            } => {
+                if let Some(pause) = open_pause {
+                    pause.end = Some(at.clone());
+                }
+                session.ended_at = Some(at.clone());
+                session.outcome = Some(*outcome);
+                session.reported_elapsed_ms = *elapsed_ms;
+            }
+            _ => {}
+        }
+    }
+
+    sessions
+        .into_values()
+        .map(|mut session| {
+            let total = millis_between(&session.started_at, session.ended_at.as_deref(), now);
+            let paused: f64 = session
+                .pauses
+                .iter()
+                .map(|pause| millis_between(&pause.start, pause.end.as_deref(), now))
+                .sum();
+            session.active_ms = (total - paused).max(0.0);
+            session
+        })
+        .collect()
+}
+
+/// Filter for [`sessions`]. Dates are inclusive and compared against the
+/// session's start in UTC.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SessionQuery {
+    pub task_id: Option<String>,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub outcome: Option<SessionOutcome>,
+    /// Return only the newest `limit` sessions.
+    pub limit: Option<usize>,
+}
+
+impl SessionQuery {
+    fn matches(&self, session: &FocusSession) -> bool {
+        if self
+            .task_id
+            .as_ref()
+            .is_some_and(|id| *id != session.task_id)
+        {
+            return false;
+        }
+        if self.outcome.is_some() && self.outcome != session.outcome {
+            return false;
+        }
+        let Some(day) = parse_time(&session.started_at).map(|time| time.date_naive()) else {
+            return self.from.is_none() && self.to.is_none();
+        };
+        !(self.from.is_some_and(|from| day < from) || self.to.is_some_and(|to| day > to))
+    }
+}
+
+/// Sessions matching `query`, oldest first.
+pub fn sessions(data_dir: &Path, query: &SessionQuery) -> Result<Vec<FocusSession>, String> {
+    let mut sessions: Vec<FocusSession> = replay(&read_events(data_dir)?, Utc::now())
+        .into_iter()
+        .filter(|session| query.matches(session))
+        .collect();
+    if let Some(limit) = query.limit {
+        let skip = sessions.len().saturating_sub(limit);
+        sessions.drain(..skip);
+    }
+    Ok(sessions)
+}
+
+/// New session id: `<task id>@<millis>`.
+pub fn new_session_id(task_id: &str) -> String {
+    format!("{task_id}@{}", Utc::now().timestamp_millis())
+}
+
+/// End every session left open by a previous run, as of its last logged
+/// event (usually a heartbeat) since the real end time is unknown. Returns
+/// how many were closed.
+pub fn close_dangling(data_dir: &Path) -> Result<usize, String> {
+    let events = read_events(data_dir)?;
+    let open: Vec<String> = replay(&events, Utc::now())
+        .into_iter()
+        .filter(|session| session.outcome.is_none())
+        .map(|session| session.id)
+        .collect();
+    for session_id in &open {
+        let last_seen = events
+            .iter()
+            .rev()
+            .find(|event| event.session_id() == session_id)
+            .map(|event| event.at().to_string())
+            .unwrap_or_else(now_iso);
+        append(
+            data_dir,
+            &FocusLogEvent::End {
+                session_id: session_id.clone(),
+                at: last_seen,
+                outcome: SessionOutcome::Interrupted,
+                elapsed_ms: None,
+            },
+        )?;
+    }
+    Ok(open.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SESSION: &str = "t1@0";
+
+    fn at(minute: u32) -> String {
+        format!("2026-03-02T09:{minute:02}:00Z")
+    }
+
+    fn start(minute: u32) -> FocusLogEvent {
+        FocusLogEvent::Start {
+            session_id: SESSION.to_string(),
+            task_id: "t1".to_string(),
+            task_name: "Write report".to_string(),
+            at: at(minute),
+        }
+    }
+
+    fn event(kind: &str, minute: u32) -> FocusLogEvent {
+        let session_id = SESSION.to_string();
+        let at = at(minute);
+        match kind {
+            "pause" => FocusLogEvent::Pause { session_id, at },
+            "resume" => FocusLogEvent::Resume { session_id, at },
+            "heartbeat" => FocusLogEvent::Heartbeat { session_id, at },
+            _ => unreachable!(),
+        }
+    }
+
+    fn end(minute: u32, outcome: SessionOutcome) -> FocusLogEvent {
+        FocusLogEvent::End {
+            session_id: SESSION.to_string(),
+            at: at(minute),
+            outcome,
+            elapsed_ms: Some(1.0),
+        }
+    }
+
+    fn now() -> DateTime<Utc> {
+        parse_time(&at(59)).unwrap()
+    }
+
+    #[test]
+    fn replays_pauses_and_handoffs() {
+        let events = [
+            start(0),
+            event("pause", 10),
+            event("pause", 12),
+            event("resume", 15),
+            FocusLogEvent::Handoff {
+                session_id: SESSION.to_string(),
+                at: at(20),
+                fullscreen: true,
+            },
+            event("heartbeat", 21),
+            end(30, SessionOutcome::Completed),
+            // Nothing counts after the end.
+            event("pause", 31),
+        ];
+        let sessions = replay(&events, now());
+        assert_eq!(sessions.len(), 1);
+        let session = &sessions[0];
+        assert_eq!(session.ended_at.as_deref(), Some(at(30).as_str()));
+        assert_eq!(session.outcome, Some(SessionOutcome::Completed));
+        assert_eq!(
+            session.pauses,
+            [PauseInterval {
+                start: at(10),
+                end: Some(at(15)),
+            }]
+        );
+        assert_eq!(session.handoffs, 1);
+        assert_eq!(session.active_ms, 25.0 * 60_000.0);
+        assert_eq!(session.reported_elapsed_ms, Some(1.0));
+    }
+
+    #[test]
+    fn runs_open_sessions_and_pauses_until_now() {
+        let sessions = replay(&[start(0), event("pause", 50)], now());
+        assert_eq!(sessions[0].outcome, None);
+        assert_eq!(sessions[0].pauses[0].end, None);
+        assert_eq!(sessions[0].active_ms, 50.0 * 60_000.0);
+
+        // Events of sessions that never started are ignored.
+        assert!(replay(&[event("pause", 5)], now()).is_empty());
+    }
+
+    #[test]
+    fn closes_dangling_sessions_at_the_last_heartbeat() {
+        let dir = tempfile::tempdir().unwrap();
+        for event in [start(0), event("heartbeat", 1), event("heartbeat", 2)] {
+            append(dir.path(), &event).unwrap();
+        }
+
+        assert_eq!(close_dangling(dir.path()).unwrap(), 1);
+        let sessions = replay(&read_events(dir.path()).unwrap(), now());
+        assert_eq!(sessions[0].outcome, Some(SessionOutcome::Interrupted));
+        assert_eq!(sessions[0].ended_at.as_deref(), Some(at(2).as_str()));
+        assert_eq!(sessions[0].active_ms, 2.0 * 60_000.0);
+
+        assert_eq!(close_dangling(dir.path()).unwrap(), 0);
+    }
+
+    #[test]
+    fn closes_a_paused_dangling_session_at_its_pause() {
+        let dir = tempfile::tempdir().unwrap();
+        for event in [start(0), event("heartbeat", 1), event("pause", 3)] {
+            append(dir.path(), &event).unwrap();
+        }
+
+        close_dangling(dir.path()).unwrap();
+        let sessions = replay(&read_events(dir.path()).unwrap(), now());
+        assert_eq!(sessions[0].ended_at.as_deref(), Some(at(3).as_str()));
+        assert_eq!(sessions[0].pauses[0].end.as_deref(), Some(at(3).as_str()));
+        assert_eq!(sessions[0].active_ms, 3.0 * 60_000.0);
+    }
+
+    #[test]
+    fn leaves_ended_sessions_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        append(dir.path(), &start(0)).unwrap();
+        append(dir.path(), &end(5, SessionOutcome::Exited)).unwrap();
+        assert_eq!(close_dangling(dir.path()).unwrap(), 0);
+        assert_eq!(read_events(dir.path()).unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,158 @@
+//! Several focus windows ("pins") visible at once.
+//!
+//! By default opening a focus window hides the others. With multiple pins
+//! enabled they stay visible, stacked under the first pin so none overlap,
+//! and wrap into a new column when a work area runs out of height.
+
+use crate::store;
+use crate::window_geometry::Rect;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+pub const SETTINGS_FILE_NAME: &str = "focus-pins.json";
+
+/// Space between stacked pins, in physical pixels.
+pub const PIN_GAP: u32 = 8;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PinSettings {
+    /// Keep every focus window visible instead of only the latest one.
+    pub multiple: bool,
+}
+
+pub fn load_settings(data_dir: &Path) -> PinSettings {
+    store::load_json_settings(data_dir, SETTINGS_FILE_NAME)
+}
+
+pub fn save_settings(data_dir: &Path, settings: &PinSettings) -> Result<(), String> {
+    store::save_json_settings(data_dir, SETTINGS_FILE_NAME, settings)
+}
+
+/// Positions for pins of the given `sizes`, in pin order. The first pin
+/// stays where it is (clamped to `work_area`); the rest stack below it.
+/// Columns grow towards the side of the work area with more room.
+pub fn stack_positions(
+    first: (i32, i32),
+    sizes: &[(u32, u32)],
+    work_area: &Rect,
+    gap: u32,
+) -> Vec<(i32, i32)> {
+    let Some(&(first_width, first_height)) = sizes.first() else {
+        return Vec::new();
+    };
+    let anchor = Rect {
+        x: first.0,
+        y: first.1,
+        width: first_width,
+        height: first_height,
+    }
+    .clamp_to(work_area);
+    let area_center = i64::from(work_area.x) + i64::from(work_area.width) / 2;
+    let grow_right = i64::from(anchor.x) + i64::from(anchor.width) / 2 <= area_center;
+    let bottom = i64::from(work_area.y) + i64::from(work_area.height);
+    let gap = i64::from(gap);
+    // Columns to the left are placed by the widest pin so none reach back
+    // into the column to their right.
+    let widest = sizes.iter().map(|&(width, _)| i64::from(width)).max();
+
+    let mut positions = Vec::with_capacity(sizes.len());
+    let (mut x, mut y) = (i64::from(anchor.x), i64::from(anchor.y));
+    let mut column_width = 0_i64;
+    for &(width, height) in sizes {
+        let (width, height) = (i64::from(width), i64::from(height));
+        // Start a new column if this pin would run past the bottom, unless
+        // it is the first in its column anyway.
+        if y + height > bottom && y > i64::from(anchor.y) {
+            x = if grow_right {
+                x + column_width + gap
+            } else {
+                x - widest.unwrap_or(width) - gap
+            };
+            y = i64::from(anchor.y);
+            column_width = 0;
+        }
+        positions.push((x as i32, y as i32));
+        y += height + gap;
+        column_width = column_width.max(width);
+    }
+    positions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCREEN: Rect = Rect {
+        x: 0,
+        y: 0,
+        width: 1920,
+        height: 1080,
+    };
+
+    #[test]
+    fn stacks_below_the_first_pin() {
+        let positions = stack_positions((100, 100), &[(360, 56), (300, 56), (360, 80)], &SCREEN, 8);
+        assert_eq!(positions, vec![(100, 100), (100, 164), (100, 228)]);
+    }
+
+    #[test]
+    fn wraps_into_a_new_column_at_the_bottom() {
+        let positions = stack_positions(
+            (100, 900),
+            &[(360, 56), (300, 56), (360, 56), (200, 56)],
+            &SCREEN,
+            8,
+        );
+        assert_eq!(
+            positions,
+            vec![(100, 900), (100, 964), (468, 900), (468, 964)]
+        );
+    }
+
+    #[test]
+    fn grows_left_from_the_right_edge() {
+        let positions = stack_positions((1500, 1000), &[(360, 56), (300, 56)], &SCREEN, 8);
+        assert_eq!(positions, vec![(1500, 1000), (1132, 1000)]);
+    }
+
+    #[test]
+    fn first_pin_is_pulled_into_the_work_area() {
+        let positions = stack_positions((-500, 2000), &[(360, 56)], &SCREEN, 8);
+        assert_eq!(positions, vec![(0, 1024)]);
+    }
+
+    #[test]
+    fn pins_never_overlap() {
+        let sizes = [
+            (360, 56),
+            (300, 120),
+            (420, 56),
+            (360, 300),
+            (200, 56),
+            (360, 56),
+        ];
+        for first in [(50, 700), (1600, 700)] {
+            let positions = stack_positions(first, &sizes, &SCREEN, 8);
+            let rects: Vec<Rect> = positions
+                .iter()
+                .zip(sizes)
+                .map(|(&(x, y), (width, height))| Rect {
+                    x,
+                    y,
+                    width,
+                    height,
+                })
+                .collect();
+            for (i, a) in rects.iter().enumerate() {
+                for b in &rects[i + 1..] {
+                    let apart = a.x + a.width as i32 <= b.x
+                        || b.x + b.width as i32 <= a.x
+                        || a.y + a.height as i32 <= b.y
+                        || b.y + b.height as i32 <= a.y;
+                    assert!(apart, "{a:?} overlaps {b:?}");
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,283 @@
+//! The focus timer state machine.
+//!
+//! One `FocusTimer` tracks the elapsed time of a single focus session. It is
+//! `idle` until started, then `running` until the elapsed time reaches the
+//! task's expected duration, and `overtime` after that. `pause` and `resume`
+//! move between `paused` and whichever of the two applies; `stop` returns to
+//! `idle` from any state. Time is passed in explicitly so the machine can be
+//! driven without a clock or a GUI.
+
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimerState {
+    Idle,
+    Running,
+    Paused,
+    /// Running past the task's expected duration.
+    Overtime,
+}
+
+/// What the frontend needs to display the timer.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimerSnapshot {
+    pub task_id: String,
+    pub state: TimerState,
+    pub elapsed_ms: u64,
+    pub duration_ms: Option<u64>,
+    /// Time left before overtime; `None` without a duration.
+    pub remaining_ms: Option<u64>,
+    pub overtime_ms: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Session {
+    task_id: String,
+    duration: Option<Duration>,
+    /// Elapsed time banked before the current running stretch.
+    banked: Duration,
+    /// Start of the current running stretch; `None` while paused.
+    running_since: Option<Instant>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FocusTimer {
+    session: Option<Session>,
+}
+
+impl FocusTimer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start timing `task_id`, continuing from `initial_elapsed` (time already
+    /// spent on the task). `duration` is the expected duration, if any.
+    pub fn start(
+        &mut self,
+        task_id: &str,
+        duration: Option<Duration>,
+        initial_elapsed: Duration,
+        now: Instant,
+    ) -> Result<(), String> {
+        if self.session.is_some() {
+            return Err("Focus timer is already running".to_string());
+        }
+        self.session = Some(Session {
+            task_id: task_id.to_string(),
+            duration: duration.filter(|d| !d.is_zero()),
+            banked: initial_elapsed,
+            running_since: Some(now),
+        });
+        Ok(())
+    }
+
+    pub fn pause(&mut self, now: Instant) -> Result<(), String> {
+        let session = self.session.as_mut().ok_or("Focus timer is not running")?;
+        let since = session
+            .running_since
+            .take()
+            .ok_or("Focus timer is already paused")?;
+        session.banked += now.saturating_duration_since(since);
+        Ok(())
+    }
+
+    pub fn resume(&mut self, now: Instant) -> Result<(), String> {
+        let session = self.session.as_mut().ok_or("Focus timer is not running")?;
+        if session.running_since.is_some() {
+            return Err("Focus timer is not paused".to_string());
+        }
+        session.running_since = Some(now);
+        Ok(())
+    }
+
+    /// Stop the timer and return its final reading, in the `idle` state.
+    pub fn stop(&mut self, now: Instant) -> Result<TimerSnapshot, String> {
+        let mut snapshot = self.snapshot(now).ok_or("Focus timer is not running")?;
+        snapshot.state = TimerState::Idle;
+        self.session = None;
+        Ok(snapshot)
+    }
+
+    /// Overwrite the elapsed time (e.g. the focus panel's reset and undo),
+    /// keeping the running/paused state.
+    pub fn set_elapsed(&mut self, elapsed: Duration, now: Instant) -> Result<(), String> {
+        let session = self.session.as_mut().ok_or("Focus timer is not running")?;
+        session.banked = elapsed;
+        if session.running_since.is_some() {
+            session.running_since = Some(now);
+        }
+        Ok(())
+    }
+
+    /// Change the expected duration of the running session.
+    pub fn set_duration(&mut self, duration: Option<Duration>) -> Result<(), String> {
+        let session = self.session.as_mut().ok_or("Focus timer is not running")?;
+        session.duration = duration.filter(|d| !d.is_zero());
+        Ok(())
+    }
+
+    pub fn task_id(&self) -> Option<&str> {
+        self.session
+            .as_ref()
+            .map(|session| session.task_id.as_str())
+    }
+
+    pub fn elapsed(&self, now: Instant) -> Duration {
+        self.session.as_ref().map_or(Duration::ZERO, |session| {
+            session.banked
+                + session
+                    .running_since
+                    .map_or(Duration::ZERO, |since| now.saturating_duration_since(since))
+        })
+    }
+
+    pub fn state(&self, now: Instant) -> TimerState {
+        let Some(session) = &self.session else {
+            return TimerState::Idle;
+        };
+        if session.running_since.is_none() {
+            TimerState::Paused
+        } else if session
+            .duration
+            .is_some_and(|duration| self.elapsed(now) >= duration)
+        {
+            TimerState::Overtime
+        } else {
+            TimerState::Running
+        }
+    }
+
+    /// Current reading, or `None` while idle.
+    pub fn snapshot(&self, now: Instant) -> Option<TimerSnapshot> {
+        let session = self.session.as_ref()?;
+        let elapsed = self.elapsed(now);
+        Some(TimerSnapshot {
+            task_id: session.task_id.clone(),
+            state: self.state(now),
+            elapsed_ms: elapsed.as_millis() as u64,
+            duration_ms: session.duration.map(|d| d.as_millis() as u64),
+            remaining_ms: session
+                .duration
+                .map(|d| d.saturating_sub(elapsed).as_millis() as u64),
+            overtime_ms: session
+                .duration
+                .map_or(0, |d| elapsed.saturating_sub(d).as_millis() as u64),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secs(n: u64) -> Duration {
+        Duration::from_secs(n)
+    }
+
+    #[test]
+    fn starts_idle_and_runs_after_start() {
+        let t0 = Instant::now();
+        let mut timer = FocusTimer::new();
+        assert_eq!(timer.state(t0), TimerState::Idle);
+        assert!(timer.snapshot(t0).is_none());
+
+        timer.start("task_1", None, Duration::ZERO, t0).unwrap();
+        assert_eq!(timer.state(t0 + secs(5)), TimerState::Running);
+        assert_eq!(timer.elapsed(t0 + secs(5)), secs(5));
+        assert!(timer.start("task_2", None, Duration::ZERO, t0).is_err());
+    }
+
+    #[test]
+    fn continues_from_initial_elapsed() {
+        let t0 = Instant::now();
+        let mut timer = FocusTimer::new();
+        timer.start("task_1", None, secs(90), t0).unwrap();
+        assert_eq!(timer.snapshot(t0 + secs(10)).unwrap().elapsed_ms, 100_000);
+    }
+
+    #[test]
+    fn pause_freezes_elapsed_until_resume() {
+        let t0 = Instant::now();
+        let mut timer = FocusTimer::new();
+        timer.start("task_1", None, Duration::ZERO, t0).unwrap();
+        timer.pause(t0 + secs(10)).unwrap();
+        assert_eq!(timer.state(t0 + secs(60)), TimerState::Paused);
+        assert_eq!(timer.elapsed(t0 + secs(60)), secs(10));
+        assert!(timer.pause(t0 + secs(61)).is_err());
+
+        timer.resume(t0 + secs(60)).unwrap();
+        assert_eq!(timer.elapsed(t0 + secs(65)), secs(15));
+        assert!(timer.resume(t0 + secs(66)).is_err());
+    }
+
+    #[test]
+    fn enters_overtime_after_duration() {
+        let t0 = Instant::now();
+        let mut timer = FocusTimer::new();
+        timer
+            .start("task_1", Some(secs(60)), Duration::ZERO, t0)
+            .unwrap();
+
+        let before = timer.snapshot(t0 + secs(45)).unwrap();
+        assert_eq!(before.state, TimerState::Running);
+        assert_eq!(before.remaining_ms, Some(15_000));
+        assert_eq!(before.overtime_ms, 0);
+
+        let after = timer.snapshot(t0 + secs(75)).unwrap();
+        assert_eq!(after.state, TimerState::Overtime);
+        assert_eq!(after.remaining_ms, Some(0));
+        assert_eq!(after.overtime_ms, 15_000);
+
+        timer.pause(t0 + secs(75)).unwrap();
+        assert_eq!(timer.state(t0 + secs(80)), TimerState::Paused);
+        timer.resume(t0 + secs(80)).unwrap();
+        assert_eq!(timer.state(t0 + secs(80)), TimerState::Overtime);
+    }
+
+    #[test]
+    fn stop_returns_final_reading_and_goes_idle() {
+        let t0 = Instant::now();
+        let mut timer = FocusTimer::new();
+        assert!(timer.stop(t0).is_err());
+
+        timer
+            .start("task_1", Some(secs(60)), Duration::ZERO, t0)
+            .unwrap();
+        timer.pause(t0 + secs(20)).unwrap();
+        let last = timer.stop(t0 + secs(30)).unwrap();
+        assert_eq!(last.task_id, "task_1");
+        assert_eq!(last.state, TimerState::Idle);
+        assert_eq!(last.elapsed_ms, 20_000);
+        assert_eq!(timer.state(t0 + secs(30)), TimerState::Idle);
+        assert!(timer.start("task_2", None, Duration::ZERO, t0).is_ok());
+    }
+
+    #[test]
+    fn set_elapsed_keeps_running_state() {
+        let t0 = Instant::now();
+        let mut timer = FocusTimer::new();
+        timer.start("task_1", None, Duration::ZERO, t0).unwrap();
+        timer.set_elapsed(Duration::ZERO, t0 + secs(30)).unwrap();
+        assert_eq!(timer.elapsed(t0 + secs(40)), secs(10));
+
+        timer.pause(t0 + secs(40)).unwrap();
+        timer.set_elapsed(secs(30), t0 + secs(50)).unwrap();
+        assert_eq!(timer.state(t0 + secs(60)), TimerState::Paused);
+        assert_eq!(timer.elapsed(t0 + secs(60)), secs(30));
+    }
+
+    #[test]
+    fn zero_duration_means_no_countdown() {
+        let t0 = Instant::now();
+        let mut timer = FocusTimer::new();
+        timer
+            .start("task_1", Some(Duration::ZERO), Duration::ZERO, t0)
+            .unwrap();
+        let snapshot = timer.snapshot(t0 + secs(1)).unwrap();
+        assert_eq!(snapshot.state, TimerState::Running);
+        assert_eq!(snapshot.duration_ms, None);
+    }
+}
@@ -0,0 +1,424 @@
+//! iCalendar (RFC 5545) VTODO export and import.
+//!
+//! Each task becomes a VTODO with SUMMARY, DESCRIPTION (plain-text notes),
+//! STATUS, CREATED, COMPLETED and ESTIMATED-DURATION (RFC 9253). The tab name
+//! goes in CATEGORIES and favourites get `PRIORITY:1`. Import reads the same
+//! properties from any calendar tool, ignoring nested components such as
+//! VALARM.
+
+use super::{notes_text, text_to_html, ImportSummary};
+use crate::store::{now_iso, AppData, Task};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, SecondsFormat, Utc};
+
+const PRODID: &str = "-//ReDD To-Do//Tasks//EN";
+const UID_DOMAIN: &str = "redd-todo";
+const IMPORTED_TAB_NAME: &str = "Imported";
+/// Content lines are folded at 75 octets (RFC 5545 §3.1).
+const MAX_LINE_OCTETS: usize = 75;
+/// PRIORITY values 1-4 are "high" (RFC 5545 §3.8.1.9).
+const FAVOURITE_PRIORITY: u8 = 1;
+
+fn escape_text(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            ';' => out.push_str("\\;"),
+            ',' => out.push_str("\\,"),
+            '\n' => out.push_str("\\n"),
+            '\r' => {}
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+fn unescape_text(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+/// Append `line` folded to 75 octets, never splitting a UTF-8 character.
+fn push_folded(out: &mut String, line: &str) {
+    let mut limit = MAX_LINE_OCTETS;
+    let mut rest = line;
+    while rest.len() > limit {
+        let mut split = limit;
+        while !rest.is_char_boundary(split) {
+            split -= 1;
+        }
+        out.push_str(&rest[..split]);
+        out.push_str("\r\n ");
+        rest = &rest[split..];
+        // Continuation lines start with a space, which counts towards the limit.
+        limit = MAX_LINE_OCTETS - 1;
+    }
+    out.push_str(rest);
+    out.push_str("\r\n");
+}
+
+fn format_datetime(time: DateTime<Utc>) -> String {
+    time.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Parse the frontend's ISO timestamps.
+fn parse_iso(iso: Option<&str>) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(iso?)
+        .ok()
+        .map(|time| time.with_timezone(&Utc))
+}
+
+fn to_iso(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+/// Parse DATE-TIME (UTC or floating) and DATE values. Times with a TZID are
+/// read as UTC; a task's dates don't need better than hour precision.
+fn parse_datetime(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if let Ok(time) = NaiveDateTime::parse_from_str(value.trim_end_matches('Z'), "%Y%m%dT%H%M%S") {
+        return Some(time.and_utc());
+    }
+    NaiveDate::parse_from_str(value, "%Y%m%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|time| time.and_utc())
+}
+
+/// Minutes as an RFC 5545 duration: `PT1H30M`.
+fn format_duration(minutes: f64) -> String {
+    let seconds = (minutes * 60.0).round().max(0.0) as u64;
+    let (h, m, s) = (seconds / 3600, seconds % 3600 / 60, seconds % 60);
+    let mut out = String::from("PT");
+    if h > 0 {
+        out.push_str(&format!("{h}H"));
+    }
+    if m > 0 || (h == 0 && s == 0) {
+        out.push_str(&format!("{m}M"));
+    }
+    if s > 0 {
+        out.push_str(&format!("{s}S"));
+    }
+    out
+}
+
+/// Parse an RFC 5545 duration (`P1W`, `P1DT2H`, `PT15M`) into minutes.
+fn parse_duration(value: &str) -> Option<f64> {
+    let rest = value.trim().trim_start_matches('+').strip_prefix('P')?;
+    let mut minutes = 0.0;
+    let mut digits = String::new();
+    for c in rest.chars() {
+        match c {
+            '0'..='9' => digits.push(c),
+            'T' if digits.is_empty() => {}
+            'W' | 'D' | 'H' | 'M' | 'S' => {
+                let n: f64 = digits.parse().ok()?;
+                minutes += n * match c {
+                    'W' => 7.0 * 24.0 * 60.0,
+                    'D' => 24.0 * 60.0,
+                    'H' => 60.0,
+                    'M' => 1.0,
+                    _ => 1.0 / 60.0,
+                };
+                digits.clear();
+            }
+            _ => return None,
+        }
+    }
+    digits.is_empty().then_some(minutes)
+}
+
+fn render_todo(out: &mut String, task: &Task, tab_name: &str, stamp: DateTime<Utc>) {
+    push_folded(out, "BEGIN:VTODO");
+    push_folded(out, &format!("UID:{}@{UID_DOMAIN}", task.id));
+    push_folded(out, &format!("DTSTAMP:{}", format_datetime(stamp)));
+    if let Some(created) = parse_iso(task.created_at.as_deref()) {
+        push_folded(out, &format!("CREATED:{}", format_datetime(created)));
+    }
+    push_folded(out, &format!("SUMMARY:{}", escape_text(&task.text)));
+    if let Some(notes) = notes_text(task.notes.as_deref()) {
+        push_folded(out, &format!("DESCRIPTION:{}", escape_text(&notes)));
+    }
+    if !tab_name.trim().is_empty() {
+        push_folded(out, &format!("CATEGORIES:{}", escape_text(tab_name)));
+    }
+    if task.is_favourite {
+        push_folded(out, &format!("PRIORITY:{FAVOURITE_PRIORITY}"));
+    }
+    if let Some(minutes) = task.expected_duration.filter(|m| *m > 0.0) {
+        push_folded(
+            out,
+            &format!("ESTIMATED-DURATION:{}", format_duration(minutes)),
+        );
+    }
+    if task.completed {
+        push_folded(out, "STATUS:COMPLETED");
+        if let Some(completed) = parse_iso(task.completed_at.as_deref()) {
+            push_folded(out, &format!("COMPLETED:{}", format_datetime(completed)));
+        }
+    } else {
+        push_folded(out, "STATUS:NEEDS-ACTION");
+    }
+    push_folded(out, "END:VTODO");
+}
+
+/// Export one tab (or every tab) as a VCALENDAR of VTODOs. `stamp` is the
+/// DTSTAMP written on every component.
+pub fn export(
+    data: &AppData,
+    tab_id: Option<&str>,
+    stamp: DateTime<Utc>,
+) -> Result<String, String> {
+    if let Some(id) = tab_id {
+        if !data.tabs.contains_key(id) {
+            return Err(format!("Tab not found: {id}"));
+        }
+    }
+
+    let mut out = String::new();
+    push_folded(&mut out, "BEGIN:VCALENDAR");
+    push_folded(&mut out, "VERSION:2.0");
+    push_folded(&mut out, &format!("PRODID:{PRODID}"));
+    push_folded(&mut out, "CALSCALE:GREGORIAN");
+    for tab in data.tabs.values() {
+        if tab_id.is_some_and(|id| id != tab.id) {
+            continue;
+        }
+        for task in &tab.tasks {
+            render_todo(&mut out, task, &tab.name, stamp);
+        }
+    }
+    push_folded(&mut out, "END:VCALENDAR");
+    Ok(out)
+}
+
+/// Join folded lines back into content lines.
+fn unfold(contents: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for raw in contents.split('\n') {
+        let raw = raw.strip_suffix('\r').unwrap_or(raw);
+        match raw.strip_prefix([' ', '\t']) {
+            Some(continuation) if !lines.is_empty() => {
+                lines.last_mut().unwrap().push_str(continuation);
+            }
+            _ if raw.is_empty() => {}
+            _ => lines.push(raw.to_string()),
+        }
+    }
+    lines
+}
+
+/// `DUE;VALUE=DATE:20240101` -> `("DUE", "20240101")`. Parameters are dropped.
+fn split_property(line: &str) -> Option<(String, &str)> {
+    // Parameter values may be quoted and contain ':', so find the first
+    // colon outside quotes.
+    let mut in_quotes = false;
+    let colon = line.char_indices().find_map(|(i, c)| match c {
+        '"' => {
+            in_quotes = !in_quotes;
+            None
+        }
+        ':' if !in_quotes => Some(i),
+        _ => None,
+    })?;
+    let name = line[..colon].split(';').next()?.trim().to_ascii_uppercase();
+    Some((name, &line[colon + 1..]))
+}
+
+/// Parse every VTODO in `contents`, in file order.
+pub fn parse(contents: &str) -> Vec<Task> {
+    let mut tasks = Vec::new();
+    let mut current: Option<Task> = None;
+    // Depth of components nested inside the current VTODO (e.g. VALARM).
+    let mut nested = 0usize;
+
+    for line in unfold(contents) {
+        let Some((name, value)) = split_property(&line) else {
+            continue;
+        };
+        match name.as_str() {
+            "BEGIN" if value.eq_ignore_ascii_case("VTODO") && current.is_none() => {
+                current = Some(Task::default());
+                nested = 0;
+            }
+            "BEGIN" if current.is_some() => nested += 1,
+            "END" if current.is_some() && nested > 0 => nested -= 1,
+            "END" if value.eq_ignore_ascii_case("VTODO") => {
+                tasks.extend(current.take());
+            }
+            _ if nested > 0 => {}
+            _ => {
+                let Some(task) = current.as_mut() else {
+                    continue;
+                };
+                match name.as_str() {
+                    "SUMMARY" => task.text = unescape_text(value).trim().to_string(),
+                    "DESCRIPTION" => {
+                        let notes = unescape_text(value);
+                        if !notes.trim().is_empty() {
+                            task.notes = Some(text_to_html(&notes));
+                        }
+                    }
+                    "STATUS" => task.completed = value.trim().eq_ignore_ascii_case("COMPLETED"),
+                    "COMPLETED" => {
+                        task.completed_at = parse_datetime(value).map(to_iso);
+                    }
+                    "CREATED" => task.created_at = parse_datetime(value).map(to_iso),
+                    "PRIORITY" => {
+                        task.is_favourite = value
+                            .trim()
+                            .parse::<u8>()
+                            .is_ok_and(|priority| (1..=4).contains(&priority));
+                    }
+                    "ESTIMATED-DURATION" => task.expected_duration = parse_duration(value),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    // A COMPLETED timestamp without STATUS still means the task is done.
+    for task in &mut tasks {
+        task.completed |= task.completed_at.is_some();
+    }
+    tasks
+}
+
+/// Import every VTODO in `contents` into a new tab named `name` (or "Imported").
+pub fn import(
+    data: &mut AppData,
+    contents: &str,
+    name: Option<&str>,
+) -> Result<ImportSummary, String> {
+    let parsed = parse(contents);
+    if parsed.is_empty() {
+        return Err("No VTODO components found".to_string());
+    }
+
+    let tab_id = data.add_tab(name.unwrap_or(IMPORTED_TAB_NAME), None);
+    let mut summary = ImportSummary {
+        tabs_created: vec![tab_id.clone()],
+        tab_id: Some(tab_id.clone()),
+        ..ImportSummary::default()
+    };
+    for mut task in parsed {
+        task.id = data.next_task_id();
+        task.created_at.get_or_insert_with(now_iso);
+        if let Some(tab) = data.tabs.get_mut(&tab_id) {
+            tab.tasks.push(task);
+            summary.tasks_imported += 1;
+        }
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::Tab;
+
+    const EXPORT_GOLDEN: &str = include_str!("../../tests/fixtures/ical/export.ics");
+    const IMPORT_SOURCE: &str = include_str!("../../tests/fixtures/ical/import.ics");
+    const IMPORT_GOLDEN: &str = include_str!("../../tests/fixtures/ical/import.json");
+
+    fn stamp() -> DateTime<Utc> {
+        parse_datetime("20240315T120000Z").unwrap()
+    }
+
+    fn sample_data() -> AppData {
+        let mut data = AppData::default();
+        let tasks = vec![
+            Task {
+                id: "task_1".into(),
+                text: "Write report; draft, then review".into(),
+                created_at: Some("2024-03-01T09:30:00.000Z".into()),
+                expected_duration: Some(90.0),
+                notes: Some("<p>Sections:</p><p>intro &amp; results</p>".into()),
+                is_favourite: true,
+                ..Task::default()
+            },
+            Task {
+                id: "task_2".into(),
+                text: "File expenses".into(),
+                completed: true,
+                created_at: Some("2024-03-02T08:00:00.000Z".into()),
+                completed_at: Some("2024-03-10T17:45:00.000Z".into()),
+                expected_duration: Some(15.0),
+                ..Task::default()
+            },
+            Task {
+                id: "task_3".into(),
+                text: "A task with a summary long enough that the content line has to be \
+                       folded across more than one physical line"
+                    .into(),
+                created_at: Some("2024-03-03T10:00:00.000Z".into()),
+                ..Task::default()
+            },
+        ];
+        data.tabs.insert(
+            "tab_1".into(),
+            Tab {
+                id: "tab_1".into(),
+                name: "Work".into(),
+                tasks,
+                ..Tab::default()
+            },
+        );
+        data
+    }
+
+    #[test]
+    fn export_matches_golden_file() {
+        let ics = export(&sample_data(), None, stamp()).unwrap();
+        assert_eq!(ics, EXPORT_GOLDEN);
+        assert!(ics.split("\r\n").all(|line| line.len() <= MAX_LINE_OCTETS));
+    }
+
+    #[test]
+    fn import_matches_golden_file() {
+        let tasks = parse(IMPORT_SOURCE);
+        let json = serde_json::to_string_pretty(&tasks).unwrap();
+        assert_eq!(format!("{json}\n"), IMPORT_GOLDEN);
+    }
+
+    #[test]
+    fn export_then_import_keeps_task_fields() {
+        let data = sample_data();
+        let ics = export(&data, Some("tab_1"), stamp()).unwrap();
+        let original = &data.tabs["tab_1"].tasks;
+        let parsed = parse(&ics);
+        assert_eq!(parsed.len(), original.len());
+        for (before, after) in original.iter().zip(&parsed) {
+            assert_eq!(after.text, before.text);
+            assert_eq!(after.completed, before.completed);
+            assert_eq!(after.is_favourite, before.is_favourite);
+            assert_eq!(after.created_at, before.created_at);
+            assert_eq!(after.completed_at, before.completed_at);
+            assert_eq!(after.expected_duration, before.expected_duration);
+            assert_eq!(after.notes, before.notes);
+        }
+    }
+
+    #[test]
+    fn import_creates_a_new_tab() {
+        let mut data = sample_data();
+        let summary = import(&mut data, IMPORT_SOURCE, Some("From Thunderbird")).unwrap();
+        let tab = &data.tabs[summary.tab_id.as_deref().unwrap()];
+        assert_eq!(tab.name, "From Thunderbird");
+        assert_eq!(tab.tasks.len(), summary.tasks_imported);
+        assert_eq!(data.tabs.len(), 2);
+        assert!(import(&mut data, "BEGIN:VCALENDAR\r\nEND:VCALENDAR\r\n", None).is_err());
+    }
+}
@@ -0,0 +1,480 @@
+//! GitHub-flavoured Markdown checklists.
+//!
+//! Each tab is a heading followed by its open tasks as `- [ ]` items and a
+//! "Done" sub-heading with the completed ones as `- [x]`. When several tabs
+//! are exported, group names become the top-level headings. Durations follow
+//! the task text in parentheses: `(15m)` for the estimate, `(15m, actual 22m)`
+//! once time has been recorded. Notes are indented under their task.
+
+use super::{format_minutes, notes_text, parse_minutes, text_to_html, ImportSummary};
+use crate::store::{now_iso, AppData, Tab, Task};
+
+const DONE_HEADING: &str = "Done";
+const NOTES_INDENT: &str = "  ";
+const IMPORTED_TAB_NAME: &str = "Imported";
+
+/// Which part of the task store to render.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Scope<'a> {
+    Tab(&'a str),
+    Group(&'a str),
+    All,
+}
+
+fn heading(level: usize, text: &str) -> String {
+    format!("{} {}\n", "#".repeat(level), text.trim())
+}
+
+fn duration_suffix(task: &Task) -> Option<String> {
+    let expected = task
+        .expected_duration
+        .filter(|m| *m > 0.0)
+        .map(format_minutes);
+    let actual = task
+        .actual_duration
+        .filter(|ms| *ms > 0.0)
+        .map(|ms| format!("actual {}", format_minutes(ms / 60_000.0)));
+    match (expected, actual) {
+        (None, None) => None,
+        (Some(expected), None) => Some(format!("({expected})")),
+        (None, Some(actual)) => Some(format!("({actual})")),
+        (Some(expected), Some(actual)) => Some(format!("({expected}, {actual})")),
+    }
+}
+
+fn render_task(out: &mut String, task: &Task) {
+    let mark = if task.completed { 'x' } else { ' ' };
+    let text = task.text.split_whitespace().collect::<Vec<_>>().join(" ");
+    out.push_str(&format!("- [{mark}] {text}"));
+    if let Some(suffix) = duration_suffix(task) {
+        out.push(' ');
+        out.push_str(&suffix);
+    }
+    out.push('\n');
+    if let Some(notes) = notes_text(task.notes.as_deref()) {
+        for line in notes.lines() {
+            if !line.trim().is_empty() {
+                out.push_str(NOTES_INDENT);
+                out.push_str(line);
+            }
+            out.push('\n');
+        }
+    }
+}
+
+fn render_tab(out: &mut String, tab: &Tab, level: usize) {
+    out.push_str(&heading(level, &tab.name));
+    out.push('\n');
+    let (done, open): (Vec<&Task>, Vec<&Task>) = tab.tasks.iter().partition(|t| t.completed);
+    for task in &open {
+        render_task(out, task);
+    }
+    if !done.is_empty() {
+        if !open.is_empty() {
+            out.push('\n');
+        }
+        out.push_str(&heading(level + 1, DONE_HEADING));
+        out.push('\n');
+        for task in &done {
+            render_task(out, task);
+        }
+    }
+    out.push('\n');
+}
+
+/// Render `scope` as a Markdown document.
+pub fn export(data: &AppData, scope: Scope) -> Result<String, String> {
+    let mut out = String::new();
+    match scope {
+        Scope::Tab(id) => {
+            let tab = data
+                .tabs
+                .get(id)
+                .ok_or_else(|| format!("Tab not found: {id}"))?;
+            render_tab(&mut out, tab, 1);
+        }
+        Scope::Group(id) => {
+            let group = data
+                .groups
+                .get(id)
+                .ok_or_else(|| format!("Group not found: {id}"))?;
+            out.push_str(&heading(1, &group.name));
+            out.push('\n');
+            for tab in data.tabs.values() {
+                if tab.group_id.as_deref() == Some(id) {
+                    render_tab(&mut out, tab, 2);
+                }
+            }
+        }
+        Scope::All => {
+            let ungrouped = data.tabs.values().filter(|tab| {
+                tab.group_id
+                    .as_ref()
+                    .map_or(true, |id| !data.groups.contains_key(id))
+            });
+            for tab in ungrouped {
+                render_tab(&mut out, tab, 2);
+            }
+            let mut groups: Vec<_> = data.groups.values().collect();
+            groups.sort_by(|a, b| {
+                a.order
+                    .unwrap_or(f64::MAX)
+                    .total_cmp(&b.order.unwrap_or(f64::MAX))
+            });
+            for group in groups {
+                out.push_str(&heading(1, &group.name));
+                out.push('\n');
+                for tab in data.tabs.values() {
+                    if tab.group_id.as_deref() == Some(group.id.as_str()) {
+                        render_tab(&mut out, tab, 2);
+                    }
+                }
+            }
+        }
+    }
+    let trimmed = out.trim_end().len();
+    out.truncate(trimmed);
+    out.push('\n');
+    Ok(out)
+}
+
+/// `## Name` -> `(2, "Name")`.
+fn parse_heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    let text = line[level..].strip_prefix(' ')?;
+    (1..=6).contains(&level).then_some((level, text.trim()))
+}
+
+/// `- [x] text` -> `(true, "text")`.
+fn parse_checkbox(line: &str) -> Option<(bool, &str)> {
+    let rest = line
+        .strip_prefix("- [")
+        .or_else(|| line.strip_prefix("* ["))?;
+    let mut chars = rest.chars();
+    let completed = match chars.next()? {
+        ' ' => false,
+        'x' | 'X' => true,
+        _ => return None,
+    };
+    let text = chars.as_str().strip_prefix(']')?;
+    Some((completed, text.trim()))
+}
+
+/// A duration with a unit, as [`format_minutes`] writes it. Bare numbers
+/// are left alone so text such as "Chapter (2)" keeps its parentheses.
+fn parse_duration(value: &str) -> Option<f64> {
+    value
+        .ends_with(['h', 'm', 's'])
+        .then(|| parse_minutes(value))
+        .flatten()
+}
+
+/// Split a trailing `(15m)` / `(15m, actual 22m)` off the task text.
+fn split_durations(text: &str) -> (&str, Option<f64>, Option<f64>) {
+    let parsed = text.strip_suffix(')').and_then(|rest| {
+        let open = rest.rfind('(')?;
+        let mut expected = None;
+        let mut actual = None;
+        for part in rest[open + 1..].split(',').map(str::trim) {
+            match part.strip_prefix("actual ") {
+                Some(value) => actual = Some(parse_duration(value.trim())? * 60_000.0),
+                None if expected.is_none() && actual.is_none() => {
+                    expected = Some(parse_duration(part)?)
+                }
+                None => return None,
+            }
+        }
+        Some((rest[..open].trim_end(), expected, actual))
+    });
+    parsed.unwrap_or((text, None, None))
+}
+
+/// One tab's worth of parsed tasks.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParsedTab {
+    pub name: Option<String>,
+    pub group: Option<String>,
+    pub tasks: Vec<Task>,
+}
+
+/// Attach the collected notes lines to the last task read.
+fn flush_notes(tabs: &mut [ParsedTab], notes: &mut Vec<String>) {
+    if let Some(task) = tabs.last_mut().and_then(|tab| tab.tasks.last_mut()) {
+        let text = notes.join("\n");
+        if !text.trim().is_empty() {
+            task.notes = Some(text_to_html(&text));
+        }
+    }
+    notes.clear();
+}
+
+/// Parse a Markdown checklist. Tabs are the deepest headings other than
+/// "Done"; shallower headings name their group. Tasks before any heading
+/// form an unnamed tab.
+pub fn parse(contents: &str) -> Vec<ParsedTab> {
+    let tab_level = contents
+        .lines()
+        .filter_map(parse_heading)
+        .filter(|(_, text)| !text.eq_ignore_ascii_case(DONE_HEADING))
+        .map(|(level, _)| level)
+        .max();
+
+    let mut tabs: Vec<ParsedTab> = vec![ParsedTab::default()];
+    let mut group: Option<String> = None;
+    // Notes lines of the task being read, blank lines included.
+    let mut notes: Vec<String> = Vec::new();
+
+    for line in contents.lines() {
+        let in_task = tabs.last().is_some_and(|tab| !tab.tasks.is_empty());
+        if in_task && (line.starts_with(NOTES_INDENT) || line.starts_with('\t')) {
+            let (_, text) = line.split_at(if line.starts_with('\t') { 1 } else { 2 });
+            notes.push(text.to_string());
+            continue;
+        }
+        if line.trim().is_empty() {
+            if !notes.is_empty() {
+                notes.push(String::new());
+            }
+            continue;
+        }
+        flush_notes(&mut tabs, &mut notes);
+
+        if let Some((level, text)) = parse_heading(line) {
+            if Some(level) == tab_level && !text.eq_ignore_ascii_case(DONE_HEADING) {
+                tabs.push(ParsedTab {
+                    name: Some(text.to_string()),
+                    group: group.clone(),
+                    tasks: Vec::new(),
+                });
+            } else if tab_level.is_some_and(|tab_level| level < tab_level) {
+                group = Some(text.to_string());
+            }
+            continue;
+        }
+
+        if let Some((completed, text)) = parse_checkbox(line.trim_start()) {
+            let (text, expected, actual) = split_durations(text);
+            tabs.last_mut().unwrap().tasks.push(Task {
+                text: text.to_string(),
+                completed,
+                expected_duration: expected,
+                actual_duration: actual,
+                ..Task::default()
+            });
+        }
+    }
+    flush_notes(&mut tabs, &mut notes);
+
+    tabs.retain(|tab| !tab.tasks.is_empty());
+    tabs
+}
+
+/// Import a Markdown checklist into new tabs, one per tab heading. Tasks
+/// outside any heading go to a tab named `name` (or "Imported").
+pub fn import(
+    data: &mut AppData,
+    contents: &str,
+    name: Option<&str>,
+) -> Result<ImportSummary, String> {
+    let parsed = parse(contents);
+    if parsed.is_empty() {
+        return Err("No Markdown checklist items found".to_string());
+    }
+
+    let mut summary = ImportSummary::default();
+    for parsed_tab in parsed {
+        let group_id = match &parsed_tab.group {
+            Some(group) => Some(match data.group_id_by_name(group) {
+                Some(id) => id,
+                None => {
+                    let id = data.add_group(group);
+                    summary.groups_created.push(id.clone());
+                    id
+                }
+            }),
+            None => None,
+        };
+        let tab_name = parsed_tab
+            .name
+            .as_deref()
+            .or(name)
+            .unwrap_or(IMPORTED_TAB_NAME);
+        let tab_id = data.add_tab(tab_name, group_id);
+        summary.tabs_created.push(tab_id.clone());
+
+        let now = now_iso();
+        for mut task in parsed_tab.tasks {
+            task.id = data.next_task_id();
+            task.created_at = Some(now.clone());
+            if task.completed {
+                task.completed_at = Some(now.clone());
+            }
+            summary.tasks_imported += 1;
+            if let Some(tab) = data.tabs.get_mut(&tab_id) {
+                tab.tasks.push(task);
+            }
+        }
+        summary.tab_id.get_or_insert(tab_id);
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::Group;
+
+    fn task(id: &str, text: &str, completed: bool) -> Task {
+        Task {
+            id: id.into(),
+            text: text.into(),
+            completed,
+            ..Task::default()
+        }
+    }
+
+    fn sample_data() -> AppData {
+        let mut data = AppData::default();
+        data.groups.insert(
+            "group_1".into(),
+            Group {
+                id: "group_1".into(),
+                name: "Work".into(),
+                order: Some(0.0),
+                ..Group::default()
+            },
+        );
+        let tabs = [
+            (
+                "tab_1",
+                "Launch",
+                Some("group_1"),
+                vec![
+                    Task {
+                        expected_duration: Some(90.0),
+                        notes: Some("<p>Agenda</p><p><br></p><p>Budget</p>".into()),
+                        ..task("task_1", "Write the plan", false)
+                    },
+                    Task {
+                        expected_duration: Some(15.0),
+                        actual_duration: Some(22.0 * 60_000.0),
+                        ..task("task_2", "Book the room", true)
+                    },
+                ],
+            ),
+            (
+                "tab_2",
+                "Reading",
+                Some("group_1"),
+                vec![task("task_3", "Chapter (2)", false)],
+            ),
+            (
+                "tab_3",
+                "Home",
+                None,
+                vec![task("task_4", "Water plants", false)],
+            ),
+        ];
+        for (id, name, group_id, tasks) in tabs {
+            data.tabs.insert(
+                id.into(),
+                Tab {
+                    id: id.into(),
+                    name: name.into(),
+                    group_id: group_id.map(str::to_string),
+                    tasks,
+                    ..Tab::default()
+                },
+            );
+        }
+        data
+    }
+
+    #[test]
+    fn splits_only_durations_with_units() {
+        assert_eq!(split_durations("Plan (15m)"), ("Plan", Some(15.0), None));
+        assert_eq!(
+            split_durations("Plan (1h30m, actual 22m)"),
+            ("Plan", Some(90.0), Some(22.0 * 60_000.0))
+        );
+        assert_eq!(
+            split_durations("Plan (actual 4m30s)"),
+            ("Plan", None, Some(4.5 * 60_000.0))
+        );
+        for text in [
+            "Chapter (2)",
+            "Chapter (2, actual 3)",
+            "Call (555) 1234",
+            "Plan (15m, 20m)",
+            "Plan (-5m)",
+            "Plan ()",
+            "Plan (soon)",
+        ] {
+            assert_eq!(split_durations(text), (text, None, None), "{text}");
+        }
+    }
+
+    #[test]
+    fn exports_a_tab() {
+        let markdown = export(&sample_data(), Scope::Tab("tab_1")).unwrap();
+        assert_eq!(
+            markdown,
+            "# Launch\n\n- [ ] Write the plan (1h30m)\n  Agenda\n\n  Budget\n\n\
+             ## Done\n\n- [x] Book the room (15m, actual 22m)\n"
+        );
+        assert!(export(&sample_data(), Scope::Tab("missing")).is_err());
+        assert!(export(&sample_data(), Scope::Group("missing")).is_err());
+    }
+
+    /// Export `scope`, import it into an empty store and export the same
+    /// scope of what was imported.
+    fn round_trip(scope: Scope, reimported: impl Fn(&AppData) -> Scope<'_>) {
+        let original = export(&sample_data(), scope).unwrap();
+        let mut data = AppData::default();
+        import(&mut data, &original, None).unwrap();
+        assert_eq!(export(&data, reimported(&data)).unwrap(), original);
+    }
+
+    #[test]
+    fn round_trips_every_scope() {
+        round_trip(Scope::Tab("tab_1"), |data| {
+            Scope::Tab(data.tabs.keys().next().unwrap())
+        });
+        round_trip(Scope::Group("group_1"), |data| {
+            Scope::Group(data.groups.keys().next().unwrap())
+        });
+        round_trip(Scope::All, |_| Scope::All);
+    }
+
+    #[test]
+    fn imports_tabs_and_groups() {
+        let mut data = AppData::default();
+        let summary = import(
+            &mut data,
+            &export(&sample_data(), Scope::All).unwrap(),
+            None,
+        )
+        .unwrap();
+        assert_eq!(summary.tasks_imported, 4);
+        assert_eq!(summary.tabs_created.len(), 3);
+        assert_eq!(summary.groups_created.len(), 1);
+        let names: Vec<_> = data.tabs.values().map(|tab| tab.name.as_str()).collect();
+        assert_eq!(names, ["Home", "Launch", "Reading"]);
+        assert_eq!(
+            data.tabs.values().nth(2).unwrap().tasks[0].text,
+            "Chapter (2)"
+        );
+        assert!(data.tabs.values().all(|tab| tab
+            .tasks
+            .iter()
+            .all(
+                |task| task.created_at.is_some() && task.completed_at.is_some() == task.completed
+            )));
+
+        let summary = import(&mut data, "- [ ] Loose\n* [X] Done too", Some("Inbox")).unwrap();
+        let tab = &data.tabs[summary.tab_id.as_deref().unwrap()];
+        assert_eq!(tab.name, "Inbox");
+        assert!(tab.tasks[1].completed);
+        assert!(import(&mut data, "# Just a heading\n\nText", None).is_err());
+    }
+}
@@ -0,0 +1,187 @@
+//! Plain-text interchange formats for tasks.
+//!
+//! Each submodule converts between the task store and one external format.
+//! Notes are stored as Quill HTML, so the helpers here flatten them to plain
+//! text on export and wrap plain text back into paragraphs on import.
+
+pub mod ical;
+pub mod markdown;
+pub mod todotxt;
+
+use serde::Serialize;
+
+/// What an import added to the task store.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportSummary {
+    pub tasks_imported: usize,
+    /// Ids of tabs the import had to create.
+    pub tabs_created: Vec<String>,
+    /// Ids of groups the import had to create.
+    pub groups_created: Vec<String>,
+    /// Tab the frontend should switch to, usually where the first task landed.
+    pub tab_id: Option<String>,
+}
+
+/// Flatten Quill HTML to plain text: block ends and `<br>` become newlines,
+/// other tags are dropped and common entities decoded.
+pub fn html_to_text(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('>') else {
+            rest = &rest[start..];
+            break;
+        };
+        let tag = rest[start + 1..start + end].trim().to_ascii_lowercase();
+        let name = tag
+            .trim_start_matches('/')
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or("");
+        let closing = tag.starts_with('/');
+        match name {
+            "br" => text.push('\n'),
+            "p" | "div" | "li" | "h1" | "h2" | "h3" | "blockquote" | "pre" if closing => {
+                text.push('\n')
+            }
+            _ => {}
+        }
+        rest = &rest[start + end + 1..];
+    }
+    text.push_str(rest);
+
+    let text = decode_entities(&text);
+    // `<p><br></p>` yields two newlines for one empty line.
+    let text = text.replace("\n\n", "\n");
+    text.trim_end().to_string()
+}
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Wrap plain text into Quill paragraphs, one per line.
+pub fn text_to_html(text: &str) -> String {
+    text.trim_end()
+        .lines()
+        .map(|line| {
+            if line.trim().is_empty() {
+                "<p><br></p>".to_string()
+            } else {
+                format!("<p>{}</p>", escape_html(line))
+            }
+        })
+        .collect()
+}
+
+/// Notes as plain text, or `None` when there is nothing worth exporting.
+pub fn notes_text(notes: Option<&str>) -> Option<String> {
+    let text = html_to_text(notes?);
+    (!text.trim().is_empty()).then_some(text)
+}
+
+/// Format a duration in minutes compactly: `15m`, `2h`, `1h30m`, `4m30s`.
+pub fn format_minutes(minutes: f64) -> String {
+    let seconds = (minutes * 60.0).round().max(0.0) as u64;
+    let (h, m, s) = (seconds / 3600, seconds % 3600 / 60, seconds % 60);
+    let mut out = String::new();
+    if h > 0 {
+        out.push_str(&format!("{h}h"));
+    }
+    if m > 0 || (h == 0 && s == 0) {
+        out.push_str(&format!("{m}m"));
+    }
+    if s > 0 {
+        out.push_str(&format!("{s}s"));
+    }
+    out
+}
+
+/// Parse what [`format_minutes`] writes, or a bare number of minutes.
+/// Negative and non-finite values are rejected.
+pub fn parse_minutes(value: &str) -> Option<f64> {
+    if let Ok(minutes) = value.parse::<f64>() {
+        return (minutes.is_finite() && minutes >= 0.0).then_some(minutes);
+    }
+    let mut total = 0.0;
+    let mut digits = String::new();
+    for c in value.chars() {
+        match c {
+            '0'..='9' | '.' => digits.push(c),
+            'h' | 'm' | 's' => {
+                let n: f64 = digits.parse().ok()?;
+                total += match c {
+                    'h' => n * 60.0,
+                    'm' => n,
+                    _ => n / 60.0,
+                };
+                digits.clear();
+            }
+            _ => return None,
+        }
+    }
+    (digits.is_empty() && !value.is_empty() && total.is_finite()).then_some(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flattens_quill_html() {
+        assert_eq!(
+            html_to_text(
+                "<p>First &amp; <strong>bold</strong></p><p><br></p><p>a&nbsp;&lt;b&gt;</p>"
+            ),
+            "First & bold\n\na <b>"
+        );
+        assert_eq!(
+            html_to_text("<ul><li>one</li><li>two</li></ul>"),
+            "one\ntwo"
+        );
+        assert_eq!(html_to_text("line<br/>break"), "line\nbreak");
+        assert_eq!(html_to_text("unclosed <tag"), "unclosed <tag");
+        assert_eq!(notes_text(Some("<p><br></p>")), None);
+        assert_eq!(
+            html_to_text(&text_to_html("a <b> & \"c\"\n\nnext")),
+            "a <b> & \"c\"\n\nnext"
+        );
+    }
+
+    #[test]
+    fn parses_and_formats_minutes() {
+        for (minutes, text) in [
+            (15.0, "15m"),
+            (120.0, "2h"),
+            (90.0, "1h30m"),
+            (4.5, "4m30s"),
+        ] {
+            assert_eq!(format_minutes(minutes), text);
+            assert_eq!(parse_minutes(text), Some(minutes));
+        }
+        assert_eq!(format_minutes(0.0), "0m");
+        assert_eq!(parse_minutes("25"), Some(25.0));
+        assert_eq!(parse_minutes("2.5"), Some(2.5));
+        assert_eq!(parse_minutes("1h 30m"), None);
+        assert_eq!(parse_minutes(""), None);
+        assert_eq!(parse_minutes("h"), None);
+        assert_eq!(parse_minutes("15x"), None);
+        for invalid in ["-5", "-1.5", "NaN", "inf", "-inf", "infinity", "1e999"] {
+            assert_eq!(parse_minutes(invalid), None, "{invalid}");
+        }
+    }
+}
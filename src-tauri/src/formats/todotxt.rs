@@ -0,0 +1,444 @@
+//! Conversion between the task store and [todo.txt](https://github.com/todotxt/todo.txt) lines.
+//!
+//! Mapping:
+//! - `x` and the completion date mark completed tasks; the creation date
+//!   follows as usual.
+//! - Favourite tasks get priority `(A)`. Completed tasks can't carry a
+//!   priority, so they keep it as `pri:A` like most todo.txt clients do. Any
+//!   priority on import marks the task as a favourite.
+//! - The tab name becomes a `+project` and its group a `@context`, with
+//!   spaces written as `_`, at the end of the line. On import only the last
+//!   `+project` of the tags ending the line, and an `@context` after it, pick
+//!   the tab; `+word` and `@word` anywhere else stay in the task text.
+//! - `est:` holds the expected duration (`15m`, `1h30m`) and `note:` the
+//!   notes as percent-encoded plain text.
+
+use super::{format_minutes, notes_text, parse_minutes, text_to_html, ImportSummary};
+use crate::store::{now_iso, AppData, Task};
+
+const FAVOURITE_PRIORITY: char = 'A';
+const IMPORTED_TAB_NAME: &str = "Imported";
+
+/// A parsed todo.txt line: the task plus where it wants to go.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TodoTxtItem {
+    pub task: Task,
+    pub project: Option<String>,
+    pub context: Option<String>,
+}
+
+fn encode_tag(name: &str) -> String {
+    name.split_whitespace().collect::<Vec<_>>().join("_")
+}
+
+fn decode_tag(tag: &str) -> String {
+    tag.replace('_', " ")
+}
+
+fn is_date(token: &str) -> bool {
+    chrono::NaiveDate::parse_from_str(token, "%Y-%m-%d").is_ok()
+}
+
+/// `(A)` through `(Z)`.
+fn is_priority(token: &str) -> bool {
+    let bytes = token.as_bytes();
+    bytes.len() == 3 && bytes[0] == b'(' && bytes[1].is_ascii_uppercase() && bytes[2] == b')'
+}
+
+/// `2024-05-01T09:30:00.000Z` -> `2024-05-01`.
+fn date_part(iso: Option<&str>) -> Option<&str> {
+    iso.and_then(|iso| iso.get(..10))
+        .filter(|date| is_date(date))
+}
+
+fn date_to_iso(date: &str) -> String {
+    format!("{date}T00:00:00.000Z")
+}
+
+/// Render one task as a todo.txt line.
+pub fn format_task(task: &Task, tab_name: Option<&str>, group_name: Option<&str>) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    let created = date_part(task.created_at.as_deref());
+
+    if task.completed {
+        parts.push("x".to_string());
+        // A lone date after `x` is read as the completion date, so the
+        // creation date can only be written when both are known.
+        if let Some(completed) = date_part(task.completed_at.as_deref()) {
+            parts.push(completed.to_string());
+            parts.extend(created.map(str::to_string));
+        }
+    } else {
+        if task.is_favourite {
+            parts.push(format!("({FAVOURITE_PRIORITY})"));
+        }
+        parts.extend(created.map(str::to_string));
+    }
+
+    parts.push(task.text.split_whitespace().collect::<Vec<_>>().join(" "));
+
+    if let Some(tab_name) = tab_name.map(encode_tag).filter(|tag| !tag.is_empty()) {
+        parts.push(format!("+{tab_name}"));
+    }
+    if let Some(group_name) = group_name.map(encode_tag).filter(|tag| !tag.is_empty()) {
+        parts.push(format!("@{group_name}"));
+    }
+    if task.completed && task.is_favourite {
+        parts.push(format!("pri:{FAVOURITE_PRIORITY}"));
+    }
+    if let Some(minutes) = task.expected_duration.filter(|m| *m > 0.0) {
+        parts.push(format!("est:{}", format_minutes(minutes)));
+    }
+    if let Some(notes) = notes_text(task.notes.as_deref()) {
+        parts.push(format!("note:{}", urlencoding::encode(&notes)));
+    }
+    parts.join(" ")
+}
+
+fn tag(word: &str, prefix: char) -> Option<&str> {
+    word.strip_prefix(prefix).filter(|tag| !tag.is_empty())
+}
+
+/// Remove the `+project` and `@context` the exporter appends from the end
+/// of `words`: the last `+project` among the trailing tags and the last
+/// `@context` after it. Tags within the text are left alone.
+fn take_destination(words: &mut Vec<&str>) -> (Option<String>, Option<String>) {
+    let trailing = words
+        .iter()
+        .rev()
+        .take_while(|word| tag(word, '+').is_some() || tag(word, '@').is_some())
+        .count();
+    let start = words.len() - trailing;
+    let Some(project_at) = (start..words.len())
+        .rev()
+        .find(|&i| tag(words[i], '+').is_some())
+    else {
+        return (None, None);
+    };
+    let context_at = (project_at + 1..words.len())
+        .rev()
+        .find(|&i| tag(words[i], '@').is_some());
+
+    let context = context_at.map(|i| decode_tag(tag(words.remove(i), '@').unwrap_or_default()));
+    let project = decode_tag(tag(words.remove(project_at), '+').unwrap_or_default());
+    (Some(project), context)
+}
+
+/// Parse one todo.txt line. Blank lines yield `None`.
+pub fn parse_line(line: &str) -> Option<TodoTxtItem> {
+    let mut tokens = line.split_whitespace().peekable();
+    tokens.peek()?;
+
+    let mut task = Task::default();
+    if tokens.peek() == Some(&"x") {
+        tokens.next();
+        task.completed = true;
+        if let Some(date) = tokens.next_if(|t| is_date(t)) {
+            task.completed_at = Some(date_to_iso(date));
+            if let Some(date) = tokens.next_if(|t| is_date(t)) {
+                task.created_at = Some(date_to_iso(date));
+            }
+        }
+    } else {
+        task.is_favourite = tokens.next_if(|t| is_priority(t)).is_some();
+        if let Some(date) = tokens.next_if(|t| is_date(t)) {
+            task.created_at = Some(date_to_iso(date));
+        }
+    }
+
+    let mut words: Vec<&str> = Vec::new();
+    for token in tokens {
+        if let Some(value) = token.strip_prefix("pri:") {
+            task.is_favourite |= !value.is_empty();
+        } else if let Some(minutes) = token.strip_prefix("est:").and_then(parse_minutes) {
+            task.expected_duration = Some(minutes);
+        } else if let Some(value) = token.strip_prefix("note:") {
+            let notes = urlencoding::decode(value)
+                .map(|decoded| decoded.into_owned())
+                .unwrap_or_else(|_| value.to_string());
+            task.notes = Some(text_to_html(&notes));
+        } else {
+            words.push(token);
+        }
+    }
+    let (project, context) = take_destination(&mut words);
+    task.text = words.join(" ");
+
+    Some(TodoTxtItem {
+        task,
+        project,
+        context,
+    })
+}
+
+/// Export the tasks of one tab, or of every tab when `tab_id` is `None`.
+pub fn export(data: &AppData, tab_id: Option<&str>) -> Result<String, String> {
+    if let Some(id) = tab_id {
+        if !data.tabs.contains_key(id) {
+            return Err(format!("Tab not found: {id}"));
+        }
+    }
+
+    let mut out = String::new();
+    for tab in data.tabs.values() {
+        if tab_id.is_some_and(|id| id != tab.id) {
+            continue;
+        }
+        let group_name = tab
+            .group_id
+            .as_ref()
+            .and_then(|id| data.groups.get(id))
+            .map(|group| group.name.as_str());
+        for task in &tab.tasks {
+            out.push_str(&format_task(task, Some(&tab.name), group_name));
+            out.push('\n');
+        }
+    }
+    Ok(out)
+}
+
+/// Add the tasks in `contents` to `data`. Tasks go to the tab named by their
+/// `+project` (created in the `@context` group if needed); tasks without one
+/// go to `default_tab_id`, the current tab, or a new "Imported" tab.
+pub fn import(
+    data: &mut AppData,
+    contents: &str,
+    default_tab_id: Option<&str>,
+) -> Result<ImportSummary, String> {
+    let items: Vec<TodoTxtItem> = contents.lines().filter_map(parse_line).collect();
+    if items.is_empty() {
+        return Err("No todo.txt tasks found".to_string());
+    }
+
+    let mut summary = ImportSummary::default();
+    let mut fallback_tab = default_tab_id
+        .or(data.current_tab_id.as_deref())
+        .filter(|id| data.tabs.contains_key(*id))
+        .map(str::to_string);
+
+    for item in items {
+        let tab_id = match &item.project {
+            Some(project) => match data.tab_id_by_name(project) {
+                Some(id) => id,
+                None => {
+                    let group_id = match &item.context {
+                        Some(context) => Some(match data.group_id_by_name(context) {
+                            Some(id) => id,
+                            None => {
+                                let id = data.add_group(context);
+                                summary.groups_created.push(id.clone());
+                                id
+                            }
+                        }),
+                        None => None,
+                    };
+                    let id = data.add_tab(project, group_id);
+                    summary.tabs_created.push(id.clone());
+                    id
+                }
+            },
+            None => match &fallback_tab {
+                Some(id) => id.clone(),
+                None => {
+                    let id = data.add_tab(IMPORTED_TAB_NAME, None);
+                    summary.tabs_created.push(id.clone());
+                    fallback_tab = Some(id.clone());
+                    id
+                }
+            },
+        };
+
+        let mut task = item.task;
+        task.id = data.next_task_id();
+        task.created_at.get_or_insert_with(now_iso);
+        if let Some(tab) = data.tabs.get_mut(&tab_id) {
+            tab.tasks.push(task);
+            summary.tasks_imported += 1;
+            summary.tab_id.get_or_insert(tab_id);
+        }
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::{Group, Tab};
+
+    fn sample_data() -> AppData {
+        let mut data = AppData::default();
+        data.groups.insert(
+            "group_1".into(),
+            Group {
+                id: "group_1".into(),
+                name: "Day job".into(),
+                ..Group::default()
+            },
+        );
+        let tasks = vec![
+            Task {
+                id: "task_1".into(),
+                text: "Email @bob about +launch".into(),
+                is_favourite: true,
+                created_at: Some("2024-03-01T09:30:00.000Z".into()),
+                expected_duration: Some(90.0),
+                notes: Some("<p>Agenda:</p><p>dates &amp; budget</p>".into()),
+                ..Task::default()
+            },
+            Task {
+                id: "task_2".into(),
+                text: "File expenses".into(),
+                completed: true,
+                is_favourite: true,
+                created_at: Some("2024-03-02T08:00:00.000Z".into()),
+                completed_at: Some("2024-03-10T17:45:00.000Z".into()),
+                ..Task::default()
+            },
+        ];
+        data.tabs.insert(
+            "tab_1".into(),
+            Tab {
+                id: "tab_1".into(),
+                name: "Client work".into(),
+                group_id: Some("group_1".into()),
+                tasks,
+                ..Tab::default()
+            },
+        );
+        data
+    }
+
+    #[test]
+    fn formats_tasks() {
+        let data = sample_data();
+        let tasks = &data.tabs["tab_1"].tasks;
+        assert_eq!(
+            format_task(&tasks[0], Some("Client work"), Some("Day job")),
+            "(A) 2024-03-01 Email @bob about +launch +Client_work @Day_job est:1h30m \
+             note:Agenda%3A%0Adates%20%26%20budget"
+        );
+        assert_eq!(
+            format_task(&tasks[1], Some("Client work"), None),
+            "x 2024-03-10 2024-03-02 File expenses +Client_work pri:A"
+        );
+        // Without a completion date the creation date can't be written.
+        let undated = Task {
+            completed_at: None,
+            ..tasks[1].clone()
+        };
+        assert_eq!(format_task(&undated, None, None), "x File expenses pri:A");
+    }
+
+    #[test]
+    fn parses_lines() {
+        let item =
+            parse_line("(B) 2024-05-01 Call mom +Home_stuff @Personal est:25 note:hi%0Athere")
+                .unwrap();
+        assert_eq!(item.project.as_deref(), Some("Home stuff"));
+        assert_eq!(item.context.as_deref(), Some("Personal"));
+        assert_eq!(item.task.text, "Call mom");
+        assert!(item.task.is_favourite);
+        assert_eq!(
+            item.task.created_at.as_deref(),
+            Some("2024-05-01T00:00:00.000Z")
+        );
+        assert_eq!(item.task.expected_duration, Some(25.0));
+        assert_eq!(item.task.notes.as_deref(), Some("<p>hi</p><p>there</p>"));
+
+        let item = parse_line("x 2024-05-02 2024-05-01 Done thing").unwrap();
+        assert!(item.task.completed);
+        assert!(!item.task.is_favourite);
+        assert_eq!(
+            item.task.completed_at.as_deref(),
+            Some("2024-05-02T00:00:00.000Z")
+        );
+        assert_eq!(
+            item.task.created_at.as_deref(),
+            Some("2024-05-01T00:00:00.000Z")
+        );
+
+        // Not a priority, and an invalid estimate stays in the text.
+        let item = parse_line("(1) weird est:-5").unwrap();
+        assert!(!item.task.is_favourite);
+        assert_eq!(item.task.text, "(1) weird est:-5");
+        assert_eq!(item.task.expected_duration, None);
+
+        assert_eq!(parse_line("   "), None);
+    }
+
+    #[test]
+    fn tags_in_the_text_stay_in_the_text() {
+        let item = parse_line("Buy +milk for @home").unwrap();
+        assert_eq!(item.task.text, "Buy +milk for @home");
+        assert_eq!((item.project, item.context), (None, None));
+
+        // A context before the project belongs to the text.
+        let item = parse_line("Email @bob +Work").unwrap();
+        assert_eq!(item.task.text, "Email @bob");
+        assert_eq!(item.project.as_deref(), Some("Work"));
+        assert_eq!(item.context, None);
+
+        let item = parse_line("Review +draft +Work @Office").unwrap();
+        assert_eq!(item.task.text, "Review +draft");
+        assert_eq!(item.project.as_deref(), Some("Work"));
+        assert_eq!(item.context.as_deref(), Some("Office"));
+    }
+
+    #[test]
+    fn export_then_import_keeps_tasks_and_tabs() {
+        let data = sample_data();
+        let exported = export(&data, None).unwrap();
+        assert!(export(&data, Some("missing")).is_err());
+
+        let mut imported = AppData::default();
+        let summary = import(&mut imported, &exported, None).unwrap();
+        assert_eq!(summary.tasks_imported, 2);
+        assert_eq!(summary.tabs_created.len(), 1);
+        assert_eq!(summary.groups_created.len(), 1);
+        let tab = &imported.tabs[summary.tab_id.as_deref().unwrap()];
+        assert_eq!(tab.name, "Client work");
+        assert_eq!(
+            imported.groups[tab.group_id.as_deref().unwrap()].name,
+            "Day job"
+        );
+
+        let original = &data.tabs["tab_1"].tasks;
+        for (before, after) in original.iter().zip(&tab.tasks) {
+            assert_eq!(after.text, before.text);
+            assert_eq!(after.completed, before.completed);
+            assert_eq!(after.is_favourite, before.is_favourite);
+            assert_eq!(after.expected_duration, before.expected_duration);
+            assert_eq!(after.notes, before.notes);
+            assert_eq!(
+                date_part(after.created_at.as_deref()),
+                date_part(before.created_at.as_deref())
+            );
+        }
+        assert_eq!(export(&imported, None).unwrap(), exported);
+    }
+
+    #[test]
+    fn import_picks_existing_or_new_tabs() {
+        let mut data = sample_data();
+        data.current_tab_id = Some("tab_1".into());
+        let summary = import(
+            &mut data,
+            "Into the current tab\nInto an existing tab +client_work\n(A) Brand new +Side_project",
+            None,
+        )
+        .unwrap();
+        assert_eq!(summary.tasks_imported, 3);
+        assert_eq!(summary.tab_id.as_deref(), Some("tab_1"));
+        assert_eq!(data.tabs["tab_1"].tasks.len(), 4);
+        assert!(summary.groups_created.is_empty());
+        let new_tab = &data.tabs[&summary.tabs_created[0]];
+        assert_eq!(new_tab.name, "Side project");
+        assert!(new_tab.tasks[0].is_favourite);
+        assert!(new_tab.tasks[0].created_at.is_some());
+
+        let mut empty = AppData::default();
+        let summary = import(&mut empty, "Loose task", None).unwrap();
+        assert_eq!(empty.tabs[&summary.tabs_created[0]].name, IMPORTED_TAB_NAME);
+        assert!(import(&mut empty, "\n  \n", None).is_err());
+    }
+}
@@ -0,0 +1,220 @@
+//! Idle detection for focus sessions.
+//!
+//! An [`IdleSource`] reports how long ago the user last gave input. The
+//! [`IdleMonitor`] polls it and reports when that reaches the configured
+//! threshold (the focus timer should pause) and when input resumes (the user
+//! should decide whether to keep or discard the time away). Platforms plug
+//! in their own source; tests drive the monitor with a scripted one.
+
+#[cfg(target_os = "linux")]
+mod x11;
+
+use crate::store;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+pub const SETTINGS_FILE_NAME: &str = "idle.json";
+
+/// Reports the time since the user's last keyboard or pointer input.
+pub trait IdleSource: Send {
+    fn idle_time(&mut self) -> Result<Duration, String>;
+}
+
+/// The idle source for this platform, or why there is none.
+pub fn system_idle_source() -> Result<Box<dyn IdleSource>, String> {
+    #[cfg(target_os = "linux")]
+    {
+        x11::X11IdleSource::open().map(|source| Box::new(source) as Box<dyn IdleSource>)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        Err("Idle detection is not available on this platform".to_string())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct IdleSettings {
+    pub enabled: bool,
+    pub threshold_minutes: f64,
+}
+
+impl Default for IdleSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            threshold_minutes: 5.0,
+        }
+    }
+}
+
+impl IdleSettings {
+    pub fn threshold(&self) -> Duration {
+        Duration::from_secs_f64(self.threshold_minutes.max(1.0) * 60.0)
+    }
+}
+
+pub fn load_settings(data_dir: &Path) -> IdleSettings {
+    store::load_json_settings(data_dir, SETTINGS_FILE_NAME)
+}
+
+pub fn save_settings(data_dir: &Path, settings: &IdleSettings) -> Result<(), String> {
+    if !settings.threshold_minutes.is_finite() || settings.threshold_minutes < 1.0 {
+        return Err("Idle threshold must be at least 1 minute".to_string());
+    }
+    store::save_json_settings(data_dir, SETTINGS_FILE_NAME, settings)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdleEvent {
+    /// Idle time reached the threshold; `idle_for` of it has already passed.
+    WentIdle { idle_for: Duration },
+    /// Input resumed after `idle_for` without any.
+    Returned { idle_for: Duration },
+}
+
+pub struct IdleMonitor {
+    source: Box<dyn IdleSource>,
+    threshold: Duration,
+    /// When the last input before going idle happened.
+    idle_since: Option<Instant>,
+}
+
+impl IdleMonitor {
+    pub fn new(source: Box<dyn IdleSource>, threshold: Duration) -> Self {
+        Self {
+            source,
+            threshold,
+            idle_since: None,
+        }
+    }
+
+    pub fn set_threshold(&mut self, threshold: Duration) {
+        self.threshold = threshold;
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.idle_since.is_some()
+    }
+
+    /// Read the source and report a transition, if any.
+    pub fn poll(&mut self, now: Instant) -> Result<Option<IdleEvent>, String> {
+        let idle = self.source.idle_time()?;
+        let last_input = now.checked_sub(idle).unwrap_or(now);
+        match self.idle_since {
+            None if idle >= self.threshold => {
+                self.idle_since = Some(last_input);
+                Ok(Some(IdleEvent::WentIdle { idle_for: idle }))
+            }
+            // Any input since going idle moves the last-input time forward.
+            Some(since) if last_input > since + Duration::from_secs(1) => {
+                self.idle_since = None;
+                Ok(Some(IdleEvent::Returned {
+                    idle_for: last_input.saturating_duration_since(since),
+                }))
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Focus time after the user answers the return prompt. `elapsed` is the
+/// timer's current reading; it had counted `counted_idle` of the
+/// `total_idle` time away before it was paused.
+pub fn resolve_elapsed(
+    elapsed: Duration,
+    counted_idle: Duration,
+    total_idle: Duration,
+    keep: bool,
+) -> Duration {
+    if keep {
+        elapsed + total_idle.saturating_sub(counted_idle)
+    } else {
+        elapsed.saturating_sub(counted_idle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+
+    /// Replays idle readings, one per poll.
+    struct ScriptedIdle(VecDeque<Duration>);
+
+    impl IdleSource for ScriptedIdle {
+        fn idle_time(&mut self) -> Result<Duration, String> {
+            self.0.pop_front().ok_or_else(|| "script ended".to_string())
+        }
+    }
+
+    fn secs(n: u64) -> Duration {
+        Duration::from_secs(n)
+    }
+
+    fn monitor(readings: &[u64], threshold: u64) -> IdleMonitor {
+        let script = readings.iter().map(|s| secs(*s)).collect();
+        IdleMonitor::new(Box::new(ScriptedIdle(script)), secs(threshold))
+    }
+
+    #[test]
+    fn goes_idle_at_threshold_once() {
+        let t0 = Instant::now();
+        let mut monitor = monitor(&[100, 299, 300, 305], 300);
+        assert_eq!(monitor.poll(t0 + secs(400)).unwrap(), None);
+        assert_eq!(monitor.poll(t0 + secs(599)).unwrap(), None);
+        assert_eq!(
+            monitor.poll(t0 + secs(600)).unwrap(),
+            Some(IdleEvent::WentIdle {
+                idle_for: secs(300)
+            })
+        );
+        assert!(monitor.is_idle());
+        assert_eq!(monitor.poll(t0 + secs(605)).unwrap(), None);
+    }
+
+    #[test]
+    fn reports_time_away_on_return() {
+        let t0 = Instant::now();
+        // Last input at t0 + 100; back at t0 + 1000.
+        let mut monitor = monitor(&[300, 600, 2], 300);
+        monitor.poll(t0 + secs(400)).unwrap();
+        assert_eq!(monitor.poll(t0 + secs(700)).unwrap(), None);
+        assert_eq!(
+            monitor.poll(t0 + secs(1002)).unwrap(),
+            Some(IdleEvent::Returned {
+                idle_for: secs(900)
+            })
+        );
+        assert!(!monitor.is_idle());
+    }
+
+    #[test]
+    fn source_errors_are_passed_on() {
+        let mut monitor = monitor(&[], 300);
+        assert!(monitor.poll(Instant::now()).is_err());
+    }
+
+    #[test]
+    fn keep_or_discard_idle_time() {
+        // Paused at 20 min, 5 of which were idle; away for 15 min in total.
+        let elapsed = secs(20 * 60);
+        let counted = secs(5 * 60);
+        let total = secs(15 * 60);
+        assert_eq!(
+            resolve_elapsed(elapsed, counted, total, true),
+            secs(30 * 60)
+        );
+        assert_eq!(
+            resolve_elapsed(elapsed, counted, total, false),
+            secs(15 * 60)
+        );
+    }
+
+    #[test]
+    fn off_until_turned_on() {
+        let dir = std::env::temp_dir().join("redd-idle-settings-default");
+        assert!(!load_settings(&dir).enabled);
+    }
+}
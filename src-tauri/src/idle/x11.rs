@@ -0,0 +1,74 @@
+//! Idle time from the X server's MIT-SCREEN-SAVER extension. libX11 and
+//! libXss are loaded at runtime, so the app still starts without them.
+//! Under Wayland this only works through XWayland and may not see input
+//! to native Wayland clients.
+
+use super::IdleSource;
+use std::os::raw::c_void;
+use std::ptr;
+use std::time::Duration;
+use x11_dl::xlib::{Display, Xlib};
+use x11_dl::xss::{XScreenSaverInfo, Xss};
+
+pub struct X11IdleSource {
+    xlib: Xlib,
+    xss: Xss,
+    display: *mut Display,
+    info: *mut XScreenSaverInfo,
+}
+
+// The display connection is owned by the source and only used from the
+// thread that polls it.
+unsafe impl Send for X11IdleSource {}
+
+impl X11IdleSource {
+    pub fn open() -> Result<Self, String> {
+        let xlib = Xlib::open().map_err(|e| format!("Failed to load libX11: {e}"))?;
+        let xss = Xss::open().map_err(|e| format!("Failed to load libXss: {e}"))?;
+        unsafe {
+            let display = (xlib.XOpenDisplay)(ptr::null());
+            if display.is_null() {
+                return Err("No X11 display".to_string());
+            }
+            let (mut event_base, mut error_base) = (0, 0);
+            if (xss.XScreenSaverQueryExtension)(display, &mut event_base, &mut error_base) == 0 {
+                (xlib.XCloseDisplay)(display);
+                return Err("The X server has no MIT-SCREEN-SAVER extension".to_string());
+            }
+            let info = (xss.XScreenSaverAllocInfo)();
+            if info.is_null() {
+                (xlib.XCloseDisplay)(display);
+                return Err("Failed to allocate XScreenSaverInfo".to_string());
+            }
+            Ok(Self {
+                xlib,
+                xss,
+                display,
+                info,
+            })
+        }
+    }
+}
+
+impl IdleSource for X11IdleSource {
+    fn idle_time(&mut self) -> Result<Duration, String> {
+        unsafe {
+            let root = (self.xlib.XDefaultRootWindow)(self.display);
+            if (self.xss.XScreenSaverQueryInfo)(self.display, root, self.info) == 0 {
+                return Err("XScreenSaverQueryInfo failed".to_string());
+            }
+            // `c_ulong` is only 32 bits wide on 32-bit targets.
+            #[allow(clippy::useless_conversion)]
+            Ok(Duration::from_millis(u64::from((*self.info).idle)))
+        }
+    }
+}
+
+impl Drop for X11IdleSource {
+    fn drop(&mut self) {
+        unsafe {
+            (self.xlib.XFree)(self.info as *mut c_void);
+            (self.xlib.XCloseDisplay)(self.display);
+        }
+    }
+}
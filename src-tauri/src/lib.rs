@@ -1,15 +1,51 @@
+mod backup_import;
+mod backups;
+mod basecamp;
+mod capture;
+mod cli;
 mod commands;
+mod deep_link;
+mod focus_log;
+mod focus_pins;
+mod focus_timer;
+mod formats;
+mod idle;
+mod mcp;
+mod migrations;
+mod oauth;
 mod opener;
+mod pomodoro;
+mod reports;
+mod rpc;
+mod secrets;
+mod shortcuts;
+mod store;
+mod window_geometry;
 
 use commands::app::*;
+use commands::backups::*;
+use commands::basecamp::*;
+use commands::deep_link::*;
+use commands::focus_log::*;
+use commands::focus_pins::*;
+use commands::focus_timer::*;
+use commands::formats::*;
+use commands::idle::*;
 use commands::oauth::*;
+use commands::pomodoro::*;
 use commands::reminders::*;
+use commands::reports::*;
+use commands::rpc::*;
+use commands::shortcuts::*;
+use commands::store::*;
+use commands::tray::*;
 use commands::window::*;
-use tauri::{Emitter, Manager};
+use tauri::Manager;
 
 /// Forward macOS menu zoom actions to the frontend (same pattern as redd-block).
 #[cfg(target_os = "macos")]
 fn emit_menu_zoom(app: &tauri::AppHandle, event: &str) {
+    use tauri::Emitter;
     if let Some(window) = app.get_webview_window("main") {
         let _ = window.emit(event, ());
     }
@@ -23,68 +59,36 @@ fn open_url(_app: &tauri::AppHandle, url: &str) {
     }
 }
 
-/// Copy every file/subdirectory from `src` into `dst` without overwriting any
-/// file that already exists at the destination. Existing directories are
-/// merged into; existing files are left alone.
-fn copy_dir_non_destructive(src: &std::path::Path, dst: &std::path::Path) -> std::io::Result<()> {
-    std::fs::create_dir_all(dst)?;
-    for entry in std::fs::read_dir(src)? {
-        let entry = entry?;
-        let file_type = entry.file_type()?;
-        let src_path = entry.path();
-        let dst_path = dst.join(entry.file_name());
-        if file_type.is_dir() {
-            copy_dir_non_destructive(&src_path, &dst_path)?;
-        } else if !dst_path.exists() {
-            std::fs::copy(&src_path, &dst_path)?;
-        }
-    }
-    Ok(())
-}
-
-/// Before v2.4.1 the Tauri app identifier was `com.redd.todo`. We renamed it
-/// to `com.redd.do` in the v2.4.0 rebrand, which moved the per-user data dir
-/// to a new location (e.g. `%APPDATA%\com.redd.do\` on Windows,
-/// `~/Library/Application Support/com.redd.do/` on macOS). On first launch of
-/// the renamed build, copy the old tree in so existing users' tasks survive.
-/// Safe to leave in place indefinitely — guarded by a marker file.
-fn migrate_legacy_identifier_data(app: &tauri::AppHandle) {
+/// Bring on-disk user data up to the current schema before any window loads.
+/// See `migrations::MIGRATIONS` for the individual steps.
+fn run_data_migrations(app: &tauri::AppHandle) {
     let resolver = app.path();
-    let targets = [
-        resolver.app_data_dir().ok(),
-        resolver.app_config_dir().ok(),
-        resolver.app_local_data_dir().ok(),
-    ];
-    for new_dir in targets.into_iter().flatten() {
-        let Some(parent) = new_dir.parent() else {
-            continue;
-        };
-        let old_dir = parent.join("com.redd.todo");
-        if old_dir == new_dir || !old_dir.exists() {
-            continue;
-        }
-        let marker = new_dir.join(".migrated-from-com-redd-todo");
-        if marker.exists() {
-            continue;
-        }
-        if let Err(e) = std::fs::create_dir_all(&new_dir) {
-            log::warn!("Failed to create {new_dir:?}: {e}");
-            continue;
-        }
-        match copy_dir_non_destructive(&old_dir, &new_dir) {
-            Ok(()) => {
-                log::info!("Migrated user data from {old_dir:?} into {new_dir:?}");
-                if let Err(e) = std::fs::write(&marker, "") {
-                    log::warn!("Failed to write migration marker {marker:?}: {e}");
-                }
-            }
-            Err(e) => log::warn!("Failed to migrate {old_dir:?} -> {new_dir:?}: {e}"),
-        }
+    let Ok(data_dir) = resolver.app_data_dir() else {
+        log::warn!("[Migrations] No app data dir; skipping migrations");
+        return;
+    };
+    let ctx = migrations::MigrationContext {
+        data_dir,
+        config_dir: resolver.app_config_dir().ok(),
+        local_data_dir: resolver.app_local_data_dir().ok(),
+    };
+    if let Err(e) = std::fs::create_dir_all(&ctx.data_dir) {
+        log::warn!("Failed to create {:?}: {e}", ctx.data_dir);
+        return;
+    }
+    match migrations::run_migrations(&ctx) {
+        Ok(version) => log::info!("[Migrations] Data schema at v{version}"),
+        Err(e) => log::warn!("[Migrations] {e}"),
     }
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    // `redd-todo list` and friends finish here without starting the app.
+    if let Some(code) = cli::run_from_args() {
+        std::process::exit(code);
+    }
+
     let mut builder = tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_clipboard_manager::init())
@@ -93,7 +97,12 @@ pub fn run() {
         .plugin(tauri_plugin_deep_link::init())
         .plugin(tauri_plugin_http::init())
         .plugin(tauri_plugin_dialog::init())
-        .plugin(tauri_plugin_fs::init());
+        .plugin(tauri_plugin_fs::init())
+        .plugin(
+            tauri_plugin_global_shortcut::Builder::new()
+                .with_handler(handle_global_shortcut)
+                .build(),
+        );
 
     #[cfg(target_os = "macos")]
     {
@@ -106,15 +115,31 @@ pub fn run() {
             if let Some(url) = argv.iter().find(|arg| {
                 arg.starts_with("redddo://") || arg.starts_with("reddtodo://")
             }) {
-                log::info!("[Deep Link] Received URL from new instance: {}", url);
-                if let Some(window) = app.get_webview_window("main") {
-                    let _ = window.emit("deep-link-received", url);
-                    let _ = window.set_focus();
-                }
+                log::info!(
+                    "[Deep Link] Received URL from new instance: {}",
+                    deep_link::redact(url)
+                );
+                handle_deep_link(app, url);
+            } else {
+                handle_command_line(app, &argv);
             }
         }))
         .setup(|app| {
-            migrate_legacy_identifier_data(app.handle());
+            run_data_migrations(app.handle());
+            let marker = store_dir(app.handle()).and_then(|dir| cli::write_instance_marker(&dir));
+            if let Err(e) = marker {
+                log::warn!("[CLI] {e}");
+            }
+            spawn_backup_scheduler(app.handle().clone());
+            close_dangling_focus_sessions(app.handle());
+            spawn_focus_ticker(app.handle().clone());
+            spawn_idle_monitor(app.handle().clone());
+            if let Err(e) = setup_tray(app.handle()) {
+                log::warn!("[Tray] Failed to create tray icon: {e}");
+            }
+            register_global_shortcuts(app.handle());
+            setup_rpc_server(app.handle());
+            setup_basecamp(app.handle());
 
             if cfg!(debug_assertions) {
                 app.handle().plugin(
@@ -261,19 +286,10 @@ pub fn run() {
                 });
             }
 
-            // Handle deep link URLs on app startup
-            #[cfg(any(target_os = "macos", target_os = "linux"))]
-            {
-                use tauri_plugin_deep_link::DeepLinkExt;
-                if let Ok(Some(urls)) = app.deep_link().get_current() {
-                    for url in urls {
-                        log::info!("[Deep Link] Startup URL: {}", url.as_str());
-                        if let Some(window) = app.get_webview_window("main") {
-                            let _ = window.emit("deep-link-received", url.as_str());
-                        }
-                    }
-                }
-            }
+            // Deep links the app was launched with (and, on macOS, later ones)
+            setup_deep_links(app.handle());
+            // A command-line command that needed the app to start
+            handle_command_line(app.handle(), &std::env::args().collect::<Vec<_>>());
 
             Ok(())
         })
@@ -291,6 +307,64 @@ pub fn run() {
             delete_reminders_task,
             create_reminders_task,
             open_reminders_privacy_settings,
+            // Store commands
+            load_state,
+            take_external_store_changes,
+            merge_store_changes,
+            save_state,
+            save_plan_data,
+            patch_task,
+            // Backup commands
+            list_backups,
+            create_backup,
+            restore_backup,
+            get_backup_settings,
+            set_backup_settings,
+            validate_backup,
+            import_backup,
+            // Import/export format commands
+            export_todotxt,
+            import_todotxt,
+            export_markdown,
+            import_markdown,
+            export_ics,
+            import_ics,
+            // Report commands
+            export_time_report,
+            // Focus session log commands
+            query_focus_sessions,
+            // Focus timer commands
+            start_focus_timer,
+            pause_focus_timer,
+            resume_focus_timer,
+            stop_focus_timer,
+            set_focus_timer_elapsed,
+            get_focus_timer,
+            // Pomodoro commands
+            skip_pomodoro_break,
+            get_pomodoro_settings,
+            set_pomodoro_settings,
+            // Focus pin commands
+            get_focus_pins,
+            get_focus_pin_settings,
+            set_focus_pin_settings,
+            // Idle detection commands
+            resolve_idle_time,
+            get_idle_settings,
+            set_idle_settings,
+            // Deep link commands
+            deep_link_ready,
+            confirm_deep_link_action,
+            // Automation API commands
+            get_rpc_settings,
+            set_rpc_settings,
+            get_rpc_status,
+            // Global shortcut commands
+            get_shortcut_settings,
+            set_shortcut_settings,
+            get_shortcut_status,
+            // Tray commands
+            set_tray_labels,
             // Window commands
             window_minimize,
             window_maximize,
@@ -310,15 +384,40 @@ pub fn run() {
             task_updated,
             focus_status_changed,
             set_focus_mode_window_state,
+            capture_task,
             // OAuth commands
             start_basecamp_auth,
             handle_oauth_callback,
+            // Basecamp commands
+            get_basecamp_status,
+            basecamp_disconnect,
+            basecamp_import_tokens,
+            basecamp_projects,
+            basecamp_project_people,
+            basecamp_todoset,
+            basecamp_todolists,
+            basecamp_todos,
+            basecamp_create_todo,
+            basecamp_update_todo,
+            basecamp_set_todo_completed,
+            basecamp_trash_todo,
+            basecamp_move_todo,
+            basecamp_comments,
+            basecamp_create_comment,
         ])
         .build(tauri::generate_context!())
         .expect("error while running tauri application")
-        .run(|app_handle, event| {
-            if let tauri::RunEvent::ExitRequested { .. } = event {
+        .run(|app_handle, event| match event {
+            tauri::RunEvent::ExitRequested { .. } => {
                 flush_all_webview_persisted_state(app_handle);
+                save_open_focus_window_geometry(app_handle);
+            }
+            tauri::RunEvent::Exit => {
+                stop_rpc_server();
+                if let Ok(dir) = store_dir(app_handle) {
+                    cli::remove_instance_marker(&dir);
+                }
             }
+            _ => {}
         });
 }
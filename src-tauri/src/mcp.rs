@@ -0,0 +1,545 @@
+//! Model Context Protocol server for AI assistants.
+//!
+//! `redd-todo mcp` speaks MCP's stdio transport: one JSON-RPC message per
+//! line on stdin, replies on stdout, logs on stderr. Its tools read the same
+//! task store the app uses. Changes go to the running app over the
+//! automation API ([`crate::rpc`]) when it is on; otherwise they follow the
+//! command-line rules in [`crate::cli`]: they are written to the store while
+//! the app is closed and passed to the app otherwise. With `--read-only` the
+//! tools that change tasks are not offered at all.
+
+use crate::capture::MAX_DURATION_MINUTES;
+use crate::cli::{self, Command};
+use crate::deep_link;
+use crate::reports;
+use crate::rpc::{self, RpcError};
+use crate::store::{self, AppData};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+/// Protocol revisions this server speaks, newest first.
+pub const PROTOCOL_VERSIONS: [&str; 3] = ["2025-06-18", "2025-03-26", "2024-11-05"];
+
+/// Tools that change tasks, hidden in read-only mode.
+const WRITE_TOOLS: [&str; 3] = ["add_task", "complete_task", "start_focus"];
+
+fn tool_definitions() -> Vec<Value> {
+    let task = json!({
+        "type": "object",
+        "properties": {
+            "task": { "type": "string", "description": "Task id (from list_tasks) or text" }
+        },
+        "required": ["task"],
+    });
+    vec![
+        json!({
+            "name": "list_tabs",
+            "description": "List the tabs (task lists) with their group and task counts.",
+            "inputSchema": { "type": "object", "properties": {} },
+            "annotations": { "readOnlyHint": true },
+        }),
+        json!({
+            "name": "list_tasks",
+            "description": "List tasks per tab. Durations are in minutes; time fields in milliseconds.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "tab": { "type": "string", "description": "Tab id or name; every tab if omitted" },
+                    "includeCompleted": { "type": "boolean", "description": "Also list completed tasks" },
+                },
+            },
+            "annotations": { "readOnlyHint": true },
+        }),
+        json!({
+            "name": "add_task",
+            "description": "Add a task, optionally with an expected duration.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "text": { "type": "string" },
+                    "tab": { "type": "string", "description": "Tab id or name; the first tab if omitted" },
+                    "minutes": {
+                        "type": "integer",
+                        "minimum": 1,
+                        "maximum": MAX_DURATION_MINUTES,
+                        "description": "Expected duration",
+                    },
+                },
+                "required": ["text"],
+            },
+            "annotations": { "readOnlyHint": false, "destructiveHint": false },
+        }),
+        json!({
+            "name": "complete_task",
+            "description": "Mark a task as done.",
+            "inputSchema": task,
+            "annotations": { "readOnlyHint": false, "destructiveHint": false, "idempotentHint": true },
+        }),
+        json!({
+            "name": "start_focus",
+            "description": "Open ReDD To-Do in focus mode on a task and start its timer.",
+            "inputSchema": task,
+            "annotations": { "readOnlyHint": false, "destructiveHint": false },
+        }),
+        json!({
+            "name": "time_totals",
+            "description": "Tracked and expected minutes in total, per day, per tab and per group, \
+                            for tasks completed in an optional date range.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "from": { "type": "string", "description": "First day, YYYY-MM-DD" },
+                    "to": { "type": "string", "description": "Last day, YYYY-MM-DD" },
+                },
+            },
+            "annotations": { "readOnlyHint": true },
+        }),
+    ]
+}
+
+#[derive(Deserialize)]
+struct ToolCall {
+    name: String,
+    #[serde(default)]
+    arguments: Value,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct ListArguments {
+    tab: Option<String>,
+    include_completed: bool,
+}
+
+#[derive(Deserialize)]
+struct AddArguments {
+    text: String,
+    tab: Option<String>,
+    minutes: Option<u32>,
+}
+
+#[derive(Deserialize)]
+struct TaskArguments {
+    task: String,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct RangeArguments {
+    from: Option<String>,
+    to: Option<String>,
+}
+
+/// Hands a command to the app, starting it if needed.
+type Launcher = Box<dyn Fn(&Command) -> Result<(), String>>;
+
+/// Run the binary with the command's arguments, which passes it to the
+/// running app or starts one. Its output must not reach our stdout.
+fn launch_app(command: &Command) -> Result<(), String> {
+    use std::process::Stdio;
+    let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+    let mut child = std::process::Command::new(exe)
+        .args(command.to_args())
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to start ReDD To-Do: {e}"))?;
+    // A forwarding process exits right away; a started app keeps running.
+    std::thread::spawn(move || child.wait());
+    Ok(())
+}
+
+/// The automation API call that carries out `command` in the running app.
+fn rpc_call(command: &Command) -> Option<(&'static str, Value)> {
+    match command {
+        Command::Add { text, minutes, tab } => Some((
+            "add",
+            json!({ "text": text, "tab": tab, "minutes": minutes }),
+        )),
+        Command::Done { task } => Some(("complete", json!({ "task": task }))),
+        Command::Focus { task } => Some(("focus", json!({ "task": task }))),
+        _ => None,
+    }
+}
+
+/// What the app's reply to [`rpc_call`] says was done.
+fn rpc_output(command: &Command, result: &Value) -> String {
+    match command {
+        Command::Add { .. } => format!("Added {}", result["id"].as_str().unwrap_or_default()),
+        Command::Done { .. } => format!(
+            "Completed {}",
+            result["taskId"].as_str().unwrap_or_default()
+        ),
+        _ => format!("Focused {}", result["taskId"].as_str().unwrap_or_default()),
+    }
+}
+
+pub struct McpServer {
+    dir: PathBuf,
+    /// Where the app's automation API listens, if it can be found.
+    socket_dir: Option<PathBuf>,
+    read_only: bool,
+    launch: Launcher,
+}
+
+impl McpServer {
+    pub fn new(dir: &Path, read_only: bool) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+            socket_dir: cli::socket_dir().ok(),
+            read_only,
+            launch: Box::new(launch_app),
+        }
+    }
+
+    /// Handle one line from the client; `None` for notifications.
+    pub fn handle(&self, line: &str) -> Option<Value> {
+        match rpc::parse_request(line) {
+            Ok(request) => {
+                let result = self.call(&request.method, request.params);
+                request.id.map(|id| rpc::response(id, result))
+            }
+            Err((id, error)) => Some(rpc::response(id, Err(error))),
+        }
+    }
+
+    fn call(&self, method: &str, params: Value) -> Result<Value, RpcError> {
+        match method {
+            "initialize" => {
+                let requested = params.get("protocolVersion").and_then(Value::as_str);
+                let version = PROTOCOL_VERSIONS
+                    .into_iter()
+                    .find(|version| Some(*version) == requested)
+                    .unwrap_or(PROTOCOL_VERSIONS[0]);
+                Ok(json!({
+                    "protocolVersion": version,
+                    "capabilities": { "tools": {} },
+                    "serverInfo": { "name": "redd-todo", "version": env!("CARGO_PKG_VERSION") },
+                }))
+            }
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(json!({ "tools": self.tools() })),
+            "tools/call" => {
+                let ToolCall { name, arguments } = rpc::params(params)?;
+                self.call_tool(&name, arguments)
+            }
+            _ if method.starts_with("notifications/") => Ok(Value::Null),
+            _ => Err(RpcError::method_not_found(method)),
+        }
+    }
+
+    fn tools(&self) -> Vec<Value> {
+        tool_definitions()
+            .into_iter()
+            .filter(|tool| {
+                !self.read_only || !WRITE_TOOLS.contains(&tool["name"].as_str().unwrap_or(""))
+            })
+            .collect()
+    }
+
+    /// Unknown tools and bad arguments are protocol errors; a tool that runs
+    /// and fails reports it in its result, so the assistant can see why.
+    fn call_tool(&self, name: &str, arguments: Value) -> Result<Value, RpcError> {
+        if self.read_only && WRITE_TOOLS.contains(&name) {
+            return Ok(tool_result(Err(format!(
+                "{name} is not available: ReDD To-Do is served read-only"
+            ))));
+        }
+        let result = match name {
+            "list_tabs" => self.list_tabs(),
+            "list_tasks" => self.list_tasks(rpc::params(arguments)?),
+            "add_task" => {
+                let AddArguments { text, tab, minutes } = rpc::params(arguments)?;
+                if minutes.is_some_and(|m| !(1..=MAX_DURATION_MINUTES).contains(&m)) {
+                    return Err(RpcError::invalid_params(format!(
+                        "minutes must be from 1 to {MAX_DURATION_MINUTES}"
+                    )));
+                }
+                self.change(Command::Add { text, minutes, tab })
+            }
+            "complete_task" => {
+                let TaskArguments { task } = rpc::params(arguments)?;
+                self.change(Command::Done { task })
+            }
+            "start_focus" => {
+                let TaskArguments { task } = rpc::params(arguments)?;
+                self.change(Command::Focus { task })
+            }
+            "time_totals" => self.time_totals(rpc::params(arguments)?),
+            _ => return Err(RpcError::invalid_params(format!("Unknown tool: {name}"))),
+        };
+        Ok(tool_result(result))
+    }
+
+    fn load(&self) -> Result<AppData, String> {
+        Ok(store::load(&self.dir)?.unwrap_or_default())
+    }
+
+    fn list_tabs(&self) -> Result<String, String> {
+        let data = self.load()?;
+        let tabs: Vec<Value> = data
+            .tabs
+            .values()
+            .map(|tab| {
+                let group = tab
+                    .group_id
+                    .as_ref()
+                    .and_then(|id| data.groups.get(id))
+                    .map(|group| group.name.clone());
+                let done = tab.tasks.iter().filter(|task| task.completed).count();
+                json!({
+                    "id": tab.id,
+                    "name": tab.name,
+                    "group": group,
+                    "openTasks": tab.tasks.len() - done,
+                    "completedTasks": done,
+                })
+            })
+            .collect();
+        to_text(&tabs)
+    }
+
+    fn list_tasks(&self, arguments: ListArguments) -> Result<String, String> {
+        let data = self.load()?;
+        let tabs =
+            deep_link::list_tasks(&data, arguments.tab.as_deref(), arguments.include_completed)
+                .map_err(|e| e.to_string())?;
+        to_text(&tabs)
+    }
+
+    fn time_totals(&self, arguments: RangeArguments) -> Result<String, String> {
+        let from = reports::parse_date(arguments.from.as_deref())?;
+        let to = reports::parse_date(arguments.to.as_deref())?;
+        let report = reports::build(&self.load()?, from, to);
+        to_text(&json!({
+            "from": report.from,
+            "to": report.to,
+            "totals": report.totals,
+            "byDay": report.by_day,
+            "byTab": report.by_tab,
+            "byGroup": report.by_group,
+        }))
+    }
+
+    /// Carry out a change in the running app through its automation API,
+    /// in the store while the app is closed, or else hand it to the app.
+    fn change(&self, command: Command) -> Result<String, String> {
+        if let (Some(socket_dir), Some((method, params))) = (&self.socket_dir, rpc_call(&command)) {
+            if let Some(result) = rpc::client::call(socket_dir, method, params)? {
+                return Ok(rpc_output(&command, &result));
+            }
+        }
+        if !cli::instance_running(&self.dir) {
+            if let Some(output) = cli::run_in_store(&self.dir, &command)? {
+                return Ok(output.trim_end().to_string());
+            }
+        }
+        (self.launch)(&command)?;
+        Ok("Passed to ReDD To-Do, which carries it out in the app.".to_string())
+    }
+}
+
+fn to_text(value: &impl serde::Serialize) -> Result<String, String> {
+    serde_json::to_string_pretty(value).map_err(|e| e.to_string())
+}
+
+fn tool_result(result: Result<String, String>) -> Value {
+    let (text, is_error) = match result {
+        Ok(text) => (text, false),
+        Err(e) => (e, true),
+    };
+    json!({ "content": [{ "type": "text", "text": text }], "isError": is_error })
+}
+
+/// Serve MCP on stdin/stdout until the client closes stdin.
+pub fn serve_stdio(dir: &Path, read_only: bool) -> Result<(), String> {
+    let server = McpServer::new(dir, read_only);
+    let stdout = std::io::stdout();
+    for line in std::io::stdin().lock().lines() {
+        let line = line.map_err(|e| e.to_string())?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(reply) = server.handle(&line) {
+            let mut out = stdout.lock();
+            writeln!(out, "{reply}")
+                .and_then(|_| out.flush())
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::Task;
+    use std::sync::{Arc, Mutex};
+
+    fn server(read_only: bool) -> (McpServer, tempfile::TempDir) {
+        let dir = tempfile::tempdir().unwrap();
+        let mut data = AppData::default();
+        let work = data.add_tab("Work", None);
+        data.tabs[&work].tasks = vec![Task {
+            id: "task_1".to_string(),
+            text: "Write report".to_string(),
+            ..Task::default()
+        }];
+        data.task_counter = 1;
+        store::save(dir.path(), &data).unwrap();
+        let mut server = McpServer::new(dir.path(), read_only);
+        // No automation API listens here unless a test starts one.
+        server.socket_dir = Some(dir.path().to_path_buf());
+        (server, dir)
+    }
+
+    fn call(server: &McpServer, method: &str, params: Value) -> Value {
+        let request = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        server.handle(&request.to_string()).unwrap()
+    }
+
+    fn call_tool(server: &McpServer, name: &str, arguments: Value) -> Value {
+        call(
+            server,
+            "tools/call",
+            json!({ "name": name, "arguments": arguments }),
+        )
+    }
+
+    fn tool_names(server: &McpServer) -> Vec<String> {
+        call(server, "tools/list", Value::Null)["result"]["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|tool| tool["name"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn negotiates_and_lists_tools() {
+        let (server, _dir) = server(false);
+        let init = call(
+            &server,
+            "initialize",
+            json!({ "protocolVersion": "2024-11-05" }),
+        );
+        assert_eq!(init["result"]["protocolVersion"], "2024-11-05");
+        let init = call(
+            &server,
+            "initialize",
+            json!({ "protocolVersion": "1999-01-01" }),
+        );
+        assert_eq!(init["result"]["protocolVersion"], PROTOCOL_VERSIONS[0]);
+        assert_eq!(
+            server.handle(r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#),
+            None
+        );
+        assert_eq!(tool_names(&server).len(), 6);
+        assert_eq!(
+            call(&server, "resources/list", Value::Null)["error"]["code"],
+            -32601
+        );
+    }
+
+    #[test]
+    fn reads_and_changes_the_store() {
+        let (server, dir) = server(false);
+        let listed = call_tool(&server, "list_tasks", json!({ "tab": "work" }));
+        assert_eq!(listed["result"]["isError"], false);
+        assert!(listed["result"]["content"][0]["text"]
+            .as_str()
+            .unwrap()
+            .contains("Write report"));
+
+        let added = call_tool(
+            &server,
+            "add_task",
+            json!({ "text": "Call Sam", "minutes": 20 }),
+        );
+        assert_eq!(
+            added["result"]["content"][0]["text"],
+            "Added task_2 to Work"
+        );
+        let done = call_tool(&server, "complete_task", json!({ "task": "Write report" }));
+        assert_eq!(done["result"]["isError"], false);
+
+        let data = store::load(dir.path()).unwrap().unwrap();
+        let tasks = &data.tabs.values().next().unwrap().tasks;
+        assert!(tasks[0].completed);
+        assert_eq!(tasks[1].expected_duration, Some(20.0));
+
+        let missing = call_tool(&server, "complete_task", json!({ "task": "Nope" }));
+        assert_eq!(missing["result"]["isError"], true);
+        let bad = call_tool(&server, "add_task", json!({ "text": "x", "minutes": 0 }));
+        assert_eq!(bad["error"]["code"], -32602);
+    }
+
+    #[test]
+    fn focus_goes_to_the_app() {
+        let (mut server, _dir) = server(false);
+        let launched = Arc::new(Mutex::new(Vec::new()));
+        let record = launched.clone();
+        server.launch = Box::new(move |command| {
+            record.lock().unwrap().push(command.clone());
+            Ok(())
+        });
+        let focus = call_tool(&server, "start_focus", json!({ "task": "task_1" }));
+        assert_eq!(focus["result"]["isError"], false);
+        assert_eq!(
+            *launched.lock().unwrap(),
+            [Command::Focus {
+                task: "task_1".to_string()
+            }]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn changes_go_to_the_running_app_over_the_automation_api() {
+        struct App(Arc<Mutex<Vec<(String, Value)>>>);
+        impl rpc::Handler for App {
+            fn call(&self, method: &str, params: Value) -> Result<Value, RpcError> {
+                self.0.lock().unwrap().push((method.to_string(), params));
+                Ok(json!({ "taskId": "task_1" }))
+            }
+        }
+
+        let (mut server, dir) = server(false);
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let _app = rpc::server::RpcServer::start(dir.path(), App(calls.clone())).unwrap();
+        server.launch = Box::new(|_| panic!("the app is already running"));
+
+        let focus = call_tool(&server, "start_focus", json!({ "task": "Write report" }));
+        assert_eq!(focus["result"]["content"][0]["text"], "Focused task_1");
+        let done = call_tool(&server, "complete_task", json!({ "task": "task_1" }));
+        assert_eq!(done["result"]["content"][0]["text"], "Completed task_1");
+        assert_eq!(
+            *calls.lock().unwrap(),
+            [
+                ("focus".to_string(), json!({ "task": "Write report" })),
+                ("complete".to_string(), json!({ "task": "task_1" }))
+            ]
+        );
+        // The app carries out the change; the store is left to it.
+        let data = store::load(dir.path()).unwrap().unwrap();
+        assert!(!data.tabs.values().next().unwrap().tasks[0].completed);
+    }
+
+    #[test]
+    fn read_only_mode_refuses_changes() {
+        let (server, dir) = server(true);
+        assert_eq!(
+            tool_names(&server),
+            ["list_tabs", "list_tasks", "time_totals"]
+        );
+        let added = call_tool(&server, "add_task", json!({ "text": "Call Sam" }));
+        assert_eq!(added["result"]["isError"], true);
+        let data = store::load(dir.path()).unwrap().unwrap();
+        assert_eq!(data.tabs.values().next().unwrap().tasks.len(), 1);
+        let totals = call_tool(&server, "time_totals", json!({ "from": "2026-01-01" }));
+        assert_eq!(totals["result"]["isError"], false);
+    }
+}
@@ -0,0 +1,306 @@
+//! Versioned migrations for on-disk user data.
+//!
+//! The current schema version is recorded in `app_data_dir()/.schema-version`.
+//! On launch every step with a higher version runs in order, after a copy of
+//! the app's own files has been taken under `migration-snapshots/`. The version file
+//! is bumped after each successful step, so a failing step is retried on the
+//! next launch without re-running the ones before it.
+
+use crate::store;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const SCHEMA_VERSION_FILE: &str = ".schema-version";
+pub const SNAPSHOT_DIR_NAME: &str = "migration-snapshots";
+const LEGACY_IDENTIFIER: &str = "com.redd.todo";
+const LEGACY_MIGRATION_MARKER: &str = ".migrated-from-com-redd-todo";
+
+/// Directories a migration step may touch. Kept explicit (rather than an
+/// `AppHandle`) so steps can run against fixture directories in tests.
+#[derive(Debug, Clone)]
+pub struct MigrationContext {
+    pub data_dir: PathBuf,
+    pub config_dir: Option<PathBuf>,
+    pub local_data_dir: Option<PathBuf>,
+}
+
+pub struct Migration {
+    pub version: u32,
+    pub name: &'static str,
+    pub run: fn(&MigrationContext) -> Result<(), String>,
+}
+
+/// All migrations, numbered 1, 2, 3, ... in order. Append only.
+pub const MIGRATIONS: &[Migration] = &[Migration {
+    version: 1,
+    name: "move-legacy-identifier-data",
+    run: move_legacy_identifier_data,
+}];
+
+pub fn latest_version() -> u32 {
+    MIGRATIONS.last().map(|m| m.version).unwrap_or(0)
+}
+
+pub fn read_schema_version(data_dir: &Path) -> Result<u32, String> {
+    let path = data_dir.join(SCHEMA_VERSION_FILE);
+    match fs::read_to_string(&path) {
+        Ok(raw) => raw
+            .trim()
+            .parse()
+            .map_err(|e| format!("Invalid schema version in {path:?}: {e}")),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(0),
+        Err(e) => Err(format!("Failed to read {path:?}: {e}")),
+    }
+}
+
+pub fn write_schema_version(data_dir: &Path, version: u32) -> Result<(), String> {
+    let path = data_dir.join(SCHEMA_VERSION_FILE);
+    store::write_atomic(&path, version.to_string().as_bytes())
+        .map_err(|e| format!("Failed to write {path:?}: {e}"))
+}
+
+/// Run every pending migration in `MIGRATIONS`. Returns the resulting version.
+pub fn run_migrations(ctx: &MigrationContext) -> Result<u32, String> {
+    run_steps(ctx, MIGRATIONS)
+}
+
+pub fn run_steps(ctx: &MigrationContext, steps: &[Migration]) -> Result<u32, String> {
+    let mut version = read_schema_version(&ctx.data_dir)?;
+    let pending: Vec<&Migration> = steps.iter().filter(|m| m.version > version).collect();
+    if pending.is_empty() {
+        return Ok(version);
+    }
+
+    if let Some(snapshot) = snapshot_data_dir(&ctx.data_dir, version)? {
+        log::info!("[Migrations] Pre-migration snapshot written to {snapshot:?}");
+    }
+
+    for migration in pending {
+        log::info!(
+            "[Migrations] Applying v{} ({})",
+            migration.version,
+            migration.name
+        );
+        (migration.run)(ctx).map_err(|e| {
+            format!(
+                "Migration v{} ({}) failed: {e}",
+                migration.version, migration.name
+            )
+        })?;
+        version = migration.version;
+        write_schema_version(&ctx.data_dir, version)?;
+    }
+
+    Ok(version)
+}
+
+/// Files in the data dir that the app writes itself. Only these are
+/// snapshotted: the webview's profile (`EBWebView`, WebKit caches), backups
+/// and the encrypted secrets are left out.
+const SNAPSHOT_FILES: &[&str] = &[
+    store::STORE_FILE_NAME,
+    store::PLAN_FILE_NAME,
+    store::EXTERNAL_CHANGES_FILE_NAME,
+    SCHEMA_VERSION_FILE,
+    crate::focus_log::LOG_FILE_NAME,
+    crate::window_geometry::FILE_NAME,
+    crate::focus_pins::SETTINGS_FILE_NAME,
+    crate::shortcuts::SETTINGS_FILE_NAME,
+    crate::pomodoro::SETTINGS_FILE_NAME,
+    crate::idle::SETTINGS_FILE_NAME,
+    crate::rpc::SETTINGS_FILE_NAME,
+];
+
+/// Copy the app's files into `migration-snapshots/pre-v<version>-<unix secs>/`.
+/// Returns `None` when there is nothing to snapshot yet.
+fn snapshot_data_dir(data_dir: &Path, version: u32) -> Result<Option<PathBuf>, String> {
+    let files: Vec<&str> = SNAPSHOT_FILES
+        .iter()
+        .copied()
+        .filter(|name| data_dir.join(name).is_file())
+        .collect();
+    if files.is_empty() {
+        return Ok(None);
+    }
+
+    let stamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let snapshot = data_dir
+        .join(SNAPSHOT_DIR_NAME)
+        .join(format!("pre-v{version}-{stamp}"));
+    fs::create_dir_all(&snapshot).map_err(|e| format!("Failed to create {snapshot:?}: {e}"))?;
+    for name in files {
+        fs::copy(data_dir.join(name), snapshot.join(name))
+            .map_err(|e| format!("Failed to snapshot {name} from {data_dir:?}: {e}"))?;
+    }
+    Ok(Some(snapshot))
+}
+
+/// Copy every file/subdirectory from `src` into `dst` without overwriting any
+/// file that already exists at the destination. Existing directories are
+/// merged into; existing files are left alone.
+fn copy_dir_non_destructive(src: &Path, dst: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(dst)?;
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        let src_path = entry.path();
+        let dst_path = dst.join(entry.file_name());
+        if file_type.is_dir() {
+            copy_dir_non_destructive(&src_path, &dst_path)?;
+        } else if !dst_path.exists() {
+            fs::copy(&src_path, &dst_path)?;
+        }
+    }
+    Ok(())
+}
+
+/// v1. Before v2.4.1 the Tauri app identifier was `com.redd.todo`. We renamed
+/// it to `com.redd.do` in the v2.4.0 rebrand, which moved the per-user data
+/// dir to a new location (e.g. `%APPDATA%\com.redd.do\` on Windows,
+/// `~/Library/Application Support/com.redd.do/` on macOS). Copy the old tree
+/// in so existing users' tasks survive. Directories already handled by the
+/// pre-framework one-off migration carry a marker file and are skipped.
+fn move_legacy_identifier_data(ctx: &MigrationContext) -> Result<(), String> {
+    let targets = [
+        Some(&ctx.data_dir),
+        ctx.config_dir.as_ref(),
+        ctx.local_data_dir.as_ref(),
+    ];
+    for new_dir in targets.into_iter().flatten() {
+        let Some(parent) = new_dir.parent() else {
+            continue;
+        };
+        let old_dir = parent.join(LEGACY_IDENTIFIER);
+        if &old_dir == new_dir || !old_dir.exists() {
+            continue;
+        }
+        if new_dir.join(LEGACY_MIGRATION_MARKER).exists() {
+            continue;
+        }
+        fs::create_dir_all(new_dir).map_err(|e| format!("Failed to create {new_dir:?}: {e}"))?;
+        copy_dir_non_destructive(&old_dir, new_dir)
+            .map_err(|e| format!("Failed to migrate {old_dir:?} -> {new_dir:?}: {e}"))?;
+        log::info!("[Migrations] Migrated user data from {old_dir:?} into {new_dir:?}");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    fn context(root: &Path) -> MigrationContext {
+        MigrationContext {
+            data_dir: root.join("com.redd.do"),
+            config_dir: None,
+            local_data_dir: None,
+        }
+    }
+
+    fn write_store(ctx: &MigrationContext, value: &Value) {
+        fs::create_dir_all(&ctx.data_dir).unwrap();
+        fs::write(store::store_path(&ctx.data_dir), value.to_string()).unwrap();
+    }
+
+    #[test]
+    fn fresh_install_records_latest_version_without_snapshot() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = context(tmp.path());
+        fs::create_dir_all(&ctx.data_dir).unwrap();
+
+        assert_eq!(run_migrations(&ctx).unwrap(), latest_version());
+        assert_eq!(
+            read_schema_version(&ctx.data_dir).unwrap(),
+            latest_version()
+        );
+        assert!(!ctx.data_dir.join(SNAPSHOT_DIR_NAME).exists());
+    }
+
+    #[test]
+    fn legacy_identifier_data_is_copied_without_overwriting() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = context(tmp.path());
+        let old_dir = tmp.path().join(LEGACY_IDENTIFIER);
+        fs::create_dir_all(old_dir.join("EBWebView")).unwrap();
+        fs::write(old_dir.join("EBWebView/Local State"), "old").unwrap();
+        fs::write(old_dir.join("keep.txt"), "old").unwrap();
+        fs::create_dir_all(&ctx.data_dir).unwrap();
+        fs::write(ctx.data_dir.join("keep.txt"), "new").unwrap();
+
+        move_legacy_identifier_data(&ctx).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(ctx.data_dir.join("EBWebView/Local State")).unwrap(),
+            "old"
+        );
+        assert_eq!(
+            fs::read_to_string(ctx.data_dir.join("keep.txt")).unwrap(),
+            "new"
+        );
+    }
+
+    #[test]
+    fn legacy_identifier_marker_skips_copy() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = context(tmp.path());
+        let old_dir = tmp.path().join(LEGACY_IDENTIFIER);
+        fs::create_dir_all(&old_dir).unwrap();
+        fs::write(old_dir.join("stale.txt"), "old").unwrap();
+        fs::create_dir_all(&ctx.data_dir).unwrap();
+        fs::write(ctx.data_dir.join(LEGACY_MIGRATION_MARKER), "").unwrap();
+
+        move_legacy_identifier_data(&ctx).unwrap();
+
+        assert!(!ctx.data_dir.join("stale.txt").exists());
+    }
+
+    #[test]
+    fn pending_steps_snapshot_first_and_stop_on_failure() {
+        fn ok(_: &MigrationContext) -> Result<(), String> {
+            Ok(())
+        }
+        fn fail(_: &MigrationContext) -> Result<(), String> {
+            Err("boom".to_string())
+        }
+        let steps = [
+            Migration {
+                version: 1,
+                name: "ok",
+                run: ok,
+            },
+            Migration {
+                version: 2,
+                name: "fail",
+                run: fail,
+            },
+        ];
+
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = context(tmp.path());
+        write_store(&ctx, &json!({ "tabs": {} }));
+        fs::create_dir_all(ctx.data_dir.join("EBWebView/Default")).unwrap();
+        fs::write(ctx.data_dir.join("EBWebView/Default/Cookies"), "webview").unwrap();
+
+        assert!(run_steps(&ctx, &steps).is_err());
+        assert_eq!(read_schema_version(&ctx.data_dir).unwrap(), 1);
+
+        let snapshots: Vec<_> = fs::read_dir(ctx.data_dir.join(SNAPSHOT_DIR_NAME))
+            .unwrap()
+            .collect();
+        assert_eq!(snapshots.len(), 1);
+        let snapshot = snapshots[0].as_ref().unwrap().path();
+        assert!(snapshot.join(store::STORE_FILE_NAME).exists());
+        assert!(!snapshot.join("EBWebView").exists());
+    }
+
+    #[test]
+    fn versions_count_up_from_one() {
+        for (index, migration) in MIGRATIONS.iter().enumerate() {
+            assert_eq!(migration.version as usize, index + 1, "{}", migration.name);
+        }
+    }
+}
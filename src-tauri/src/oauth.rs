@@ -0,0 +1,206 @@
+//! The `state` parameter of the Basecamp sign-in.
+//!
+//! Each sign-in gets a random nonce that must come back, once, on whichever
+//! path finishes it: the dev callback server, the localhost bridge or the
+//! `oauth-callback` deep link. A callback without it is rejected, so another
+//! site can't connect the app to its own Basecamp account. The nonce expires
+//! with the callback listener.
+
+use ring::rand::{SecureRandom, SystemRandom};
+use std::time::{Duration, Instant};
+
+/// Prefix of a `state` that asks the Netlify function to hand the tokens to
+/// the localhost bridge: `localhost:<port>:<nonce>`.
+pub const LOCAL_CALLBACK_STATE_PREFIX: &str = "localhost:";
+
+/// How long to wait for the browser to come back by default.
+pub const DEFAULT_CALLBACK_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+const MIN_CALLBACK_TIMEOUT: Duration = Duration::from_secs(30);
+const MAX_CALLBACK_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+const NONCE_LEN: usize = 32;
+
+pub const TIMED_OUT: &str = "The Basecamp sign-in timed out; please try again";
+
+/// The callback timeout for a requested number of seconds, within sane bounds.
+pub fn callback_timeout(seconds: Option<u64>) -> Duration {
+    seconds
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_CALLBACK_TIMEOUT)
+        .clamp(MIN_CALLBACK_TIMEOUT, MAX_CALLBACK_TIMEOUT)
+}
+
+/// The `state` to send: the nonce, led by the bridge port if there is one.
+pub fn encode_state(nonce: &str, bridge_port: Option<u16>) -> String {
+    match bridge_port {
+        Some(port) => format!("{LOCAL_CALLBACK_STATE_PREFIX}{port}:{nonce}"),
+        None => nonce.to_string(),
+    }
+}
+
+/// The nonce in a `state` made by [`encode_state`].
+fn state_nonce(state: &str) -> &str {
+    match state.strip_prefix(LOCAL_CALLBACK_STATE_PREFIX) {
+        Some(rest) => rest.split_once(':').map_or("", |(_, nonce)| nonce),
+        None => state,
+    }
+}
+
+/// Whether a request to a callback listener is the callback itself, rather
+/// than something the browser fetched on the side (such as `/favicon.ico`).
+pub fn is_callback_path(url: &str) -> bool {
+    let path = url.split(['?', '#']).next().unwrap_or_default();
+    path == "/callback"
+}
+
+/// Compare without stopping at the first difference.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn new_nonce() -> Result<String, String> {
+    let mut bytes = [0u8; NONCE_LEN];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .map_err(|_| "Failed to generate the sign-in state".to_string())?;
+    Ok(bytes.iter().map(|b| format!("{b:02x}")).collect())
+}
+
+/// Where a sign-in stands, from a callback listener's point of view.
+#[derive(Debug, PartialEq)]
+pub enum Progress {
+    Waiting,
+    /// Finished elsewhere, or replaced by a newer sign-in.
+    Over,
+    /// Ran out of time; reported once, after which the sign-in is over.
+    TimedOut,
+}
+
+#[derive(Debug)]
+struct Pending {
+    nonce: String,
+    expires_at: Instant,
+}
+
+/// The sign-in in progress, if any. Starting another one replaces it.
+#[derive(Debug, Default)]
+pub struct AuthStates {
+    pending: Option<Pending>,
+}
+
+impl AuthStates {
+    /// Start a sign-in and return its nonce.
+    pub fn begin(&mut self, now: Instant, timeout: Duration) -> Result<String, String> {
+        let nonce = new_nonce()?;
+        self.pending = Some(Pending {
+            nonce: nonce.clone(),
+            expires_at: now + timeout,
+        });
+        Ok(nonce)
+    }
+
+    /// Check on the sign-in with `nonce`, ending it if it timed out.
+    pub fn progress(&mut self, nonce: &str, now: Instant) -> Progress {
+        match &self.pending {
+            Some(pending) if pending.nonce == nonce => {
+                if now < pending.expires_at {
+                    Progress::Waiting
+                } else {
+                    self.pending = None;
+                    Progress::TimedOut
+                }
+            }
+            _ => Progress::Over,
+        }
+    }
+
+    /// Check a callback's `state` and use up the nonce. A mismatch leaves the
+    /// sign-in pending, so a forged callback can't cancel the real one; an
+    /// expired one is left for [`AuthStates::progress`] to report.
+    pub fn verify(&mut self, state: Option<&str>, now: Instant) -> Result<(), String> {
+        let Some(pending) = &self.pending else {
+            return Err("No Basecamp sign-in is in progress".to_string());
+        };
+        if now >= pending.expires_at {
+            return Err(TIMED_OUT.to_string());
+        }
+        let nonce = state.map(state_nonce).unwrap_or_default();
+        if !constant_time_eq(nonce.as_bytes(), pending.nonce.as_bytes()) {
+            return Err("The sign-in response doesn't match this sign-in".to_string());
+        }
+        self.pending = None;
+        Ok(())
+    }
+
+    /// Give up on the sign-in with `nonce`. Returns whether it was pending.
+    pub fn cancel(&mut self, nonce: &str) -> bool {
+        let matches = self
+            .pending
+            .as_ref()
+            .is_some_and(|pending| pending.nonce == nonce);
+        if matches {
+            self.pending = None;
+        }
+        matches
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nonces_are_single_use_and_expire() {
+        let now = Instant::now();
+        let timeout = Duration::from_secs(60);
+        let mut states = AuthStates::default();
+        let nonce = states.begin(now, timeout).unwrap();
+        assert_eq!(nonce.len(), NONCE_LEN * 2);
+
+        assert!(states.verify(None, now).is_err());
+        assert!(states.verify(Some("forged"), now).is_err());
+        assert_eq!(states.progress(&nonce, now), Progress::Waiting);
+        let state = encode_state(&nonce, Some(4321));
+        assert_eq!(state, format!("localhost:4321:{nonce}"));
+        assert_eq!(states.verify(Some(&state), now), Ok(()));
+        assert_eq!(states.progress(&nonce, now), Progress::Over);
+        assert!(states.verify(Some(&state), now).is_err());
+
+        let nonce = states.begin(now, timeout).unwrap();
+        let later = now + timeout;
+        assert_eq!(
+            states.verify(Some(&nonce), later),
+            Err(TIMED_OUT.to_string())
+        );
+        assert_eq!(states.progress(&nonce, later), Progress::TimedOut);
+        assert_eq!(states.progress(&nonce, later), Progress::Over);
+        assert!(states.verify(Some(&nonce), now).is_err());
+
+        let first = states.begin(now, timeout).unwrap();
+        let second = states.begin(now, timeout).unwrap();
+        assert_ne!(first, second);
+        assert_eq!(states.progress(&first, now), Progress::Over);
+        assert!(!states.cancel(&first));
+        assert!(states.verify(Some(&first), now).is_err());
+        assert!(states.cancel(&second));
+        assert_eq!(states.progress(&second, now), Progress::Over);
+    }
+
+    #[test]
+    fn parses_states_paths_and_timeouts() {
+        assert_eq!(state_nonce("abc"), "abc");
+        assert_eq!(state_nonce("localhost:80:abc"), "abc");
+        // The port-only state of older versions carries no nonce.
+        assert_eq!(state_nonce("localhost:80"), "");
+
+        assert!(is_callback_path("/callback?code=1&state=x"));
+        assert!(is_callback_path("/callback"));
+        assert!(!is_callback_path("/favicon.ico"));
+        assert!(!is_callback_path("/callbacks"));
+
+        assert_eq!(callback_timeout(None), DEFAULT_CALLBACK_TIMEOUT);
+        assert_eq!(callback_timeout(Some(1)), MIN_CALLBACK_TIMEOUT);
+        assert_eq!(callback_timeout(Some(120)), Duration::from_secs(120));
+        assert_eq!(callback_timeout(Some(u64::MAX)), MAX_CALLBACK_TIMEOUT);
+    }
+}
@@ -0,0 +1,297 @@
+//! Pomodoro cycles for focus mode.
+//!
+//! With Pomodoro enabled a focus session alternates work phases with short
+//! breaks, and every `cycles_before_long_break` work phases with a long
+//! break instead. Work phases are measured in focus-timer time, so pausing
+//! the timer pauses the phase; breaks run on the wall clock.
+
+use crate::store;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+pub const SETTINGS_FILE_NAME: &str = "pomodoro.json";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PomodoroSettings {
+    pub enabled: bool,
+    pub work_minutes: f64,
+    pub short_break_minutes: f64,
+    pub long_break_minutes: f64,
+    pub cycles_before_long_break: u32,
+}
+
+impl Default for PomodoroSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            work_minutes: 25.0,
+            short_break_minutes: 5.0,
+            long_break_minutes: 15.0,
+            cycles_before_long_break: 4,
+        }
+    }
+}
+
+impl PomodoroSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, minutes) in [
+            ("Work", self.work_minutes),
+            ("Short break", self.short_break_minutes),
+            ("Long break", self.long_break_minutes),
+        ] {
+            if !minutes.is_finite() || !(1.0..=24.0 * 60.0).contains(&minutes) {
+                return Err(format!("{name} length must be between 1 and 1440 minutes"));
+            }
+        }
+        if self.cycles_before_long_break == 0 {
+            return Err("Cycles before a long break must be at least 1".to_string());
+        }
+        Ok(())
+    }
+
+    fn length(&self, phase: Phase) -> Duration {
+        let minutes = match phase {
+            Phase::Work => self.work_minutes,
+            Phase::ShortBreak => self.short_break_minutes,
+            Phase::LongBreak => self.long_break_minutes,
+        };
+        Duration::from_secs_f64(minutes.max(0.0) * 60.0)
+    }
+}
+
+pub fn load_settings(data_dir: &Path) -> PomodoroSettings {
+    store::load_json_settings(data_dir, SETTINGS_FILE_NAME)
+}
+
+pub fn save_settings(data_dir: &Path, settings: &PomodoroSettings) -> Result<(), String> {
+    settings.validate()?;
+    store::save_json_settings(data_dir, SETTINGS_FILE_NAME, settings)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Phase {
+    Work,
+    ShortBreak,
+    LongBreak,
+}
+
+impl Phase {
+    pub fn is_break(self) -> bool {
+        self != Phase::Work
+    }
+}
+
+/// Where a phase started: focus-timer time for work, wall clock for breaks.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PhaseStart {
+    Work { focus_elapsed: Duration },
+    Break { at: Instant },
+}
+
+/// Details of a phase, as sent with the break events.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PhaseInfo {
+    pub phase: Phase,
+    pub length_ms: u64,
+    /// Work phases finished so far in this focus session.
+    pub completed_work_phases: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PomodoroCycle {
+    settings: PomodoroSettings,
+    phase: Phase,
+    start: PhaseStart,
+    completed_work_phases: u32,
+}
+
+impl PomodoroCycle {
+    /// Begin with a work phase at the focus timer's current elapsed time.
+    pub fn new(settings: PomodoroSettings, focus_elapsed: Duration) -> Self {
+        Self {
+            settings,
+            phase: Phase::Work,
+            start: PhaseStart::Work { focus_elapsed },
+            completed_work_phases: 0,
+        }
+    }
+
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    pub fn info(&self) -> PhaseInfo {
+        PhaseInfo {
+            phase: self.phase,
+            length_ms: self.settings.length(self.phase).as_millis() as u64,
+            completed_work_phases: self.completed_work_phases,
+        }
+    }
+
+    /// Focus-timer elapsed time at which the current work phase ends.
+    pub fn work_ends_at(&self) -> Option<Duration> {
+        match self.start {
+            PhaseStart::Work { focus_elapsed } => {
+                Some(focus_elapsed + self.settings.length(Phase::Work))
+            }
+            PhaseStart::Break { .. } => None,
+        }
+    }
+
+    /// Move to the next phase if the current one has run its length.
+    /// Returns the new phase's details when it changed.
+    pub fn advance(&mut self, focus_elapsed: Duration, now: Instant) -> Option<PhaseInfo> {
+        let done = match self.start {
+            PhaseStart::Work {
+                focus_elapsed: started,
+            } if focus_elapsed < started => {
+                // The timer was reset below the phase start: restart the phase.
+                self.start = PhaseStart::Work { focus_elapsed };
+                false
+            }
+            PhaseStart::Work { .. } => self
+                .work_ends_at()
+                .is_some_and(|ends_at| focus_elapsed >= ends_at),
+            PhaseStart::Break { at } => {
+                now.saturating_duration_since(at) >= self.settings.length(self.phase)
+            }
+        };
+        done.then(|| self.next(focus_elapsed, now))
+    }
+
+    /// End the current phase early (e.g. skipping a break).
+    pub fn skip(&mut self, focus_elapsed: Duration, now: Instant) -> PhaseInfo {
+        self.next(focus_elapsed, now)
+    }
+
+    fn next(&mut self, focus_elapsed: Duration, now: Instant) -> PhaseInfo {
+        if self.phase == Phase::Work {
+            self.completed_work_phases += 1;
+            let cycles = self.settings.cycles_before_long_break.max(1);
+            self.phase = if self.completed_work_phases % cycles == 0 {
+                Phase::LongBreak
+            } else {
+                Phase::ShortBreak
+            };
+            self.start = PhaseStart::Break { at: now };
+        } else {
+            self.phase = Phase::Work;
+            self.start = PhaseStart::Work { focus_elapsed };
+        }
+        self.info()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINUTE: Duration = Duration::from_secs(60);
+
+    fn settings() -> PomodoroSettings {
+        PomodoroSettings {
+            enabled: true,
+            work_minutes: 25.0,
+            short_break_minutes: 5.0,
+            long_break_minutes: 15.0,
+            cycles_before_long_break: 3,
+        }
+    }
+
+    /// Run the current work phase to its end and return the break it starts.
+    fn finish_work(cycle: &mut PomodoroCycle, now: Instant) -> PhaseInfo {
+        let ends_at = cycle.work_ends_at().expect("in a work phase");
+        assert_eq!(cycle.advance(ends_at - Duration::from_millis(1), now), None);
+        cycle.advance(ends_at, now).expect("work phase ended")
+    }
+
+    #[test]
+    fn takes_a_long_break_every_n_cycles() {
+        let start = Instant::now();
+        let mut cycle = PomodoroCycle::new(settings(), Duration::ZERO);
+        let mut breaks = Vec::new();
+        for round in 0..6u32 {
+            let at = start + round * 40 * MINUTE;
+            let elapsed = round * 25 * MINUTE + 25 * MINUTE;
+            let info = finish_work(&mut cycle, at);
+            breaks.push(info.phase);
+            assert_eq!(info.completed_work_phases, round + 1);
+            let break_ends = at + Duration::from_millis(info.length_ms);
+            let work = cycle.advance(elapsed, break_ends).expect("break ended");
+            assert_eq!(work.phase, Phase::Work);
+        }
+        assert_eq!(
+            breaks,
+            [
+                Phase::ShortBreak,
+                Phase::ShortBreak,
+                Phase::LongBreak,
+                Phase::ShortBreak,
+                Phase::ShortBreak,
+                Phase::LongBreak,
+            ]
+        );
+    }
+
+    #[test]
+    fn work_phases_follow_the_focus_timer() {
+        let now = Instant::now();
+        let mut cycle = PomodoroCycle::new(settings(), 10 * MINUTE);
+        assert_eq!(cycle.work_ends_at(), Some(35 * MINUTE));
+        finish_work(&mut cycle, now);
+        assert_eq!(cycle.work_ends_at(), None);
+
+        // The next work phase starts where the timer stood when the break ended.
+        cycle.skip(35 * MINUTE, now);
+        assert_eq!(cycle.work_ends_at(), Some(60 * MINUTE));
+    }
+
+    #[test]
+    fn ends_a_phase_on_its_exact_boundary() {
+        let now = Instant::now();
+        let mut cycle = PomodoroCycle::new(settings(), Duration::ZERO);
+        assert_eq!(
+            cycle.advance(25 * MINUTE, now).map(|info| info.phase),
+            Some(Phase::ShortBreak)
+        );
+
+        assert_eq!(
+            cycle.advance(25 * MINUTE, now + 5 * MINUTE - Duration::from_millis(1)),
+            None
+        );
+        let work = cycle
+            .advance(25 * MINUTE, now + 5 * MINUTE)
+            .expect("break ended");
+        assert_eq!(work.phase, Phase::Work);
+        assert_eq!(work.length_ms, 25 * 60_000);
+    }
+
+    #[test]
+    fn skipping_a_break_starts_the_next_work_phase() {
+        let now = Instant::now();
+        let mut cycle = PomodoroCycle::new(settings(), Duration::ZERO);
+        finish_work(&mut cycle, now);
+        assert!(cycle.phase().is_break());
+
+        let work = cycle.skip(25 * MINUTE, now + MINUTE);
+        assert_eq!(work.phase, Phase::Work);
+        assert_eq!(work.completed_work_phases, 1);
+        assert_eq!(cycle.work_ends_at(), Some(50 * MINUTE));
+        // A skipped break still counts towards the long break.
+        assert_eq!(finish_work(&mut cycle, now).phase, Phase::ShortBreak);
+        cycle.skip(50 * MINUTE, now);
+        assert_eq!(finish_work(&mut cycle, now).phase, Phase::LongBreak);
+    }
+
+    #[test]
+    fn restarts_the_work_phase_when_the_timer_is_reset() {
+        let now = Instant::now();
+        let mut cycle = PomodoroCycle::new(settings(), 20 * MINUTE);
+        assert_eq!(cycle.advance(5 * MINUTE, now), None);
+        assert_eq!(cycle.work_ends_at(), Some(30 * MINUTE));
+    }
+}
@@ -0,0 +1,468 @@
+//! Time-tracking reports built from the persisted task store.
+//!
+//! A task's tracked time is its `actualDuration` once completed, otherwise
+//! the cumulative focus `timeSpent`. Tasks don't record when that time was
+//! spent, so all of it is credited to the local day the task was completed,
+//! or created if it is still open: a task worked on over a week and finished
+//! on Friday counts entirely towards Friday. Reports cover an inclusive date
+//! range and can be grouped by task, day, tab or group.
+
+use crate::store::{AppData, Task};
+use chrono::{DateTime, Local, NaiveDate};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    #[default]
+    Csv,
+    Json,
+}
+
+/// How CSV rows are grouped. JSON reports always include every grouping.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportGrouping {
+    #[default]
+    Task,
+    Day,
+    Tab,
+    Group,
+}
+
+/// One task's tracked time.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskEntry {
+    pub date: NaiveDate,
+    pub task_id: String,
+    pub task: String,
+    pub completed: bool,
+    pub tab_id: String,
+    pub tab: String,
+    pub group_id: Option<String>,
+    pub group: Option<String>,
+    pub expected_minutes: Option<f64>,
+    pub tracked_minutes: f64,
+}
+
+/// Totals for one day, tab or group.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Bucket {
+    pub key: String,
+    pub label: String,
+    pub tasks: usize,
+    pub tracked_minutes: f64,
+    /// Sum of estimates, for tasks that have one.
+    pub expected_minutes: f64,
+    /// Tracked time of the tasks that have an estimate, so the two compare
+    /// like for like.
+    pub tracked_with_estimate_minutes: f64,
+}
+
+impl Bucket {
+    fn add(&mut self, entry: &TaskEntry) {
+        self.tasks += 1;
+        self.tracked_minutes += entry.tracked_minutes;
+        if let Some(expected) = entry.expected_minutes {
+            self.expected_minutes += expected;
+            self.tracked_with_estimate_minutes += entry.tracked_minutes;
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeReport {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub totals: Bucket,
+    pub by_day: Vec<Bucket>,
+    pub by_tab: Vec<Bucket>,
+    pub by_group: Vec<Bucket>,
+    pub tasks: Vec<TaskEntry>,
+}
+
+const MS_PER_MINUTE: f64 = 60_000.0;
+const UNGROUPED_LABEL: &str = "(no group)";
+
+fn tracked_minutes(task: &Task) -> Option<f64> {
+    task.actual_duration
+        .or(task.time_spent)
+        .filter(|ms| *ms > 0.0)
+        .map(|ms| ms / MS_PER_MINUTE)
+}
+
+fn local_date(iso: Option<&str>) -> Option<NaiveDate> {
+    DateTime::parse_from_rfc3339(iso?)
+        .ok()
+        .map(|time| time.with_timezone(&Local).date_naive())
+}
+
+/// Collect every task with tracked time inside `from..=to`.
+pub fn entries(data: &AppData, from: Option<NaiveDate>, to: Option<NaiveDate>) -> Vec<TaskEntry> {
+    let mut entries = Vec::new();
+    for tab in data.tabs.values() {
+        let group = tab.group_id.as_ref().and_then(|id| data.groups.get(id));
+        for task in &tab.tasks {
+            let Some(tracked) = tracked_minutes(task) else {
+                continue;
+            };
+            let Some(date) = local_date(task.completed_at.as_deref())
+                .filter(|_| task.completed)
+                .or_else(|| local_date(task.created_at.as_deref()))
+            else {
+                continue;
+            };
+            if from.is_some_and(|from| date < from) || to.is_some_and(|to| date > to) {
+                continue;
+            }
+            entries.push(TaskEntry {
+                date,
+                task_id: task.id.clone(),
+                task: task.text.clone(),
+                completed: task.completed,
+                tab_id: tab.id.clone(),
+                tab: tab.name.clone(),
+                group_id: group.map(|g| g.id.clone()),
+                group: group.map(|g| g.name.clone()),
+                expected_minutes: task.expected_duration.filter(|m| *m > 0.0),
+                tracked_minutes: tracked,
+            });
+        }
+    }
+    entries.sort_by_key(|entry| entry.date);
+    entries
+}
+
+fn buckets<'a>(
+    entries: &'a [TaskEntry],
+    key: impl Fn(&'a TaskEntry) -> (String, String),
+) -> Vec<Bucket> {
+    let mut buckets: IndexMap<String, Bucket> = IndexMap::new();
+    for entry in entries {
+        let (key, label) = key(entry);
+        buckets
+            .entry(key.clone())
+            .or_insert_with(|| Bucket {
+                key,
+                label,
+                ..Bucket::default()
+            })
+            .add(entry);
+    }
+    buckets.into_values().collect()
+}
+
+pub fn build(data: &AppData, from: Option<NaiveDate>, to: Option<NaiveDate>) -> TimeReport {
+    let tasks = entries(data, from, to);
+    let mut totals = Bucket {
+        key: "total".to_string(),
+        label: "Total".to_string(),
+        ..Bucket::default()
+    };
+    for entry in &tasks {
+        totals.add(entry);
+    }
+
+    let mut by_day = buckets(&tasks, |e| (e.date.to_string(), e.date.to_string()));
+    by_day.sort_by(|a, b| a.key.cmp(&b.key));
+    TimeReport {
+        from,
+        to,
+        totals,
+        by_day,
+        by_tab: buckets(&tasks, |e| (e.tab_id.clone(), e.tab.clone())),
+        by_group: buckets(&tasks, |e| {
+            (
+                e.group_id.clone().unwrap_or_default(),
+                e.group
+                    .clone()
+                    .unwrap_or_else(|| UNGROUPED_LABEL.to_string()),
+            )
+        }),
+        tasks,
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn minutes(value: f64) -> String {
+    format!("{:.1}", value)
+}
+
+fn csv_row(fields: &[String]) -> String {
+    let mut row = fields
+        .iter()
+        .map(|field| csv_field(field))
+        .collect::<Vec<_>>()
+        .join(",");
+    row.push_str("\r\n");
+    row
+}
+
+pub fn to_csv(report: &TimeReport, grouping: ReportGrouping) -> String {
+    let mut out = String::new();
+    match grouping {
+        ReportGrouping::Task => {
+            out.push_str(&csv_row(
+                &[
+                    "date",
+                    "group",
+                    "tab",
+                    "task",
+                    "completed",
+                    "expected_minutes",
+                    "tracked_minutes",
+                    "variance_minutes",
+                ]
+                .map(String::from),
+            ));
+            for entry in &report.tasks {
+                out.push_str(&csv_row(&[
+                    entry.date.to_string(),
+                    entry.group.clone().unwrap_or_default(),
+                    entry.tab.clone(),
+                    entry.task.clone(),
+                    entry.completed.to_string(),
+                    entry.expected_minutes.map(minutes).unwrap_or_default(),
+                    minutes(entry.tracked_minutes),
+                    entry
+                        .expected_minutes
+                        .map(|expected| minutes(entry.tracked_minutes - expected))
+                        .unwrap_or_default(),
+                ]));
+            }
+        }
+        ReportGrouping::Day | ReportGrouping::Tab | ReportGrouping::Group => {
+            let (heading, buckets) = match grouping {
+                ReportGrouping::Day => ("date", &report.by_day),
+                ReportGrouping::Tab => ("tab", &report.by_tab),
+                _ => ("group", &report.by_group),
+            };
+            out.push_str(&csv_row(
+                &[
+                    heading,
+                    "tasks",
+                    "expected_minutes",
+                    "tracked_minutes",
+                    "tracked_with_estimate_minutes",
+                    "variance_minutes",
+                ]
+                .map(String::from),
+            ));
+            for bucket in buckets.iter().chain(std::iter::once(&report.totals)) {
+                out.push_str(&csv_row(&[
+                    bucket.label.clone(),
+                    bucket.tasks.to_string(),
+                    minutes(bucket.expected_minutes),
+                    minutes(bucket.tracked_minutes),
+                    minutes(bucket.tracked_with_estimate_minutes),
+                    minutes(bucket.tracked_with_estimate_minutes - bucket.expected_minutes),
+                ]));
+            }
+        }
+    }
+    out
+}
+
+pub fn render(
+    report: &TimeReport,
+    format: ReportFormat,
+    grouping: ReportGrouping,
+) -> Result<String, String> {
+    match format {
+        ReportFormat::Csv => Ok(to_csv(report, grouping)),
+        ReportFormat::Json => serde_json::to_string_pretty(report).map_err(|e| e.to_string()),
+    }
+}
+
+/// Parse an optional `YYYY-MM-DD` range bound.
+pub fn parse_date(value: Option<&str>) -> Result<Option<NaiveDate>, String> {
+    value
+        .filter(|v| !v.trim().is_empty())
+        .map(|v| {
+            NaiveDate::parse_from_str(v.trim(), "%Y-%m-%d")
+                .map_err(|e| format!("Invalid date {v:?}: {e}"))
+        })
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use serde_json::json;
+
+    /// An RFC 3339 timestamp at `hour` local time on `date`.
+    fn at(date: &str, hour: u32) -> String {
+        let naive = parse_date(Some(date)).unwrap().unwrap();
+        Local
+            .from_local_datetime(&naive.and_hms_opt(hour, 0, 0).unwrap())
+            .unwrap()
+            .to_rfc3339()
+    }
+
+    fn day(date: &str) -> Option<NaiveDate> {
+        parse_date(Some(date)).unwrap()
+    }
+
+    fn data() -> AppData {
+        serde_json::from_value(json!({
+            "groups": { "g1": { "id": "g1", "name": "Work" } },
+            "tabs": {
+                "t1": {
+                    "id": "t1",
+                    "name": "Client",
+                    "groupId": "g1",
+                    "tasks": [
+                        {
+                            "id": "a",
+                            "text": "Done, worked on all week",
+                            "completed": true,
+                            "createdAt": at("2026-03-02", 9),
+                            "completedAt": at("2026-03-06", 17),
+                            "expectedDuration": 60,
+                            "actualDuration": 90.0 * MS_PER_MINUTE,
+                            "timeSpent": 5.0 * MS_PER_MINUTE
+                        },
+                        {
+                            "id": "b",
+                            "text": "Still open, \"quoted\", with commas",
+                            "createdAt": at("2026-03-03", 10),
+                            "timeSpent": 30.0 * MS_PER_MINUTE
+                        },
+                        { "id": "c", "text": "Never tracked", "createdAt": at("2026-03-03", 10) }
+                    ]
+                },
+                "t2": {
+                    "id": "t2",
+                    "name": "Home",
+                    "tasks": [
+                        {
+                            "id": "d",
+                            "text": "Completed without a timestamp",
+                            "completed": true,
+                            "createdAt": at("2026-03-04", 8),
+                            "actualDuration": 15.0 * MS_PER_MINUTE
+                        }
+                    ]
+                }
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn credits_time_to_completion_or_creation_day() {
+        let entries = entries(&data(), None, None);
+        let dates: Vec<_> = entries
+            .iter()
+            .map(|e| (e.task_id.as_str(), e.date.to_string()))
+            .collect();
+        assert_eq!(
+            dates,
+            [
+                ("b", "2026-03-03".to_string()),
+                ("d", "2026-03-04".to_string()),
+                ("a", "2026-03-06".to_string()),
+            ]
+        );
+        // Actual duration wins over focus time once a task is completed.
+        assert_eq!(entries[2].tracked_minutes, 90.0);
+        assert_eq!(entries[2].group.as_deref(), Some("Work"));
+        assert_eq!(entries[1].group, None);
+
+        // Work done on the 2nd..5th only shows up on the completion day.
+        let report = build(&data(), day("2026-03-02"), day("2026-03-05"));
+        assert!(report.tasks.iter().all(|e| e.task_id != "a"));
+        assert_eq!(report.totals.tracked_minutes, 45.0);
+    }
+
+    #[test]
+    fn totals_buckets_by_day_tab_and_group() {
+        let report = build(&data(), None, None);
+        assert_eq!(report.totals.tasks, 3);
+        assert_eq!(report.totals.tracked_minutes, 135.0);
+        assert_eq!(report.totals.expected_minutes, 60.0);
+        assert_eq!(report.totals.tracked_with_estimate_minutes, 90.0);
+
+        let keys = |buckets: &[Bucket]| {
+            buckets
+                .iter()
+                .map(|b| (b.label.clone(), b.tracked_minutes))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            keys(&report.by_day),
+            [
+                ("2026-03-03".to_string(), 30.0),
+                ("2026-03-04".to_string(), 15.0),
+                ("2026-03-06".to_string(), 90.0),
+            ]
+        );
+        assert_eq!(
+            keys(&report.by_tab),
+            [("Client".to_string(), 120.0), ("Home".to_string(), 15.0)]
+        );
+        assert_eq!(
+            keys(&report.by_group),
+            [
+                ("Work".to_string(), 120.0),
+                (UNGROUPED_LABEL.to_string(), 15.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn renders_csv_and_json() {
+        let report = build(&data(), day("2026-03-03"), day("2026-03-06"));
+
+        let csv = to_csv(&report, ReportGrouping::Task);
+        let lines: Vec<_> = csv.split("\r\n").collect();
+        assert_eq!(
+            lines[0],
+            "date,group,tab,task,completed,expected_minutes,tracked_minutes,variance_minutes"
+        );
+        assert_eq!(
+            lines[1],
+            "2026-03-03,Work,Client,\"Still open, \"\"quoted\"\", with commas\",false,,30.0,"
+        );
+        assert_eq!(
+            lines[3],
+            "2026-03-06,Work,Client,\"Done, worked on all week\",true,60.0,90.0,30.0"
+        );
+
+        let by_tab = render(&report, ReportFormat::Csv, ReportGrouping::Tab).unwrap();
+        assert!(by_tab.ends_with("Total,3,60.0,135.0,90.0,30.0\r\n"));
+
+        let json: serde_json::Value = serde_json::from_str(
+            &render(&report, ReportFormat::Json, ReportGrouping::Task).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(json["from"], "2026-03-03");
+        assert_eq!(json["to"], "2026-03-06");
+        assert_eq!(json["totals"]["trackedMinutes"], 135.0);
+        assert_eq!(json["tasks"].as_array().unwrap().len(), 3);
+        assert_eq!(json["byGroup"][1]["label"], UNGROUPED_LABEL);
+    }
+
+    #[test]
+    fn parses_range_bounds() {
+        assert_eq!(parse_date(None), Ok(None));
+        assert_eq!(parse_date(Some("  ")), Ok(None));
+        assert_eq!(
+            parse_date(Some(" 2026-03-01 ")).unwrap(),
+            NaiveDate::from_ymd_opt(2026, 3, 1)
+        );
+        assert!(parse_date(Some("03/01/2026")).is_err());
+    }
+}
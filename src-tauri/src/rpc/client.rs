@@ -0,0 +1,45 @@
+//! Calls into the running app's server, for commands run outside the app.
+
+use super::{SOCKET_FILE_NAME, TOKEN_FILE_NAME};
+use serde_json::{json, Value};
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+use std::time::Duration;
+
+/// How long the app gets to carry out a call.
+const REPLY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Authenticate with the token in `dir` and call `method`. `Ok(None)` means
+/// no server is listening there: the app is closed or has the API turned off.
+pub fn call(dir: &Path, method: &str, params: Value) -> Result<Option<Value>, String> {
+    let Ok(stream) = UnixStream::connect(dir.join(SOCKET_FILE_NAME)) else {
+        return Ok(None);
+    };
+    let token_path = dir.join(TOKEN_FILE_NAME);
+    let token = fs::read_to_string(&token_path)
+        .map_err(|e| format!("Failed to read {token_path:?}: {e}"))?;
+    stream
+        .set_read_timeout(Some(REPLY_TIMEOUT))
+        .map_err(|e| e.to_string())?;
+    let mut writer = stream.try_clone().map_err(|e| e.to_string())?;
+    let mut reader = BufReader::new(stream);
+
+    let mut request = |id: u64, method: &str, params: Value| -> Result<Value, String> {
+        let request = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+        writeln!(writer, "{request}").map_err(|e| format!("Failed to reach ReDD To-Do: {e}"))?;
+        let mut line = String::new();
+        reader
+            .read_line(&mut line)
+            .map_err(|e| format!("No reply from ReDD To-Do: {e}"))?;
+        let mut reply: Value = serde_json::from_str(&line)
+            .map_err(|e| format!("Unreadable reply from ReDD To-Do: {e}"))?;
+        match reply["error"]["message"].as_str() {
+            Some(message) => Err(message.to_string()),
+            None => Ok(reply["result"].take()),
+        }
+    };
+    request(1, "authenticate", json!({ "token": token.trim() }))?;
+    request(2, method, params).map(Some)
+}
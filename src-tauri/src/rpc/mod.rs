@@ -0,0 +1,254 @@
+//! Local automation API.
+//!
+//! An opt-in JSON-RPC 2.0 server for editors, shell prompts and status bars.
+//! It listens on a Unix domain socket in the user's runtime directory, so it
+//! never touches the network. Messages are one JSON object per line.
+//!
+//! A client's first call must be `authenticate` with the token from the
+//! token file next to the socket. The file is rewritten, readable only by
+//! the user, each time the server starts. After that, the server handles
+//! `subscribe` and `unsubscribe` for the [`EVENTS`] it pushes as
+//! notifications. Every other method goes to the app's [`Handler`].
+//! [`client`] makes such calls from outside the app, e.g. for `redd-todo mcp`.
+
+#[cfg(unix)]
+pub mod client;
+#[cfg(unix)]
+pub mod server;
+
+/// Without Unix domain sockets there is no transport, so the server never
+/// starts.
+#[cfg(not(unix))]
+pub mod server {
+    use super::Handler;
+    use serde_json::Value;
+    use std::path::Path;
+
+    pub struct RpcServer;
+
+    impl RpcServer {
+        pub fn start(_dir: &Path, _handler: impl Handler) -> Result<Self, String> {
+            Err("The automation API needs Unix domain sockets".to_string())
+        }
+
+        pub fn notify(&self, _event: &str, _params: &Value) {}
+    }
+}
+
+/// Nor is there ever a server to call.
+#[cfg(not(unix))]
+pub mod client {
+    use serde_json::Value;
+    use std::path::Path;
+
+    pub fn call(_dir: &Path, _method: &str, _params: Value) -> Result<Option<Value>, String> {
+        Ok(None)
+    }
+}
+
+use crate::store;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::Path;
+
+pub const SETTINGS_FILE_NAME: &str = "rpc.json";
+pub const SOCKET_FILE_NAME: &str = "rpc.sock";
+pub const TOKEN_FILE_NAME: &str = "rpc.token";
+
+/// App events clients can subscribe to; notifications use the event name
+/// as the method and its payload as the params.
+pub const EVENTS: [&str; 2] = ["task-updated", "focus-status-changed"];
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RpcSettings {
+    pub enabled: bool,
+}
+
+pub fn load_settings(data_dir: &Path) -> RpcSettings {
+    store::load_json_settings(data_dir, SETTINGS_FILE_NAME)
+}
+
+pub fn save_settings(data_dir: &Path, settings: &RpcSettings) -> Result<(), String> {
+    store::save_json_settings(data_dir, SETTINGS_FILE_NAME, settings)
+}
+
+/// A JSON-RPC error object.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl RpcError {
+    pub fn parse_error(message: impl Into<String>) -> Self {
+        Self::new(-32700, message)
+    }
+
+    pub fn invalid_request(message: impl Into<String>) -> Self {
+        Self::new(-32600, message)
+    }
+
+    pub fn method_not_found(method: &str) -> Self {
+        Self::new(-32601, format!("Unknown method: {method}"))
+    }
+
+    pub fn invalid_params(message: impl Into<String>) -> Self {
+        Self::new(-32602, message)
+    }
+
+    /// The app couldn't carry out a valid call, e.g. the task doesn't exist.
+    pub fn failed(message: impl Into<String>) -> Self {
+        Self::new(-32000, message)
+    }
+
+    pub fn unauthorized() -> Self {
+        Self::new(-32001, "Call authenticate with the token first")
+    }
+
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<String> for RpcError {
+    fn from(message: String) -> Self {
+        Self::failed(message)
+    }
+}
+
+/// Carries out the app's own methods.
+pub trait Handler: Send + Sync + 'static {
+    fn call(&self, method: &str, params: Value) -> Result<Value, RpcError>;
+}
+
+/// Deserialize a call's params, treating missing params as `{}`.
+pub fn params<T: serde::de::DeserializeOwned>(params: Value) -> Result<T, RpcError> {
+    let params = if params.is_null() { json!({}) } else { params };
+    serde_json::from_value(params).map_err(|e| RpcError::invalid_params(e.to_string()))
+}
+
+pub fn response(id: Value, result: Result<Value, RpcError>) -> Value {
+    match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(error) => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": error.code, "message": error.message },
+        }),
+    }
+}
+
+pub fn notification(method: &str, params: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "method": method, "params": params })
+}
+
+/// A parsed request. `id` is `None` for notifications, which get no reply.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Request {
+    pub id: Option<Value>,
+    pub method: String,
+    pub params: Value,
+}
+
+/// Parse one line from a client. The error carries the id to reply with,
+/// `null` when the line couldn't be read as a request at all.
+pub fn parse_request(line: &str) -> Result<Request, (Value, RpcError)> {
+    let value: Value = serde_json::from_str(line)
+        .map_err(|e| (Value::Null, RpcError::parse_error(e.to_string())))?;
+    let Value::Object(mut object) = value else {
+        return Err((
+            Value::Null,
+            RpcError::invalid_request("Expected a request object"),
+        ));
+    };
+    let id = object.remove("id");
+    let fail = |message: &str| {
+        (
+            id.clone().unwrap_or(Value::Null),
+            RpcError::invalid_request(message),
+        )
+    };
+    if object.get("jsonrpc").and_then(Value::as_str) != Some("2.0") {
+        return Err(fail("jsonrpc must be \"2.0\""));
+    }
+    let Some(Value::String(method)) = object.remove("method") else {
+        return Err(fail("method must be a string"));
+    };
+    Ok(Request {
+        id,
+        method,
+        params: object.remove("params").unwrap_or(Value::Null),
+    })
+}
+
+/// Compare tokens without stopping at the first difference.
+pub fn token_matches(expected: &str, given: &str) -> bool {
+    expected.len() == given.len()
+        && expected
+            .bytes()
+            .zip(given.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// A fresh random token, as hex.
+#[cfg(unix)]
+pub fn generate_token() -> Result<String, String> {
+    use std::io::Read;
+    let mut bytes = [0u8; 32];
+    fs::File::open("/dev/urandom")
+        .and_then(|mut random| random.read_exact(&mut bytes))
+        .map_err(|e| format!("Failed to generate a token: {e}"))?;
+    Ok(bytes.iter().map(|b| format!("{b:02x}")).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_requests() {
+        assert_eq!(
+            parse_request(
+                r#"{"jsonrpc":"2.0","id":7,"method":"list","params":{"includeCompleted":true}}"#
+            ),
+            Ok(Request {
+                id: Some(json!(7)),
+                method: "list".to_string(),
+                params: json!({ "includeCompleted": true }),
+            })
+        );
+        assert_eq!(
+            parse_request(r#"{"jsonrpc":"2.0","method":"current-timer"}"#),
+            Ok(Request {
+                id: None,
+                method: "current-timer".to_string(),
+                params: Value::Null,
+            })
+        );
+        let code = |line: &str| parse_request(line).map_err(|(id, e)| (id, e.code));
+        assert_eq!(code("{"), Err((Value::Null, -32700)));
+        assert_eq!(code("[1]"), Err((Value::Null, -32600)));
+        assert_eq!(
+            code(r#"{"id":"a","method":"list"}"#),
+            Err((json!("a"), -32600))
+        );
+        assert_eq!(
+            code(r#"{"jsonrpc":"2.0","id":1,"method":3}"#),
+            Err((json!(1), -32600))
+        );
+    }
+
+    #[test]
+    fn compares_tokens() {
+        assert!(token_matches("abc123", "abc123"));
+        assert!(!token_matches("abc123", "abc124"));
+        assert!(!token_matches("abc123", "abc12"));
+        assert!(!token_matches("abc123", ""));
+    }
+}
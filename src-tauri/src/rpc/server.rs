@@ -0,0 +1,403 @@
+//! The Unix domain socket transport, one thread per client.
+
+use super::{
+    generate_token, notification, params, parse_request, response, token_matches, Handler, Request,
+    RpcError, EVENTS, SOCKET_FILE_NAME, TOKEN_FILE_NAME,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::net::Shutdown;
+use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt, PermissionsExt};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// A client that stops reading is dropped rather than stalling the others.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Write half of a client's socket; the client's thread reads from its own
+/// clone. Its lock keeps replies and notifications from interleaving.
+type Writer = Arc<Mutex<UnixStream>>;
+
+struct Client {
+    id: u64,
+    writer: Writer,
+    authenticated: bool,
+    events: Vec<String>,
+}
+
+struct Shared {
+    token: String,
+    handler: Box<dyn Handler>,
+    clients: Mutex<Vec<Client>>,
+    next_client: AtomicU64,
+    stopped: AtomicBool,
+}
+
+impl Shared {
+    fn with_client<T>(&self, id: u64, f: impl FnOnce(&mut Client) -> T) -> Option<T> {
+        let mut clients = self.clients.lock().ok()?;
+        clients.iter_mut().find(|client| client.id == id).map(f)
+    }
+
+    /// Write `message` to a client, dropping the client if that fails.
+    fn send(&self, id: u64, message: &Value) -> bool {
+        let Some(writer) = self.with_client(id, |client| client.writer.clone()) else {
+            return false;
+        };
+        let delivered = write_line(&writer, message).is_ok();
+        if !delivered {
+            self.disconnect(id);
+        }
+        delivered
+    }
+
+    fn disconnect(&self, id: u64) {
+        let removed = self.clients.lock().ok().and_then(|mut clients| {
+            let index = clients.iter().position(|client| client.id == id)?;
+            Some(clients.remove(index))
+        });
+        if let Some(client) = removed {
+            shutdown(&client.writer);
+        }
+    }
+}
+
+/// Write one line; the clients list must not be locked meanwhile, so a slow
+/// client only holds up writes to itself.
+fn write_line(writer: &Mutex<UnixStream>, message: &Value) -> std::io::Result<()> {
+    let mut line = message.to_string();
+    line.push('\n');
+    let mut stream = writer
+        .lock()
+        .map_err(|e| std::io::Error::other(e.to_string()))?;
+    stream.write_all(line.as_bytes())
+}
+
+fn shutdown(writer: &Mutex<UnixStream>) {
+    if let Ok(stream) = writer.lock() {
+        let _ = stream.shutdown(Shutdown::Both);
+    }
+}
+
+pub struct RpcServer {
+    shared: Arc<Shared>,
+    dir: PathBuf,
+}
+
+impl RpcServer {
+    /// Listen on `rpc.sock` in `dir`, writing a new token to `rpc.token`.
+    /// Both, and `dir` itself, are accessible only to the user.
+    pub fn start(dir: &Path, handler: impl Handler) -> Result<Self, String> {
+        fs::DirBuilder::new()
+            .recursive(true)
+            .mode(0o700)
+            .create(dir)
+            .map_err(|e| format!("Failed to create {dir:?}: {e}"))?;
+
+        let token = generate_token()?;
+        let token_path = dir.join(TOKEN_FILE_NAME);
+        fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(&token_path)
+            .and_then(|mut file| {
+                file.set_permissions(fs::Permissions::from_mode(0o600))?;
+                file.write_all(token.as_bytes())
+            })
+            .map_err(|e| format!("Failed to write {token_path:?}: {e}"))?;
+
+        let socket = dir.join(SOCKET_FILE_NAME);
+        // Left over from a crash; only one instance of the app runs.
+        let _ = fs::remove_file(&socket);
+        let listener =
+            UnixListener::bind(&socket).map_err(|e| format!("Failed to bind {socket:?}: {e}"))?;
+        fs::set_permissions(&socket, fs::Permissions::from_mode(0o600))
+            .map_err(|e| format!("Failed to restrict {socket:?}: {e}"))?;
+
+        let shared = Arc::new(Shared {
+            token,
+            handler: Box::new(handler),
+            clients: Mutex::new(Vec::new()),
+            next_client: AtomicU64::new(1),
+            stopped: AtomicBool::new(false),
+        });
+        let accepting = shared.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                if accepting.stopped.load(Ordering::SeqCst) {
+                    break;
+                }
+                match stream {
+                    Ok(stream) => {
+                        let shared = accepting.clone();
+                        std::thread::spawn(move || serve(&shared, stream));
+                    }
+                    Err(e) => log::warn!("[RPC] Failed to accept a client: {e}"),
+                }
+            }
+        });
+        Ok(Self {
+            shared,
+            dir: dir.to_path_buf(),
+        })
+    }
+
+    /// Push `event` to the clients subscribed to it.
+    pub fn notify(&self, event: &str, params: &Value) {
+        let message = notification(event, params.clone());
+        let subscribers: Vec<(u64, Writer)> = match self.shared.clients.lock() {
+            Ok(clients) => clients
+                .iter()
+                .filter(|client| client.authenticated && client.events.iter().any(|e| e == event))
+                .map(|client| (client.id, client.writer.clone()))
+                .collect(),
+            Err(_) => return,
+        };
+        for (id, writer) in subscribers {
+            if write_line(&writer, &message).is_err() {
+                self.shared.disconnect(id);
+            }
+        }
+    }
+
+    /// Disconnect every client and remove the socket and token files.
+    pub fn stop(&self) {
+        if self.shared.stopped.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let clients = self
+            .shared
+            .clients
+            .lock()
+            .map(|mut clients| std::mem::take(&mut *clients))
+            .unwrap_or_default();
+        for client in clients {
+            shutdown(&client.writer);
+        }
+        let socket = self.dir.join(SOCKET_FILE_NAME);
+        // Wake the accept loop so it sees `stopped`.
+        let _ = UnixStream::connect(&socket);
+        let _ = fs::remove_file(socket);
+        let _ = fs::remove_file(self.dir.join(TOKEN_FILE_NAME));
+    }
+}
+
+impl Drop for RpcServer {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[derive(Deserialize)]
+struct AuthenticateParams {
+    token: String,
+}
+
+/// `subscribe`/`unsubscribe` params; no list means every event.
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct EventParams {
+    events: Option<Vec<String>>,
+}
+
+fn serve(shared: &Shared, stream: UnixStream) {
+    let Ok(writer) = stream.try_clone() else {
+        return;
+    };
+    let _ = writer.set_write_timeout(Some(WRITE_TIMEOUT));
+    let id = shared.next_client.fetch_add(1, Ordering::SeqCst);
+    if let Ok(mut clients) = shared.clients.lock() {
+        clients.push(Client {
+            id,
+            writer: Arc::new(Mutex::new(writer)),
+            authenticated: false,
+            events: Vec::new(),
+        });
+    }
+
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let reply = match parse_request(&line) {
+            Ok(request) => {
+                let request_id = request.id.clone();
+                let result = handle(shared, id, request);
+                request_id.map(|request_id| response(request_id, result))
+            }
+            Err((request_id, error)) => Some(response(request_id, Err(error))),
+        };
+        if reply.is_some_and(|reply| !shared.send(id, &reply)) {
+            break;
+        }
+    }
+    shared.disconnect(id);
+}
+
+fn handle(shared: &Shared, id: u64, request: Request) -> Result<Value, RpcError> {
+    match request.method.as_str() {
+        "authenticate" => {
+            let AuthenticateParams { token } = params(request.params)?;
+            if !token_matches(&shared.token, &token) {
+                return Err(RpcError::unauthorized());
+            }
+            shared.with_client(id, |client| client.authenticated = true);
+            Ok(json!(true))
+        }
+        _ if shared.with_client(id, |client| client.authenticated) != Some(true) => {
+            Err(RpcError::unauthorized())
+        }
+        "subscribe" | "unsubscribe" => {
+            let EventParams { events } = params(request.params)?;
+            let events = events.unwrap_or_else(|| EVENTS.map(str::to_string).to_vec());
+            if let Some(unknown) = events.iter().find(|e| !EVENTS.contains(&e.as_str())) {
+                return Err(RpcError::invalid_params(format!(
+                    "Unknown event: {unknown}"
+                )));
+            }
+            let subscribe = request.method == "subscribe";
+            shared
+                .with_client(id, |client| {
+                    client.events.retain(|e| subscribe || !events.contains(e));
+                    for event in events.iter().filter(|_| subscribe) {
+                        if !client.events.contains(event) {
+                            client.events.push(event.clone());
+                        }
+                    }
+                    json!({ "events": client.events })
+                })
+                .ok_or_else(|| RpcError::failed("Client disconnected"))
+        }
+        method => shared.handler.call(method, request.params),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Echo;
+
+    impl Handler for Echo {
+        fn call(&self, method: &str, params: Value) -> Result<Value, RpcError> {
+            match method {
+                "echo" => Ok(params),
+                _ => Err(RpcError::method_not_found(method)),
+            }
+        }
+    }
+
+    struct TestClient {
+        writer: UnixStream,
+        reader: BufReader<UnixStream>,
+    }
+
+    impl TestClient {
+        fn connect(dir: &Path) -> Self {
+            let writer = UnixStream::connect(dir.join(SOCKET_FILE_NAME)).unwrap();
+            writer
+                .set_read_timeout(Some(Duration::from_secs(5)))
+                .unwrap();
+            let reader = BufReader::new(writer.try_clone().unwrap());
+            Self { writer, reader }
+        }
+
+        fn send(&mut self, line: &str) {
+            self.writer
+                .write_all(format!("{line}\n").as_bytes())
+                .unwrap();
+        }
+
+        fn receive(&mut self) -> Value {
+            let mut line = String::new();
+            self.reader.read_line(&mut line).unwrap();
+            serde_json::from_str(&line).unwrap()
+        }
+
+        fn call(&mut self, id: u64, method: &str, params: Value) -> Value {
+            self.send(
+                &json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params })
+                    .to_string(),
+            );
+            self.receive()
+        }
+    }
+
+    fn token(dir: &Path) -> String {
+        fs::read_to_string(dir.join(TOKEN_FILE_NAME)).unwrap()
+    }
+
+    #[test]
+    fn requires_the_token() {
+        let dir = tempfile::tempdir().unwrap();
+        let server = RpcServer::start(dir.path(), Echo).unwrap();
+        let mode = |name| {
+            fs::metadata(dir.path().join(name))
+                .unwrap()
+                .permissions()
+                .mode()
+                & 0o777
+        };
+        assert_eq!(mode(TOKEN_FILE_NAME), 0o600);
+        assert_eq!(mode(SOCKET_FILE_NAME), 0o600);
+
+        let mut client = TestClient::connect(dir.path());
+        let reply = client.call(1, "echo", json!({ "a": 1 }));
+        assert_eq!(reply["error"]["code"], -32001);
+        let reply = client.call(2, "authenticate", json!({ "token": "wrong" }));
+        assert_eq!(reply["error"]["code"], -32001);
+        let reply = client.call(3, "authenticate", json!({ "token": token(dir.path()) }));
+        assert_eq!(reply, json!({ "jsonrpc": "2.0", "id": 3, "result": true }));
+        let reply = client.call(4, "echo", json!({ "a": 1 }));
+        assert_eq!(reply["result"], json!({ "a": 1 }));
+        assert_eq!(client.call(5, "nope", Value::Null)["error"]["code"], -32601);
+        client.send("not json");
+        assert_eq!(client.receive()["error"]["code"], -32700);
+
+        server.stop();
+        assert!(!dir.path().join(SOCKET_FILE_NAME).exists());
+        assert!(!dir.path().join(TOKEN_FILE_NAME).exists());
+    }
+
+    #[test]
+    fn pushes_subscribed_events() {
+        let dir = tempfile::tempdir().unwrap();
+        let server = RpcServer::start(dir.path(), Echo).unwrap();
+        let token = json!({ "token": token(dir.path()) });
+        let mut subscriber = TestClient::connect(dir.path());
+        let mut other = TestClient::connect(dir.path());
+        subscriber.call(1, "authenticate", token.clone());
+        other.call(1, "authenticate", token);
+
+        let reply = subscriber.call(2, "subscribe", json!({ "events": ["task-updated"] }));
+        assert_eq!(reply["result"], json!({ "events": ["task-updated"] }));
+        let reply = other.call(2, "subscribe", json!({ "events": ["timer"] }));
+        assert_eq!(reply["error"]["code"], -32602);
+
+        server.notify("focus-status-changed", &json!({ "activeTaskId": null }));
+        server.notify("task-updated", &json!({ "taskId": "task_1" }));
+        assert_eq!(
+            subscriber.receive(),
+            json!({
+                "jsonrpc": "2.0",
+                "method": "task-updated",
+                "params": { "taskId": "task_1" },
+            })
+        );
+        // Nothing was queued for the other client before its reply.
+        assert_eq!(other.call(3, "echo", json!(1))["result"], 1);
+
+        let reply = subscriber.call(3, "unsubscribe", Value::Null);
+        assert_eq!(reply["result"], json!({ "events": [] }));
+    }
+}
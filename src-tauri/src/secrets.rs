@@ -0,0 +1,287 @@
+//! Encrypted storage for credentials.
+//!
+//! Each secret is a JSON value sealed with ChaCha20-Poly1305 into
+//! `<name>.secret` in the app data directory. The key lives in the OS
+//! keychain on macOS and Windows, and in the Secret Service (GNOME Keyring,
+//! KWallet) on Linux. Where no Secret Service is running, or an older build
+//! already wrote one, it lives in `secrets.key` next to the secrets,
+//! readable only by the user.
+
+use crate::store;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, CHACHA20_POLY1305, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+pub const KEY_FILE_NAME: &str = "secrets.key";
+const SECRET_EXTENSION: &str = "secret";
+const KEY_LEN: usize = 32;
+#[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+const KEYRING_SERVICE: &str = "com.redd.do";
+#[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+const KEYRING_USER: &str = "secrets-key";
+
+/// Where the encryption key is kept.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum KeyStore {
+    /// The OS keychain, or the Secret Service on Linux.
+    Keychain,
+    File,
+}
+
+impl KeyStore {
+    /// Where the key for the secrets in `dir` is kept. On Linux that is the
+    /// Secret Service when one answers, unless a key file is already there.
+    pub fn detect(dir: &Path) -> Self {
+        if cfg!(any(target_os = "macos", target_os = "windows")) {
+            return KeyStore::Keychain;
+        }
+        if dir.join(KEY_FILE_NAME).exists() {
+            return KeyStore::File;
+        }
+        match keychain_key(false) {
+            Ok(_) => KeyStore::Keychain,
+            Err(e) => {
+                log::info!("[Secrets] {e}; keeping the key in {KEY_FILE_NAME}");
+                KeyStore::File
+            }
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            KeyStore::Keychain if cfg!(target_os = "linux") => "secret-service",
+            KeyStore::Keychain => "keychain",
+            KeyStore::File => "file",
+        }
+    }
+}
+
+fn random_bytes<const N: usize>() -> Result<[u8; N], String> {
+    let mut bytes = [0u8; N];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .map_err(|_| "Failed to generate random bytes".to_string())?;
+    Ok(bytes)
+}
+
+fn to_key(bytes: &[u8]) -> Result<[u8; KEY_LEN], String> {
+    bytes
+        .try_into()
+        .map_err(|_| "The credentials key is corrupt".to_string())
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+fn keychain_key(create: bool) -> Result<Option<[u8; KEY_LEN]>, String> {
+    let entry = keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER)
+        .map_err(|e| format!("Keychain unavailable: {e}"))?;
+    match entry.get_secret() {
+        Ok(secret) => to_key(&secret).map(Some),
+        Err(keyring::Error::NoEntry) if create => {
+            let key = random_bytes::<KEY_LEN>()?;
+            entry
+                .set_secret(&key)
+                .map_err(|e| format!("Failed to store the credentials key: {e}"))?;
+            Ok(Some(key))
+        }
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(format!("Failed to read the credentials key: {e}")),
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+fn keychain_key(_create: bool) -> Result<Option<[u8; KEY_LEN]>, String> {
+    Err("No keychain on this platform".to_string())
+}
+
+/// Create `path` for the user only; fails if it already exists.
+fn create_private(path: &Path) -> std::io::Result<fs::File> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)
+}
+
+fn file_key(dir: &Path, create: bool) -> Result<Option<[u8; KEY_LEN]>, String> {
+    let path = dir.join(KEY_FILE_NAME);
+    match fs::read(&path) {
+        Ok(bytes) => return to_key(&bytes).map(Some),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(format!("Failed to read {path:?}: {e}")),
+    }
+    if !create {
+        return Ok(None);
+    }
+    let key = random_bytes::<KEY_LEN>()?;
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create {dir:?}: {e}"))?;
+    create_private(&path)
+        .and_then(|mut file| file.write_all(&key).and_then(|_| file.sync_all()))
+        .map_err(|e| format!("Failed to write {path:?}: {e}"))?;
+    Ok(Some(key))
+}
+
+/// Encrypted secrets in one directory.
+pub struct Secrets {
+    dir: PathBuf,
+    key_store: KeyStore,
+}
+
+impl Secrets {
+    pub fn new(dir: &Path, key_store: KeyStore) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+            key_store,
+        }
+    }
+
+    pub fn key_store(&self) -> KeyStore {
+        self.key_store
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{name}.{SECRET_EXTENSION}"))
+    }
+
+    fn key(&self, create: bool) -> Result<Option<LessSafeKey>, String> {
+        let key = match self.key_store {
+            KeyStore::Keychain => keychain_key(create)?,
+            KeyStore::File => file_key(&self.dir, create)?,
+        };
+        key.map(|key| {
+            UnboundKey::new(&CHACHA20_POLY1305, &key)
+                .map(LessSafeKey::new)
+                .map_err(|_| "Invalid credentials key".to_string())
+        })
+        .transpose()
+    }
+
+    /// The secret saved as `name`, or `None` if there is none.
+    pub fn load<T: DeserializeOwned>(&self, name: &str) -> Result<Option<T>, String> {
+        let path = self.path(name);
+        let sealed = match fs::read(&path) {
+            Ok(sealed) => sealed,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(format!("Failed to read {path:?}: {e}")),
+        };
+        let key = self
+            .key(false)?
+            .ok_or_else(|| format!("The key for {path:?} is missing"))?;
+        if sealed.len() < NONCE_LEN {
+            return Err(format!("{path:?} is corrupt"));
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        let nonce =
+            Nonce::try_assume_unique_for_key(nonce).map_err(|_| format!("{path:?} is corrupt"))?;
+        let mut buffer = ciphertext.to_vec();
+        // The name is authenticated too, so a secret can't be swapped for another.
+        let plaintext = key
+            .open_in_place(nonce, Aad::from(name.as_bytes()), &mut buffer)
+            .map_err(|_| format!("Could not decrypt {path:?}"))?;
+        serde_json::from_slice(plaintext)
+            .map(Some)
+            .map_err(|e| format!("Failed to parse {path:?}: {e}"))
+    }
+
+    pub fn save<T: Serialize>(&self, name: &str, value: &T) -> Result<(), String> {
+        let key = self
+            .key(true)?
+            .ok_or_else(|| "No credentials key".to_string())?;
+        let nonce = random_bytes::<NONCE_LEN>()?;
+        let mut buffer = serde_json::to_vec(value).map_err(|e| e.to_string())?;
+        key.seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::from(name.as_bytes()),
+            &mut buffer,
+        )
+        .map_err(|_| "Failed to encrypt credentials".to_string())?;
+        let mut sealed = nonce.to_vec();
+        sealed.extend(buffer);
+        let path = self.path(name);
+        store::write_atomic(&path, &sealed).map_err(|e| format!("Failed to write {path:?}: {e}"))
+    }
+
+    pub fn delete(&self, name: &str) -> Result<(), String> {
+        let path = self.path(name);
+        match fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(format!("Failed to remove {path:?}: {e}"))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    #[test]
+    fn seals_and_opens_secrets() {
+        let dir = tempfile::tempdir().unwrap();
+        let secrets = Secrets::new(dir.path(), KeyStore::File);
+        assert_eq!(secrets.load::<Value>("basecamp"), Ok(None));
+
+        let value = json!({ "accessToken": "very-secret" });
+        secrets.save("basecamp", &value).unwrap();
+        assert_eq!(secrets.load("basecamp"), Ok(Some(value.clone())));
+        let sealed = fs::read(dir.path().join("basecamp.secret")).unwrap();
+        assert!(!String::from_utf8_lossy(&sealed).contains("very-secret"));
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(dir.path().join(KEY_FILE_NAME))
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        // A secret renamed to another name doesn't open.
+        fs::copy(
+            dir.path().join("basecamp.secret"),
+            dir.path().join("other.secret"),
+        )
+        .unwrap();
+        assert!(secrets.load::<Value>("other").is_err());
+
+        secrets.delete("basecamp").unwrap();
+        secrets.delete("basecamp").unwrap();
+        assert_eq!(secrets.load::<Value>("basecamp"), Ok(None));
+    }
+
+    #[test]
+    fn rejects_tampering_and_a_lost_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let secrets = Secrets::new(dir.path(), KeyStore::File);
+        secrets.save("basecamp", &json!("token")).unwrap();
+        let path = dir.path().join("basecamp.secret");
+        let mut sealed = fs::read(&path).unwrap();
+        let last = sealed.len() - 1;
+        sealed[last] ^= 1;
+        fs::write(&path, &sealed).unwrap();
+        assert!(secrets.load::<Value>("basecamp").is_err());
+
+        secrets.save("basecamp", &json!("token")).unwrap();
+        fs::remove_file(dir.path().join(KEY_FILE_NAME)).unwrap();
+        assert!(secrets.load::<Value>("basecamp").is_err());
+    }
+
+    #[test]
+    fn keeps_an_existing_key_file() {
+        let dir = tempfile::tempdir().unwrap();
+        Secrets::new(dir.path(), KeyStore::File)
+            .save("basecamp", &json!("token"))
+            .unwrap();
+        assert_eq!(KeyStore::detect(dir.path()), KeyStore::File);
+        assert_eq!(KeyStore::File.label(), "file");
+    }
+}
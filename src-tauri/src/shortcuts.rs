@@ -0,0 +1,251 @@
+//! Global keyboard shortcuts.
+//!
+//! Each action can be bound to one accelerator such as
+//! `CmdOrCtrl+Shift+Space`. Accelerators are normalized so different
+//! spellings of the same keys compare equal, which is how two actions bound
+//! to the same keys are caught before anything is registered with the OS.
+
+use crate::store;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+pub const SETTINGS_FILE_NAME: &str = "shortcuts.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ShortcutAction {
+    /// Open the capture window to add a task to the current tab.
+    QuickAdd,
+    /// Enter or leave focus mode (the focus pin).
+    ToggleFocus,
+    CompleteTask,
+    /// Pause or resume the focus timer.
+    ToggleTimer,
+}
+
+impl ShortcutAction {
+    pub const ALL: [ShortcutAction; 4] = [
+        ShortcutAction::QuickAdd,
+        ShortcutAction::ToggleFocus,
+        ShortcutAction::CompleteTask,
+        ShortcutAction::ToggleTimer,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ShortcutAction::QuickAdd => "Quick add",
+            ShortcutAction::ToggleFocus => "Toggle focus",
+            ShortcutAction::CompleteTask => "Complete task",
+            ShortcutAction::ToggleTimer => "Start/stop timer",
+        }
+    }
+}
+
+/// Accelerator per action; `None` leaves the action unbound. Every action
+/// starts unbound, so nothing is grabbed from other apps until the user
+/// picks the keys.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ShortcutSettings {
+    pub quick_add: Option<String>,
+    pub toggle_focus: Option<String>,
+    pub complete_task: Option<String>,
+    pub toggle_timer: Option<String>,
+}
+
+impl ShortcutSettings {
+    pub fn get(&self, action: ShortcutAction) -> Option<&str> {
+        match action {
+            ShortcutAction::QuickAdd => self.quick_add.as_deref(),
+            ShortcutAction::ToggleFocus => self.toggle_focus.as_deref(),
+            ShortcutAction::CompleteTask => self.complete_task.as_deref(),
+            ShortcutAction::ToggleTimer => self.toggle_timer.as_deref(),
+        }
+        .filter(|accelerator| !accelerator.trim().is_empty())
+    }
+}
+
+pub fn load_settings(data_dir: &Path) -> ShortcutSettings {
+    store::load_json_settings(data_dir, SETTINGS_FILE_NAME)
+}
+
+pub fn save_settings(data_dir: &Path, settings: &ShortcutSettings) -> Result<(), String> {
+    store::save_json_settings(data_dir, SETTINGS_FILE_NAME, settings)
+}
+
+/// Modifiers in the order they are written out.
+const MODIFIERS: [(&str, &[&str]); 4] = [
+    ("Ctrl", &["ctrl", "control"]),
+    ("Alt", &["alt", "option"]),
+    ("Shift", &["shift"]),
+    ("Super", &["super", "cmd", "command", "meta"]),
+];
+const CTRL: usize = 0;
+const SUPER: usize = 3;
+const CMD_OR_CTRL: [&str; 3] = ["cmdorctrl", "commandorcontrol", "cmdorcontrol"];
+
+/// Canonical spelling of an accelerator, e.g. `shift+ctrl+k` becomes
+/// `Ctrl+Shift+K`. `CmdOrCtrl` becomes this platform's modifier, so it
+/// matches the same keys spelled out. At least one modifier is required so a
+/// global shortcut can't swallow ordinary typing.
+pub fn normalize_accelerator(accelerator: &str) -> Result<String, String> {
+    normalize_for(accelerator, cfg!(target_os = "macos"))
+}
+
+/// [`normalize_accelerator`] with `CmdOrCtrl` as Cmd (`Super`) on macOS and
+/// as Ctrl elsewhere.
+fn normalize_for(accelerator: &str, macos: bool) -> Result<String, String> {
+    let mut modifiers = [false; MODIFIERS.len()];
+    let mut key: Option<String> = None;
+    for part in accelerator.split('+').map(str::trim) {
+        if part.is_empty() {
+            return Err(format!("Invalid shortcut: {accelerator}"));
+        }
+        let lower = part.to_ascii_lowercase();
+        if CMD_OR_CTRL.contains(&lower.as_str()) {
+            modifiers[if macos { SUPER } else { CTRL }] = true;
+        } else if let Some(index) = MODIFIERS
+            .iter()
+            .position(|(_, aliases)| aliases.contains(&lower.as_str()))
+        {
+            modifiers[index] = true;
+        } else if key.is_some() {
+            return Err(format!("Shortcut has more than one key: {accelerator}"));
+        } else if part.chars().count() == 1 {
+            key = Some(part.to_ascii_uppercase());
+        } else {
+            let mut chars = lower.chars();
+            key = chars
+                .next()
+                .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str());
+        }
+    }
+    let key = key.ok_or_else(|| format!("Shortcut has no key: {accelerator}"))?;
+    if !modifiers.contains(&true) {
+        return Err(format!("Shortcut needs a modifier key: {accelerator}"));
+    }
+    let mut parts: Vec<String> = MODIFIERS
+        .iter()
+        .zip(modifiers)
+        .filter(|(_, used)| *used)
+        .map(|((name, _), _)| name.to_string())
+        .collect();
+    parts.push(key);
+    Ok(parts.join("+"))
+}
+
+/// Bound actions with their normalized accelerators, or the reason each one
+/// can't be used: an invalid accelerator, or keys already taken by an
+/// earlier action.
+pub fn resolve_bindings(
+    settings: &ShortcutSettings,
+) -> Vec<(ShortcutAction, String, Result<String, String>)> {
+    let mut taken: Vec<(String, ShortcutAction)> = Vec::new();
+    ShortcutAction::ALL
+        .into_iter()
+        .filter_map(|action| {
+            let accelerator = settings.get(action)?.to_string();
+            let resolved = normalize_accelerator(&accelerator).and_then(|normalized| {
+                match taken.iter().find(|(keys, _)| *keys == normalized) {
+                    Some((_, other)) => Err(format!("Already used by {}", other.label())),
+                    None => {
+                        taken.push((normalized.clone(), action));
+                        Ok(normalized)
+                    }
+                }
+            });
+            Some((action, accelerator, resolved))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_spelling_and_order() {
+        assert_eq!(
+            normalize_accelerator("shift+control+k").unwrap(),
+            "Ctrl+Shift+K"
+        );
+        assert_eq!(
+            normalize_for("CommandOrControl + Option + space", false).unwrap(),
+            "Ctrl+Alt+Space"
+        );
+        assert_eq!(normalize_accelerator("Cmd+F12").unwrap(), "Super+F12");
+    }
+
+    #[test]
+    fn spells_cmd_or_ctrl_as_the_platform_modifier() {
+        assert_eq!(normalize_for("CmdOrCtrl+K", false).unwrap(), "Ctrl+K");
+        assert_eq!(normalize_for("CmdOrCtrl+K", true).unwrap(), "Super+K");
+        assert_eq!(
+            normalize_for("cmd+shift+k", true),
+            normalize_for("Shift+CommandOrControl+K", true)
+        );
+        assert_eq!(
+            normalize_accelerator("CmdOrCtrl+K"),
+            normalize_accelerator(if cfg!(target_os = "macos") {
+                "Cmd+K"
+            } else {
+                "Ctrl+K"
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_incomplete_accelerators() {
+        assert!(normalize_accelerator("K").is_err());
+        assert!(normalize_accelerator("Ctrl+Shift").is_err());
+        assert!(normalize_accelerator("Ctrl+A+B").is_err());
+        assert!(normalize_accelerator("Ctrl++").is_err());
+    }
+
+    #[test]
+    fn reports_actions_bound_to_the_same_keys() {
+        let primary = if cfg!(target_os = "macos") {
+            "Cmd"
+        } else {
+            "Ctrl"
+        };
+        let settings = ShortcutSettings {
+            quick_add: Some("CmdOrCtrl+Shift+Space".to_string()),
+            toggle_focus: Some("shift+cmdorctrl+space".to_string()),
+            complete_task: Some("Ctrl".to_string()),
+            toggle_timer: Some(format!("{primary}+Shift+Space")),
+        };
+        let bindings = resolve_bindings(&settings);
+        assert_eq!(bindings.len(), 4);
+        assert_eq!(
+            bindings[0],
+            (
+                ShortcutAction::QuickAdd,
+                "CmdOrCtrl+Shift+Space".to_string(),
+                normalize_accelerator("CmdOrCtrl+Shift+Space")
+            )
+        );
+        assert_eq!(bindings[1].2, Err("Already used by Quick add".to_string()));
+        assert!(bindings[2].2.is_err());
+        assert_eq!(bindings[3].2, Err("Already used by Quick add".to_string()));
+    }
+
+    #[test]
+    fn actions_are_unbound_by_default() {
+        let tmp = tempfile::tempdir().unwrap();
+        let settings = load_settings(tmp.path());
+        assert_eq!(settings, ShortcutSettings::default());
+        assert!(resolve_bindings(&settings).is_empty());
+    }
+
+    #[test]
+    fn blank_accelerators_are_unbound() {
+        let settings = ShortcutSettings {
+            quick_add: Some("  ".to_string()),
+            toggle_focus: None,
+            complete_task: None,
+            toggle_timer: None,
+        };
+        assert!(resolve_bindings(&settings).is_empty());
+    }
+}
@@ -0,0 +1,742 @@
+//! Rust-owned persistence for tasks, tabs and groups.
+//!
+//! Mirrors the shape the frontend keeps under the `redd-todo-data`
+//! localStorage key so the two can be exchanged verbatim. Fields the backend
+//! does not know about (integration ids, UI flags, onboarding state) are kept
+//! in the `extra` maps so a load/save round trip never drops data.
+
+use indexmap::IndexMap;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+/// File name of the task store inside `app_data_dir()`.
+pub const STORE_FILE_NAME: &str = "tasks.json";
+/// Plan-mode data, keyed by the short key (without the `redd-do-plan-` prefix).
+pub const PLAN_FILE_NAME: &str = "plan.json";
+/// Left next to the task store by edits made while the app wasn't running.
+pub const EXTERNAL_CHANGES_FILE_NAME: &str = "tasks.changed";
+/// Exists while a process outside the app is changing the task store.
+pub const LOCK_FILE_NAME: &str = "tasks.lock";
+/// How long to wait for another process to finish with the store.
+const LOCK_TIMEOUT: Duration = Duration::from_secs(5);
+/// A lock older than this was left by a process that died holding it.
+const STALE_LOCK_AGE: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Task {
+    pub id: String,
+    #[serde(default)]
+    pub text: String,
+    #[serde(default)]
+    pub completed: bool,
+    #[serde(default)]
+    pub is_favourite: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<String>,
+    /// Planned duration in minutes.
+    #[serde(default)]
+    pub expected_duration: Option<f64>,
+    /// Duration recorded when the task was completed, in milliseconds.
+    #[serde(default)]
+    pub actual_duration: Option<f64>,
+    /// Cumulative focus-mode time, in milliseconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_spent: Option<f64>,
+    /// Rich-text (Quill HTML) notes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Tab {
+    pub id: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub tasks: Vec<Task>,
+    #[serde(default)]
+    pub group_id: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Group {
+    pub id: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order: Option<f64>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Root of the persisted task data. Tabs and groups keep insertion order,
+/// which the frontend relies on for tab ordering.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppData {
+    #[serde(default)]
+    pub tabs: IndexMap<String, Tab>,
+    #[serde(default)]
+    pub groups: IndexMap<String, Group>,
+    #[serde(default)]
+    pub current_tab_id: Option<String>,
+    #[serde(default)]
+    pub current_group_id: Option<String>,
+    #[serde(default)]
+    pub task_counter: u64,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl AppData {
+    pub fn find_task(&self, task_id: &str) -> Option<(&Tab, &Task)> {
+        self.tabs.values().find_map(|tab| {
+            tab.tasks
+                .iter()
+                .find(|task| task.id == task_id)
+                .map(|task| (tab, task))
+        })
+    }
+
+    pub fn find_task_mut(&mut self, task_id: &str) -> Option<&mut Task> {
+        self.tabs
+            .values_mut()
+            .flat_map(|tab| tab.tasks.iter_mut())
+            .find(|task| task.id == task_id)
+    }
+
+    /// Merge `patch` into the task's JSON representation, the same way the
+    /// focus panel's `Object.assign(storedTask, patch)` does.
+    pub fn patch_task(
+        &mut self,
+        task_id: &str,
+        patch: &Map<String, Value>,
+    ) -> Result<Task, String> {
+        let task = self
+            .find_task_mut(task_id)
+            .ok_or_else(|| format!("Task not found: {task_id}"))?;
+
+        let mut merged = match serde_json::to_value(&*task).map_err(|e| e.to_string())? {
+            Value::Object(map) => map,
+            _ => return Err("Task did not serialize to an object".to_string()),
+        };
+        for (key, value) in patch {
+            if key == "id" {
+                continue;
+            }
+            merged.insert(key.clone(), value.clone());
+        }
+
+        *task = serde_json::from_value(Value::Object(merged))
+            .map_err(|e| format!("Invalid task patch: {e}"))?;
+        Ok(task.clone())
+    }
+
+    /// Allocate the next `task_<n>` id, matching `addTask` in the frontend.
+    pub fn next_task_id(&mut self) -> String {
+        loop {
+            self.task_counter += 1;
+            let id = format!("task_{}", self.task_counter);
+            if self.find_task(&id).is_none() {
+                return id;
+            }
+        }
+    }
+
+    /// Append a new, empty tab, mirroring `createNewTab` in the frontend.
+    /// Returns the new tab's id.
+    pub fn add_tab(&mut self, name: &str, group_id: Option<String>) -> String {
+        let id = unique_collection_id("tab", &self.tabs);
+        let name = match name.trim() {
+            "" => "New Tab",
+            trimmed => trimmed,
+        };
+        self.tabs.insert(
+            id.clone(),
+            Tab {
+                id: id.clone(),
+                name: name.to_string(),
+                group_id: group_id.or_else(|| self.current_group_id.clone()),
+                ..Tab::default()
+            },
+        );
+        id
+    }
+
+    /// Append a new group, mirroring `createGroup` in the frontend.
+    /// Returns the new group's id.
+    pub fn add_group(&mut self, name: &str) -> String {
+        let id = unique_collection_id("group", &self.groups);
+        let name = match name.trim() {
+            "" => "New Group",
+            trimmed => trimmed,
+        };
+        self.groups.insert(
+            id.clone(),
+            Group {
+                id: id.clone(),
+                name: name.to_string(),
+                order: Some(self.groups.len() as f64),
+                ..Group::default()
+            },
+        );
+        id
+    }
+
+    /// Id of the first tab named `name` (case-insensitive), if any.
+    pub fn tab_id_by_name(&self, name: &str) -> Option<String> {
+        self.tabs
+            .values()
+            .find(|tab| tab.name.trim().eq_ignore_ascii_case(name.trim()))
+            .map(|tab| tab.id.clone())
+    }
+
+    /// Id of the first group named `name` (case-insensitive), if any.
+    pub fn group_id_by_name(&self, name: &str) -> Option<String> {
+        self.groups
+            .values()
+            .find(|group| group.name.trim().eq_ignore_ascii_case(name.trim()))
+            .map(|group| group.id.clone())
+    }
+}
+
+/// Current time in the `toISOString()` format the frontend stores.
+pub fn now_iso() -> String {
+    chrono::Utc::now()
+        .format("%Y-%m-%dT%H:%M:%S%.3fZ")
+        .to_string()
+}
+
+/// Generate a `<prefix>_<millis>_<n>` id not yet used in `collection`,
+/// the backend counterpart of `generateUniqueCollectionId` in the frontend.
+pub fn unique_collection_id<V>(prefix: &str, collection: &IndexMap<String, V>) -> String {
+    let millis = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    (0..)
+        .map(|n| format!("{prefix}_{millis}_{n}"))
+        .find(|candidate| !collection.contains_key(candidate))
+        .unwrap_or_default()
+}
+
+pub fn store_path(dir: &Path) -> PathBuf {
+    dir.join(STORE_FILE_NAME)
+}
+
+/// Write `contents` to `path` via a temp file + rename so a crash mid-write
+/// leaves either the old or the new file, never a truncated one.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut tmp_name = path.as_os_str().to_owned();
+    tmp_name.push(".tmp");
+    let tmp_path = PathBuf::from(tmp_name);
+
+    {
+        let mut file = fs::File::create(&tmp_path)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+    }
+    fs::rename(&tmp_path, path)
+}
+
+/// Read the settings file `file_name` in `dir`, falling back to the defaults
+/// when it is missing or unreadable.
+pub fn load_json_settings<T: DeserializeOwned + Default>(dir: &Path, file_name: &str) -> T {
+    fs::read_to_string(dir.join(file_name))
+        .ok()
+        .and_then(|raw| serde_json::from_str(&raw).ok())
+        .unwrap_or_default()
+}
+
+/// Write `settings` to the settings file `file_name` in `dir`.
+pub fn save_json_settings<T: Serialize>(
+    dir: &Path,
+    file_name: &str,
+    settings: &T,
+) -> Result<(), String> {
+    let path = dir.join(file_name);
+    let json = serde_json::to_vec_pretty(settings).map_err(|e| e.to_string())?;
+    write_atomic(&path, &json).map_err(|e| format!("Failed to write {path:?}: {e}"))
+}
+
+/// Load the task store from `dir`. Returns `Ok(None)` when nothing has been
+/// persisted yet.
+pub fn load(dir: &Path) -> Result<Option<AppData>, String> {
+    let path = store_path(dir);
+    let raw = match fs::read_to_string(&path) {
+        Ok(raw) => raw,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(format!("Failed to read {path:?}: {e}")),
+    };
+    serde_json::from_str(&raw)
+        .map(Some)
+        .map_err(|e| format!("Failed to parse {path:?}: {e}"))
+}
+
+/// Basecamp credentials the frontend used to keep in its config. The backend
+/// keeps them encrypted instead, so they are never written to the store (or
+/// the backups made from it).
+pub const SECRET_CONFIG_KEYS: [&str; 3] = ["accessToken", "refreshToken", "clientSecret"];
+
+pub fn save(dir: &Path, data: &AppData) -> Result<(), String> {
+    let path = store_path(dir);
+    let mut value = serde_json::to_value(data).map_err(|e| e.to_string())?;
+    if let Some(config) = value
+        .get_mut("basecampConfig")
+        .and_then(Value::as_object_mut)
+    {
+        for key in SECRET_CONFIG_KEYS {
+            config.remove(key);
+        }
+    }
+    let json = serde_json::to_vec_pretty(&value).map_err(|e| e.to_string())?;
+    write_atomic(&path, &json).map_err(|e| format!("Failed to write {path:?}: {e}"))
+}
+
+/// Cross-process lock on the task store, taken by every writer: the app, the
+/// command line and the MCP server, which may run side by side. Released on
+/// drop.
+#[derive(Debug)]
+pub struct StoreLock {
+    path: PathBuf,
+}
+
+impl StoreLock {
+    pub fn acquire(dir: &Path) -> Result<Self, String> {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create {dir:?}: {e}"))?;
+        let path = dir.join(LOCK_FILE_NAME);
+        let deadline = Instant::now() + LOCK_TIMEOUT;
+        loop {
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(mut file) => {
+                    let _ = write!(file, "{}", std::process::id());
+                    return Ok(Self { path });
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                    let stale = fs::metadata(&path)
+                        .and_then(|meta| meta.modified())
+                        .ok()
+                        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+                        .is_some_and(|age| age > STALE_LOCK_AGE);
+                    if stale {
+                        let _ = fs::remove_file(&path);
+                        continue;
+                    }
+                    if Instant::now() >= deadline {
+                        return Err("The task store is busy in another process".to_string());
+                    }
+                    std::thread::sleep(Duration::from_millis(20));
+                }
+                Err(e) => return Err(format!("Failed to lock {path:?}: {e}")),
+            }
+        }
+    }
+}
+
+impl Drop for StoreLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Tasks edited outside the app since the frontend last picked them up.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ExternalChanges {
+    /// Tasks created outside the app.
+    pub added: Vec<String>,
+    /// Existing tasks changed outside the app.
+    pub changed: Vec<String>,
+}
+
+impl ExternalChanges {
+    /// The tasks that differ between two versions of the store.
+    pub fn between(before: &AppData, after: &AppData) -> Self {
+        let mut changes = Self::default();
+        for task in after.tabs.values().flat_map(|tab| &tab.tasks) {
+            match before.find_task(&task.id) {
+                None => changes.added.push(task.id.clone()),
+                Some((_, old)) if old != task => changes.changed.push(task.id.clone()),
+                Some(_) => {}
+            }
+        }
+        changes
+    }
+
+    fn extend(&mut self, other: ExternalChanges) {
+        for id in other.added {
+            if !self.added.contains(&id) {
+                self.added.push(id);
+            }
+        }
+        for id in other.changed {
+            if !self.added.contains(&id) && !self.changed.contains(&id) {
+                self.changed.push(id);
+            }
+        }
+    }
+}
+
+/// Save edits made outside the app (the command line). The frontend keeps
+/// its own copy in localStorage, so the changed tasks are recorded for it to
+/// merge in on its next start; see [`take_external_changes`].
+pub fn save_external(dir: &Path, data: &AppData, changes: ExternalChanges) -> Result<(), String> {
+    save(dir, data)?;
+    let path = dir.join(EXTERNAL_CHANGES_FILE_NAME);
+    let mut pending = read_external_changes(&path);
+    pending.extend(changes);
+    let json = serde_json::to_vec_pretty(&pending).map_err(|e| e.to_string())?;
+    write_atomic(&path, &json).map_err(|e| format!("Failed to write {path:?}: {e}"))
+}
+
+fn read_external_changes(path: &Path) -> ExternalChanges {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|raw| serde_json::from_str(&raw).ok())
+        .unwrap_or_default()
+}
+
+/// The tasks changed outside the app since the last call, if any.
+pub fn take_external_changes(dir: &Path) -> Option<ExternalChanges> {
+    let path = dir.join(EXTERNAL_CHANGES_FILE_NAME);
+    fs::metadata(&path).ok()?;
+    let changes = read_external_changes(&path);
+    let _ = fs::remove_file(&path);
+    Some(changes)
+}
+
+/// Bring the tasks in `changes` over from the store (`external`) into the
+/// frontend's copy (`local`), leaving everything else in `local` alone so
+/// edits the store never saw survive. Tasks deleted in `local` stay deleted,
+/// and merging the same changes again changes nothing.
+pub fn merge_external(local: &mut AppData, external: &AppData, changes: &ExternalChanges) {
+    local.task_counter = local.task_counter.max(external.task_counter);
+    for task_id in &changes.changed {
+        if let (Some(task), Some((_, updated))) =
+            (local.find_task_mut(task_id), external.find_task(task_id))
+        {
+            *task = updated.clone();
+        }
+    }
+    for task_id in &changes.added {
+        let Some((tab, task)) = external.find_task(task_id) else {
+            continue;
+        };
+        let mut task = task.clone();
+        match local.find_task(task_id) {
+            Some((_, existing)) if *existing == task => continue,
+            // The frontend gave the same id to a task the store never saw.
+            Some(_) => task.id = local.next_task_id(),
+            None => {}
+        }
+        local
+            .tabs
+            .entry(tab.id.clone())
+            .or_insert_with(|| Tab {
+                tasks: Vec::new(),
+                ..tab.clone()
+            })
+            .tasks
+            .push(task);
+    }
+}
+
+pub fn plan_path(dir: &Path) -> PathBuf {
+    dir.join(PLAN_FILE_NAME)
+}
+
+/// Load plan-mode data from `dir`, or an empty map if none has been saved.
+pub fn load_plan(dir: &Path) -> Result<Map<String, Value>, String> {
+    let path = plan_path(dir);
+    match fs::read_to_string(&path) {
+        Ok(raw) => serde_json::from_str(&raw).map_err(|e| format!("Failed to parse {path:?}: {e}")),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Map::new()),
+        Err(e) => Err(format!("Failed to read {path:?}: {e}")),
+    }
+}
+
+pub fn save_plan(dir: &Path, plan: &Map<String, Value>) -> Result<(), String> {
+    let path = plan_path(dir);
+    let json = serde_json::to_vec_pretty(plan).map_err(|e| e.to_string())?;
+    write_atomic(&path, &json).map_err(|e| format!("Failed to write {path:?}: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn snapshot() -> Value {
+        json!({
+            "tabs": {
+                "tab_1": {
+                    "id": "tab_1",
+                    "name": "Work",
+                    "groupId": "group_1",
+                    "tasks": [{
+                        "id": "task_1",
+                        "text": "Ship it",
+                        "completed": false,
+                        "isFavourite": true,
+                        "createdAt": "2024-03-01T09:30:00.000Z",
+                        "expectedDuration": 30.0,
+                        "actualDuration": null,
+                        "basecampId": 42,
+                        "reminder": { "at": "2024-03-02T09:00:00.000Z" }
+                    }],
+                    "basecampListId": "7"
+                }
+            },
+            "groups": { "group_1": { "id": "group_1", "name": "Day job", "order": 0.0, "color": "red" } },
+            "currentTabId": "tab_1",
+            "currentGroupId": "group_1",
+            "taskCounter": 1,
+            "enableGroups": true,
+            "basecampConfig": { "accountId": "1", "accessToken": "secret" }
+        })
+    }
+
+    #[test]
+    fn keeps_unknown_fields_through_a_round_trip() {
+        let data: AppData = serde_json::from_value(snapshot()).unwrap();
+        let task = &data.tabs["tab_1"].tasks[0];
+        assert_eq!(task.extra["basecampId"], json!(42));
+        assert_eq!(data.tabs["tab_1"].extra["basecampListId"], json!("7"));
+        assert_eq!(data.groups["group_1"].extra["color"], json!("red"));
+        assert_eq!(data.extra["enableGroups"], json!(true));
+        assert_eq!(serde_json::to_value(&data).unwrap(), snapshot());
+
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(load(dir.path()), Ok(None));
+        save(dir.path(), &data).unwrap();
+        let loaded = load(dir.path()).unwrap().unwrap();
+        // Everything but the credentials is written.
+        let mut expected = snapshot();
+        expected["basecampConfig"] = json!({ "accountId": "1" });
+        assert_eq!(serde_json::to_value(&loaded).unwrap(), expected);
+    }
+
+    #[test]
+    fn writes_atomically() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested/dir/file.json");
+        write_atomic(&path, b"first").unwrap();
+        write_atomic(&path, b"second").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second");
+        let names: Vec<_> = fs::read_dir(path.parent().unwrap())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(names, ["file.json"]);
+    }
+
+    #[test]
+    fn patches_one_task() {
+        let mut data: AppData = serde_json::from_value(snapshot()).unwrap();
+        let patch = json!({
+            "id": "task_9",
+            "text": "Shipped",
+            "timeSpent": 60000.0,
+            "focusStartedAt": 123
+        });
+        let task = data
+            .patch_task("task_1", patch.as_object().unwrap())
+            .unwrap();
+        assert_eq!(task.id, "task_1");
+        assert_eq!(task.text, "Shipped");
+        assert_eq!(task.time_spent, Some(60000.0));
+        assert!(task.is_favourite);
+        assert_eq!(task.extra["focusStartedAt"], json!(123));
+        assert_eq!(task.extra["basecampId"], json!(42));
+        assert_eq!(data.find_task("task_1").unwrap().1, &task);
+
+        let bad = json!({ "completed": "yes" });
+        assert!(data.patch_task("task_1", bad.as_object().unwrap()).is_err());
+        assert_eq!(data.find_task("task_1").unwrap().1, &task);
+        assert_eq!(
+            data.patch_task("missing", &Map::new()),
+            Err("Task not found: missing".to_string())
+        );
+    }
+
+    fn add_task(data: &mut AppData, tab_id: &str, text: &str) -> String {
+        let id = data.next_task_id();
+        let task = Task {
+            id: id.clone(),
+            text: text.to_string(),
+            ..Task::default()
+        };
+        data.tabs.get_mut(tab_id).unwrap().tasks.push(task);
+        id
+    }
+
+    fn texts(data: &AppData) -> Vec<(String, String)> {
+        data.tabs
+            .values()
+            .flat_map(|tab| &tab.tasks)
+            .map(|task| (task.id.clone(), task.text.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn merges_only_the_externally_changed_tasks() {
+        let stored: AppData = serde_json::from_value(snapshot()).unwrap();
+
+        // The command line completes task_1 and adds task_2 to the store...
+        let mut external = stored.clone();
+        external.find_task_mut("task_1").unwrap().completed = true;
+        add_task(&mut external, "tab_1", "From the CLI");
+        let changes = ExternalChanges::between(&stored, &external);
+        assert_eq!(changes.added, ["task_2"]);
+        assert_eq!(changes.changed, ["task_1"]);
+
+        // ...while the webview holds edits the store never saw, including
+        // its own task_2.
+        let mut local = stored.clone();
+        local.find_task_mut("task_1").unwrap().text = "Ship it today".to_string();
+        add_task(&mut local, "tab_1", "Unsaved");
+        local.extra.insert("enableGroups".to_string(), json!(false));
+
+        merge_external(&mut local, &external, &changes);
+        assert_eq!(
+            texts(&local),
+            [
+                ("task_1".to_string(), "Ship it".to_string()),
+                ("task_2".to_string(), "Unsaved".to_string()),
+                ("task_3".to_string(), "From the CLI".to_string()),
+            ]
+        );
+        assert!(local.find_task("task_1").unwrap().1.completed);
+        assert_eq!(local.task_counter, 3);
+        assert_eq!(local.extra["enableGroups"], json!(false));
+    }
+
+    #[test]
+    fn merging_the_same_changes_twice_adds_nothing() {
+        let stored: AppData = serde_json::from_value(snapshot()).unwrap();
+        let mut external = stored.clone();
+        add_task(&mut external, "tab_1", "Captured");
+        let changes = ExternalChanges::between(&stored, &external);
+
+        let mut local = stored.clone();
+        merge_external(&mut local, &external, &changes);
+        let once = local.clone();
+        merge_external(&mut local, &external, &changes);
+        assert_eq!(local, once);
+        assert_eq!(
+            texts(&local),
+            [
+                ("task_1".to_string(), "Ship it".to_string()),
+                ("task_2".to_string(), "Captured".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn merges_into_a_new_tab_and_keeps_local_deletions() {
+        let stored: AppData = serde_json::from_value(snapshot()).unwrap();
+        let mut external = stored.clone();
+        external.find_task_mut("task_1").unwrap().completed = true;
+        let tab_id = external.add_tab("Tasks", None);
+        add_task(&mut external, &tab_id, "Elsewhere");
+        let changes = ExternalChanges::between(&stored, &external);
+
+        let mut local = stored.clone();
+        local.tabs["tab_1"].tasks.clear();
+        merge_external(&mut local, &external, &changes);
+        assert!(local.find_task("task_1").is_none());
+        assert_eq!(local.tabs[&tab_id].name, "Tasks");
+        assert_eq!(
+            texts(&local),
+            [("task_2".to_string(), "Elsewhere".to_string())]
+        );
+    }
+
+    #[test]
+    fn accumulates_external_changes_until_taken() {
+        let dir = tempfile::tempdir().unwrap();
+        let data: AppData = serde_json::from_value(snapshot()).unwrap();
+        assert_eq!(take_external_changes(dir.path()), None);
+
+        let first = ExternalChanges {
+            added: vec!["task_2".to_string()],
+            changed: vec!["task_1".to_string()],
+        };
+        let second = ExternalChanges {
+            added: vec!["task_3".to_string()],
+            changed: vec!["task_1".to_string(), "task_2".to_string()],
+        };
+        save_external(dir.path(), &data, first).unwrap();
+        save_external(dir.path(), &data, second).unwrap();
+        assert_eq!(
+            take_external_changes(dir.path()),
+            Some(ExternalChanges {
+                added: vec!["task_2".to_string(), "task_3".to_string()],
+                changed: vec!["task_1".to_string()],
+            })
+        );
+        assert_eq!(take_external_changes(dir.path()), None);
+    }
+
+    #[test]
+    fn locks_the_store_across_processes() {
+        let dir = tempfile::tempdir().unwrap();
+        let lock = StoreLock::acquire(dir.path()).unwrap();
+        let path = dir.path().join(LOCK_FILE_NAME);
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            std::process::id().to_string()
+        );
+
+        let waiter = {
+            let dir = dir.path().to_path_buf();
+            std::thread::spawn(move || StoreLock::acquire(&dir).map(drop))
+        };
+        std::thread::sleep(Duration::from_millis(100));
+        assert!(!waiter.is_finished());
+        drop(lock);
+        assert_eq!(waiter.join().unwrap(), Ok(()));
+        assert!(!path.exists());
+
+        // A lock left behind by a crashed process is taken over.
+        let file = fs::File::create(&path).unwrap();
+        file.set_modified(SystemTime::now() - 2 * STALE_LOCK_AGE)
+            .unwrap();
+        drop(file);
+        drop(StoreLock::acquire(dir.path()).unwrap());
+    }
+
+    #[test]
+    fn settings_fall_back_to_their_defaults() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing: IndexMap<String, u32> = load_json_settings(dir.path(), "test.json");
+        assert!(missing.is_empty());
+
+        let settings = IndexMap::from([("interval".to_string(), 30u32)]);
+        save_json_settings(dir.path(), "test.json", &settings).unwrap();
+        assert_eq!(
+            load_json_settings::<IndexMap<String, u32>>(dir.path(), "test.json"),
+            settings
+        );
+
+        fs::write(dir.path().join("test.json"), "not json").unwrap();
+        assert!(load_json_settings::<IndexMap<String, u32>>(dir.path(), "test.json").is_empty());
+    }
+}
@@ -0,0 +1,230 @@
+//! Focus window geometry saved across restarts.
+//!
+//! The focus window's last position and size is kept per monitor, keyed by
+//! the monitor's name and resolution, so docking and undocking a laptop
+//! brings the pin back to where it was on each setup. When none of the saved
+//! monitors is connected, the most recent geometry is clamped into a visible
+//! work area instead of reopening off screen.
+//!
+//! Geometry a window returns to after full-screen focus is kept here too, so
+//! it survives the app quitting while a task is full screen.
+
+use crate::store;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+pub const FILE_NAME: &str = "focus-window.json";
+
+/// A window or work-area rectangle in physical pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Rect {
+    fn center(&self) -> (i64, i64) {
+        (
+            i64::from(self.x) + i64::from(self.width) / 2,
+            i64::from(self.y) + i64::from(self.height) / 2,
+        )
+    }
+
+    fn contains(&self, (x, y): (i64, i64)) -> bool {
+        let (left, top) = (i64::from(self.x), i64::from(self.y));
+        x >= left
+            && y >= top
+            && x < left + i64::from(self.width)
+            && y < top + i64::from(self.height)
+    }
+
+    /// Shrink and move `self` so it lies inside `area`.
+    pub fn clamp_to(&self, area: &Rect) -> Rect {
+        let width = self.width.min(area.width);
+        let height = self.height.min(area.height);
+        let max_x = i64::from(area.x) + i64::from(area.width - width);
+        let max_y = i64::from(area.y) + i64::from(area.height - height);
+        Rect {
+            x: i64::from(self.x).clamp(i64::from(area.x), max_x) as i32,
+            y: i64::from(self.y).clamp(i64::from(area.y), max_y) as i32,
+            width,
+            height,
+        }
+    }
+}
+
+/// A connected monitor as seen when saving or restoring.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MonitorArea {
+    /// See [`monitor_key`].
+    pub key: String,
+    /// The part of the monitor not covered by panels and docks.
+    pub work_area: Rect,
+}
+
+/// Identify a monitor by name and resolution, e.g. `DP-1@2560x1440`.
+pub fn monitor_key(name: Option<&str>, width: u32, height: u32) -> String {
+    format!("{}@{width}x{height}", name.unwrap_or("unknown"))
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SavedGeometry {
+    /// Key of the monitor the window was last saved on.
+    pub last_monitor: Option<String>,
+    pub monitors: IndexMap<String, Rect>,
+    /// The window that entered full-screen focus on Windows and Linux, to
+    /// restore when it leaves.
+    pub pre_fullscreen: Option<Rect>,
+    /// The macOS focus panel at the handoff to full-screen focus, by task id.
+    pub handoff: IndexMap<String, Rect>,
+}
+
+/// Pull `rect` into the work area it overlaps, or the primary one.
+pub fn fit(rect: &Rect, monitors: &[MonitorArea]) -> Option<Rect> {
+    let area = monitors
+        .iter()
+        .find(|monitor| monitor.work_area.contains(rect.center()))
+        .or_else(|| monitors.first())?;
+    Some(rect.clamp_to(&area.work_area))
+}
+
+impl SavedGeometry {
+    pub fn record(&mut self, monitor_key: &str, rect: Rect) {
+        self.monitors.insert(monitor_key.to_string(), rect);
+        self.last_monitor = Some(monitor_key.to_string());
+    }
+
+    /// Where to put the focus window on the given monitors, or `None` if
+    /// nothing was saved. `monitors` should list the primary monitor first.
+    pub fn resolve(&self, monitors: &[MonitorArea]) -> Option<Rect> {
+        let saved_on = |key: &str| {
+            let monitor = monitors.iter().find(|monitor| monitor.key == key)?;
+            Some(self.monitors.get(key)?.clamp_to(&monitor.work_area))
+        };
+        if let Some(rect) = self.last_monitor.as_deref().and_then(saved_on) {
+            return Some(rect);
+        }
+        if let Some(rect) = monitors.iter().find_map(|monitor| saved_on(&monitor.key)) {
+            return Some(rect);
+        }
+
+        // None of the saved monitors is connected: keep the latest size and
+        // pull it into whichever work area it overlaps, or the primary one.
+        let latest = self
+            .last_monitor
+            .as_deref()
+            .and_then(|key| self.monitors.get(key))
+            .or_else(|| self.monitors.values().last())?;
+        fit(latest, monitors)
+    }
+}
+
+pub fn load(data_dir: &Path) -> SavedGeometry {
+    fs::read_to_string(data_dir.join(FILE_NAME))
+        .ok()
+        .and_then(|raw| serde_json::from_str(&raw).ok())
+        .unwrap_or_default()
+}
+
+pub fn save(data_dir: &Path, geometry: &SavedGeometry) -> Result<(), String> {
+    let path = data_dir.join(FILE_NAME);
+    let json = serde_json::to_vec_pretty(geometry).map_err(|e| e.to_string())?;
+    store::write_atomic(&path, &json).map_err(|e| format!("Failed to write {path:?}: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x: i32, y: i32, width: u32, height: u32) -> Rect {
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    fn monitor(name: &str, work_area: Rect) -> MonitorArea {
+        MonitorArea {
+            key: monitor_key(Some(name), work_area.width, work_area.height),
+            work_area,
+        }
+    }
+
+    #[test]
+    fn restores_geometry_for_each_monitor() {
+        let laptop = monitor("eDP-1", rect(0, 0, 1920, 1080));
+        let external = monitor("DP-1", rect(1920, 0, 2560, 1440));
+        let mut saved = SavedGeometry::default();
+        saved.record(&laptop.key, rect(100, 100, 360, 56));
+        saved.record(&external.key, rect(3000, 40, 400, 60));
+
+        assert_eq!(
+            saved.resolve(&[laptop.clone(), external.clone()]),
+            Some(rect(3000, 40, 400, 60))
+        );
+        // Undocked: the laptop's own geometry comes back.
+        assert_eq!(saved.resolve(&[laptop]), Some(rect(100, 100, 360, 56)));
+    }
+
+    #[test]
+    fn clamps_into_a_work_area_when_the_monitor_is_gone() {
+        let laptop = monitor("eDP-1", rect(0, 32, 1920, 1048));
+        let mut saved = SavedGeometry::default();
+        saved.record("DP-1@2560x1440", rect(3000, 900, 400, 60));
+
+        assert_eq!(saved.resolve(&[laptop]), Some(rect(1520, 900, 400, 60)));
+    }
+
+    #[test]
+    fn clamps_to_a_shrunken_work_area() {
+        let panel_added = monitor("eDP-1", rect(0, 48, 1920, 1032));
+        let mut saved = SavedGeometry::default();
+        saved.record(&panel_added.key, rect(10, 0, 360, 56));
+
+        assert_eq!(saved.resolve(&[panel_added]), Some(rect(10, 48, 360, 56)));
+    }
+
+    #[test]
+    fn nothing_saved_means_no_geometry() {
+        let laptop = monitor("eDP-1", rect(0, 0, 1920, 1080));
+        assert_eq!(SavedGeometry::default().resolve(&[laptop]), None);
+    }
+
+    #[test]
+    fn fits_full_screen_geometry_into_the_monitor_it_was_on() {
+        let laptop = monitor("eDP-1", rect(0, 0, 1920, 1080));
+        let external = monitor("DP-1", rect(1920, 0, 2560, 1440));
+        let monitors = [laptop, external];
+
+        assert_eq!(
+            fit(&rect(2000, 1400, 400, 60), &monitors),
+            Some(rect(2000, 1380, 400, 60))
+        );
+        // Its monitor is gone: onto the primary one.
+        assert_eq!(
+            fit(&rect(5000, 100, 400, 60), &monitors),
+            Some(rect(1520, 100, 400, 60))
+        );
+        assert_eq!(fit(&rect(0, 0, 400, 60), &[]), None);
+    }
+
+    #[test]
+    fn round_trips_through_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut saved = SavedGeometry::default();
+        saved.record("DP-1@2560x1440", rect(-200, 40, 400, 60));
+        saved.pre_fullscreen = Some(rect(100, 100, 900, 700));
+        saved
+            .handoff
+            .insert("task_1".to_string(), rect(40, 40, 360, 56));
+        save(dir.path(), &saved).unwrap();
+        assert_eq!(load(dir.path()), saved);
+    }
+}
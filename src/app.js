@@ -38,7 +38,10 @@ const reddIpc = {
         if (reddIsTauri && typeof tauriAPI !== 'undefined') {
             // Map Electron channel names to Tauri commands
             const channelMap = {
-                'exit-focus-mode': () => tauriAPI.exitFocusMode(args[0]?.taskId ?? null),
+                'exit-focus-mode': () => tauriAPI.exitFocusMode(
+                    args[0]?.taskId ?? null,
+                    args[0]?.completed ?? false
+                ),
                 'open-focus-window': () => tauriAPI.openFocusWindow(
                     args[0]?.taskId,
                     args[0]?.taskName,
@@ -192,6 +195,12 @@ if (isFocusPanelWindow) {
     }
 }
 
+// Quick Add window opened by the global shortcut; shows only the capture input.
+const isCaptureWindow = launchParams.get('capture') === '1';
+if (isCaptureWindow) {
+    document.body.classList.add('capture-window');
+}
+
 function markAsFocusPanelWindow() {
     if (isFocusPanelWindow) return;
     isFocusPanelWindow = true;
@@ -229,6 +238,9 @@ let previousFocusStartTime = null;
 let focusDuration = null; // Expected duration in minutes for the current focus session
 let focusTimerInterval = null;
 let focusPersistInterval = null; // Periodic save of cumulative timeSpent (crash resilience)
+let focusTimerTaskId = null; // Task whose backend focus timer this window started
+let focusBreakInterval = null; // Countdown for the Pomodoro break view
+let focusBreakEndsAt = null;
 const FOCUS_PERSIST_INTERVAL_MS = 30000;
 let preTaskSwitchMenuHeight = null; // Restore exact panel height after menu closes
 // Track dragged items
@@ -238,6 +250,7 @@ let draggedGroupId = null; // Track dragged group
 let focusedTaskId = null; // To track which task is currently in focus mode
 let activeFocusTaskIds = new Set(); // Used to style focused tasks in the main window
 let preFocusMainWindowSize = null; // Restore size after in-window focus mode
+let multipleFocusPins = false; // Open a focus window per task instead of one at a time
 let currentView = 'lists'; // 'lists', 'favourites', or 'plan'
 let favouritesOrder = []; // Order of favourite task IDs for custom sorting
 let planModuleLoaded = false; // Track if plan module has been initialized
@@ -271,6 +284,13 @@ const translations = {
     en: {
         // Add task
         addTaskPlaceholder: 'Add task...',
+        capturePlaceholder: 'Add task, e.g. "Write email 15"',
+        deepLinkFailed: 'Could not open link',
+        deepLinkConfirmTitle: 'Link from another app',
+        deepLinkConfirmFocus: 'A link asks to start focus on "{task}". Start it?',
+        deepLinkConfirmComplete: 'A link asks to complete "{task}". Complete it?',
+        deepLinkStartFocus: 'Start focus',
+        deepLinkComplete: 'Complete',
         // Done section
         done: 'Done',
         clearAll: 'Clear all',
@@ -286,6 +306,29 @@ const translations = {
         settingsDataHint: 'Save a backup or restore from a file.',
         exportLabel: 'Export',
         importLabel: 'Import',
+        automaticBackups: 'Automatic backups',
+        automaticBackupsHint: 'Restore a snapshot saved in the background.',
+        noAutomaticBackups: 'No backups yet',
+        restoreLabel: 'Restore',
+        restoreBackupFailed: 'Failed to restore the backup.',
+        importModeMessage: 'Replace your current data with the backup, or add its lists and tasks to what you have?',
+        importReplace: 'Replace',
+        importMerge: 'Merge',
+        markdownScope: 'Markdown export',
+        markdownScopeAll: 'All tabs',
+        markdownScopeTab: 'Current tab',
+        markdownScopeGroup: 'Current group',
+        timeReport: 'Time report',
+        timeReportHint: 'Tracked time per task, credited to the day it was completed (or created, if still open).',
+        reportFrom: 'From',
+        reportTo: 'To',
+        reportFormat: 'Format',
+        reportGrouping: 'Group by',
+        reportGroupTask: 'Task',
+        reportGroupDay: 'Day',
+        reportGroupTab: 'Tab',
+        reportGroupGroup: 'Group',
+        reportExportFailed: 'Failed to export the time report.',
         connect: 'Connect',
         language: 'Language',
         languagePickerCurrent: 'Current language',
@@ -305,6 +348,35 @@ const translations = {
         addedToPlanner: 'Added to planner',
         tabGroupsInfo: 'Organize your to-do lists into groups (shown in a top bar).',
         planModeInfo: 'A Danish-style calendar you can sync with important tasks or events from any external calendar, for a bird\u2019s-eye view of the months or week ahead.',
+        pomodoro: 'Pomodoro',
+        pomodoroInfo: 'Split focus sessions into work phases with short breaks, and a long break every few cycles.',
+        pomodoroWork: 'Work (min)',
+        pomodoroShortBreak: 'Short break (min)',
+        pomodoroLongBreak: 'Long break (min)',
+        pomodoroCycles: 'Cycles before long break',
+        pomodoroSkipBreak: 'Skip',
+        shortBreak: 'Short break',
+        longBreak: 'Long break',
+        multiplePins: 'Multiple focus pins',
+        multiplePinsInfo: 'Keep a focus window open for each focused task, stacked so they don\'t overlap, each with its own timer.',
+        idlePause: 'Pause when idle',
+        idleInfo: 'Pause the focus timer when there is no keyboard or mouse input, and ask whether to keep the time away when you return.',
+        idleThreshold: 'Idle after (min)',
+        idleAway: 'Away for {duration}',
+        idleKeep: 'Keep',
+        idleDiscard: 'Discard',
+        globalShortcuts: 'Global shortcuts',
+        shortcutsInfo: 'Click a shortcut and press the new keys, or Backspace to turn it off. These work even when ReDD To-Do is in the background.',
+        shortcutQuickAdd: 'Quick add',
+        shortcutToggleFocus: 'Start or stop focus',
+        shortcutCompleteTask: 'Complete focused task',
+        shortcutToggleTimer: 'Pause or resume timer',
+        shortcutOff: 'Off',
+        shortcutPress: 'Press keys…',
+        automationApi: 'Automation API',
+        rpcEnable: 'Local automation socket',
+        rpcInfo: 'Lets editors, shell prompts and status bars list, add, complete and focus tasks over a JSON-RPC socket on this computer. Clients need the token from the token file next to the socket.',
+        rpcListening: 'Listening on',
         dataManagement: 'Data',
         dataManagementDesc: 'Backup or restore your data.',
         exportBackup: 'Export Backup',
@@ -344,6 +416,16 @@ const translations = {
         // Focus mode
         focus: 'Focus',
         exitFocus: 'Exit Focus',
+        // Tray menu
+        trayNoFocusSession: 'No focus session',
+        trayStartFocus: 'Start focus',
+        trayStartFocusOn: 'Start focus: {task}',
+        trayStopFocus: 'Stop focus',
+        trayCompleteTask: 'Complete task',
+        traySwitchToFavourite: 'Switch to favourite',
+        trayOpenApp: 'Open ReDD To-Do',
+        trayUntitledTask: 'Untitled task',
+        trayPaused: 'paused',
         // Time
         minutes: 'm',
         rebrandOnboardingTitleHtml:
@@ -355,6 +437,13 @@ const translations = {
     da: {
         // Add task
         addTaskPlaceholder: 'Tilføj opgave...',
+        capturePlaceholder: 'Tilføj opgave, f.eks. "Skriv e-mail 15"',
+        deepLinkFailed: 'Kunne ikke åbne link',
+        deepLinkConfirmTitle: 'Link fra en anden app',
+        deepLinkConfirmFocus: 'Et link vil starte fokus på "{task}". Vil du starte det?',
+        deepLinkConfirmComplete: 'Et link vil markere "{task}" som færdig. Vil du gøre det?',
+        deepLinkStartFocus: 'Start fokus',
+        deepLinkComplete: 'Marker som færdig',
         // Done section
         done: 'Færdig',
         clearAll: 'Ryd alle',
@@ -370,6 +459,29 @@ const translations = {
         settingsDataHint: 'Gem en sikkerhedskopi eller gendan fra en fil.',
         exportLabel: 'Eksporter',
         importLabel: 'Importer',
+        automaticBackups: 'Automatiske sikkerhedskopier',
+        automaticBackupsHint: 'Gendan et øjebliksbillede gemt i baggrunden.',
+        noAutomaticBackups: 'Ingen sikkerhedskopier endnu',
+        restoreLabel: 'Gendan',
+        restoreBackupFailed: 'Sikkerhedskopien kunne ikke gendannes.',
+        importModeMessage: 'Vil du erstatte dine nuværende data med sikkerhedskopien, eller tilføje dens lister og opgaver til dem, du har?',
+        importReplace: 'Erstat',
+        importMerge: 'Flet',
+        markdownScope: 'Markdown-eksport',
+        markdownScopeAll: 'Alle faner',
+        markdownScopeTab: 'Aktuel fane',
+        markdownScopeGroup: 'Aktuel gruppe',
+        timeReport: 'Tidsrapport',
+        timeReportHint: 'Registreret tid pr. opgave, henført til dagen den blev fuldført (eller oprettet, hvis den stadig er åben).',
+        reportFrom: 'Fra',
+        reportTo: 'Til',
+        reportFormat: 'Format',
+        reportGrouping: 'Gruppér efter',
+        reportGroupTask: 'Opgave',
+        reportGroupDay: 'Dag',
+        reportGroupTab: 'Fane',
+        reportGroupGroup: 'Gruppe',
+        reportExportFailed: 'Tidsrapporten kunne ikke eksporteres.',
         connect: 'Forbind',
         language: 'Sprog',
         languagePickerCurrent: 'Nuværende sprog',
@@ -389,6 +501,35 @@ const translations = {
         addedToPlanner: 'Tilføjet til planlægger',
         tabGroupsInfo: 'Organiser dine to-do lister i grupper (vist i en topbar).',
         planModeInfo: 'En dansk-inspireret kalender, du kan synkronisere med vigtige opgaver eller begivenheder fra enhver ekstern kalender \u2014 et fugleperspektiv over m\u00e5nederne eller ugen forude.',
+        pomodoro: 'Pomodoro',
+        pomodoroInfo: 'Del fokussessioner op i arbejdsperioder med korte pauser og en lang pause efter et antal runder.',
+        pomodoroWork: 'Arbejde (min)',
+        pomodoroShortBreak: 'Kort pause (min)',
+        pomodoroLongBreak: 'Lang pause (min)',
+        pomodoroCycles: 'Runder før lang pause',
+        pomodoroSkipBreak: 'Spring over',
+        shortBreak: 'Kort pause',
+        longBreak: 'Lang pause',
+        multiplePins: 'Flere fokusvinduer',
+        multiplePinsInfo: 'Hold et fokusvindue åbent for hver fokuseret opgave, stablet så de ikke overlapper, hver med sin egen timer.',
+        idlePause: 'Pause ved inaktivitet',
+        idleInfo: 'Sæt fokustimeren på pause, når der ikke er tastatur- eller musebrug, og spørg om tiden væk skal beholdes, når du vender tilbage.',
+        idleThreshold: 'Inaktiv efter (min)',
+        idleAway: 'Væk i {duration}',
+        idleKeep: 'Behold',
+        idleDiscard: 'Kassér',
+        globalShortcuts: 'Globale genveje',
+        shortcutsInfo: 'Klik på en genvej og tryk på de nye taster, eller Backspace for at slå den fra. De virker også, når ReDD To-Do er i baggrunden.',
+        shortcutQuickAdd: 'Hurtig tilføjelse',
+        shortcutToggleFocus: 'Start eller stop fokus',
+        shortcutCompleteTask: 'Fuldfør fokuseret opgave',
+        shortcutToggleTimer: 'Pause eller genoptag timer',
+        shortcutOff: 'Fra',
+        shortcutPress: 'Tryk på taster…',
+        automationApi: 'Automatiserings-API',
+        rpcEnable: 'Lokal automatiserings-socket',
+        rpcInfo: 'Lader editorer, shell-prompter og statuslinjer vise, tilføje, fuldføre og fokusere opgaver via en JSON-RPC-socket på denne computer. Klienter skal bruge tokenet fra token-filen ved siden af socketten.',
+        rpcListening: 'Lytter på',
         dataManagement: 'Data',
         dataManagementDesc: 'Sikkerhedskopier eller gendan dine data.',
         exportBackup: 'Eksporter sikkerhedskopi',
@@ -424,6 +565,16 @@ const translations = {
         // Focus mode
         focus: 'Fokus',
         exitFocus: 'Afslut fokus',
+        // Tray menu
+        trayNoFocusSession: 'Ingen fokussession',
+        trayStartFocus: 'Start fokus',
+        trayStartFocusOn: 'Start fokus: {task}',
+        trayStopFocus: 'Stop fokus',
+        trayCompleteTask: 'Fuldfør opgave',
+        traySwitchToFavourite: 'Skift til favorit',
+        trayOpenApp: 'Åbn ReDD To-Do',
+        trayUntitledTask: 'Unavngivet opgave',
+        trayPaused: 'på pause',
         // Time
         minutes: 'm',
         rebrandOnboardingTitleHtml:
@@ -473,12 +624,9 @@ const groupsContainerMain = document.querySelector('.groups-container');
 const tabsContainerMain = document.querySelector('.tabs-container');
 
 // Basecamp State
+// The backend keeps the tokens; this mirrors its connection status.
 let basecampConfig = {
     accountId: null,
-    accessToken: null,
-    refreshToken: null,
-    clientId: null,
-    clientSecret: null,
     email: null,
     isConnected: false
 };
@@ -546,8 +694,6 @@ const bcAccountIdInput = document.getElementById('bc-account-id');
 const bcEmailInput = document.getElementById('bc-email');
 const bcAccessTokenInput = document.getElementById('bc-access-token');
 const bcRefreshTokenInput = document.getElementById('bc-refresh-token');
-const bcClientIdInput = document.getElementById('bc-client-id');
-const bcClientSecretInput = document.getElementById('bc-client-secret');
 const connectBcBtn = document.getElementById('connect-bc-btn');
 const disconnectBcBtn = document.getElementById('disconnect-bc-btn');
 const bcHelpLink = document.getElementById('bc-help-link');
@@ -663,7 +809,7 @@ window.addEventListener('storage', (e) => {
     // Keep focus panel in sync when the main window saves task changes.
     if (e.key === 'redd-todo-data' && e.newValue) {
         loadData();
-        if (!isFocusPanelWindow) {
+        if (!isFocusPanelWindow && !isCaptureWindow) {
             renderGroups();
             renderTabs();
             renderTasks();
@@ -797,6 +943,8 @@ function applyTranslations() {
     // Add task placeholder
     const newTaskInput = document.getElementById('new-task-input');
     if (newTaskInput) newTaskInput.placeholder = t('addTaskPlaceholder');
+    const captureInput = document.getElementById('capture-input');
+    if (captureInput) captureInput.placeholder = t('capturePlaceholder');
 
     // Done section
     const doneLabel = document.querySelector('.done-label');
@@ -841,6 +989,7 @@ function applyTranslations() {
     if (viewPlanBtn) viewPlanBtn.title = t('enablePlanMode');
 
     syncLanguagePickerUI();
+    syncTrayLabels();
 
     // Plan module - update labels if loaded
     if (planModuleLoaded && typeof PlanModule !== 'undefined' && PlanModule.refresh) {
@@ -848,6 +997,24 @@ function applyTranslations() {
     }
 }
 
+// The tray menu is built by the backend; the main window keeps it in the
+// app's language.
+function syncTrayLabels() {
+    if (!reddIsTauri || typeof tauriAPI === 'undefined') return;
+    if (isFocusPanelWindow || isCaptureWindow) return;
+    tauriAPI.setTrayLabels({
+        noFocusSession: t('trayNoFocusSession'),
+        startFocus: t('trayStartFocus'),
+        startFocusOn: t('trayStartFocusOn'),
+        stopFocus: t('trayStopFocus'),
+        completeTask: t('trayCompleteTask'),
+        switchToFavourite: t('traySwitchToFavourite'),
+        openApp: t('trayOpenApp'),
+        untitledTask: t('trayUntitledTask'),
+        paused: t('trayPaused'),
+    }).catch(err => console.warn('Failed to translate tray menu:', err));
+}
+
 function initLanguage() {
     const savedLanguage = localStorage.getItem('language') || 'en';
     currentLang = savedLanguage;
@@ -1094,6 +1261,7 @@ function finishCommonInit() {
     initLanguage();
 
     updateBasecampUI();
+    void syncBasecampStatus();
     updateRemindersUI();
 
     updatePlanButtonVisibility();
@@ -1115,6 +1283,10 @@ function finishCommonInit() {
 
     if (!isFocusPanelWindow) {
         void initUiZoom();
+        // Links that arrived while the app was starting are held until now.
+        tauriAPI.deepLinkReady().catch((err) => {
+            console.warn('Failed to release pending deep links:', err);
+        });
     }
 }
 
@@ -1128,8 +1300,53 @@ async function initUiZoom() {
     }
 }
 
+function populateCaptureTabSelect(select) {
+    select.innerHTML = '';
+    const groupName = (tab) => (enableGroups && groups[tab.groupId] ? `${groups[tab.groupId].name} / ` : '');
+    Object.values(tabs).forEach((tab) => {
+        const option = document.createElement('option');
+        option.value = tab.id;
+        option.textContent = `${groupName(tab)}${tab.name}`;
+        select.appendChild(option);
+    });
+    if (tabs[currentTabId]) select.value = currentTabId;
+}
+
+// "Write email 15" is parsed by the backend: the trailing number becomes the
+// expected duration in minutes.
+function initCaptureWindow() {
+    initTheme();
+    initLanguage();
+    loadData();
+    normalMode?.classList.add('hidden');
+    const captureMode = document.getElementById('capture-mode');
+    const input = document.getElementById('capture-input');
+    const tabSelect = document.getElementById('capture-tab-select');
+    if (!captureMode || !input || !tabSelect) return;
+    populateCaptureTabSelect(tabSelect);
+    tabSelect.classList.toggle('hidden', tabSelect.options.length < 2);
+    captureMode.classList.remove('hidden');
+    captureMode.addEventListener('keydown', (e) => {
+        if (e.key === 'Enter' && !e.isComposing) {
+            e.preventDefault();
+            if (!input.value.trim()) return;
+            tauriAPI.captureTask(input.value, tabSelect.value || null).catch((err) => {
+                console.warn('Failed to add captured task:', err);
+            });
+        } else if (e.key === 'Escape') {
+            e.preventDefault();
+            tauriAPI.windowClose();
+        }
+    });
+    input.focus();
+}
+
 // Initialize app
 function initApp() {
+    if (isCaptureWindow) {
+        initCaptureWindow();
+        return;
+    }
     if (!isFocusPanelWindow && detectFocusPanelByWindowLabel()) {
         markAsFocusPanelWindow();
     }
@@ -1151,7 +1368,10 @@ function initApp() {
         return;
     }
 
+    loadFocusPinSettingsUI();
+
     void (async () => {
+        await restoreStateFromBackendIfMissing();
         if (reddIsTauri && typeof tauriAPI !== 'undefined' && tauriAPI.getDistributionChannel) {
             try {
                 distributionChannel = await tauriAPI.getDistributionChannel() || 'desktop';
@@ -2267,7 +2487,7 @@ function focusTask(taskId, anchorElement = null) {
 
     // If this task is already focused (as indicated in the main window), clicking the icon exits focus mode.
     if (!isFocusPanelWindow && activeFocusTaskIds.has(taskId)) {
-        if (platform !== 'darwin') {
+        if (platform !== 'darwin' && !multipleFocusPins) {
             reddIpc.send('set-focus-mode-window-state', false);
         }
         reddIpc.send('exit-focus-mode', { taskId });
@@ -2277,16 +2497,16 @@ function focusTask(taskId, anchorElement = null) {
     }
 
     // Main-window behavior is platform-specific:
-    // - macOS: dedicated NSPanel focus window
+    // - macOS, or any platform with multiple pins: dedicated focus windows
     // - others: in-window focus mode
     if (!isFocusPanelWindow) {
-        if (platform === 'darwin') {
+        if (platform === 'darwin' || (reddIsTauri && multipleFocusPins)) {
             const anchorRect = anchorElement?.getBoundingClientRect?.();
             const anchorLeft = anchorRect ? anchorRect.left : null;
             const anchorRight = anchorRect ? anchorRect.right : null;
             const anchorTop = anchorRect ? anchorRect.top : null;
 
-            activeFocusTaskIds.clear();
+            if (!multipleFocusPins) activeFocusTaskIds.clear();
             activeFocusTaskIds.add(taskId);
             renderTasks();
             reddIpc.send('open-focus-window', {
@@ -4419,6 +4639,13 @@ function setupEventListeners() {
             planToggle.checked = enablePlan;
         }
 
+        loadFocusPinSettingsUI();
+        loadPomodoroSettingsUI();
+        loadIdleSettingsUI();
+        loadShortcutSettingsUI();
+        loadAutomaticBackupsUI();
+        loadRpcSettingsUI();
+
         // Show current version
         const versionEl = document.getElementById('current-app-version');
         if (versionEl) {
@@ -4475,6 +4702,36 @@ function setupEventListeners() {
         });
     }
 
+    const multiplePinsToggle = document.getElementById('enable-multiple-pins-toggle');
+    if (multiplePinsToggle) multiplePinsToggle.addEventListener('change', saveFocusPinSettingsFromUI);
+
+    const idleToggle = document.getElementById('enable-idle-toggle');
+    if (idleToggle) idleToggle.addEventListener('change', saveIdleSettingsFromUI);
+    const idleThresholdInput = document.getElementById('idle-threshold-input');
+    if (idleThresholdInput) idleThresholdInput.addEventListener('change', saveIdleSettingsFromUI);
+
+    const rpcToggle = document.getElementById('enable-rpc-toggle');
+    if (rpcToggle) rpcToggle.addEventListener('change', saveRpcSettingsFromUI);
+
+    document.querySelectorAll('.shortcut-input').forEach((input) => {
+        input.addEventListener('keydown', handleShortcutInputKeydown);
+        input.addEventListener('focus', () => {
+            input.value = t('shortcutPress');
+        });
+        input.addEventListener('blur', () => {
+            input.value = formatAccelerator(input.dataset.accelerator);
+        });
+    });
+
+    const pomodoroToggle = document.getElementById('enable-pomodoro-toggle');
+    if (pomodoroToggle) {
+        pomodoroToggle.addEventListener('change', savePomodoroSettingsFromUI);
+    }
+    Object.values(POMODORO_INPUT_IDS).forEach((id) => {
+        const input = document.getElementById(id);
+        if (input) input.addEventListener('change', savePomodoroSettingsFromUI);
+    });
+
     closeSettingsBtn.addEventListener('click', () => {
         settingsModal.classList.add('hidden');
     });
@@ -4541,23 +4798,23 @@ function setupEventListeners() {
         const accountId = bcAccountIdInput.value.trim();
         const token = bcAccessTokenInput.value.trim();
         const refreshToken = bcRefreshTokenInput.value.trim();
-        const clientId = bcClientIdInput.value.trim();
-        const clientSecret = bcClientSecretInput.value.trim();
         const email = bcEmailInput.value.trim();
 
         if (accountId && token) {
-            basecampConfig.accountId = accountId;
-            basecampConfig.accessToken = token;
-            basecampConfig.refreshToken = refreshToken || null;
-            basecampConfig.clientId = clientId || null;
-            basecampConfig.clientSecret = clientSecret || null;
-            basecampConfig.email = email;
-            basecampConfig.isConnected = true;
-            saveData();
-            updateBasecampUI();
-            updateRemindersUI();
-            updateSyncButtonState();
-            settingsModal.classList.add('hidden');
+            try {
+                const status = await tauriAPI.basecampImportTokens({
+                    accessToken: token,
+                    refreshToken: refreshToken || null,
+                    accountId: Number(accountId),
+                    email: email || null
+                });
+                applyBasecampStatus(status);
+                updateRemindersUI();
+                settingsModal.classList.add('hidden');
+            } catch (e) {
+                console.error('[Basecamp] Failed to save credentials:', e);
+                alert('Could not save Basecamp credentials: ' + e);
+            }
         }
     });
 
@@ -4661,17 +4918,12 @@ function setupEventListeners() {
         });
     }
 
-    disconnectBcBtn.addEventListener('click', () => {
-        basecampConfig.accountId = null;
-        basecampConfig.accessToken = null;
-        basecampConfig.refreshToken = null;
-        basecampConfig.clientId = null;
-        basecampConfig.clientSecret = null;
-        basecampConfig.email = null;
-        basecampConfig.isConnected = false;
-        saveData();
-        updateBasecampUI();
-        updateSyncButtonState();
+    disconnectBcBtn.addEventListener('click', async () => {
+        try {
+            applyBasecampStatus(await tauriAPI.basecampDisconnect());
+        } catch (e) {
+            console.error('[Basecamp] Failed to disconnect:', e);
+        }
     });
 
     if (bcHelpLink) {
@@ -5488,7 +5740,7 @@ function setupEventListeners() {
                 saveData();
             }
 
-            exitFocusMode();
+            exitFocusMode({ completed: true });
 
             // Animate the task completion after a short delay (matches macOS home flow).
             requestAnimationFrame(() => {
@@ -5565,6 +5817,7 @@ function setupEventListeners() {
             // Reset timer to start from 0
             focusStartTime = Date.now();
             updateFocusTimer();
+            setBackendFocusTimerElapsed(0);
 
             // Hide buttons immediately to show timer clearly
             const buttonsContainer = document.querySelector('.focus-buttons-container');
@@ -5618,6 +5871,36 @@ function setupEventListeners() {
         });
     }
 
+    const focusIdleKeepBtn = document.getElementById('focus-idle-keep-btn');
+    if (focusIdleKeepBtn) {
+        focusIdleKeepBtn.addEventListener('click', (e) => {
+            e.preventDefault();
+            e.stopPropagation();
+            resolveFocusIdleTime(true);
+        });
+    }
+    const focusIdleDiscardBtn = document.getElementById('focus-idle-discard-btn');
+    if (focusIdleDiscardBtn) {
+        focusIdleDiscardBtn.addEventListener('click', (e) => {
+            e.preventDefault();
+            e.stopPropagation();
+            resolveFocusIdleTime(false);
+        });
+    }
+
+    const focusBreakSkipBtn = document.getElementById('focus-break-skip-btn');
+    if (focusBreakSkipBtn) {
+        focusBreakSkipBtn.addEventListener('click', (e) => {
+            e.preventDefault();
+            e.stopPropagation();
+            if (!reddIsTauri || !focusedTaskId) return;
+            tauriAPI.skipPomodoroBreak(focusedTaskId).catch((err) => {
+                console.warn('Failed to skip break:', err);
+                hideFocusBreak();
+            });
+        });
+    }
+
     // Undo button handler
     const focusUndoBtn = document.getElementById('focus-undo-btn');
     if (focusUndoBtn) {
@@ -5628,6 +5911,7 @@ function setupEventListeners() {
             if (previousFocusStartTime) {
                 focusStartTime = previousFocusStartTime;
                 updateFocusTimer();
+                setBackendFocusTimerElapsed(Date.now() - previousFocusStartTime);
 
                 // Hide toast immediately
                 const toast = document.getElementById('focus-toast');
@@ -5958,6 +6242,257 @@ function setupEventListeners() {
     }
 }
 
+// Focus pin settings (stored by the backend, Tauri only)
+async function loadFocusPinSettingsUI() {
+    const row = document.getElementById('focus-pins-settings-row');
+    const toggle = document.getElementById('enable-multiple-pins-toggle');
+    if (row) row.classList.toggle('hidden', !reddIsTauri);
+    if (!reddIsTauri) return;
+    try {
+        const settings = await tauriAPI.getFocusPinSettings();
+        multipleFocusPins = !!settings.multiple;
+        if (toggle) toggle.checked = multipleFocusPins;
+    } catch (err) {
+        console.warn('Failed to load focus pin settings:', err);
+    }
+}
+
+async function saveFocusPinSettingsFromUI() {
+    const toggle = document.getElementById('enable-multiple-pins-toggle');
+    if (!toggle || !reddIsTauri) return;
+    try {
+        await tauriAPI.setFocusPinSettings({ multiple: toggle.checked });
+        multipleFocusPins = toggle.checked;
+    } catch (err) {
+        console.warn('Failed to save focus pin settings:', err);
+        loadFocusPinSettingsUI();
+    }
+}
+
+// Pomodoro settings (stored by the backend, Tauri only)
+const POMODORO_INPUT_IDS = {
+    workMinutes: 'pomodoro-work-input',
+    shortBreakMinutes: 'pomodoro-short-break-input',
+    longBreakMinutes: 'pomodoro-long-break-input',
+    cyclesBeforeLongBreak: 'pomodoro-cycles-input'
+};
+
+async function loadPomodoroSettingsUI() {
+    const row = document.getElementById('pomodoro-settings-row');
+    const lengthsRow = document.getElementById('pomodoro-lengths-row');
+    const toggle = document.getElementById('enable-pomodoro-toggle');
+    if (!row || !lengthsRow || !toggle) return;
+    if (!reddIsTauri) {
+        row.classList.add('hidden');
+        lengthsRow.classList.add('hidden');
+        return;
+    }
+    try {
+        const settings = await tauriAPI.getPomodoroSettings();
+        toggle.checked = !!settings.enabled;
+        lengthsRow.classList.toggle('hidden', !settings.enabled);
+        for (const [key, id] of Object.entries(POMODORO_INPUT_IDS)) {
+            const input = document.getElementById(id);
+            if (input) input.value = settings[key];
+        }
+    } catch (err) {
+        console.warn('Failed to load Pomodoro settings:', err);
+    }
+}
+
+async function savePomodoroSettingsFromUI() {
+    const toggle = document.getElementById('enable-pomodoro-toggle');
+    if (!toggle || !reddIsTauri) return;
+    const settings = { enabled: toggle.checked };
+    for (const [key, id] of Object.entries(POMODORO_INPUT_IDS)) {
+        const value = Number(document.getElementById(id)?.value);
+        if (Number.isFinite(value) && value > 0) settings[key] = value;
+    }
+    document.getElementById('pomodoro-lengths-row')?.classList.toggle('hidden', !toggle.checked);
+    try {
+        await tauriAPI.setPomodoroSettings(settings);
+    } catch (err) {
+        console.warn('Failed to save Pomodoro settings:', err);
+        loadPomodoroSettingsUI();
+    }
+}
+
+// Idle detection settings (Linux/X11 only for now)
+async function loadIdleSettingsUI() {
+    const row = document.getElementById('idle-settings-row');
+    const thresholdRow = document.getElementById('idle-threshold-row');
+    const toggle = document.getElementById('enable-idle-toggle');
+    const input = document.getElementById('idle-threshold-input');
+    if (!row || !thresholdRow || !toggle || !input) return;
+    const available = reddIsTauri && platform === 'linux';
+    row.classList.toggle('hidden', !available);
+    if (!available) {
+        thresholdRow.classList.add('hidden');
+        return;
+    }
+    try {
+        const settings = await tauriAPI.getIdleSettings();
+        toggle.checked = !!settings.enabled;
+        input.value = settings.thresholdMinutes;
+        thresholdRow.classList.toggle('hidden', !settings.enabled);
+    } catch (err) {
+        console.warn('Failed to load idle settings:', err);
+    }
+}
+
+async function saveIdleSettingsFromUI() {
+    const toggle = document.getElementById('enable-idle-toggle');
+    const input = document.getElementById('idle-threshold-input');
+    if (!toggle || !input || !reddIsTauri) return;
+    const settings = { enabled: toggle.checked };
+    const threshold = Number(input.value);
+    if (Number.isFinite(threshold) && threshold > 0) settings.thresholdMinutes = threshold;
+    document.getElementById('idle-threshold-row')?.classList.toggle('hidden', !toggle.checked);
+    try {
+        await tauriAPI.setIdleSettings(settings);
+    } catch (err) {
+        console.warn('Failed to save idle settings:', err);
+        loadIdleSettingsUI();
+    }
+}
+
+// Global shortcuts (registered by the backend, Tauri only). Accelerators use
+// the backend's syntax, e.g. "CmdOrCtrl+Shift+Space".
+const MODIFIER_KEYS = ['Control', 'Shift', 'Alt', 'Meta', 'OS'];
+
+function formatAccelerator(accelerator) {
+    if (!accelerator) return t('shortcutOff');
+    const primary = platform === 'darwin' ? 'Cmd' : 'Ctrl';
+    return accelerator.replace(/CmdOrCtrl|CommandOrControl/gi, primary);
+}
+
+function acceleratorFromKeyEvent(e) {
+    if (MODIFIER_KEYS.includes(e.key)) return null;
+    const parts = [];
+    const primary = platform === 'darwin' ? e.metaKey : e.ctrlKey;
+    if (primary) parts.push('CmdOrCtrl');
+    if (platform === 'darwin' && e.ctrlKey) parts.push('Ctrl');
+    if (e.altKey) parts.push('Alt');
+    if (e.shiftKey) parts.push('Shift');
+    if (platform !== 'darwin' && e.metaKey) parts.push('Super');
+    if (parts.length === 0) return null;
+    let key = e.code || e.key;
+    if (/^Key[A-Z]$/.test(key)) key = key.slice(3);
+    else if (/^Digit[0-9]$/.test(key)) key = key.slice(5);
+    parts.push(key);
+    return parts.join('+');
+}
+
+function renderShortcutInputs(settings, statuses = []) {
+    document.querySelectorAll('.shortcut-input').forEach((input) => {
+        const action = input.dataset.shortcutAction;
+        const accelerator = settings[action] || '';
+        const status = statuses.find((s) => s.action === action);
+        const error = accelerator && status && !status.registered ? status.error : null;
+        input.dataset.accelerator = accelerator;
+        input.value = formatAccelerator(accelerator);
+        input.classList.toggle('has-error', !!error);
+        const errorEl = document.querySelector(`[data-shortcut-error="${action}"]`);
+        if (errorEl) {
+            errorEl.textContent = error || '';
+            errorEl.classList.toggle('hidden', !error);
+        }
+    });
+}
+
+async function loadShortcutSettingsUI() {
+    const section = document.getElementById('shortcuts-settings-section');
+    if (section) section.classList.toggle('hidden', !reddIsTauri);
+    if (!reddIsTauri) return;
+    try {
+        const [settings, statuses] = await Promise.all([
+            tauriAPI.getShortcutSettings(),
+            tauriAPI.getShortcutStatus()
+        ]);
+        renderShortcutInputs(settings, statuses);
+    } catch (err) {
+        console.warn('Failed to load shortcut settings:', err);
+    }
+}
+
+async function saveShortcutSettingsFromUI() {
+    if (!reddIsTauri) return;
+    const settings = {};
+    document.querySelectorAll('.shortcut-input').forEach((input) => {
+        settings[input.dataset.shortcutAction] = input.dataset.accelerator || null;
+    });
+    try {
+        // Conflicts come back per shortcut and are shown next to it.
+        const statuses = await tauriAPI.setShortcutSettings(settings);
+        renderShortcutInputs(settings, statuses);
+    } catch (err) {
+        console.warn('Failed to save shortcut settings:', err);
+        loadShortcutSettingsUI();
+    }
+}
+
+// Local automation API (Unix socket, so not on Windows)
+function renderRpcStatus(status) {
+    const hint = document.getElementById('rpc-status');
+    if (!hint) return;
+    const text = status?.error || (status?.running ? `${t('rpcListening')} ${status.socketPath}` : '');
+    hint.textContent = text;
+    hint.classList.toggle('shortcut-error', !!status?.error);
+    hint.classList.toggle('hidden', !text);
+}
+
+async function loadRpcSettingsUI() {
+    const section = document.getElementById('automation-settings-section');
+    const toggle = document.getElementById('enable-rpc-toggle');
+    const available = reddIsTauri && platform !== 'win32';
+    if (section) section.classList.toggle('hidden', !available);
+    if (!available || !toggle) return;
+    try {
+        const [settings, status] = await Promise.all([
+            tauriAPI.getRpcSettings(),
+            tauriAPI.getRpcStatus()
+        ]);
+        toggle.checked = !!settings.enabled;
+        renderRpcStatus(status);
+    } catch (err) {
+        console.warn('Failed to load automation API settings:', err);
+    }
+}
+
+async function saveRpcSettingsFromUI() {
+    const toggle = document.getElementById('enable-rpc-toggle');
+    if (!toggle || !reddIsTauri) return;
+    try {
+        renderRpcStatus(await tauriAPI.setRpcSettings({ enabled: toggle.checked }));
+    } catch (err) {
+        console.warn('Failed to save automation API settings:', err);
+        loadRpcSettingsUI();
+    }
+}
+
+function handleShortcutInputKeydown(e) {
+    const input = e.currentTarget;
+    if (e.key === 'Tab') return;
+    e.preventDefault();
+    if (e.key === 'Escape') {
+        input.blur();
+        return;
+    }
+    const noModifiers = !e.ctrlKey && !e.metaKey && !e.altKey && !e.shiftKey;
+    if ((e.key === 'Backspace' || e.key === 'Delete') && noModifiers) {
+        input.dataset.accelerator = '';
+        input.value = formatAccelerator('');
+        saveShortcutSettingsFromUI();
+        return;
+    }
+    const accelerator = acceleratorFromKeyEvent(e);
+    if (!accelerator) return;
+    input.dataset.accelerator = accelerator;
+    input.value = formatAccelerator(accelerator);
+    input.blur();
+    saveShortcutSettingsFromUI();
+}
+
 // Focus mode functions
 function persistCurrentFocusTaskTime() {
     if (!focusedTaskId || !isFocusMode || !focusStartTime) return;
@@ -6141,6 +6676,7 @@ function enterFocusMode(taskName, duration = null, initialTimeSpent = 0, preserv
     console.log('enterFocusMode called with taskName:', taskName, 'duration:', duration, 'initialTimeSpent:', initialTimeSpent);
     // If we re-enter focus mode (e.g. switching tasks), avoid duplicating timer intervals.
     stopFocusTimer();
+    hideFocusBreak();
     isFocusMode = true;
     focusDuration = duration; // Set the duration
     preTaskSwitchMenuHeight = null;
@@ -6207,8 +6743,11 @@ function enterFocusMode(taskName, duration = null, initialTimeSpent = 0, preserv
     }
 }
 
-function exitFocusMode() {
+function exitFocusMode({ completed = null } = {}) {
     const closingTaskId = focusedTaskId;
+    // Tell the backend whether the session ended in completion; the store
+    // on disk may not have the completed task yet.
+    const closingCompleted = completed ?? !!(closingTaskId && getTaskContext(closingTaskId)?.task.completed);
 
     // Save progress for the current task before leaving focus mode.
     persistCurrentFocusTaskTime();
@@ -6220,17 +6759,20 @@ function exitFocusMode() {
         activeFocusTaskIds.clear();
     }
     focusDuration = null; // Reset duration
+    focusTimerTaskId = null; // exit-focus-mode stops the backend timer
     stopFocusTimer();
+    hideFocusBreak();
+    hideFocusIdlePrompt();
 
     // Reset overtime style
     if (focusTimer) {
-        focusTimer.classList.remove('overtime');
+        focusTimer.classList.remove('overtime', 'paused');
     }
 
     // If this is the focus panel window, just call the backend to close it
     // Don't switch to normal mode as that would show main app content in the panel
     if (isFocusPanelWindow) {
-        reddIpc.send('exit-focus-mode', { taskId: closingTaskId });
+        reddIpc.send('exit-focus-mode', { taskId: closingTaskId, completed: closingCompleted });
         return;
     }
 
@@ -6252,14 +6794,32 @@ function exitFocusMode() {
     if (platform !== 'darwin') {
         reddIpc.send('set-focus-mode-window-state', false);
     }
-    reddIpc.send('exit-focus-mode', { taskId: closingTaskId });
+    reddIpc.send('exit-focus-mode', { taskId: closingTaskId, completed: closingCompleted });
 }
 
 function startFocusTimer(initialTimeSpent = 0) {
     focusStartTime = Date.now() - initialTimeSpent;
     // Update immediately
     updateFocusTimer();
-    focusTimerInterval = setInterval(updateFocusTimer, 1000);
+    if (reddIsTauri && focusedTaskId) {
+        // The backend timer is authoritative; focus-tick events drive the display.
+        if (focusTimerTaskId && focusTimerTaskId !== focusedTaskId) {
+            tauriAPI.stopFocusTimer(focusTimerTaskId).catch((err) => {
+                console.warn('Failed to stop previous focus timer:', err);
+            });
+        }
+        focusTimerTaskId = focusedTaskId;
+        tauriAPI.startFocusTimer(
+            focusedTaskId,
+            focusTaskName?.textContent || null,
+            focusDuration ?? null,
+            initialTimeSpent
+        ).then(applyFocusTimerSnapshot).catch((err) => {
+            console.warn('Failed to start focus timer:', err);
+        });
+    } else {
+        focusTimerInterval = setInterval(updateFocusTimer, 1000);
+    }
 
     // Periodically persist cumulative timeSpent so a crash/force-quit doesn't
     // lose progress — exit and task-switch still persist explicitly.
@@ -6267,6 +6827,94 @@ function startFocusTimer(initialTimeSpent = 0) {
     focusPersistInterval = setInterval(persistCurrentFocusTaskTime, FOCUS_PERSIST_INTERVAL_MS);
 }
 
+// Sync the display with a reading from the backend focus timer.
+function applyFocusTimerSnapshot(snapshot) {
+    if (!snapshot || !isFocusMode || snapshot.taskId !== focusedTaskId) return;
+    if (snapshot.state === 'idle') return;
+    focusStartTime = Date.now() - snapshot.elapsedMs;
+    focusDuration = snapshot.durationMs ? snapshot.durationMs / 60000 : null;
+    if (focusTimer) {
+        focusTimer.classList.toggle('paused', snapshot.state === 'paused');
+    }
+    updateFocusTimer();
+}
+
+// Pomodoro break view: the backend pauses the focus timer for the break and
+// announces it; the panel only counts down to endsAt.
+function updateFocusBreakTimer() {
+    const timerEl = document.getElementById('focus-break-timer');
+    if (!timerEl || !focusBreakEndsAt) return;
+    const remaining = Math.max(0, Math.ceil((focusBreakEndsAt - Date.now()) / 1000));
+    const minutes = Math.floor(remaining / 60);
+    const seconds = remaining % 60;
+    timerEl.textContent = `${minutes.toString().padStart(2, '0')}:${seconds.toString().padStart(2, '0')}`;
+}
+
+function showFocusBreak(payload) {
+    const breakEl = document.getElementById('focus-break');
+    if (!breakEl) return;
+    const label = document.getElementById('focus-break-label');
+    if (label) {
+        label.textContent = t(payload.phase === 'longBreak' ? 'longBreak' : 'shortBreak');
+    }
+    focusBreakEndsAt = payload.endsAt;
+    breakEl.classList.remove('hidden');
+    updateFocusBreakTimer();
+    if (focusBreakInterval) clearInterval(focusBreakInterval);
+    focusBreakInterval = setInterval(updateFocusBreakTimer, 1000);
+}
+
+function formatAwayDuration(ms) {
+    const totalMinutes = Math.max(1, Math.round(ms / 60000));
+    const hours = Math.floor(totalMinutes / 60);
+    const minutes = totalMinutes % 60;
+    if (hours === 0) return `${minutes}m`;
+    return minutes === 0 ? `${hours}h` : `${hours}h ${minutes}m`;
+}
+
+// Idle return prompt: the backend resumed the timer and waits for keep/discard.
+function showFocusIdlePrompt(payload) {
+    const prompt = document.getElementById('focus-idle-prompt');
+    if (!prompt) return;
+    const label = document.getElementById('focus-idle-label');
+    if (label) {
+        const awayMs = payload.totalIdleMs ?? payload.countedIdleMs ?? 0;
+        label.textContent = t('idleAway').replace('{duration}', formatAwayDuration(awayMs));
+    }
+    prompt.classList.remove('hidden');
+}
+
+function hideFocusIdlePrompt() {
+    document.getElementById('focus-idle-prompt')?.classList.add('hidden');
+}
+
+function resolveFocusIdleTime(keep) {
+    hideFocusIdlePrompt();
+    if (!reddIsTauri) return;
+    tauriAPI.resolveIdleTime(keep).catch((err) => {
+        console.warn('Failed to resolve idle time:', err);
+    });
+}
+
+function hideFocusBreak() {
+    if (focusBreakInterval) {
+        clearInterval(focusBreakInterval);
+        focusBreakInterval = null;
+    }
+    focusBreakEndsAt = null;
+    document.getElementById('focus-break')?.classList.add('hidden');
+}
+
+// Push a local reset/undo of the elapsed time to the backend timer.
+function setBackendFocusTimerElapsed(elapsedMs) {
+    if (!reddIsTauri || !focusTimerTaskId) return;
+    tauriAPI.setFocusTimerElapsed(focusTimerTaskId, elapsedMs)
+        .then(applyFocusTimerSnapshot)
+        .catch((err) => {
+            console.warn('Failed to update focus timer:', err);
+        });
+}
+
 function stopFocusTimer() {
     if (focusTimerInterval) {
         clearInterval(focusTimerInterval);
@@ -6966,8 +7614,8 @@ async function handleTaskSyncOnMove(task, sourceTab, targetTab) {
 
     // For Basecamp: move the todo to the new list (preserves the todo, no duplicates)
     if (sourceTab.basecampListId && task.basecampId && targetTab.basecampListId) {
-        if (!basecampConfig.isConnected || !basecampConfig.accessToken) {
-            console.warn('Basecamp not connected or no access token - skipping Basecamp move');
+        if (!basecampConfig.isConnected) {
+            console.warn('Basecamp not connected - skipping Basecamp move');
             return;
         }
         console.log('Moving Basecamp todo:', task.basecampId, 'from list', sourceTab.basecampListId, 'to list', targetTab.basecampListId);
@@ -7454,6 +8102,110 @@ reddIpc.on('enter-focus-mode', (event, payload) => {
     enterFocusMode(payload);
 });
 
+reddIpc.on('focus-tick', (event, snapshot) => {
+    applyFocusTimerSnapshot(snapshot);
+});
+
+reddIpc.on('focus-break-started', (event, payload) => {
+    if (!isFocusMode || !payload || payload.taskId !== focusedTaskId) return;
+    showFocusBreak(payload);
+});
+
+reddIpc.on('focus-break-ended', (event, payload) => {
+    if (!payload || payload.taskId !== focusedTaskId) return;
+    hideFocusBreak();
+});
+
+// Tray menu actions arrive at the window hosting the focus session (or the
+// main window when starting one) and go through the same paths as its buttons.
+reddIpc.on('tray-action', (event, payload) => {
+    const action = payload?.action;
+    const taskId = payload?.taskId;
+    const hostsTask = isFocusMode && !!focusedTaskId && focusedTaskId === taskId;
+    switch (action) {
+        case 'start':
+            if (!isFocusPanelWindow && taskId && !activeFocusTaskIds.has(taskId)) {
+                focusTask(taskId);
+            }
+            break;
+        case 'stop':
+            if (hostsTask) exitFocusBtn.click();
+            break;
+        case 'complete':
+            if (hostsTask) completeFocusBtn.click();
+            break;
+        case 'switch':
+            if (!payload.nextTaskId) break;
+            if (hostsTask) {
+                switchFocusTask(payload.nextTaskId);
+            } else if (!isFocusPanelWindow && !taskId) {
+                focusTask(payload.nextTaskId);
+            }
+            break;
+    }
+});
+
+// The backend added a task from the Quick Add window; `store-updated` has
+// already brought it in. Basecamp lists still need the todo created there.
+reddIpc.on('task-captured', (event, payload) => {
+    if (isFocusPanelWindow || isCaptureWindow || !payload?.taskId) return;
+    const tab = tabs[payload.tabId];
+    const task = tab?.tasks.find((t) => t.id === payload.taskId);
+    if (task && tab.basecampListId && basecampConfig.isConnected) {
+        createBasecampTodo(payload.tabId, task);
+    }
+});
+
+// `redddo://complete` and `redddo://open` links resolved by the backend.
+reddIpc.on('deep-link-action', (event, payload) => {
+    if (isFocusPanelWindow || isCaptureWindow || !payload) return;
+    switch (payload.action) {
+        case 'complete': {
+            const context = payload.taskId ? getTaskContext(payload.taskId) : null;
+            if (context && !context.task.completed) toggleTask(payload.taskId);
+            break;
+        }
+        case 'open':
+            if (payload.tabId && tabs[payload.tabId]) {
+                switchView('lists');
+                switchToTab(payload.tabId);
+            }
+            break;
+    }
+});
+
+// `redddo://focus` and `redddo://complete` links opened from outside the app
+// wait for the user's go-ahead.
+reddIpc.on('deep-link-confirm', async (event, payload) => {
+    if (isFocusPanelWindow || isCaptureWindow || !payload?.taskId) return;
+    const focus = payload.action === 'focus';
+    const confirmed = await showConfirmModal(
+        t('deepLinkConfirmTitle'),
+        t(focus ? 'deepLinkConfirmFocus' : 'deepLinkConfirmComplete').replace('{task}', payload.text || payload.taskId),
+        t(focus ? 'deepLinkStartFocus' : 'deepLinkComplete'),
+        t('cancel')
+    );
+    if (!confirmed) return;
+    tauriAPI.confirmDeepLinkAction(payload.action, payload.taskId).catch((err) => {
+        console.warn('Failed to run link action:', err);
+    });
+});
+
+reddIpc.on('deep-link-error', (event, payload) => {
+    if (isFocusPanelWindow || isCaptureWindow) return;
+    console.warn('Deep link failed:', payload?.url, payload?.message);
+    alert(`${t('deepLinkFailed')}: ${payload?.message || payload?.url || ''}`);
+});
+
+reddIpc.on('focus-idle-returned', (event, payload) => {
+    if (!isFocusMode || !payload || !payload.taskIds?.includes(focusedTaskId)) return;
+    showFocusIdlePrompt(payload);
+});
+
+reddIpc.on('focus-idle-resolved', () => {
+    hideFocusIdlePrompt();
+});
+
 reddIpc.on('exit-focus-mode', () => {
     if (!isFocusPanelWindow) return;
     exitFocusMode();
@@ -7530,94 +8282,36 @@ reddIpc.on('task-updated', (event, payload) => {
     renderTasks();
 });
 
-// Basecamp Authentication Logic
-
-// Handle deep link callback from Tauri (redddo://oauth-callback?...)
-if (reddIsTauri && typeof tauriAPI !== 'undefined') {
-    tauriAPI.onEvent('deep-link-received', async (url) => {
-        console.log('[Basecamp OAuth] Deep link received:', url);
-
-        try {
-            const parsed = new URL(url);
-            if ((parsed.protocol !== 'redddo:' && parsed.protocol !== 'reddtodo:') || parsed.hostname !== 'oauth-callback') {
-                console.log('[Basecamp OAuth] Ignoring non-OAuth deep link');
-                return;
-            }
-
-            // Check for error
-            const error = parsed.searchParams.get('error');
-            if (error) {
-                console.error('[Basecamp OAuth] Error from callback:', error);
-                const errorDesc = parsed.searchParams.get('error_description') || error;
-                alert(`Basecamp authentication failed: ${errorDesc}`);
-                if (oauthConnectBtn) {
-                    oauthConnectBtn.textContent = t('connect');
-                    oauthConnectBtn.disabled = false;
-                }
-                return;
-            }
-
-            // Extract tokens
-            const accessToken = parsed.searchParams.get('access_token');
-            const refreshToken = parsed.searchParams.get('refresh_token');
-            const expiresIn = parsed.searchParams.get('expires_in');
-
-            if (accessToken) {
-                console.log('[Basecamp OAuth] SUCCESS - Tokens received via deep link');
-
-                basecampConfig.accessToken = accessToken;
-                basecampConfig.refreshToken = refreshToken;
-
-                // Fetch identity and save
-                try {
-                    await fetchBasecampIdentity();
-                    console.log('[Basecamp OAuth] Identity fetched, account ID:', basecampConfig.accountId);
-                } catch (e) {
-                    console.error('[Basecamp OAuth] Failed to fetch identity:', e);
-                }
-
-                basecampConfig.isConnected = true;
-                saveData();
-                updateBasecampUI();
-                updateSyncButtonState();
-
-                if (oauthConnectBtn) {
-                    oauthConnectBtn.textContent = t('connect');
-                    oauthConnectBtn.disabled = false;
-                }
-            } else {
-                console.error('[Basecamp OAuth] No access token in callback');
-            }
-        } catch (e) {
-            console.error('[Basecamp OAuth] Error parsing deep link:', e);
-        }
-    });
-}
-
-reddIpc.on('basecamp-auth-success', async (event, data) => {
-    console.log('[Basecamp OAuth] SUCCESS - Tokens received from main process');
-    console.log('[Basecamp OAuth] Access token received:', !!data.access_token);
-    console.log('[Basecamp OAuth] Refresh token received:', !!data.refresh_token);
-
-    basecampConfig.accessToken = data.access_token;
-    basecampConfig.refreshToken = data.refresh_token;
-    basecampConfig.clientId = data.client_id;
-    basecampConfig.clientSecret = data.client_secret;
-
-    // Now we need to get the account ID (Identity)
-    console.log('[Basecamp OAuth] Fetching Basecamp identity...');
+// The backend changed tasks in the store (imports, quick capture, ...). The
+// store may not have our latest saves yet, so only the changed tasks are
+// merged into our copy; the main window does it for every window, since they
+// share localStorage.
+reddIpc.on('store-updated', async (event, payload) => {
+    if (!payload?.data || !payload.changes) return;
+    if (isFocusPanelWindow || isCaptureWindow) return;
     try {
-        await fetchBasecampIdentity();
-        console.log('[Basecamp OAuth] Identity fetched successfully, account ID:', basecampConfig.accountId);
+        const raw = localStorage.getItem('redd-todo-data');
+        const local = raw ? JSON.parse(raw) : { tabs: {} };
+        const merged = await tauriAPI.mergeStoreChanges(local, payload.data, payload.changes);
+        if (!merged?.tabs) return;
+        applySerializedAppState(JSON.stringify(merged));
+        persistStateToBackend(merged);
+        if (pendingImportTabId && tabs[pendingImportTabId]) {
+            switchToTab(pendingImportTabId);
+            pendingImportTabId = null;
+        }
     } catch (e) {
-        console.error('[Basecamp OAuth] Failed to fetch identity:', e);
+        console.error('[store-updated] merge failed:', e);
     }
+});
 
-    basecampConfig.isConnected = true;
-    saveData();
-    updateBasecampUI();
-    updateSyncButtonState();
-    console.log('[Basecamp OAuth] Connection complete! UI updated.');
+// Basecamp Authentication Logic
+
+// The backend finishes every OAuth callback (including redddo://oauth-callback
+// deep links), keeps the tokens and sends only the connection status.
+reddIpc.on('basecamp-auth-success', (event, status) => {
+    console.log('[Basecamp OAuth] SUCCESS - Connected to account', status.accountId);
+    applyBasecampStatus(status);
 
     // Reset button
     if (oauthConnectBtn) {
@@ -7626,6 +8320,12 @@ reddIpc.on('basecamp-auth-success', async (event, data) => {
     }
 });
 
+// Sign-ins, disconnects, and refresh tokens Basecamp stopped accepting.
+reddIpc.on('basecamp-status-changed', (event, status) => {
+    if (!status) return;
+    applyBasecampStatus(status);
+});
+
 reddIpc.on('basecamp-auth-error', (event, errorMessage) => {
     console.error('[Basecamp OAuth] ERROR - Authentication failed:', errorMessage);
     alert('Authentication failed: ' + errorMessage);
@@ -7659,6 +8359,11 @@ function mergeFocusedTaskPatchIntoStorage(taskId, patch) {
         if (!updated) return false;
 
         localStorage.setItem('redd-todo-data', JSON.stringify(data));
+        if (reddIsTauri && typeof tauriAPI !== 'undefined' && tauriAPI.patchTask) {
+            tauriAPI.patchTask(taskId, patch).catch(e => {
+                console.error('[mergeFocusedTaskPatchIntoStorage] backend patch failed:', e);
+            });
+        }
 
         const liveContext = getTaskContext(taskId);
         if (liveContext) {
@@ -7703,6 +8408,70 @@ function saveData() {
     }
 
     localStorage.setItem('redd-todo-data', nextState);
+    persistStateToBackend(data);
+}
+
+/** Snapshot waiting to be written to the Rust task store, and the loop writing it. */
+let pendingBackendState = null;
+let backendSaveLoop = null;
+
+/** Mirror the saved snapshot into the Rust task store so it survives a
+ *  crashed or killed webview. Saves go out one at a time, and only the newest
+ *  snapshot waiting behind a save in flight is sent, so an older snapshot can
+ *  never land after a newer one. */
+function persistStateToBackend(data) {
+    if (!reddIsTauri || typeof tauriAPI === 'undefined' || !tauriAPI.saveState) return;
+    pendingBackendState = data;
+    if (!backendSaveLoop) {
+        backendSaveLoop = drainBackendSaves();
+    }
+}
+
+async function drainBackendSaves() {
+    while (pendingBackendState) {
+        const state = pendingBackendState;
+        pendingBackendState = null;
+        try {
+            await tauriAPI.saveState(state);
+        } catch (e) {
+            console.error('[persistStateToBackend] failed:', e);
+        }
+        try {
+            await tauriAPI.savePlanData(collectPlanDataForBackup());
+        } catch (e) {
+            console.error('[persistStateToBackend] plan data failed:', e);
+        }
+    }
+    backendSaveLoop = null;
+}
+
+/**
+ * Restore `redd-todo-data` from the Rust task store when the webview lost it,
+ * and merge in tasks changed from the command line while the app was closed.
+ */
+async function restoreStateFromBackendIfMissing() {
+    if (!reddIsTauri || typeof tauriAPI === 'undefined' || !tauriAPI.loadState) return;
+    try {
+        const local = localStorage.getItem('redd-todo-data');
+        const changed = await tauriAPI.takeExternalStoreChanges(local ? JSON.parse(local) : null);
+        if (changed && changed.tabs) {
+            localStorage.setItem('redd-todo-data', JSON.stringify(changed));
+            loadData();
+            return;
+        }
+    } catch (e) {
+        console.error('[restoreStateFromBackendIfMissing] external changes failed:', e);
+    }
+    if (localStorage.getItem('redd-todo-data')) return;
+    try {
+        const stored = await tauriAPI.loadState();
+        if (stored && stored.tabs) {
+            localStorage.setItem('redd-todo-data', JSON.stringify(stored));
+            loadData();
+        }
+    } catch (e) {
+        console.error('[restoreStateFromBackendIfMissing] failed:', e);
+    }
 }
 
 /** Best-effort flush before quit/hide — rewrites localStorage so WebKit commits to disk. */
@@ -7763,10 +8532,6 @@ function loadData() {
             taskCounter = data.taskCounter || 0;
             basecampConfig = data.basecampConfig || {
                 accountId: null,
-                accessToken: null,
-                refreshToken: null,
-                clientId: null,
-                clientSecret: null,
                 email: null,
                 isConnected: false
             };
@@ -7794,45 +8559,6 @@ function loadData() {
     }
 }
 
-// New function to fetch identity/accounts
-async function fetchBasecampIdentity() {
-    try {
-
-        // Use Tauri HTTP client if available (bypasses CORS)
-        const fetchFn = (reddIsTauri && typeof tauriAPI !== 'undefined' && tauriAPI.fetch)
-            ? tauriAPI.fetch.bind(tauriAPI)
-            : fetch;
-
-        const response = await fetchFn('https://launchpad.37signals.com/authorization.json', {
-            headers: {
-                'Authorization': `Bearer ${basecampConfig.accessToken}`
-            }
-        });
-
-
-
-        if (!response.ok) throw new Error('Failed to fetch identity');
-
-        const data = await response.json();
-
-        const accounts = data.accounts;
-
-        if (accounts && accounts.length > 0) {
-            // For now, default to the first account. 
-            // Ideally we'd let the user choose if > 1, but this is a good start.
-            const account = accounts[0];
-            basecampConfig.accountId = account.id;
-            basecampConfig.email = data.identity.email_address;
-            console.log(`[Basecamp] Connected to account: ${account.name} (${account.id})`);
-        } else {
-            throw new Error('No Basecamp accounts found for this user.');
-        }
-    } catch (e) {
-        console.error('[Basecamp] Identity Error:', e);
-        alert('Could not fetch Basecamp account details. Please try again.');
-    }
-}
-
 // Basecamp API Logic
 function updateBasecampUI() {
     if (basecampConfig.isConnected) {
@@ -7849,13 +8575,6 @@ function updateBasecampUI() {
             bcAccountInfo.textContent = `Account ID: ${basecampConfig.accountId} ${basecampConfig.email ? `(${basecampConfig.email})` : ''}`;
         }
 
-        // Fill hidden inputs (legacy support)
-        bcAccountIdInput.value = basecampConfig.accountId || '';
-        bcAccessTokenInput.value = basecampConfig.accessToken || '';
-        bcRefreshTokenInput.value = basecampConfig.refreshToken || '';
-        bcClientIdInput.value = basecampConfig.clientId || '';
-        bcClientSecretInput.value = basecampConfig.clientSecret || '';
-        bcEmailInput.value = basecampConfig.email || '';
     } else {
         bcConnectionStatus.classList.add('hidden');
         bcLoginForm.classList.remove('hidden');
@@ -7868,114 +8587,49 @@ function updateBasecampUI() {
         bcAccountIdInput.value = '';
         bcAccessTokenInput.value = '';
         bcRefreshTokenInput.value = '';
-        bcClientIdInput.value = '';
-        bcClientSecretInput.value = '';
         bcEmailInput.value = '';
     }
 }
 
-let basecampRefreshPromise = null;
-
-async function refreshBasecampToken() {
-    if (basecampRefreshPromise) {
-        return basecampRefreshPromise;
-    }
-
-    basecampRefreshPromise = (async () => {
-        if (!basecampConfig.refreshToken) {
-            console.warn('Cannot refresh token: Missing refresh token.');
-            return false;
-        }
-
-        try {
-            const fetchFn = (reddIsTauri && typeof tauriAPI !== 'undefined' && tauriAPI.fetch)
-                ? tauriAPI.fetch.bind(tauriAPI)
-                : fetch;
-
-            // Use Netlify function to refresh token (keeps client_secret server-side)
-            const response = await fetchFn('https://redd-todo.netlify.app/.netlify/functions/auth', {
-                method: 'POST',
-                headers: {
-                    'Content-Type': 'application/json'
-                },
-                body: JSON.stringify({
-                    refresh_token: basecampConfig.refreshToken
-                })
-            });
-
-            if (!response.ok) {
-                console.error(`Token refresh failed: ${response.status} ${response.statusText}`);
-                return false;
-            }
-
-            const data = await response.json();
-            if (data.access_token) {
-                basecampConfig.accessToken = data.access_token;
-                // Update refresh token if provided (rare but possible)
-                if (data.refresh_token) {
-                    basecampConfig.refreshToken = data.refresh_token;
-                }
-                saveData();
-                updateBasecampUI();
-                console.log('Basecamp token refreshed successfully via Netlify.');
-                return true;
-            }
-        } catch (e) {
-            console.error('Error refreshing Basecamp token:', e);
-        }
-        return false;
-    })();
-
-    try {
-        return await basecampRefreshPromise;
-    } finally {
-        basecampRefreshPromise = null;
-    }
+// Mirror the backend's Basecamp connection status.
+function applyBasecampStatus(status) {
+    basecampConfig.accountId = status.connected ? status.accountId : null;
+    basecampConfig.email = status.connected ? status.email : null;
+    basecampConfig.isConnected = status.connected;
+    saveData();
+    updateBasecampUI();
+    updateSyncButtonState();
 }
 
-async function basecampFetch(url, options = {}) {
-    // Ensure headers exist
-    if (!options.headers) options.headers = {};
-
-    // Add Authorization header
-    options.headers['Authorization'] = `Bearer ${basecampConfig.accessToken}`;
-
-    // Use Tauri HTTP client if available (bypasses CORS)
-    const fetchFn = (reddIsTauri && typeof tauriAPI !== 'undefined' && tauriAPI.fetch)
-        ? tauriAPI.fetch.bind(tauriAPI)
-        : fetch;
-
-    // First attempt
-    let response = await fetchFn(url, options);
-
-    // If 401, try to refresh
-    if (response.status === 401) {
-        console.log('Received 401 from Basecamp. Attempting to refresh token...');
-        const refreshed = await refreshBasecampToken();
-
-        if (refreshed) {
-            // Update header with new token
-            options.headers['Authorization'] = `Bearer ${basecampConfig.accessToken}`;
-            // Retry request
-            response = await fetchFn(url, options);
-        } else {
-            console.error('Failed to refresh token or no refresh credentials available.');
+// The backend owns the Basecamp tokens. Hand over any an older version kept
+// in localStorage, then adopt the backend's status.
+async function syncBasecampStatus() {
+    if (!reddIsTauri || typeof tauriAPI === 'undefined') return;
+    try {
+        if (basecampConfig.accessToken) {
+            try {
+                await tauriAPI.basecampImportTokens({
+                    accessToken: basecampConfig.accessToken,
+                    refreshToken: basecampConfig.refreshToken || null,
+                    accountId: basecampConfig.accountId ? Number(basecampConfig.accountId) : null,
+                    email: basecampConfig.email || null
+                });
+                ['accessToken', 'refreshToken', 'clientId', 'clientSecret']
+                    .forEach((key) => delete basecampConfig[key]);
+            } catch (e) {
+                console.error('[Basecamp] Failed to hand stored tokens to the backend:', e);
+            }
         }
+        applyBasecampStatus(await tauriAPI.getBasecampStatus());
+    } catch (e) {
+        console.error('[Basecamp] Failed to load connection status:', e);
     }
-
-    return response;
 }
 
 async function checkProjectAccess(projectId, email) {
     try {
-        const response = await basecampFetch(`https://3.basecampapi.com/${basecampConfig.accountId}/projects/${projectId}/people.json`, {
-            headers: {
-                'Content-Type': 'application/json'
-            }
-        });
-        if (!response.ok) return false;
-        const people = await response.json();
-        return people.some(p => p.email_address && p.email_address.toLowerCase() === email.toLowerCase());
+        const people = await tauriAPI.basecampProjectPeople(Number(projectId));
+        return (people || []).some(p => p.email_address && p.email_address.toLowerCase() === email.toLowerCase());
     } catch (e) {
         console.error(`Error checking access for project ${projectId}:`, e);
         return false;
@@ -7985,21 +8639,13 @@ async function checkProjectAccess(projectId, email) {
 async function fetchBasecampProjects() {
     if (!basecampConfig.isConnected) return [];
     try {
-        // Basecamp 3 API: GET /projects.json
-        const response = await basecampFetch(`https://3.basecampapi.com/${basecampConfig.accountId}/projects.json`, {
-            headers: {
-                'Content-Type': 'application/json'
-            }
-        });
-        if (!response.ok) throw new Error('Failed to fetch projects');
-        let projects = await response.json();
+        let projects = (await tauriAPI.basecampProjects()) || [];
 
         // Filter by email if provided
         if (basecampConfig.email && basecampConfig.email.trim()) {
             const email = basecampConfig.email.trim();
 
-            // Check access for all projects in parallel
-            // Note: This might hit rate limits if there are many projects
+            // Check access for all projects in parallel; the client waits out rate limits
             const accessResults = await Promise.all(
                 projects.map(async (p) => {
                     const hasAccess = await checkProjectAccess(p.id, email);
@@ -8018,22 +8664,8 @@ async function fetchBasecampProjects() {
 
 async function getBasecampTodoLists(projectId) {
     try {
-        // 1. Get the "todoset" (dock) for the project
-        const projectResp = await basecampFetch(`https://3.basecampapi.com/${basecampConfig.accountId}/projects/${projectId}.json`);
-        const projectData = await projectResp.json();
-
-        const todoset = projectData.dock.find(d => d.name === 'todoset');
-        if (!todoset) return [];
-
-        // 2. Get the todolists in that set
-        // In BC3, we need to follow the url to get the set details which contains 'todolists_url'
-        // Actually: GET /buckets/1/todosets/1/todolists.json is the pattern
-        const realListsUrl = todoset.url.replace('.json', '/todolists.json');
-
-        const finalListsResp = await basecampFetch(realListsUrl);
-        const finalLists = await finalListsResp.json();
-
-        return finalLists;
+        // The lists of the project's to-do set (empty if the project has no to-dos tool)
+        return (await tauriAPI.basecampTodolists(Number(projectId))) || [];
     } catch (e) {
         console.error('Basecamp Lists Error:', e);
         return [];
@@ -8059,16 +8691,14 @@ async function syncBasecampList(tabId) {
     if (!tab || !tab.basecampListId || !basecampConfig.isConnected) return;
 
     try {
-        // Fetch both active (default) and completed todos
-        const baseUrl = `https://3.basecampapi.com/${basecampConfig.accountId}/buckets/${tab.basecampProjectId}/todolists/${tab.basecampListId}/todos.json`;
+        // Fetch both active (default) and completed todos; the client follows pagination
+        const projectId = Number(tab.basecampProjectId);
+        const listId = Number(tab.basecampListId);
 
-        const [activeResp, completedResp] = await Promise.all([
-            basecampFetch(baseUrl),
-            basecampFetch(`${baseUrl}?completed=true`)
+        const [activeTodos, completedTodos] = await Promise.all([
+            tauriAPI.basecampTodos(projectId, listId, false),
+            tauriAPI.basecampTodos(projectId, listId, true)
         ]);
-
-        const activeTodos = await activeResp.json();
-        const completedTodos = await completedResp.json();
         const remoteTodos = [
             ...(Array.isArray(activeTodos) ? activeTodos : []),
             ...(Array.isArray(completedTodos) ? completedTodos : [])
@@ -8232,13 +8862,7 @@ async function updateBasecampCompletion(tabId, task) {
     if (!tab || !task.basecampId) return;
 
     try {
-        const url = `https://3.basecampapi.com/${basecampConfig.accountId}/buckets/${tab.basecampProjectId}/todos/${task.basecampId}/completion.json`;
-
-        const method = task.completed ? 'POST' : 'DELETE';
-
-        await basecampFetch(url, {
-            method: method
-        });
+        await tauriAPI.basecampSetTodoCompleted(Number(tab.basecampProjectId), task.basecampId, !!task.completed);
     } catch (e) {
         console.error('Update BC Error:', e);
     }
@@ -8249,15 +8873,7 @@ async function updateBasecampTodoText(tabId, task) {
     if (!tab || !task.basecampId) return;
 
     try {
-        const url = `https://3.basecampapi.com/${basecampConfig.accountId}/buckets/${tab.basecampProjectId}/todos/${task.basecampId}.json`;
-
-        await basecampFetch(url, {
-            method: 'PUT',
-            headers: {
-                'Content-Type': 'application/json'
-            },
-            body: JSON.stringify({ content: task.text })
-        });
+        await tauriAPI.basecampUpdateTodo(Number(tab.basecampProjectId), task.basecampId, { content: task.text });
     } catch (e) {
         console.error('Update BC Text Error:', e);
     }
@@ -8268,23 +8884,13 @@ async function updateBasecampTodoDescription(tabId, task) {
     if (!tab || !task.basecampId) return;
 
     try {
-        const url = `https://3.basecampapi.com/${basecampConfig.accountId}/buckets/${tab.basecampProjectId}/todos/${task.basecampId}.json`;
-
         console.log('Pushing notes to Basecamp:', task.text, 'description:', task.notes);
 
         // Basecamp API requires content field when updating - include both content and description
-        const response = await basecampFetch(url, {
-            method: 'PUT',
-            headers: {
-                'Content-Type': 'application/json'
-            },
-            body: JSON.stringify({
-                content: task.text,
-                description: task.notes || ''
-            })
+        await tauriAPI.basecampUpdateTodo(Number(tab.basecampProjectId), task.basecampId, {
+            content: task.text,
+            description: task.notes || ''
         });
-
-        console.log('Basecamp description update response:', response.status, response.ok);
     } catch (e) {
         console.error('Update BC Description Error:', e);
     }
@@ -8295,11 +8901,7 @@ async function deleteBasecampTodo(tabId, basecampId) {
     if (!tab || !tab.basecampProjectId || !basecampConfig.isConnected) return;
 
     try {
-        const url = `https://3.basecampapi.com/${basecampConfig.accountId}/buckets/${tab.basecampProjectId}/todos/${basecampId}.json`;
-
-        await basecampFetch(url, {
-            method: 'DELETE'
-        });
+        await tauriAPI.basecampTrashTodo(Number(tab.basecampProjectId), basecampId);
     } catch (e) {
         console.error('Delete BC Error:', e);
     }
@@ -8310,143 +8912,51 @@ async function createBasecampTodo(tabId, task) {
     if (!tab || !tab.basecampListId) return;
 
     try {
-        const url = `https://3.basecampapi.com/${basecampConfig.accountId}/buckets/${tab.basecampProjectId}/todolists/${tab.basecampListId}/todos.json`;
-
         // Build request body with content and optional description (notes)
-        const body = { content: task.text };
+        const todo = { content: task.text };
         if (task.notes) {
-            body.description = task.notes;
+            todo.description = task.notes;
         }
 
-        const response = await basecampFetch(url, {
-            method: 'POST',
-            headers: {
-                'Content-Type': 'application/json'
-            },
-            body: JSON.stringify(body)
-        });
-        const data = await response.json();
+        const data = await tauriAPI.basecampCreateTodo(Number(tab.basecampProjectId), Number(tab.basecampListId), todo);
 
         // Link local task to remote ID
-        task.basecampId = data.id;
-        saveData();
+        if (data && data.id) {
+            task.basecampId = data.id;
+            saveData();
+        }
     } catch (e) {
         console.error('Create BC Error:', e);
     }
 }
 
-// Move a Basecamp todo to a different list (possibly in a different project)
+// Move a Basecamp todo to a different list (possibly in a different project).
+// The client re-parents it, or copies it and trashes the original when Basecamp refuses.
 async function moveBasecampTodo(task, sourceTab, targetTab) {
     if (!task.basecampId) {
         console.warn('moveBasecampTodo: No basecampId on task');
         return;
     }
-    if (!basecampConfig.isConnected || !basecampConfig.accessToken) {
+    if (!basecampConfig.isConnected) {
         console.warn('moveBasecampTodo: Not connected to Basecamp');
         return;
     }
 
     try {
-        // Basecamp 3 API: To move a recording to a different parent, use PUT on the recordings/parent endpoint
-        // PUT /buckets/{bucket_id}/recordings/{recording_id}/parent.json
-        // Body: { "parent": { "id": target_todolist_id, "type": "Todolist" } }
-        // If moving to a different project, also include "bucket_id" in the parent object
-
-        const sourceBucketId = sourceTab.basecampProjectId;
-        const targetBucketId = targetTab.basecampProjectId;
-        const targetListId = targetTab.basecampListId;
-
-        console.log('Attempting Basecamp move via parent endpoint...');
-        console.log('  Source bucket:', sourceBucketId, 'Target bucket:', targetBucketId);
-        console.log('  Target list:', targetListId, 'Todo ID:', task.basecampId);
-
-        const url = `https://3.basecampapi.com/${basecampConfig.accountId}/buckets/${sourceBucketId}/recordings/${task.basecampId}/parent.json`;
-
-        const parentData = {
-            parent: {
-                id: parseInt(targetListId),
-                type: "Todolist"
-            }
-        };
-
-        // If moving to a different project, include bucket_id
-        if (sourceBucketId !== targetBucketId) {
-            parentData.parent.bucket_id = parseInt(targetBucketId);
-        }
-
-        const response = await basecampFetch(url, {
-            method: 'PUT',
-            headers: {
-                'Content-Type': 'application/json'
-            },
-            body: JSON.stringify(parentData)
-        });
-
-        if (response.ok) {
-            console.log('✓ Successfully moved Basecamp todo to new list');
-        } else {
-            // Log the error response for debugging
-            const errorText = await response.text();
-            console.warn('Move endpoint failed with status:', response.status, errorText);
-            console.log('Falling back to copy+delete...');
-            await fallbackMoveBasecampTodo(task, sourceTab, targetTab);
-        }
-    } catch (e) {
-        console.error('Move BC Error:', e);
-        // Fall back to copy+delete on error
-        console.log('Falling back to copy+delete due to error...');
-        await fallbackMoveBasecampTodo(task, sourceTab, targetTab);
-    }
-}
-
-// Fallback: Create in new list, then delete from old (creates archived copy but at least works)
-async function fallbackMoveBasecampTodo(task, sourceTab, targetTab) {
-    try {
-        const oldBasecampId = task.basecampId;
-        console.log('Fallback move: Creating todo in target list...');
-
-        // Create in new list
-        const createUrl = `https://3.basecampapi.com/${basecampConfig.accountId}/buckets/${targetTab.basecampProjectId}/todolists/${targetTab.basecampListId}/todos.json`;
-        const createResponse = await basecampFetch(createUrl, {
-            method: 'POST',
-            headers: {
-                'Content-Type': 'application/json'
-            },
-            body: JSON.stringify({ content: task.text })
-        });
-
-        if (createResponse.ok) {
-            const data = await createResponse.json();
-            task.basecampId = data.id;
-            console.log('✓ Created new todo with ID:', task.basecampId);
-
-            // If the task was completed, mark the new one as completed too
-            if (task.completed) {
-                console.log('Marking new todo as completed...');
-                const completeUrl = `https://3.basecampapi.com/${basecampConfig.accountId}/buckets/${targetTab.basecampProjectId}/todos/${task.basecampId}/completion.json`;
-                await basecampFetch(completeUrl, { method: 'POST' });
-            }
-
-            // Delete the old one (this will archive it in Basecamp)
-            console.log('Deleting old todo:', oldBasecampId);
-            const deleteUrl = `https://3.basecampapi.com/${basecampConfig.accountId}/buckets/${sourceTab.basecampProjectId}/todos/${oldBasecampId}.json`;
-            const deleteResponse = await basecampFetch(deleteUrl, { method: 'DELETE' });
-
-            if (deleteResponse.ok) {
-                console.log('✓ Fallback move completed successfully');
-            } else {
-                console.warn('Delete returned status:', deleteResponse.status);
-            }
-
+        const todoId = await tauriAPI.basecampMoveTodo(
+            Number(sourceTab.basecampProjectId),
+            task.basecampId,
+            Number(targetTab.basecampProjectId),
+            Number(targetTab.basecampListId)
+        );
+        if (todoId && todoId !== task.basecampId) {
+            console.log('Basecamp todo was copied to the new list with ID:', todoId);
+            task.basecampId = todoId;
             saveData();
-        } else {
-            const errorText = await createResponse.text();
-            console.error('Failed to create todo in target list:', createResponse.status, errorText);
-            // Don't clear basecampId - keep the old one so the task stays linked to source
         }
     } catch (e) {
-        console.error('Fallback move BC Error:', e);
-        // Don't clear basecampId on error - leave it linked to old location
+        // Keep the old basecampId so the task stays linked to its source
+        console.error('Move BC Error:', e);
     }
 }
 
@@ -8826,7 +9336,19 @@ async function exportData() {
             await tauriAPI.saveTextFile({
                 title: 'Export ReDD To-Do Backup',
                 defaultPath: filename,
-                contents: exportJson
+                contents: async (path) => {
+                    const format = exportFormatForPath(path);
+                    if (format === 'todotxt') return tauriAPI.exportTodoTxt();
+                    if (format === 'markdown') return tauriAPI.exportMarkdown(...markdownExportScope());
+                    if (format === 'ics') return tauriAPI.exportIcs();
+                    return exportJson;
+                },
+                filters: [
+                    { name: 'JSON', extensions: ['json'] },
+                    { name: 'todo.txt', extensions: ['txt'] },
+                    { name: 'Markdown', extensions: ['md'] },
+                    { name: 'iCalendar', extensions: ['ics'] }
+                ]
             });
             return;
         }
@@ -8844,28 +9366,146 @@ async function exportData() {
     }
 }
 
+/** `[tabId, groupId]` for the Markdown scope picked in settings; both null for everything. */
+function markdownExportScope() {
+    const scope = document.getElementById('markdown-scope-select')?.value;
+    if (scope === 'tab' && currentTabId) return [currentTabId, null];
+    if (scope === 'group' && currentGroupId) return [null, currentGroupId];
+    return [null, null];
+}
+
+function exportFormatForPath(path) {
+    const lower = typeof path === 'string' ? path.toLowerCase() : '';
+    if (lower.endsWith('.txt')) return 'todotxt';
+    if (lower.endsWith('.md') || lower.endsWith('.markdown')) return 'markdown';
+    if (lower.endsWith('.ics')) return 'ics';
+    return 'json';
+}
+
+/** Guess an imported file's format from its contents. */
+function detectImportFormat(content) {
+    if (content.trimStart().startsWith('{')) return 'json';
+    if (/^BEGIN:VCALENDAR/i.test(content.trimStart())) return 'ics';
+    if (/^\s*[-*] \[[ xX]\]/m.test(content)) return 'markdown';
+    return 'todotxt';
+}
+
+/** List to show once an import's tasks arrive through `store-updated`. */
+let pendingImportTabId = null;
+
+/** Add tasks from a todo.txt, Markdown or iCalendar file via the backend and show where they landed. */
+async function importTaskListContent(content, format) {
+    let summary;
+    if (format === 'markdown') {
+        summary = await tauriAPI.importMarkdown(content);
+    } else if (format === 'ics') {
+        summary = await tauriAPI.importIcs(content);
+    } else {
+        summary = await tauriAPI.importTodoTxt(content, currentTabId);
+    }
+    // The tasks come in through `store-updated`, which may arrive either side
+    // of this reply.
+    if (!summary?.tabId) return;
+    if (tabs[summary.tabId]) {
+        switchToTab(summary.tabId);
+    } else {
+        pendingImportTabId = summary.tabId;
+    }
+}
+
+/** Ask whether a backup should replace the current data or be merged into it; `null` if cancelled. */
+function chooseBackupImportMode() {
+    return new Promise((resolve) => {
+        const modal = document.getElementById('import-mode-modal');
+        const buttons = {
+            replace: document.getElementById('import-mode-replace'),
+            merge: document.getElementById('import-mode-merge'),
+            cancel: document.getElementById('import-mode-cancel')
+        };
+        if (!modal || !buttons.replace || !buttons.merge || !buttons.cancel) {
+            resolve('replace');
+            return;
+        }
+        const handlers = {};
+        const finish = (mode) => {
+            modal.classList.add('hidden');
+            Object.keys(buttons).forEach(key => buttons[key].removeEventListener('click', handlers[key]));
+            resolve(mode);
+        };
+        handlers.replace = () => finish('replace');
+        handlers.merge = () => finish('merge');
+        handlers.cancel = () => finish(null);
+        Object.keys(buttons).forEach(key => buttons[key].addEventListener('click', handlers[key]));
+        modal.classList.remove('hidden');
+    });
+}
+
 async function restoreBackupData(data) {
     if (!data.tabs) {
         throw new Error('Invalid backup file format.');
     }
 
+    // In Tauri the backend validates the file and reports what would change.
+    const useBackendImport = reddIsTauri && typeof tauriAPI !== 'undefined' && tauriAPI.validateBackup;
+    const contents = JSON.stringify(data);
     const settingsWereOpen = settingsModal && !settingsModal.classList.contains('hidden');
+    const reopenSettings = () => {
+        if (settingsWereOpen) settingsModal.classList.remove('hidden');
+    };
+
+    // Merging needs the backend; without it a backup always replaces.
+    let mode = 'replace';
+    let summary = '';
+    if (useBackendImport) {
+        settingsModal?.classList.add('hidden');
+        mode = await chooseBackupImportMode();
+        if (!mode) {
+            reopenSettings();
+            return;
+        }
+        const report = await tauriAPI.validateBackup(contents, mode);
+        if (report && !report.valid) {
+            const details = report.errors.slice(0, 5).map(e => `${e.path}: ${e.message}`).join('\n');
+            reopenSettings();
+            throw new Error(`Invalid backup file:\n${details}`);
+        }
+        if (report?.diff) {
+            summary = ` (${describeBackupDiff(report.diff)})`;
+        }
+    }
+
     settingsModal?.classList.add('hidden');
 
     const confirmRestore = await showConfirmModal(
-        'Restore Backup',
-        'This will overwrite your current data and reload the app. Proceed?',
-        'Restore',
+        mode === 'merge' ? 'Merge Backup' : 'Restore Backup',
+        mode === 'merge'
+            ? `This will add the backup's lists and tasks to your current data${summary} and reload the app. Proceed?`
+            : `This will overwrite your current data${summary} and reload the app. Proceed?`,
+        mode === 'merge' ? 'Merge' : 'Restore',
         'Cancel'
     );
 
     if (!confirmRestore) {
-        if (settingsWereOpen) {
-            settingsModal.classList.remove('hidden');
-        }
+        reopenSettings();
         return;
     }
 
+    const payload = useBackendImport ? await tauriAPI.importBackup(contents, mode) : data;
+    writeBackupPayloadToStorage(payload || data);
+    window.location.reload();
+}
+
+function describeBackupDiff(diff) {
+    const parts = [];
+    if (diff.tabsAdded.length) parts.push(`${diff.tabsAdded.length} list(s) added`);
+    if (diff.tabsRemoved.length) parts.push(`${diff.tabsRemoved.length} list(s) removed`);
+    if (diff.tabsChanged.length) parts.push(`${diff.tabsChanged.length} list(s) changed`);
+    parts.push(`${diff.tasksAdded} task(s) added, ${diff.tasksRemoved} removed, ${diff.tasksChanged} changed`);
+    return parts.join('; ');
+}
+
+/** Replace task, plan and UI-pref storage with an export-shaped payload. */
+function writeBackupPayloadToStorage(data) {
     const planData = data.planData;
     const uiPrefs = data.uiPrefs;
     delete data.planData;
@@ -8887,6 +9527,59 @@ async function restoreBackupData(data) {
     }
 
     restoreUiPrefsFromBackup(uiPrefs);
+}
+
+/** Fill the automatic-backup picker in settings with the backend's snapshots. */
+async function loadAutomaticBackupsUI() {
+    const row = document.getElementById('automatic-backups-row');
+    const select = document.getElementById('automatic-backup-select');
+    const restoreBtn = document.getElementById('restore-backup-btn');
+    if (!row || !select || !restoreBtn) return;
+    if (!reddIsTauri) {
+        row.classList.add('hidden');
+        return;
+    }
+    try {
+        const backups = await tauriAPI.listBackups();
+        select.replaceChildren(...backups.map((backup) => {
+            const option = document.createElement('option');
+            option.value = backup.id;
+            option.textContent = `${new Date(backup.createdAt).toLocaleString()} (${backup.taskCount})`;
+            return option;
+        }));
+        if (!backups.length) {
+            const option = document.createElement('option');
+            option.value = '';
+            option.textContent = t('noAutomaticBackups');
+            select.appendChild(option);
+        }
+        select.disabled = restoreBtn.disabled = !backups.length;
+    } catch (err) {
+        console.warn('Failed to list backups:', err);
+    }
+}
+
+/** Restore one of the backend's rotating snapshots (see `list_backups`). */
+async function restoreAutomaticBackup(backupId) {
+    if (!backupId || !reddIsTauri || typeof tauriAPI === 'undefined' || !tauriAPI.restoreBackup) return;
+    const confirmRestore = await showConfirmModal(
+        'Restore Backup',
+        'This will overwrite your current data and reload the app. Proceed?',
+        'Restore',
+        'Cancel'
+    );
+    if (!confirmRestore) return;
+
+    let payload;
+    try {
+        payload = await tauriAPI.restoreBackup(backupId);
+    } catch (err) {
+        console.error('Restore failed:', err);
+        alert(`${t('restoreBackupFailed')}\n${err?.message || err}`);
+        return;
+    }
+    if (!payload) return;
+    writeBackupPayloadToStorage(payload);
     window.location.reload();
 }
 
@@ -8894,9 +9587,21 @@ async function importDataFromFilePicker() {
     try {
         if (reddIsTauri && typeof tauriAPI !== 'undefined' && tauriAPI.openTextFile) {
             const content = await tauriAPI.openTextFile({
-                title: 'Import ReDD To-Do Backup'
+                title: 'Import ReDD To-Do Backup',
+                filters: [
+                    { name: 'JSON', extensions: ['json'] },
+                    { name: 'todo.txt', extensions: ['txt'] },
+                    { name: 'Markdown', extensions: ['md'] },
+                    { name: 'iCalendar', extensions: ['ics'] },
+                    { name: 'All files', extensions: ['*'] }
+                ]
             });
             if (!content) return;
+            const format = detectImportFormat(content);
+            if (format !== 'json') {
+                await importTaskListContent(content, format);
+                return;
+            }
             await restoreBackupData(JSON.parse(content));
             return;
         }
@@ -8926,6 +9631,26 @@ function importData(file) {
     reader.readAsText(file);
 }
 
+/** Save a time report for the range, format and grouping picked in settings. */
+async function exportTimeReportFile() {
+    const from = document.getElementById('report-from-input')?.value || null;
+    const to = document.getElementById('report-to-input')?.value || null;
+    const format = document.getElementById('report-format-select')?.value || 'csv';
+    const grouping = document.getElementById('report-grouping-select')?.value || 'task';
+    const range = [from, to].filter(Boolean).join('_to_') || new Date().toISOString().split('T')[0];
+    try {
+        await tauriAPI.saveTextFile({
+            title: t('timeReport'),
+            defaultPath: `redd-todo-time-report-${range}.${format}`,
+            contents: () => tauriAPI.exportTimeReport({ from, to, format, grouping }),
+            filters: [{ name: format.toUpperCase(), extensions: [format] }]
+        });
+    } catch (e) {
+        console.error('Time report export failed:', e);
+        alert(`${t('reportExportFailed')}\n${e?.message || e}`);
+    }
+}
+
 function setupDataBackupButtons() {
     const exportBtn = document.getElementById('export-data-btn');
     const importBtn = document.getElementById('import-data-btn');
@@ -8938,6 +9663,26 @@ function setupDataBackupButtons() {
         });
     }
 
+    const reportBtn = document.getElementById('export-report-btn');
+    if (!reddIsTauri) {
+        document.getElementById('time-report-row')?.classList.add('hidden');
+        document.getElementById('time-report-options')?.classList.add('hidden');
+        document.getElementById('markdown-scope-options')?.classList.add('hidden');
+    } else if (reportBtn && !reportBtn.dataset.bound) {
+        reportBtn.dataset.bound = '1';
+        reportBtn.addEventListener('click', () => {
+            void exportTimeReportFile();
+        });
+    }
+
+    const restoreBtn = document.getElementById('restore-backup-btn');
+    if (restoreBtn && !restoreBtn.dataset.bound) {
+        restoreBtn.dataset.bound = '1';
+        restoreBtn.addEventListener('click', () => {
+            void restoreAutomaticBackup(document.getElementById('automatic-backup-select')?.value);
+        });
+    }
+
     if (importBtn && !importBtn.dataset.bound) {
         importBtn.dataset.bound = '1';
         importBtn.addEventListener('click', () => {
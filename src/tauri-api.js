@@ -80,6 +80,18 @@ const tauriAPI = {
         return this.invoke('window_close');
     },
 
+    async captureTask(text, tabId = null) {
+        return this.invoke('capture_task', { text, tabId });
+    },
+
+    async deepLinkReady() {
+        return this.invoke('deep_link_ready');
+    },
+
+    async confirmDeepLinkAction(action, taskId) {
+        return this.invoke('confirm_deep_link_action', { action, taskId });
+    },
+
     async setWindowFullscreen(fullscreen) {
         if (!this.isTauri) return null;
         const windowApi = window.__TAURI__.window;
@@ -109,8 +121,8 @@ const tauriAPI = {
         });
     },
 
-    async exitFocusMode(taskId = null) {
-        return this.invoke('exit_focus_mode', { taskId });
+    async exitFocusMode(taskId = null, completed = false) {
+        return this.invoke('exit_focus_mode', { taskId, completed });
     },
 
     async setFocusWindowSize(width) {
@@ -175,6 +187,251 @@ const tauriAPI = {
         return this.invoke('set_focus_mode_window_state', { enabled });
     },
 
+    // Store commands (Rust-owned task persistence)
+    async loadState() {
+        return this.invoke('load_state');
+    },
+
+    async mergeStoreChanges(local, data, changes) {
+        return this.invoke('merge_store_changes', { local, data, changes });
+    },
+
+    async takeExternalStoreChanges(local = null) {
+        return this.invoke('take_external_store_changes', { local });
+    },
+
+    async saveState(state) {
+        return this.invoke('save_state', { state });
+    },
+
+    async savePlanData(plan) {
+        return this.invoke('save_plan_data', { plan });
+    },
+
+    async patchTask(taskId, patch) {
+        return this.invoke('patch_task', { taskId, patch });
+    },
+
+    // Automatic backup commands
+    async listBackups() {
+        return this.invoke('list_backups');
+    },
+
+    async createBackup() {
+        return this.invoke('create_backup');
+    },
+
+    async restoreBackup(backupId) {
+        return this.invoke('restore_backup', { backupId });
+    },
+
+    async getBackupSettings() {
+        return this.invoke('get_backup_settings');
+    },
+
+    async setBackupSettings(settings) {
+        return this.invoke('set_backup_settings', { settings });
+    },
+
+    async validateBackup(contents, mode = 'replace') {
+        return this.invoke('validate_backup', { contents, mode });
+    },
+
+    async importBackup(contents, mode = 'replace') {
+        return this.invoke('import_backup', { contents, mode });
+    },
+
+    // Import/export format commands
+    async exportTodoTxt(tabId = null) {
+        return this.invoke('export_todotxt', { tabId });
+    },
+
+    async importTodoTxt(contents, tabId = null) {
+        return this.invoke('import_todotxt', { contents, tabId });
+    },
+
+    async exportMarkdown(tabId = null, groupId = null) {
+        return this.invoke('export_markdown', { tabId, groupId });
+    },
+
+    async importMarkdown(contents, name = null) {
+        return this.invoke('import_markdown', { contents, name });
+    },
+
+    async exportIcs(tabId = null) {
+        return this.invoke('export_ics', { tabId });
+    },
+
+    async importIcs(contents, name = null) {
+        return this.invoke('import_ics', { contents, name });
+    },
+
+    // Focus session log commands
+    async queryFocusSessions(query = {}) {
+        return this.invoke('query_focus_sessions', { query });
+    },
+
+    // Focus timer commands
+    async startFocusTimer(taskId, taskName = null, duration = null, initialElapsedMs = 0) {
+        return this.invoke('start_focus_timer', { taskId, taskName, duration, initialElapsedMs });
+    },
+
+    async pauseFocusTimer(taskId) {
+        return this.invoke('pause_focus_timer', { taskId });
+    },
+
+    async resumeFocusTimer(taskId) {
+        return this.invoke('resume_focus_timer', { taskId });
+    },
+
+    async stopFocusTimer(taskId) {
+        return this.invoke('stop_focus_timer', { taskId });
+    },
+
+    async setFocusTimerElapsed(taskId, elapsedMs) {
+        return this.invoke('set_focus_timer_elapsed', { taskId, elapsedMs });
+    },
+
+    async getFocusTimer(taskId) {
+        return this.invoke('get_focus_timer', { taskId });
+    },
+
+    // Pomodoro commands
+    async skipPomodoroBreak(taskId) {
+        return this.invoke('skip_pomodoro_break', { taskId });
+    },
+
+    async getPomodoroSettings() {
+        return this.invoke('get_pomodoro_settings');
+    },
+
+    async setPomodoroSettings(settings) {
+        return this.invoke('set_pomodoro_settings', { settings });
+    },
+
+    // Focus pin commands
+    async getFocusPins() {
+        return this.invoke('get_focus_pins');
+    },
+
+    async getFocusPinSettings() {
+        return this.invoke('get_focus_pin_settings');
+    },
+
+    async setFocusPinSettings(settings) {
+        return this.invoke('set_focus_pin_settings', { settings });
+    },
+
+    // Idle detection commands
+    async resolveIdleTime(keep) {
+        return this.invoke('resolve_idle_time', { keep });
+    },
+
+    async getIdleSettings() {
+        return this.invoke('get_idle_settings');
+    },
+
+    async setIdleSettings(settings) {
+        return this.invoke('set_idle_settings', { settings });
+    },
+
+    // Global shortcut commands
+    async getShortcutSettings() {
+        return this.invoke('get_shortcut_settings');
+    },
+
+    async setShortcutSettings(settings) {
+        return this.invoke('set_shortcut_settings', { settings });
+    },
+
+    async getShortcutStatus() {
+        return this.invoke('get_shortcut_status');
+    },
+
+    // Tray commands
+    async setTrayLabels(labels) {
+        return this.invoke('set_tray_labels', { labels });
+    },
+
+    // Local automation API
+    async getRpcSettings() {
+        return this.invoke('get_rpc_settings');
+    },
+
+    async setRpcSettings(settings) {
+        return this.invoke('set_rpc_settings', { settings });
+    },
+
+    async getRpcStatus() {
+        return this.invoke('get_rpc_status');
+    },
+
+    // Basecamp commands (native API client). The backend keeps the tokens.
+    async getBasecampStatus() {
+        return this.invoke('get_basecamp_status');
+    },
+
+    async basecampDisconnect() {
+        return this.invoke('basecamp_disconnect');
+    },
+
+    async basecampImportTokens({ accessToken, refreshToken = null, accountId = null, email = null }) {
+        return this.invoke('basecamp_import_tokens', { accessToken, refreshToken, accountId, email });
+    },
+
+    async basecampProjects() {
+        return this.invoke('basecamp_projects');
+    },
+
+    async basecampProjectPeople(projectId) {
+        return this.invoke('basecamp_project_people', { projectId });
+    },
+
+    async basecampTodoset(projectId) {
+        return this.invoke('basecamp_todoset', { projectId });
+    },
+
+    async basecampTodolists(projectId) {
+        return this.invoke('basecamp_todolists', { projectId });
+    },
+
+    async basecampTodos(projectId, todolistId, completed = false) {
+        return this.invoke('basecamp_todos', { projectId, todolistId, completed });
+    },
+
+    async basecampCreateTodo(projectId, todolistId, todo) {
+        return this.invoke('basecamp_create_todo', { projectId, todolistId, todo });
+    },
+
+    async basecampUpdateTodo(projectId, todoId, todo) {
+        return this.invoke('basecamp_update_todo', { projectId, todoId, todo });
+    },
+
+    async basecampSetTodoCompleted(projectId, todoId, completed) {
+        return this.invoke('basecamp_set_todo_completed', { projectId, todoId, completed });
+    },
+
+    async basecampTrashTodo(projectId, todoId) {
+        return this.invoke('basecamp_trash_todo', { projectId, todoId });
+    },
+
+    async basecampMoveTodo(projectId, todoId, targetProjectId, targetTodolistId) {
+        return this.invoke('basecamp_move_todo', { projectId, todoId, targetProjectId, targetTodolistId });
+    },
+
+    async basecampComments(projectId, recordingId) {
+        return this.invoke('basecamp_comments', { projectId, recordingId });
+    },
+
+    async basecampCreateComment(projectId, recordingId, content) {
+        return this.invoke('basecamp_create_comment', { projectId, recordingId, content });
+    },
+
+    // Report commands
+    async exportTimeReport({ from = null, to = null, format = 'csv', grouping = 'task' } = {}) {
+        return this.invoke('export_time_report', { from, to, format, grouping });
+    },
+
     // Event listeners - matches Electron's ipcRenderer.on(channel, (event, data) => ...)
     onEvent(eventName, callback) {
         if (!this.isTauri) return () => { };
@@ -293,7 +550,9 @@ const tauriAPI = {
         });
         if (!selectedPath || typeof selectedPath !== 'string') return null;
 
-        await fs.writeTextFile(selectedPath, contents);
+        // `contents` may be a function so callers can pick a format from the chosen path.
+        const text = typeof contents === 'function' ? await contents(selectedPath) : contents;
+        await fs.writeTextFile(selectedPath, text);
         return selectedPath;
     },
 